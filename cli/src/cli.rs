@@ -62,6 +62,10 @@ pub enum Subcommand {
 pub struct ValidationWorkerCommand {
 	#[allow(missing_docs)]
 	pub mem_id: String,
+
+	/// Validate candidates even if the worker can't fully sandbox itself.
+	#[structopt(long = "degraded-sandbox")]
+	pub degraded_sandbox: bool,
 }
 
 #[allow(missing_docs)]
@@ -98,6 +102,30 @@ pub struct RunCmd {
 	/// commonly `127.0.0.1:6831`.
 	#[structopt(long)]
 	pub jaeger_agent: Option<std::net::SocketAddr>,
+
+	/// Run the validation workers even if the kernel lacks some of the features needed to
+	/// sandbox them.
+	///
+	/// This weakens the protection of the node from malicious parachain validation code, so it
+	/// should only be used when upgrading the kernel isn't an option.
+	#[structopt(long = "allow-degraded-worker-sandbox")]
+	pub allow_degraded_worker_sandbox: bool,
+
+	/// The number of validation workers reserved for backing candidates.
+	///
+	/// Backing may also use idle workers reserved for disputes and approval checking.
+	#[structopt(long = "pvf-backing-workers")]
+	pub pvf_backing_workers: Option<usize>,
+
+	/// The number of validation workers reserved for participating in disputes.
+	///
+	/// Disputes may also use idle workers reserved for approval checking.
+	#[structopt(long = "pvf-dispute-workers")]
+	pub pvf_dispute_workers: Option<usize>,
+
+	/// The number of validation workers reserved for approval checking.
+	#[structopt(long = "pvf-approval-workers")]
+	pub pvf_approval_workers: Option<usize>,
 }
 
 #[allow(missing_docs)]
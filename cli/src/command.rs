@@ -17,7 +17,7 @@
 use log::info;
 use service::{IdentifyVariant, self};
 use tc_cli::{TetcoreCli, RuntimeVersion, Role};
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{Cli, RunCmd, Subcommand};
 use futures::future::TryFutureExt;
 
 #[derive(thiserror::Error, Debug)]
@@ -138,12 +138,65 @@ fn set_default_ss58_version(spec: &Box<dyn service::ChainSpec>) {
 	tet_core::crypto::set_default_ss58_version(ss58_version);
 }
 
+/// The isolation strategy for validating candidates, with the validation workers sandboxed and
+/// split between the validation priorities as requested on the command line.
+fn isolation_strategy(cmd: &RunCmd) -> service::IsolationStrategy {
+	#[cfg(not(any(target_os = "android", feature = "browser")))]
+	{
+		use tetcoin_parachain::wasm_executor::{SandboxMode, ValidationPoolConfig};
+
+		let default = ValidationPoolConfig::default();
+		service::IsolationStrategy::with_pool_config(ValidationPoolConfig {
+			backing_hosts: cmd.pvf_backing_workers.unwrap_or(default.backing_hosts),
+			dispute_hosts: cmd.pvf_dispute_workers.unwrap_or(default.dispute_hosts),
+			approval_hosts: cmd.pvf_approval_workers.unwrap_or(default.approval_hosts),
+			sandbox_mode: if cmd.allow_degraded_worker_sandbox {
+				SandboxMode::Degraded
+			} else {
+				SandboxMode::Strict
+			},
+		})
+	}
+
+	#[cfg(any(target_os = "android", feature = "browser"))]
+	{
+		let _ = cmd;
+		Default::default()
+	}
+}
+
+/// Checks that the validation workers will be able to sandbox themselves, unless a degraded sandbox
+/// is allowed on the command line.
+///
+/// This forks the process, so it must run before the node spawns any threads.
+fn check_worker_sandbox(cmd: &RunCmd) -> Result<()> {
+	#[cfg(not(any(target_os = "android", feature = "browser")))]
+	{
+		if !cmd.allow_degraded_worker_sandbox && !cmd.base.light {
+			tetcoin_parachain::wasm_executor::check_sandbox().map_err(|e| Error::Other(format!(
+				"The validation workers can't sandbox themselves: {}. Upgrade the kernel or pass \
+				--allow-degraded-worker-sandbox to run them anyway, at the expense of security.",
+				e,
+			)))?;
+		}
+	}
+
+	#[cfg(any(target_os = "android", feature = "browser"))]
+	{
+		let _ = cmd;
+	}
+
+	Ok(())
+}
+
 /// Parses tetcoin specific CLI arguments and run the service.
 pub fn run() -> Result<()> {
 	let cli = Cli::from_args();
 
 	match &cli.subcommand {
 		None => {
+			check_worker_sandbox(&cli.run)?;
+
 			let runner = cli.create_runner(&cli.run.base)
 				.map_err(Error::from)?;
 			let chain_spec = &runner.config().chain_spec;
@@ -165,6 +218,7 @@ pub fn run() -> Result<()> {
 			}
 
 			let jaeger_agent = cli.run.jaeger_agent;
+			let isolation_strategy = isolation_strategy(&cli.run);
 
 			runner.run_node_until_exit(move |config| async move {
 				let role = config.role.clone();
@@ -176,6 +230,7 @@ pub fn run() -> Result<()> {
 						service::IsCollator::No,
 						grandpa_pause,
 						jaeger_agent,
+						isolation_strategy,
 					).map(|full| full.task_manager)
 				}?;
 				Ok::<_, Error>(task_manager)
@@ -257,7 +312,16 @@ pub fn run() -> Result<()> {
 				Err(tc_cli::Error::Input("Cannot run validation worker in browser".into()).into())
 			} else {
 				#[cfg(not(any(target_os = "android", feature = "browser")))]
-				tetcoin_parachain::wasm_executor::run_worker(&cmd.mem_id)?;
+				{
+					use tetcoin_parachain::wasm_executor::SandboxMode;
+
+					let sandbox_mode = if cmd.degraded_sandbox {
+						SandboxMode::Degraded
+					} else {
+						SandboxMode::Strict
+					};
+					tetcoin_parachain::wasm_executor::run_worker(&cmd.mem_id, sandbox_mode)?;
+				}
 				Ok(())
 			}
 		},
@@ -33,13 +33,13 @@ use tetcoin_node_subsystem::{
 };
 use tetcoin_node_subsystem_util::{
 	request_availability_cores_ctx, request_persisted_validation_data_ctx,
-	request_validators_ctx,
+	request_validators_ctx, request_outbound_hrmp_channels_bandwidth_ctx,
 	metrics::{self, prometheus},
 };
 use tetcoin_primitives::v1::{
 	collator_signature_payload, AvailableData, CandidateCommitments,
 	CandidateDescriptor, CandidateReceipt, CoreState, Hash, OccupiedCoreAssumption,
-	PersistedValidationData, PoV,
+	PersistedValidationData, PoV, Id as ParaId, OutboundHrmpMessage, OutboundHrmpBandwidth,
 };
 use tet_core::crypto::Pair;
 use std::sync::Arc;
@@ -270,13 +270,27 @@ async fn handle_new_activations<Context: SubsystemContext>(
 				}
 			};
 
+			// the relay chain rejects candidates overflowing any outbound channel, so the collator
+			// is told the bandwidth left as of the relay parent, and whatever exceeds it is trimmed.
+			let hrmp_bandwidth = request_outbound_hrmp_channels_bandwidth_ctx(
+				relay_parent,
+				scheduled_core.para_id,
+				ctx,
+			)
+			.await?
+			.await??;
+
 			let task_config = config.clone();
 			let mut task_sender = sender.clone();
 			let metrics = metrics.clone();
 			ctx.spawn("collation generation collation builder", Box::pin(async move {
 				let persisted_validation_data_hash = validation_data.hash();
 
-				let collation = match (task_config.collator)(relay_parent, &validation_data).await {
+				let collation = match (task_config.collator)(
+					relay_parent,
+					&validation_data,
+					&hrmp_bandwidth,
+				).await {
 					Some(collation) => collation,
 					None => {
 						tracing::debug!(
@@ -288,6 +302,21 @@ async fn handle_new_activations<Context: SubsystemContext>(
 					}
 				};
 
+				let n_horizontal_messages = collation.horizontal_messages.len();
+				let horizontal_messages = trim_horizontal_messages(
+					collation.horizontal_messages,
+					&hrmp_bandwidth,
+				);
+				if horizontal_messages.len() != n_horizontal_messages {
+					tracing::debug!(
+						target: LOG_TARGET,
+						para_id = %scheduled_core.para_id,
+						sent = n_horizontal_messages,
+						kept = horizontal_messages.len(),
+						"trimmed horizontal messages exceeding the outbound channel bandwidth",
+					);
+				}
+
 				let pov_hash = collation.proof_of_validity.hash();
 
 				let signature_payload = collator_signature_payload(
@@ -316,7 +345,7 @@ async fn handle_new_activations<Context: SubsystemContext>(
 
 				let commitments = CandidateCommitments {
 					upward_messages: collation.upward_messages,
-					horizontal_messages: collation.horizontal_messages,
+					horizontal_messages,
 					new_validation_code: collation.new_validation_code,
 					head_data: collation.head_data,
 					processed_downward_messages: collation.processed_downward_messages,
@@ -356,6 +385,36 @@ async fn handle_new_activations<Context: SubsystemContext>(
 	Ok(())
 }
 
+/// Trim the horizontal messages of a collation to what the outbound HRMP channels can still take.
+///
+/// A candidate may send at most one message to each recipient, and the messages must be sorted
+/// by recipient. Only the first message to each recipient is kept, and it is dropped if there is no
+/// such channel or the message doesn't fit into the remaining bandwidth of the channel. Past the
+/// maximum number of messages per candidate, the messages to the highest recipients are dropped.
+fn trim_horizontal_messages(
+	mut messages: Vec<OutboundHrmpMessage<ParaId>>,
+	bandwidth: &OutboundHrmpBandwidth,
+) -> Vec<OutboundHrmpMessage<ParaId>> {
+	// the sort is stable, so the first message to each recipient survives the dedup.
+	messages.sort_by_key(|msg| msg.recipient);
+	messages.dedup_by_key(|msg| msg.recipient);
+
+	messages.retain(|msg| {
+		let msg_size = msg.data.len();
+		bandwidth.channels
+			.binary_search_by_key(&msg.recipient, |channel| channel.recipient)
+			.map(|idx| &bandwidth.channels[idx])
+			.map_or(false, |channel| {
+				channel.messages_remaining > 0
+					&& msg_size <= channel.max_message_size as usize
+					&& msg_size <= channel.bytes_remaining as usize
+			})
+	});
+	messages.truncate(bandwidth.max_messages_per_candidate as usize);
+
+	messages
+}
+
 #[tracing::instrument(level = "trace", fields(subsystem = LOG_TARGET))]
 fn erasure_root(
 	n_validators: usize,
@@ -466,7 +525,7 @@ mod tests {
 		};
 		use tetcoin_primitives::v1::{
 			BlockData, BlockNumber, CollatorPair, Id as ParaId,
-			PersistedValidationData, PoV, ScheduledCore,
+			PersistedValidationData, PoV, ScheduledCore, OutboundHrmpBandwidth, OutboundHrmpChannelBandwidth,
 		};
 		use std::pin::Pin;
 
@@ -500,7 +559,7 @@ mod tests {
 		fn test_config<Id: Into<ParaId>>(para_id: Id) -> Arc<CollationGenerationConfig> {
 			Arc::new(CollationGenerationConfig {
 				key: CollatorPair::generate().0,
-				collator: Box::new(|_: Hash, _vd: &PersistedValidationData| {
+				collator: Box::new(|_: Hash, _vd: &PersistedValidationData, _bw: &OutboundHrmpBandwidth| {
 					TestCollator.boxed()
 				}),
 				para_id: para_id.into(),
@@ -617,6 +676,12 @@ mod tests {
 						))) => {
 							tx.send(Ok(vec![Default::default(); 3])).unwrap();
 						}
+						Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+							_hash,
+							RuntimeApiRequest::OutboundHrmpChannelsBandwidth(_para_id, tx),
+						))) => {
+							tx.send(Ok(Default::default())).unwrap();
+						}
 						Some(msg) => {
 							panic!("didn't expect any other overseer requests; got {:?}", msg)
 						}
@@ -643,6 +708,107 @@ mod tests {
 			assert_eq!(requested_validation_data, vec![[4; 32].into()]);
 		}
 
+		#[test]
+		fn collator_is_given_the_outbound_hrmp_bandwidth_and_held_to_it() {
+			let activated_hashes: Vec<Hash> = vec![Hash::repeat_byte(4)];
+			let bandwidth = OutboundHrmpBandwidth {
+				max_messages_per_candidate: 7,
+				channels: vec![OutboundHrmpChannelBandwidth {
+					recipient: 1.into(),
+					messages_remaining: 1,
+					bytes_remaining: 4,
+					max_message_size: 4,
+				}],
+			};
+			let message = |recipient: u32, size: usize| OutboundHrmpMessage {
+				recipient: recipient.into(),
+				data: vec![0; size],
+			};
+
+			let overseer_bandwidth = bandwidth.clone();
+			let overseer = |mut handle: TestSubsystemContextHandle<CollationGenerationMessage>| async move {
+				loop {
+					match handle.try_recv().await {
+						None => break,
+						Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+							_hash,
+							RuntimeApiRequest::AvailabilityCores(tx),
+						))) => {
+							tx.send(Ok(vec![CoreState::Scheduled(scheduled_core_for(16))])).unwrap();
+						}
+						Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+							_hash,
+							RuntimeApiRequest::PersistedValidationData(_para_id, _assumption, tx),
+						))) => {
+							tx.send(Ok(Some(Default::default()))).unwrap();
+						}
+						Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+							_hash,
+							RuntimeApiRequest::Validators(tx),
+						))) => {
+							tx.send(Ok(vec![Default::default(); 3])).unwrap();
+						}
+						Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+							_hash,
+							RuntimeApiRequest::OutboundHrmpChannelsBandwidth(para_id, tx),
+						))) => {
+							assert_eq!(para_id, ParaId::from(16));
+							tx.send(Ok(overseer_bandwidth.clone())).unwrap();
+						}
+						Some(msg) => {
+							panic!("didn't expect any other overseer requests; got {:?}", msg)
+						}
+					}
+				}
+			};
+
+			let given_bandwidth = Arc::new(std::sync::Mutex::new(Vec::new()));
+			let collator_given_bandwidth = given_bandwidth.clone();
+			let config = Arc::new(CollationGenerationConfig {
+				key: CollatorPair::generate().0,
+				collator: Box::new(move |_: Hash, _vd: &PersistedValidationData, bw: &OutboundHrmpBandwidth| {
+					collator_given_bandwidth.lock().unwrap().push(bw.clone());
+					// the message to the second recipient has no channel to go over.
+					let collation = Collation {
+						horizontal_messages: vec![message(1, 4), message(2, 1)],
+						..test_collation()
+					};
+					futures::future::ready(Some(collation)).boxed()
+				}),
+				para_id: 16.into(),
+			});
+
+			let (tx, rx) = mpsc::channel(0);
+			let sent_messages = Arc::new(Mutex::new(Vec::new()));
+			let subsystem_sent_messages = sent_messages.clone();
+			subsystem_test_harness(overseer, |mut ctx| async move {
+				handle_new_activations(config, activated_hashes, &mut ctx, Metrics(None), &tx)
+					.await
+					.unwrap();
+
+				std::mem::drop(tx);
+				*subsystem_sent_messages.lock().await = rx.collect().await;
+			});
+
+			assert_eq!(*given_bandwidth.lock().unwrap(), vec![bandwidth]);
+
+			let expect_commitments = CandidateCommitments {
+				horizontal_messages: vec![message(1, 4)],
+				..Default::default()
+			};
+			let sent_messages = Arc::try_unwrap(sent_messages)
+				.expect("subsystem should have shut down by now")
+				.into_inner();
+			assert_eq!(sent_messages.len(), 1);
+			match &sent_messages[0] {
+				AllMessages::CollatorProtocol(CollatorProtocolMessage::DistributeCollation(
+					CandidateReceipt { commitments_hash, .. },
+					_pov,
+				)) => assert_eq!(*commitments_hash, expect_commitments.hash()),
+				_ => panic!("received wrong message type"),
+			}
+		}
+
 		#[test]
 		fn sends_distribute_collation_message() {
 			let activated_hashes: Vec<Hash> = vec![
@@ -688,6 +854,12 @@ mod tests {
 						))) => {
 							tx.send(Ok(vec![Default::default(); 3])).unwrap();
 						}
+						Some(AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+							_hash,
+							RuntimeApiRequest::OutboundHrmpChannelsBandwidth(_para_id, tx),
+						))) => {
+							tx.send(Ok(Default::default())).unwrap();
+						}
 						Some(msg) => {
 							panic!("didn't expect any other overseer requests; got {:?}", msg)
 						}
@@ -775,4 +947,77 @@ mod tests {
 			}
 		}
 	}
+
+	mod trim_horizontal_messages {
+		use super::super::*;
+		use tetcoin_primitives::v1::OutboundHrmpChannelBandwidth;
+
+		fn message(recipient: u32, size: usize) -> OutboundHrmpMessage<ParaId> {
+			OutboundHrmpMessage {
+				recipient: recipient.into(),
+				data: vec![0; size],
+			}
+		}
+
+		fn channel(recipient: u32, messages_remaining: u32, bytes_remaining: u32) -> OutboundHrmpChannelBandwidth {
+			OutboundHrmpChannelBandwidth {
+				recipient: recipient.into(),
+				messages_remaining,
+				bytes_remaining,
+				max_message_size: 8,
+			}
+		}
+
+		fn bandwidth(
+			max_messages_per_candidate: u32,
+			channels: Vec<OutboundHrmpChannelBandwidth>,
+		) -> OutboundHrmpBandwidth {
+			OutboundHrmpBandwidth { max_messages_per_candidate, channels }
+		}
+
+		#[test]
+		fn keeps_messages_fitting_the_bandwidth() {
+			let bandwidth = bandwidth(3, vec![channel(1, 1, 16), channel(2, 4, 16), channel(3, 4, 16)]);
+			let messages = vec![message(3, 8), message(1, 4), message(2, 1)];
+
+			assert_eq!(
+				trim_horizontal_messages(messages, &bandwidth),
+				vec![message(1, 4), message(2, 1), message(3, 8)],
+			);
+		}
+
+		#[test]
+		fn drops_messages_exceeding_the_bandwidth() {
+			let bandwidth = bandwidth(
+				4,
+				vec![channel(1, 0, 16), channel(2, 4, 4), channel(3, 4, 16), channel(4, 4, 16)],
+			);
+			let messages = vec![
+				// the channel is full.
+				message(1, 1),
+				// doesn't fit into the remaining bytes.
+				message(2, 5),
+				// exceeds the maximum message size.
+				message(3, 9),
+				// only the first message to a recipient is kept.
+				message(4, 2),
+				message(4, 3),
+				// there is no such channel.
+				message(5, 1),
+			];
+
+			assert_eq!(trim_horizontal_messages(messages, &bandwidth), vec![message(4, 2)]);
+		}
+
+		#[test]
+		fn drops_messages_exceeding_the_candidate_limit() {
+			let bandwidth = bandwidth(2, vec![channel(1, 4, 16), channel(2, 4, 16), channel(3, 4, 16)]);
+			let messages = vec![message(3, 1), message(2, 1), message(1, 1)];
+
+			assert_eq!(
+				trim_horizontal_messages(messages, &bandwidth),
+				vec![message(1, 1), message(2, 1)],
+			);
+		}
+	}
 }
@@ -32,7 +32,7 @@ use tetcoin_primitives::v1::{
 	PoV, SigningContext, ValidatorId, ValidatorIndex, ValidatorSignature, ValidityAttestation,
 };
 use tetcoin_node_primitives::{
-	Statement, SignedFullStatement, ValidationResult,
+	Statement, SignedFullStatement, ValidationResult, ValidationPriority,
 };
 use tetcoin_subsystem::{
 	JaegerSpan, PerLeafSpan,
@@ -356,6 +356,7 @@ async fn request_candidate_validation(
 			CandidateValidationMessage::ValidateFromChainState(
 				candidate,
 				pov,
+				ValidationPriority::Backing,
 				tx,
 			)
 		).into()
@@ -1448,6 +1449,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate.descriptor() => {
@@ -1586,6 +1588,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate_a.descriptor() => {
@@ -1735,6 +1738,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate_a.descriptor() => {
@@ -1884,6 +1888,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate_a.descriptor() => {
@@ -2063,6 +2068,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate_a.descriptor() => {
@@ -2091,6 +2097,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate_b.descriptor() => {
@@ -2205,6 +2212,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate.descriptor() => {
@@ -2271,6 +2279,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						_,
 						pov,
+						ValidationPriority::Backing,
 						_,
 					)
 				) => {
@@ -2342,6 +2351,7 @@ mod tests {
 					CandidateValidationMessage::ValidateFromChainState(
 						c,
 						pov,
+						ValidationPriority::Backing,
 						tx,
 					)
 				) if pov == pov && &c == candidate.descriptor() => {
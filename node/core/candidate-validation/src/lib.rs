@@ -19,6 +19,10 @@
 //! This handles incoming requests from other subsystems to validate candidates
 //! according to a validation function. This delegates validation to an underlying
 //! pool of processes used for execution of the Wasm.
+//!
+//! On every new leaf, this also pre-checks the validation code the runtime put up for a vote
+//! and, if the node is a validator, submits a signed statement with the verdict. Pre-checking
+//! runs in the background, isolated just like validation is.
 
 #![deny(unused_crate_dependencies, unused_results)]
 #![warn(missing_docs)]
@@ -31,12 +35,13 @@ use tetcoin_subsystem::{
 		ValidationFailed, RuntimeApiRequest,
 	},
 };
-use tetcoin_node_subsystem_util::metrics::{self, prometheus};
+use tetcoin_node_subsystem_util::{signing_key, metrics::{self, prometheus}};
 use tetcoin_subsystem::errors::RuntimeApiError;
-use tetcoin_node_primitives::{ValidationResult, InvalidCandidate};
+use tetcoin_node_primitives::{ValidationResult, ValidationPriority, InvalidCandidate};
 use tetcoin_primitives::v1::{
 	ValidationCode, PoV, CandidateDescriptor, PersistedValidationData,
-	OccupiedCoreAssumption, Hash, CandidateCommitments,
+	OccupiedCoreAssumption, Hash, CandidateCommitments, PvfCheckStatement,
+	SessionIndex, ValidatorId, ValidatorIndex, ValidatorSignature, PvfExecutionLimits,
 };
 use tetcoin_parachain::wasm_executor::{
 	self, IsolationStrategy, ValidationError, InvalidCandidate as WasmInvalidCandidate
@@ -45,28 +50,45 @@ use tetcoin_parachain::primitives::{ValidationResult as WasmValidationResult, Va
 
 use tetsy_scale_codec::Encode;
 use tet_core::traits::SpawnNamed;
+use tet_application_crypto::AppKey;
+use tp_keystore::{CryptoStore, SyncCryptoStorePtr, Error as KeystoreError};
 
-use futures::channel::oneshot;
+use futures::channel::{mpsc, oneshot};
 use futures::prelude::*;
 
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
 use std::sync::Arc;
 
 const LOG_TARGET: &'static str = "candidate_validation";
 
+/// The capacity of the channel over which background tasks have the main loop send messages on
+/// their behalf.
+const TO_OVERSEER_CHANNEL_SIZE: usize = 64;
+
+/// Messages which the background tasks have the main loop send to the overseer on their behalf.
+type ToOverseer = mpsc::Sender<AllMessages>;
+
 /// The candidate validation subsystem.
 pub struct CandidateValidationSubsystem<S> {
 	spawn: S,
+	keystore: SyncCryptoStorePtr,
 	metrics: Metrics,
 	isolation_strategy: IsolationStrategy,
 }
 
 impl<S> CandidateValidationSubsystem<S> {
-	/// Create a new `CandidateValidationSubsystem` with the given task spawner and isolation
-	/// strategy.
+	/// Create a new `CandidateValidationSubsystem` with the given task spawner, keystore and
+	/// isolation strategy. The keystore is used to sign pre-checking statements.
 	///
 	/// Check out [`IsolationStrategy`] to get more details.
-	pub fn new(spawn: S, metrics: Metrics, isolation_strategy: IsolationStrategy) -> Self {
-		CandidateValidationSubsystem { spawn, metrics, isolation_strategy }
+	pub fn new(
+		spawn: S,
+		keystore: SyncCryptoStorePtr,
+		metrics: Metrics,
+		isolation_strategy: IsolationStrategy,
+	) -> Self {
+		CandidateValidationSubsystem { spawn, keystore, metrics, isolation_strategy }
 	}
 }
 
@@ -75,7 +97,7 @@ impl<S, C> Subsystem<C> for CandidateValidationSubsystem<S> where
 	S: SpawnNamed + Clone + 'static,
 {
 	fn start(self, ctx: C) -> SpawnedSubsystem {
-		let future = run(ctx, self.spawn, self.metrics, self.isolation_strategy)
+		let future = run(ctx, self.spawn, self.keystore, self.metrics, self.isolation_strategy)
 			.map_err(|e| SubsystemError::with_origin("candidate-validation", e))
 			.boxed();
 		SpawnedSubsystem {
@@ -85,97 +107,400 @@ impl<S, C> Subsystem<C> for CandidateValidationSubsystem<S> where
 	}
 }
 
-#[tracing::instrument(skip(ctx, spawn, metrics), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(skip(ctx, spawn, keystore, metrics), fields(subsystem = LOG_TARGET))]
 async fn run(
 	mut ctx: impl SubsystemContext<Message = CandidateValidationMessage>,
 	spawn: impl SpawnNamed + Clone + 'static,
+	keystore: SyncCryptoStorePtr,
 	metrics: Metrics,
 	isolation_strategy: IsolationStrategy,
 ) -> SubsystemResult<()> {
+	let (to_overseer, mut from_tasks) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
+
+	// the pre-checking state is owned by the pre-checking task while one is running.
+	let mut pvf_check_state = Some(PvfCheckState::default());
+	let mut precheck_done = future::Fuse::terminated();
+	// the latest leaf to pre-check once the running pre-checking task is done.
+	let mut next_precheck_leaf = None;
+
 	loop {
-		match ctx.recv().await? {
-			FromOverseer::Signal(OverseerSignal::ActiveLeaves(_)) => {}
+		let from_overseer = futures::select! {
+			from_overseer = ctx.recv().fuse() => from_overseer?,
+			msg = from_tasks.next() => {
+				if let Some(msg) = msg {
+					ctx.send_message(msg).await;
+				}
+				continue;
+			}
+			state = precheck_done => {
+				// a task which died is not worth taking down the subsystem for, but its votes are lost.
+				let state = state.unwrap_or_default();
+				match next_precheck_leaf.take() {
+					Some(leaf) => precheck_done = spawn_precheck_pvfs(
+						&mut ctx,
+						&to_overseer,
+						&keystore,
+						&isolation_strategy,
+						state,
+						leaf,
+					).await?,
+					None => pvf_check_state = Some(state),
+				}
+				continue;
+			}
+		};
+
+		match from_overseer {
+			FromOverseer::Signal(OverseerSignal::ActiveLeaves(update)) => {
+				if let Some(leaf) = update.activated.last().map(|(leaf, _)| *leaf) {
+					match pvf_check_state.take() {
+						Some(state) => precheck_done = spawn_precheck_pvfs(
+							&mut ctx,
+							&to_overseer,
+							&keystore,
+							&isolation_strategy,
+							state,
+							leaf,
+						).await?,
+						None => next_precheck_leaf = Some(leaf),
+					}
+				}
+			}
 			FromOverseer::Signal(OverseerSignal::BlockFinalized(..)) => {}
 			FromOverseer::Signal(OverseerSignal::Conclude) => return Ok(()),
 			FromOverseer::Communication { msg } => match msg {
 				CandidateValidationMessage::ValidateFromChainState(
 					descriptor,
 					pov,
+					priority,
 					response_sender,
 				) => {
-					let _timer = metrics.time_validate_from_chain_state();
-
-					let res = spawn_validate_from_chain_state(
-						&mut ctx,
-						isolation_strategy.clone(),
-						descriptor,
-						pov,
-						spawn.clone(),
-						&metrics,
-					).await;
-
-					match res {
-						Ok(x) => {
-							metrics.on_validation_event(&x);
-							let _ = response_sender.send(x);
+					let mut to_overseer = to_overseer.clone();
+					let isolation_strategy = isolation_strategy.clone();
+					let spawn = spawn.clone();
+					let metrics = metrics.clone();
+					let fut = async move {
+						let _timer = metrics.time_validate_from_chain_state();
+
+						let res = validate_from_chain_state(
+							&mut to_overseer,
+							isolation_strategy,
+							descriptor,
+							pov,
+							priority,
+							spawn,
+							&metrics,
+						).await;
+
+						match res {
+							Ok(x) => {
+								metrics.on_validation_event(&x);
+								let _ = response_sender.send(x);
+							}
+							Err(e) => tracing::debug!(
+								target: LOG_TARGET,
+								err = ?e,
+								"Failed to validate a candidate from the chain state",
+							),
 						}
-						Err(e) => return Err(e),
-					}
+					};
+
+					spawn_validation(&mut ctx, fut).await?;
 				}
 				CandidateValidationMessage::ValidateFromExhaustive(
 					persisted_validation_data,
 					validation_code,
+					limits,
 					descriptor,
 					pov,
+					priority,
 					response_sender,
 				) => {
-					let _timer = metrics.time_validate_from_exhaustive();
+					let isolation_strategy = isolation_strategy.clone();
+					let spawn = spawn.clone();
+					let metrics = metrics.clone();
+					let fut = async move {
+						let _timer = metrics.time_validate_from_exhaustive();
+
+						let res = validate_candidate_exhaustive::<RealValidationBackend, _>(
+							(isolation_strategy, limits, priority),
+							persisted_validation_data,
+							validation_code,
+							descriptor,
+							pov,
+							spawn,
+							&metrics,
+						);
+
+						metrics.on_validation_event(&res);
+						if let Err(_e) = response_sender.send(res) {
+							tracing::warn!(
+								target: LOG_TARGET,
+								"Requester of candidate validation dropped",
+							)
+						}
+					};
 
-					let res = spawn_validate_exhaustive(
-						&mut ctx,
-						isolation_strategy.clone(),
-						persisted_validation_data,
-						validation_code,
-						descriptor,
-						pov,
-						spawn.clone(),
-						&metrics,
-					).await;
-
-					match res {
-						Ok(x) => {
-							metrics.on_validation_event(&x);
-							if let Err(_e) = response_sender.send(x) {
-								tracing::warn!(
-									target: LOG_TARGET,
-									"Requester of candidate validation dropped",
-								)
-							}
-						},
-						Err(e) => return Err(e),
-					}
+					spawn_validation(&mut ctx, fut).await?;
 				}
 			}
 		}
 	}
 }
 
-async fn runtime_api_request<T>(
+/// Spawns a detached task validating a candidate and sending the result to the requester.
+///
+/// Validations don't wait for each other, so work of every priority is handed to the validation
+/// host right away, which is what gives precedence to the more urgent work.
+async fn spawn_validation(
 	ctx: &mut impl SubsystemContext<Message = CandidateValidationMessage>,
+	fut: impl Future<Output = ()> + Send + 'static,
+) -> SubsystemResult<()> {
+	// validation blocks until the validation code finishes running.
+	ctx.spawn_blocking("blocking-candidate-validation-task", fut.boxed()).await
+}
+
+/// Requests from the runtime API on behalf of a background task, through the main loop.
+async fn runtime_api_request<T>(
+	to_overseer: &mut ToOverseer,
 	relay_parent: Hash,
 	request: RuntimeApiRequest,
 	receiver: oneshot::Receiver<Result<T, RuntimeApiError>>,
 ) -> SubsystemResult<Result<T, RuntimeApiError>> {
-	ctx.send_message(
+	to_overseer.send(
 		AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 			relay_parent,
 			request,
 		))
-	).await;
+	).await?;
 
 	receiver.await.map_err(Into::into)
 }
 
+/// What the local node knows about the validation code under pre-checking.
+#[derive(Default)]
+struct PvfCheckState {
+	/// The verdicts on the code which was already pre-checked, by code hash.
+	/// `true` means the code could be prepared for execution.
+	judgements: HashMap<Hash, bool>,
+	/// The session the statements in `voted` were submitted in.
+	session_index: Option<SessionIndex>,
+	/// The code hashes a statement was submitted for in the current session.
+	voted: HashSet<Hash>,
+}
+
+/// Spawns a background task which pre-checks the validation code awaiting votes as of the given
+/// leaf, taking over the state. The returned future resolves to the state once the task is done.
+async fn spawn_precheck_pvfs(
+	ctx: &mut impl SubsystemContext<Message = CandidateValidationMessage>,
+	to_overseer: &ToOverseer,
+	keystore: &SyncCryptoStorePtr,
+	isolation_strategy: &IsolationStrategy,
+	mut state: PvfCheckState,
+	leaf: Hash,
+) -> SubsystemResult<future::Fuse<oneshot::Receiver<PvfCheckState>>> {
+	let (tx, rx) = oneshot::channel();
+	let mut to_overseer = to_overseer.clone();
+	let keystore = keystore.clone();
+	let isolation_strategy = isolation_strategy.clone();
+
+	let fut = async move {
+		if let Err(e) = precheck_pvfs(&mut to_overseer, &keystore, &isolation_strategy, &mut state, leaf).await {
+			tracing::debug!(target: LOG_TARGET, err = ?e, "Failed to pre-check the validation code");
+		}
+
+		let _ = tx.send(state);
+	};
+
+	// pre-checking waits for the validation code to be compiled.
+	ctx.spawn_blocking("blocking-pvf-precheck-task", fut.boxed()).await?;
+	Ok(rx.fuse())
+}
+
+/// Pre-checks all the validation code awaiting votes as of the given leaf and, if the node is a
+/// validator, submits a statement on each one not voted on yet in the current session.
+///
+/// Failures to talk to the runtime or to run the validation code are logged and otherwise
+/// ignored, since the next leaf will give another opportunity to vote.
+#[tracing::instrument(level = "trace", skip(to_overseer, keystore, isolation_strategy, state), fields(subsystem = LOG_TARGET))]
+async fn precheck_pvfs(
+	to_overseer: &mut ToOverseer,
+	keystore: &SyncCryptoStorePtr,
+	isolation_strategy: &IsolationStrategy,
+	state: &mut PvfCheckState,
+	leaf: Hash,
+) -> SubsystemResult<()> {
+	let (tx, rx) = oneshot::channel();
+	let pending = match runtime_api_request(
+		to_overseer,
+		leaf,
+		RuntimeApiRequest::PvfsRequirePrecheck(tx),
+		rx,
+	).await? {
+		Ok(pending) => pending,
+		Err(e) => {
+			tracing::debug!(target: LOG_TARGET, err = ?e, "Failed to fetch the code requiring pre-checking");
+			return Ok(());
+		}
+	};
+
+	state.judgements.retain(|code_hash, _| pending.contains(code_hash));
+	if pending.is_empty() {
+		return Ok(());
+	}
+
+	let (tx, rx) = oneshot::channel();
+	let session_index = match runtime_api_request(
+		to_overseer,
+		leaf,
+		RuntimeApiRequest::SessionIndexForChild(tx),
+		rx,
+	).await? {
+		Ok(session_index) => session_index,
+		Err(e) => {
+			tracing::debug!(target: LOG_TARGET, err = ?e, "Failed to fetch the session index");
+			return Ok(());
+		}
+	};
+
+	// votes are reset at every session change.
+	if state.session_index != Some(session_index) {
+		state.session_index = Some(session_index);
+		state.voted.clear();
+	}
+
+	let (tx, rx) = oneshot::channel();
+	let validators = match runtime_api_request(
+		to_overseer,
+		leaf,
+		RuntimeApiRequest::Validators(tx),
+		rx,
+	).await? {
+		Ok(validators) => validators,
+		Err(e) => {
+			tracing::debug!(target: LOG_TARGET, err = ?e, "Failed to fetch the validators");
+			return Ok(());
+		}
+	};
+
+	let key = match signing_key(&validators, keystore.clone()).await {
+		Some(key) => key,
+		// not a validator in this session, nothing to vote on.
+		None => return Ok(()),
+	};
+	let validator_index = validators.iter()
+		.position(|v| v == &key)
+		.expect("signing key is one of the validators; qed") as ValidatorIndex;
+
+	// the limits are only fetched once there is code to pre-check.
+	let mut limits = None;
+
+	for code_hash in pending {
+		if state.voted.contains(&code_hash) {
+			continue;
+		}
+
+		let accept = match state.judgements.get(&code_hash) {
+			Some(accept) => *accept,
+			None => {
+				let (tx, rx) = oneshot::channel();
+				let validation_code = match runtime_api_request(
+					to_overseer,
+					leaf,
+					RuntimeApiRequest::ValidationCodeByHash(code_hash, tx),
+					rx,
+				).await? {
+					Ok(Some(validation_code)) => validation_code,
+					Ok(None) | Err(_) => {
+						tracing::debug!(
+							target: LOG_TARGET,
+							?code_hash,
+							"Failed to fetch the code requiring pre-checking",
+						);
+						continue;
+					}
+				};
+
+				if limits.is_none() {
+					let (tx, rx) = oneshot::channel();
+					match runtime_api_request(
+						to_overseer,
+						leaf,
+						RuntimeApiRequest::PvfExecutionLimits(tx),
+						rx,
+					).await? {
+						Ok(l) => limits = Some(l),
+						Err(e) => {
+							tracing::debug!(target: LOG_TARGET, err = ?e, "Failed to fetch the PVF execution limits");
+							return Ok(());
+						}
+					}
+				}
+				let limits = limits.expect("fetched above; qed");
+
+				let accept = match wasm_executor::precheck(&validation_code.0, isolation_strategy, limits) {
+					Ok(()) => true,
+					Err(ValidationError::InvalidCandidate(e)) => {
+						tracing::debug!(target: LOG_TARGET, err = ?e, "Validation code failed pre-checking");
+						false
+					}
+					Err(ValidationError::Internal(e)) => {
+						tracing::warn!(target: LOG_TARGET, err = ?e, "Failed to pre-check validation code");
+						continue;
+					}
+				};
+				let _ = state.judgements.insert(code_hash, accept);
+				accept
+			}
+		};
+
+		let stmt = PvfCheckStatement {
+			accept,
+			subject: code_hash,
+			session_index,
+			validator_index,
+		};
+
+		let signature = match sign_pvf_check_statement(keystore, &key, &stmt).await {
+			Ok(signature) => signature,
+			Err(e) => {
+				tracing::warn!(target: LOG_TARGET, err = ?e, "Failed to sign a pre-checking statement");
+				continue;
+			}
+		};
+
+		let (tx, rx) = oneshot::channel();
+		match runtime_api_request(
+			to_overseer,
+			leaf,
+			RuntimeApiRequest::SubmitPvfCheckStatement(stmt, signature, tx),
+			rx,
+		).await? {
+			Ok(()) => {
+				let _ = state.voted.insert(code_hash);
+			}
+			Err(e) => {
+				tracing::debug!(target: LOG_TARGET, err = ?e, "Failed to submit a pre-checking statement");
+			}
+		}
+	}
+
+	Ok(())
+}
+
+async fn sign_pvf_check_statement(
+	keystore: &SyncCryptoStorePtr,
+	key: &ValidatorId,
+	stmt: &PvfCheckStatement,
+) -> Result<ValidatorSignature, KeystoreError> {
+	CryptoStore::sign_with(
+		&**keystore,
+		ValidatorId::ID,
+		&key.into(),
+		&stmt.signing_payload(),
+	).await?.try_into().map_err(|_| KeystoreError::KeyNotSupported(ValidatorId::ID))
+}
+
 #[derive(Debug)]
 enum AssumptionCheckOutcome {
 	Matches(PersistedValidationData, ValidationCode),
@@ -183,16 +508,16 @@ enum AssumptionCheckOutcome {
 	BadRequest,
 }
 
-#[tracing::instrument(level = "trace", skip(ctx), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(level = "trace", skip(to_overseer), fields(subsystem = LOG_TARGET))]
 async fn check_assumption_validation_data(
-	ctx: &mut impl SubsystemContext<Message = CandidateValidationMessage>,
+	to_overseer: &mut ToOverseer,
 	descriptor: &CandidateDescriptor,
 	assumption: OccupiedCoreAssumption,
 ) -> SubsystemResult<AssumptionCheckOutcome> {
 	let validation_data = {
 		let (tx, rx) = oneshot::channel();
 		let d = runtime_api_request(
-			ctx,
+			to_overseer,
 			descriptor.relay_parent,
 			RuntimeApiRequest::PersistedValidationData(
 				descriptor.para_id,
@@ -215,7 +540,7 @@ async fn check_assumption_validation_data(
 	SubsystemResult::Ok(if descriptor.persisted_validation_data_hash == persisted_validation_data_hash {
 		let (code_tx, code_rx) = oneshot::channel();
 		let validation_code = runtime_api_request(
-			ctx,
+			to_overseer,
 			descriptor.relay_parent,
 			RuntimeApiRequest::ValidationCode(
 				descriptor.para_id,
@@ -234,9 +559,9 @@ async fn check_assumption_validation_data(
 	})
 }
 
-#[tracing::instrument(level = "trace", skip(ctx), fields(subsystem = LOG_TARGET))]
+#[tracing::instrument(level = "trace", skip(to_overseer), fields(subsystem = LOG_TARGET))]
 async fn find_assumed_validation_data(
-	ctx: &mut impl SubsystemContext<Message = CandidateValidationMessage>,
+	to_overseer: &mut ToOverseer,
 	descriptor: &CandidateDescriptor,
 ) -> SubsystemResult<AssumptionCheckOutcome> {
 	// The candidate descriptor has a `persisted_validation_data_hash` which corresponds to
@@ -254,7 +579,7 @@ async fn find_assumed_validation_data(
 
 	// Consider running these checks in parallel to reduce validation latency.
 	for assumption in ASSUMPTIONS {
-		let outcome = check_assumption_validation_data(ctx, descriptor, *assumption).await?;
+		let outcome = check_assumption_validation_data(to_overseer, descriptor, *assumption).await?;
 
 		match outcome {
 			AssumptionCheckOutcome::Matches(_, _) => return Ok(outcome),
@@ -266,17 +591,18 @@ async fn find_assumed_validation_data(
 	Ok(AssumptionCheckOutcome::DoesNotMatch)
 }
 
-#[tracing::instrument(level = "trace", skip(ctx, pov, spawn, metrics), fields(subsystem = LOG_TARGET))]
-async fn spawn_validate_from_chain_state(
-	ctx: &mut impl SubsystemContext<Message = CandidateValidationMessage>,
+#[tracing::instrument(level = "trace", skip(to_overseer, pov, spawn, metrics), fields(subsystem = LOG_TARGET))]
+async fn validate_from_chain_state(
+	to_overseer: &mut ToOverseer,
 	isolation_strategy: IsolationStrategy,
 	descriptor: CandidateDescriptor,
 	pov: Arc<PoV>,
+	priority: ValidationPriority,
 	spawn: impl SpawnNamed + 'static,
 	metrics: &Metrics,
 ) -> SubsystemResult<Result<ValidationResult, ValidationFailed>> {
 	let (validation_data, validation_code) =
-		match find_assumed_validation_data(ctx, &descriptor).await? {
+		match find_assumed_validation_data(to_overseer, &descriptor).await? {
 			AssumptionCheckOutcome::Matches(validation_data, validation_code) => {
 				(validation_data, validation_code)
 			}
@@ -291,22 +617,33 @@ async fn spawn_validate_from_chain_state(
 			}
 		};
 
-	let validation_result = spawn_validate_exhaustive(
-		ctx,
-		isolation_strategy,
+	// the limits are those of the relay parent, so that every validator checking the candidate
+	// applies the same ones.
+	let (tx, rx) = oneshot::channel();
+	let limits = match runtime_api_request(
+		to_overseer,
+		descriptor.relay_parent,
+		RuntimeApiRequest::PvfExecutionLimits(tx),
+		rx,
+	).await? {
+		Ok(limits) => limits,
+		Err(_) => return Ok(Err(ValidationFailed("PVF execution limits: Bad request".into()))),
+	};
+
+	let validation_result = validate_candidate_exhaustive::<RealValidationBackend, _>(
+		(isolation_strategy, limits, priority),
 		validation_data,
 		validation_code,
 		descriptor.clone(),
 		pov,
 		spawn,
 		metrics,
-	)
-	.await;
+	);
 
-	if let Ok(Ok(ValidationResult::Valid(ref outputs, _))) = validation_result {
+	if let Ok(ValidationResult::Valid(ref outputs, _)) = validation_result {
 		let (tx, rx) = oneshot::channel();
 		match runtime_api_request(
-			ctx,
+			to_overseer,
 			descriptor.relay_parent,
 			RuntimeApiRequest::CheckValidationOutputs(descriptor.para_id, outputs.clone(), tx),
 			rx,
@@ -325,38 +662,7 @@ async fn spawn_validate_from_chain_state(
 		}
 	}
 
-	validation_result
-}
-
-#[tracing::instrument(level = "trace", skip(ctx, validation_code, pov, spawn, metrics), fields(subsystem = LOG_TARGET))]
-async fn spawn_validate_exhaustive(
-	ctx: &mut impl SubsystemContext<Message = CandidateValidationMessage>,
-	isolation_strategy: IsolationStrategy,
-	persisted_validation_data: PersistedValidationData,
-	validation_code: ValidationCode,
-	descriptor: CandidateDescriptor,
-	pov: Arc<PoV>,
-	spawn: impl SpawnNamed + 'static,
-	metrics: &Metrics,
-) -> SubsystemResult<Result<ValidationResult, ValidationFailed>> {
-	let (tx, rx) = oneshot::channel();
-	let metrics = metrics.clone();
-	let fut = async move {
-		let res = validate_candidate_exhaustive::<RealValidationBackend, _>(
-			isolation_strategy,
-			persisted_validation_data,
-			validation_code,
-			descriptor,
-			pov,
-			spawn,
-			&metrics,
-		);
-
-		let _ = tx.send(res);
-	};
-
-	ctx.spawn_blocking("blocking-candidate-validation-task", fut.boxed()).await?;
-	rx.await.map_err(Into::into)
+	Ok(validation_result)
 }
 
 /// Does basic checks of a candidate. Provide the encoded PoV-block. Returns `Ok` if basic checks
@@ -399,10 +705,10 @@ trait ValidationBackend {
 struct RealValidationBackend;
 
 impl ValidationBackend for RealValidationBackend {
-	type Arg = IsolationStrategy;
+	type Arg = (IsolationStrategy, PvfExecutionLimits, ValidationPriority);
 
 	fn validate<S: SpawnNamed + 'static>(
-		isolation_strategy: IsolationStrategy,
+		(isolation_strategy, limits, priority): (IsolationStrategy, PvfExecutionLimits, ValidationPriority),
 		validation_code: &ValidationCode,
 		params: ValidationParams,
 		spawn: S,
@@ -411,6 +717,8 @@ impl ValidationBackend for RealValidationBackend {
 			&validation_code.0,
 			params,
 			&isolation_strategy,
+			limits,
+			priority,
 			spawn,
 		)
 	}
@@ -447,6 +755,10 @@ fn validate_candidate_exhaustive<B: ValidationBackend, S: SpawnNamed + 'static>(
 	match B::validate(backend_arg, &validation_code, params, spawn) {
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::Timeout)) =>
 			Ok(ValidationResult::Invalid(InvalidCandidate::Timeout)),
+		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::OutOfMemory)) =>
+			Ok(ValidationResult::Invalid(InvalidCandidate::OutOfMemory)),
+		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::Trap(e))) =>
+			Ok(ValidationResult::Invalid(InvalidCandidate::Trap(e))),
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::ParamsTooLarge(l))) =>
 			Ok(ValidationResult::Invalid(InvalidCandidate::ParamsTooLarge(l as u64))),
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::CodeTooLarge(l))) =>
@@ -457,6 +769,10 @@ fn validate_candidate_exhaustive<B: ValidationBackend, S: SpawnNamed + 'static>(
 			Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(e.to_string()))),
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::ExternalWasmExecutor(e))) =>
 			Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(e.to_string()))),
+		Err(ValidationError::InvalidCandidate(e @ WasmInvalidCandidate::InvalidModule(_)))
+			| Err(ValidationError::InvalidCandidate(e @ WasmInvalidCandidate::ReservedExport(_)))
+			| Err(ValidationError::InvalidCandidate(e @ WasmInvalidCandidate::Preparation(_))) =>
+			Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(e.to_string()))),
 		Err(ValidationError::Internal(e)) => Err(ValidationFailed(e.to_string())),
 		Ok(res) => {
 			if res.head_data.hash() != descriptor.para_head {
@@ -569,12 +885,14 @@ impl metrics::Metrics for Metrics {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use tetcoin_node_subsystem_test_helpers as test_helpers;
 	use tetcoin_primitives::v1::{HeadData, BlockData, UpwardMessage};
 	use tet_core::testing::TaskExecutor;
 	use futures::executor;
 	use assert_matches::assert_matches;
 	use tp_keyring::Sr25519Keyring;
+	use tp_keystore::SyncCryptoStore;
+	use tc_keystore::LocalKeystore;
+	use tet_application_crypto::RuntimeAppPublic;
 
 	struct MockValidationBackend;
 
@@ -622,18 +940,17 @@ mod tests {
 		candidate.persisted_validation_data_hash = persisted_validation_data_hash;
 		candidate.para_id = para_id;
 
-		let pool = TaskExecutor::new();
-		let (mut ctx, mut ctx_handle) = test_helpers::make_subsystem_context(pool.clone());
+		let (mut to_overseer, mut from_task) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
 
 		let (check_fut, check_result) = check_assumption_validation_data(
-			&mut ctx,
+			&mut to_overseer,
 			&candidate,
 			OccupiedCoreAssumption::Included,
 		).remote_handle();
 
 		let test_fut = async move {
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::PersistedValidationData(
@@ -650,7 +967,7 @@ mod tests {
 			);
 
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::ValidationCode(p, OccupiedCoreAssumption::Included, tx)
@@ -686,18 +1003,17 @@ mod tests {
 		candidate.persisted_validation_data_hash = persisted_validation_data_hash;
 		candidate.para_id = para_id;
 
-		let pool = TaskExecutor::new();
-		let (mut ctx, mut ctx_handle) = test_helpers::make_subsystem_context(pool.clone());
+		let (mut to_overseer, mut from_task) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
 
 		let (check_fut, check_result) = check_assumption_validation_data(
-			&mut ctx,
+			&mut to_overseer,
 			&candidate,
 			OccupiedCoreAssumption::TimedOut,
 		).remote_handle();
 
 		let test_fut = async move {
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::PersistedValidationData(
@@ -714,7 +1030,7 @@ mod tests {
 			);
 
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::ValidationCode(p, OccupiedCoreAssumption::TimedOut, tx)
@@ -748,18 +1064,17 @@ mod tests {
 		candidate.persisted_validation_data_hash = persisted_validation_data_hash;
 		candidate.para_id = para_id;
 
-		let pool = TaskExecutor::new();
-		let (mut ctx, mut ctx_handle) = test_helpers::make_subsystem_context(pool.clone());
+		let (mut to_overseer, mut from_task) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
 
 		let (check_fut, check_result) = check_assumption_validation_data(
-			&mut ctx,
+			&mut to_overseer,
 			&candidate,
 			OccupiedCoreAssumption::Included,
 		).remote_handle();
 
 		let test_fut = async move {
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::PersistedValidationData(
@@ -794,18 +1109,17 @@ mod tests {
 		candidate.persisted_validation_data_hash = persisted_validation_data_hash;
 		candidate.para_id = para_id;
 
-		let pool = TaskExecutor::new();
-		let (mut ctx, mut ctx_handle) = test_helpers::make_subsystem_context(pool.clone());
+		let (mut to_overseer, mut from_task) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
 
 		let (check_fut, check_result) = check_assumption_validation_data(
-			&mut ctx,
+			&mut to_overseer,
 			&candidate,
 			OccupiedCoreAssumption::TimedOut,
 		).remote_handle();
 
 		let test_fut = async move {
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::PersistedValidationData(
@@ -822,7 +1136,7 @@ mod tests {
 			);
 
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::ValidationCode(p, OccupiedCoreAssumption::TimedOut, tx)
@@ -852,18 +1166,17 @@ mod tests {
 		candidate.persisted_validation_data_hash = [3; 32].into();
 		candidate.para_id = para_id;
 
-		let pool = TaskExecutor::new();
-		let (mut ctx, mut ctx_handle) = test_helpers::make_subsystem_context(pool.clone());
+		let (mut to_overseer, mut from_task) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
 
 		let (check_fut, check_result) = check_assumption_validation_data(
-			&mut ctx,
+			&mut to_overseer,
 			&candidate,
 			OccupiedCoreAssumption::Included,
 		).remote_handle();
 
 		let test_fut = async move {
 			assert_matches!(
-				ctx_handle.recv().await,
+				from_task.next().await.unwrap(),
 				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
 					rp,
 					RuntimeApiRequest::PersistedValidationData(
@@ -986,4 +1299,159 @@ mod tests {
 
 		assert_matches!(v, Ok(ValidationResult::Invalid(InvalidCandidate::Timeout)));
 	}
+	#[test]
+	fn candidate_validation_out_of_memory_is_invalid() {
+		let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
+
+		let pov = PoV { block_data: BlockData(vec![1; 32]) };
+
+		let mut descriptor = CandidateDescriptor::default();
+		descriptor.pov_hash = pov.hash();
+		collator_sign(&mut descriptor, Sr25519Keyring::Alice);
+
+		assert!(perform_basic_checks(&descriptor, validation_data.max_pov_size, &pov).is_ok());
+
+		let v = validate_candidate_exhaustive::<MockValidationBackend, _>(
+			MockValidationArg {
+				result: Err(ValidationError::InvalidCandidate(
+					WasmInvalidCandidate::OutOfMemory
+				))
+			},
+			validation_data,
+			vec![1, 2, 3].into(),
+			descriptor,
+			Arc::new(pov),
+			TaskExecutor::new(),
+			&Default::default(),
+		);
+
+		assert_matches!(v, Ok(ValidationResult::Invalid(InvalidCandidate::OutOfMemory)));
+	}
+
+	#[test]
+	fn candidate_validation_trap_is_invalid() {
+		let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
+
+		let pov = PoV { block_data: BlockData(vec![1; 32]) };
+
+		let mut descriptor = CandidateDescriptor::default();
+		descriptor.pov_hash = pov.hash();
+		collator_sign(&mut descriptor, Sr25519Keyring::Alice);
+
+		assert!(perform_basic_checks(&descriptor, validation_data.max_pov_size, &pov).is_ok());
+
+		let v = validate_candidate_exhaustive::<MockValidationBackend, _>(
+			MockValidationArg {
+				result: Err(ValidationError::InvalidCandidate(
+					WasmInvalidCandidate::from("Wasm execution trapped: unreachable".to_string())
+				))
+			},
+			validation_data,
+			vec![1, 2, 3].into(),
+			descriptor,
+			Arc::new(pov),
+			TaskExecutor::new(),
+			&Default::default(),
+		);
+
+		assert_matches!(v, Ok(ValidationResult::Invalid(InvalidCandidate::Trap(_))));
+	}
+
+	#[test]
+	fn precheck_pvfs_submits_one_statement_per_session() {
+		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+		let local: ValidatorId = SyncCryptoStore::sr25519_generate_new(
+			&*keystore,
+			ValidatorId::ID,
+			Some(&Sr25519Keyring::Alice.to_seed()),
+		).expect("Insert key into keystore").into();
+		let validators = vec![Sr25519Keyring::Bob.public().into(), local];
+
+		let leaf = Hash::repeat_byte(1);
+		let code_hash = Hash::repeat_byte(2);
+		// not even a wasm module, so pre-checking rejects it.
+		let validation_code: ValidationCode = vec![1, 2, 3].into();
+
+		let (mut to_overseer, mut from_task) = mpsc::channel(TO_OVERSEER_CHANNEL_SIZE);
+
+		let check_fut = async move {
+			let mut state = PvfCheckState::default();
+			let strategy = IsolationStrategy::InProcess;
+			precheck_pvfs(&mut to_overseer, &keystore, &strategy, &mut state, leaf).await.unwrap();
+			// the code was voted on in this session already.
+			precheck_pvfs(&mut to_overseer, &keystore, &strategy, &mut state, leaf).await.unwrap();
+		};
+
+		let test_fut = async {
+			for round in 0..2 {
+				assert_matches!(
+					from_task.next().await.unwrap(),
+					AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						rp,
+						RuntimeApiRequest::PvfsRequirePrecheck(tx),
+					)) => {
+						assert_eq!(rp, leaf);
+						let _ = tx.send(Ok(vec![code_hash]));
+					}
+				);
+
+				assert_matches!(
+					from_task.next().await.unwrap(),
+					AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						_,
+						RuntimeApiRequest::SessionIndexForChild(tx),
+					)) => {
+						let _ = tx.send(Ok(1));
+					}
+				);
+
+				assert_matches!(
+					from_task.next().await.unwrap(),
+					AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						_,
+						RuntimeApiRequest::Validators(tx),
+					)) => {
+						let _ = tx.send(Ok(validators.clone()));
+					}
+				);
+
+				if round > 0 {
+					break;
+				}
+
+				assert_matches!(
+					from_task.next().await.unwrap(),
+					AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						_,
+						RuntimeApiRequest::ValidationCodeByHash(h, tx),
+					)) => {
+						assert_eq!(h, code_hash);
+						let _ = tx.send(Ok(Some(validation_code.clone())));
+					}
+				);
+
+				assert_matches!(
+					from_task.next().await.unwrap(),
+					AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+						_,
+						RuntimeApiRequest::SubmitPvfCheckStatement(stmt, signature, tx),
+					)) => {
+						assert_eq!(stmt, PvfCheckStatement {
+							accept: false,
+							subject: code_hash,
+							session_index: 1,
+							validator_index: 1,
+						});
+						assert!(validators[1].verify(&stmt.signing_payload(), &signature));
+						let _ = tx.send(Ok(()));
+					}
+				);
+			}
+		};
+
+		executor::block_on(future::join(test_fut, check_fut));
+
+		// no second statement was submitted.
+		assert!(executor::block_on(from_task.next()).is_none());
+	}
 }
@@ -19,6 +19,7 @@ use tetcoin_primitives::v1::{
 	CoreState, GroupRotationInfo, InboundDownwardMessage, InboundHrmpMessage, Hash,
 	PersistedValidationData, Id as ParaId, OccupiedCoreAssumption,
 	SessionIndex, SessionInfo, ValidationCode, ValidatorId, ValidatorIndex,
+	OutboundHrmpBandwidth, ParaLifecycle, PvfExecutionLimits,
 };
 use tetsy_util_mem::{MallocSizeOf, MallocSizeOfExt};
 
@@ -37,9 +38,13 @@ const VALIDATION_CODE_CACHE_SIZE: usize = 10 * 1024 * 1024;
 const HISTORICAL_VALIDATION_CODE_CACHE_SIZE: usize = 10 * 1024 * 1024;
 const CANDIDATE_PENDING_AVAILABILITY_CACHE_SIZE: usize = 64 * 1024;
 const CANDIDATE_EVENTS_CACHE_SIZE: usize = 64 * 1024;
+const PARA_LIFECYCLE_CACHE_SIZE: usize = 64 * 1024;
 const SESSION_INFO_CACHE_SIZE: usize = 64 * 1024;
+const EARLIEST_STORED_SESSION_CACHE_SIZE: usize = 64 * 1024;
 const DMQ_CONTENTS_CACHE_SIZE: usize = 64 * 1024;
 const INBOUND_HRMP_CHANNELS_CACHE_SIZE: usize = 64 * 1024;
+const OUTBOUND_HRMP_CHANNELS_BANDWIDTH_CACHE_SIZE: usize = 64 * 1024;
+const PVF_EXECUTION_LIMITS_CACHE_SIZE: usize = 64 * 1024;
 
 struct ResidentSizeOf<T>(T);
 
@@ -60,9 +65,13 @@ pub(crate) struct RequestResultCache {
 	historical_validation_code: MemoryLruCache<(Hash, ParaId, BlockNumber), ResidentSizeOf<Option<ValidationCode>>>,
 	candidate_pending_availability: MemoryLruCache<(Hash, ParaId), ResidentSizeOf<Option<CommittedCandidateReceipt>>>,
 	candidate_events: MemoryLruCache<Hash, ResidentSizeOf<Vec<CandidateEvent>>>,
+	para_lifecycle: MemoryLruCache<(Hash, ParaId), ResidentSizeOf<Option<ParaLifecycle>>>,
 	session_info: MemoryLruCache<(Hash, SessionIndex), ResidentSizeOf<Option<SessionInfo>>>,
+	earliest_stored_session: MemoryLruCache<Hash, ResidentSizeOf<SessionIndex>>,
 	dmq_contents: MemoryLruCache<(Hash, ParaId), ResidentSizeOf<Vec<InboundDownwardMessage<BlockNumber>>>>,
 	inbound_hrmp_channels_contents: MemoryLruCache<(Hash, ParaId), ResidentSizeOf<BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>>>,
+	outbound_hrmp_channels_bandwidth: MemoryLruCache<(Hash, ParaId), ResidentSizeOf<OutboundHrmpBandwidth>>,
+	pvf_execution_limits: MemoryLruCache<Hash, ResidentSizeOf<PvfExecutionLimits>>,
 }
 
 impl Default for RequestResultCache {
@@ -78,9 +87,13 @@ impl Default for RequestResultCache {
 			historical_validation_code: MemoryLruCache::new(HISTORICAL_VALIDATION_CODE_CACHE_SIZE),
 			candidate_pending_availability: MemoryLruCache::new(CANDIDATE_PENDING_AVAILABILITY_CACHE_SIZE),
 			candidate_events: MemoryLruCache::new(CANDIDATE_EVENTS_CACHE_SIZE),
+			para_lifecycle: MemoryLruCache::new(PARA_LIFECYCLE_CACHE_SIZE),
 			session_info: MemoryLruCache::new(SESSION_INFO_CACHE_SIZE),
+			earliest_stored_session: MemoryLruCache::new(EARLIEST_STORED_SESSION_CACHE_SIZE),
 			dmq_contents: MemoryLruCache::new(DMQ_CONTENTS_CACHE_SIZE),
 			inbound_hrmp_channels_contents: MemoryLruCache::new(INBOUND_HRMP_CHANNELS_CACHE_SIZE),
+			outbound_hrmp_channels_bandwidth: MemoryLruCache::new(OUTBOUND_HRMP_CHANNELS_BANDWIDTH_CACHE_SIZE),
+			pvf_execution_limits: MemoryLruCache::new(PVF_EXECUTION_LIMITS_CACHE_SIZE),
 		}
 	}
 }
@@ -166,6 +179,14 @@ impl RequestResultCache {
 		self.candidate_events.insert(relay_parent, ResidentSizeOf(events));
 	}
 
+	pub(crate) fn para_lifecycle(&mut self, key: (Hash, ParaId)) -> Option<&Option<ParaLifecycle>> {
+		self.para_lifecycle.get(&key).map(|v| &v.0)
+	}
+
+	pub(crate) fn cache_para_lifecycle(&mut self, key: (Hash, ParaId), value: Option<ParaLifecycle>) {
+		self.para_lifecycle.insert(key, ResidentSizeOf(value));
+	}
+
 	pub(crate) fn session_info(&mut self, key: (Hash, SessionIndex)) -> Option<&Option<SessionInfo>> {
 		self.session_info.get(&key).map(|v| &v.0)
 	}
@@ -174,6 +195,14 @@ impl RequestResultCache {
 		self.session_info.insert(key, ResidentSizeOf(value));
 	}
 
+	pub(crate) fn earliest_stored_session(&mut self, relay_parent: &Hash) -> Option<&SessionIndex> {
+		self.earliest_stored_session.get(relay_parent).map(|v| &v.0)
+	}
+
+	pub(crate) fn cache_earliest_stored_session(&mut self, relay_parent: Hash, index: SessionIndex) {
+		self.earliest_stored_session.insert(relay_parent, ResidentSizeOf(index));
+	}
+
 	pub(crate) fn dmq_contents(&mut self, key: (Hash, ParaId)) -> Option<&Vec<InboundDownwardMessage<BlockNumber>>> {
 		self.dmq_contents.get(&key).map(|v| &v.0)
 	}
@@ -189,6 +218,22 @@ impl RequestResultCache {
 	pub(crate) fn cache_inbound_hrmp_channel_contents(&mut self, key: (Hash, ParaId), value: BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>) {
 		self.inbound_hrmp_channels_contents.insert(key, ResidentSizeOf(value));
 	}
+
+	pub(crate) fn outbound_hrmp_channels_bandwidth(&mut self, key: (Hash, ParaId)) -> Option<&OutboundHrmpBandwidth> {
+		self.outbound_hrmp_channels_bandwidth.get(&key).map(|v| &v.0)
+	}
+
+	pub(crate) fn cache_outbound_hrmp_channels_bandwidth(&mut self, key: (Hash, ParaId), value: OutboundHrmpBandwidth) {
+		self.outbound_hrmp_channels_bandwidth.insert(key, ResidentSizeOf(value));
+	}
+
+	pub(crate) fn pvf_execution_limits(&mut self, relay_parent: &Hash) -> Option<&PvfExecutionLimits> {
+		self.pvf_execution_limits.get(relay_parent).map(|v| &v.0)
+	}
+
+	pub(crate) fn cache_pvf_execution_limits(&mut self, relay_parent: Hash, limits: PvfExecutionLimits) {
+		self.pvf_execution_limits.insert(relay_parent, ResidentSizeOf(limits));
+	}
 }
 
 pub(crate) enum RequestResult {
@@ -202,7 +247,11 @@ pub(crate) enum RequestResult {
 	HistoricalValidationCode(Hash, ParaId, BlockNumber, Option<ValidationCode>),
 	CandidatePendingAvailability(Hash, ParaId, Option<CommittedCandidateReceipt>),
 	CandidateEvents(Hash, Vec<CandidateEvent>),
+	ParaLifecycle(Hash, ParaId, Option<ParaLifecycle>),
 	SessionInfo(Hash, SessionIndex, Option<SessionInfo>),
+	EarliestStoredSession(Hash, SessionIndex),
 	DmqContents(Hash, ParaId, Vec<InboundDownwardMessage<BlockNumber>>),
 	InboundHrmpChannelsContents(Hash, ParaId, BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>),
+	OutboundHrmpChannelsBandwidth(Hash, ParaId, OutboundHrmpBandwidth),
+	PvfExecutionLimits(Hash, PvfExecutionLimits),
 }
@@ -121,12 +121,20 @@ impl<Client> RuntimeApiSubsystem<Client> where
 				self.requests_cache.cache_candidate_pending_availability((relay_parent, para_id), candidate),
 			CandidateEvents(relay_parent, events) =>
 				self.requests_cache.cache_candidate_events(relay_parent, events),
+			ParaLifecycle(relay_parent, para_id, lifecycle) =>
+				self.requests_cache.cache_para_lifecycle((relay_parent, para_id), lifecycle),
 			SessionInfo(relay_parent, session_index, info) =>
 				self.requests_cache.cache_session_info((relay_parent, session_index), info),
+			EarliestStoredSession(relay_parent, session_index) =>
+				self.requests_cache.cache_earliest_stored_session(relay_parent, session_index),
 			DmqContents(relay_parent, para_id, messages) =>
 				self.requests_cache.cache_dmq_contents((relay_parent, para_id), messages),
 			InboundHrmpChannelsContents(relay_parent, para_id, contents) =>
 				self.requests_cache.cache_inbound_hrmp_channel_contents((relay_parent, para_id), contents),
+			OutboundHrmpChannelsBandwidth(relay_parent, para_id, bandwidth) =>
+				self.requests_cache.cache_outbound_hrmp_channels_bandwidth((relay_parent, para_id), bandwidth),
+			PvfExecutionLimits(relay_parent, limits) =>
+				self.requests_cache.cache_pvf_execution_limits(relay_parent, limits),
 		}
 	}
 
@@ -183,13 +191,27 @@ impl<Client> RuntimeApiSubsystem<Client> where
 					.map(|sender| Request::CandidatePendingAvailability(para, sender)),
 			Request::CandidateEvents(sender) => query!(candidate_events(), sender)
 				.map(|sender| Request::CandidateEvents(sender)),
+			Request::ParaLifecycle(id, sender) => query!(para_lifecycle(id), sender)
+				.map(|sender| Request::ParaLifecycle(id, sender)),
 			Request::SessionInfo(index, sender) => query!(session_info(index), sender)
 				.map(|sender| Request::SessionInfo(index, sender)),
+			Request::EarliestStoredSession(sender) => query!(earliest_stored_session(), sender)
+				.map(|sender| Request::EarliestStoredSession(sender)),
 			Request::DmqContents(id, sender) => query!(dmq_contents(id), sender)
 				.map(|sender| Request::DmqContents(id, sender)),
 			Request::InboundHrmpChannelsContents(id, sender) =>
 				query!(inbound_hrmp_channels_contents(id), sender)
-					.map(|sender| Request::InboundHrmpChannelsContents(id, sender))
+					.map(|sender| Request::InboundHrmpChannelsContents(id, sender)),
+			Request::OutboundHrmpChannelsBandwidth(id, sender) =>
+				query!(outbound_hrmp_channels_bandwidth(id), sender)
+					.map(|sender| Request::OutboundHrmpChannelsBandwidth(id, sender)),
+			Request::PvfExecutionLimits(sender) => query!(pvf_execution_limits(), sender)
+				.map(|sender| Request::PvfExecutionLimits(sender)),
+			// The pre-checking state changes with every vote and submissions have side effects,
+			// so none of these are cached.
+			request @ Request::PvfsRequirePrecheck(_)
+				| request @ Request::ValidationCodeByHash(..)
+				| request @ Request::SubmitPvfCheckStatement(..) => Some(request),
 		}
 	}
 
@@ -320,6 +342,20 @@ where
 		}}
 	}
 
+	// Queries which are not cached and thus don't yield a `RequestResult`.
+	macro_rules! query_uncached {
+		($api_name:ident ($($param:expr),*), $sender:expr) => {{
+			let sender = $sender;
+			let api = client.runtime_api();
+			let res = api.$api_name(&BlockId::Hash(relay_parent), $($param),*)
+				.map_err(|e| RuntimeApiError::from(format!("{:?}", e)));
+			metrics.on_request(res.is_ok());
+			let _ = sender.send(res);
+
+			None
+		}}
+	}
+
 	match request {
 		Request::Validators(sender) => query!(Validators, validators(), sender),
 		Request::ValidatorGroups(sender) => query!(ValidatorGroups, validator_groups(), sender),
@@ -336,9 +372,20 @@ where
 		Request::CandidatePendingAvailability(para, sender) =>
 			query!(CandidatePendingAvailability, candidate_pending_availability(para), sender),
 		Request::CandidateEvents(sender) => query!(CandidateEvents, candidate_events(), sender),
+		Request::ParaLifecycle(id, sender) => query!(ParaLifecycle, para_lifecycle(id), sender),
 		Request::SessionInfo(index, sender) => query!(SessionInfo, session_info(index), sender),
+		Request::EarliestStoredSession(sender) =>
+			query!(EarliestStoredSession, earliest_stored_session(), sender),
 		Request::DmqContents(id, sender) => query!(DmqContents, dmq_contents(id), sender),
 		Request::InboundHrmpChannelsContents(id, sender) => query!(InboundHrmpChannelsContents, inbound_hrmp_channels_contents(id), sender),
+		Request::OutboundHrmpChannelsBandwidth(id, sender) =>
+			query!(OutboundHrmpChannelsBandwidth, outbound_hrmp_channels_bandwidth(id), sender),
+		Request::PvfExecutionLimits(sender) =>
+			query!(PvfExecutionLimits, pvf_execution_limits(), sender),
+		Request::PvfsRequirePrecheck(sender) => query_uncached!(pvfs_require_precheck(), sender),
+		Request::ValidationCodeByHash(hash, sender) => query_uncached!(validation_code_by_hash(hash), sender),
+		Request::SubmitPvfCheckStatement(stmt, signature, sender) =>
+			query_uncached!(submit_pvf_check_statement(stmt, signature), sender),
 	}
 }
 
@@ -409,7 +456,8 @@ mod tests {
 		ValidatorId, ValidatorIndex, GroupRotationInfo, CoreState, PersistedValidationData,
 		Id as ParaId, OccupiedCoreAssumption, SessionIndex, ValidationCode,
 		CommittedCandidateReceipt, CandidateEvent, InboundDownwardMessage,
-		BlockNumber, InboundHrmpMessage, SessionInfo,
+		BlockNumber, InboundHrmpMessage, SessionInfo, Hash, PvfCheckStatement, ValidatorSignature,
+		OutboundHrmpBandwidth, OutboundHrmpChannelBandwidth, ParaLifecycle, PvfExecutionLimits,
 	};
 	use tetcoin_node_subsystem_test_helpers as test_helpers;
 	use tet_core::testing::TaskExecutor;
@@ -425,13 +473,19 @@ mod tests {
 		validation_data: HashMap<ParaId, PersistedValidationData>,
 		session_index_for_child: SessionIndex,
 		session_info: HashMap<SessionIndex, SessionInfo>,
+		earliest_stored_session: SessionIndex,
 		validation_code: HashMap<ParaId, ValidationCode>,
 		historical_validation_code: HashMap<ParaId, Vec<(BlockNumber, ValidationCode)>>,
 		validation_outputs_results: HashMap<ParaId, bool>,
 		candidate_pending_availability: HashMap<ParaId, CommittedCandidateReceipt>,
 		candidate_events: Vec<CandidateEvent>,
+		para_lifecycles: HashMap<ParaId, ParaLifecycle>,
 		dmq_contents: HashMap<ParaId, Vec<InboundDownwardMessage>>,
 		hrmp_channels: HashMap<ParaId, BTreeMap<ParaId, Vec<InboundHrmpMessage>>>,
+		hrmp_outbound_bandwidth: HashMap<ParaId, OutboundHrmpBandwidth>,
+		pvfs_require_precheck: Vec<Hash>,
+		validation_code_by_hash: HashMap<Hash, ValidationCode>,
+		pvf_execution_limits: PvfExecutionLimits,
 	}
 
 	impl ProvideRuntimeApi<Block> for MockRuntimeApi {
@@ -495,6 +549,10 @@ mod tests {
 				self.session_info.get(&index).cloned()
 			}
 
+			fn earliest_stored_session(&self) -> SessionIndex {
+				self.earliest_stored_session
+			}
+
 			fn validation_code(
 				&self,
 				para: ParaId,
@@ -527,6 +585,10 @@ mod tests {
 				self.candidate_events.clone()
 			}
 
+			fn para_lifecycle(&self, para: ParaId) -> Option<ParaLifecycle> {
+				self.para_lifecycles.get(&para).cloned()
+			}
+
 			fn dmq_contents(
 				&self,
 				recipient: ParaId,
@@ -540,6 +602,27 @@ mod tests {
 			) -> BTreeMap<ParaId, Vec<InboundHrmpMessage>> {
 				self.hrmp_channels.get(&recipient).map(|q| q.clone()).unwrap_or_default()
 			}
+
+			fn outbound_hrmp_channels_bandwidth(
+				&self,
+				sender: ParaId
+			) -> OutboundHrmpBandwidth {
+				self.hrmp_outbound_bandwidth.get(&sender).map(|b| b.clone()).unwrap_or_default()
+			}
+
+			fn pvfs_require_precheck(&self) -> Vec<Hash> {
+				self.pvfs_require_precheck.clone()
+			}
+
+			fn validation_code_by_hash(&self, hash: Hash) -> Option<ValidationCode> {
+				self.validation_code_by_hash.get(&hash).cloned()
+			}
+
+			fn submit_pvf_check_statement(&self, _stmt: PvfCheckStatement, _signature: ValidatorSignature) {}
+
+			fn pvf_execution_limits(&self) -> PvfExecutionLimits {
+				self.pvf_execution_limits
+			}
 		}
 	}
 
@@ -766,6 +849,62 @@ mod tests {
 		futures::executor::block_on(future::join(subsystem_task, test_task));
 	}
 
+	#[test]
+	fn requests_earliest_stored_session() {
+		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+		let mut runtime_api = MockRuntimeApi::default();
+		runtime_api.earliest_stored_session = 7;
+		let runtime_api = Arc::new(runtime_api);
+		let relay_parent = [1; 32].into();
+		let spawner = tet_core::testing::TaskExecutor::new();
+
+		let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), spawner);
+		let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+		let test_task = async move {
+			let (tx, rx) = oneshot::channel();
+
+			ctx_handle.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::EarliestStoredSession(tx))
+			}).await;
+
+			assert_eq!(rx.await.unwrap().unwrap(), 7);
+
+			ctx_handle.send(FromOverseer::Signal(OverseerSignal::Conclude)).await;
+		};
+
+		futures::executor::block_on(future::join(subsystem_task, test_task));
+	}
+
+	#[test]
+	fn requests_pvf_execution_limits() {
+		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+		let mut runtime_api = MockRuntimeApi::default();
+		runtime_api.pvf_execution_limits = PvfExecutionLimits {
+			timeout_ms: 2_000,
+			max_memory_pages: 512,
+			max_stack_height: 32 * 1024,
+		};
+		let runtime_api = Arc::new(runtime_api);
+		let relay_parent = [1; 32].into();
+		let spawner = tet_core::testing::TaskExecutor::new();
+
+		let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), spawner);
+		let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+		let test_task = async move {
+			let (tx, rx) = oneshot::channel();
+
+			ctx_handle.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::PvfExecutionLimits(tx))
+			}).await;
+
+			assert_eq!(rx.await.unwrap().unwrap(), runtime_api.pvf_execution_limits);
+
+			ctx_handle.send(FromOverseer::Signal(OverseerSignal::Conclude)).await;
+		};
+
+		futures::executor::block_on(future::join(subsystem_task, test_task));
+	}
+
 	#[test]
 	fn requests_validation_code() {
 		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
@@ -876,6 +1015,42 @@ mod tests {
 		futures::executor::block_on(future::join(subsystem_task, test_task));
 	}
 
+	#[test]
+	fn requests_para_lifecycle() {
+		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+		let mut runtime_api = MockRuntimeApi::default();
+		let relay_parent = [1; 32].into();
+		let para_a = 5.into();
+		let para_b = 6.into();
+		let spawner = tet_core::testing::TaskExecutor::new();
+
+		runtime_api.para_lifecycles.insert(para_a, ParaLifecycle::UpgradingToParachain);
+		let runtime_api = Arc::new(runtime_api);
+
+		let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), spawner);
+		let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+		let test_task = async move {
+			let (tx, rx) = oneshot::channel();
+
+			ctx_handle.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::ParaLifecycle(para_a, tx))
+			}).await;
+
+			assert_eq!(rx.await.unwrap().unwrap(), Some(ParaLifecycle::UpgradingToParachain));
+
+			let (tx, rx) = oneshot::channel();
+			ctx_handle.send(FromOverseer::Communication {
+				msg: RuntimeApiMessage::Request(relay_parent, Request::ParaLifecycle(para_b, tx))
+			}).await;
+
+			assert_eq!(rx.await.unwrap().unwrap(), None);
+
+			ctx_handle.send(FromOverseer::Signal(OverseerSignal::Conclude)).await;
+		};
+
+		futures::executor::block_on(future::join(subsystem_task, test_task));
+	}
+
 	#[test]
 	fn requests_dmq_contents() {
 		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
@@ -999,6 +1174,63 @@ mod tests {
 		futures::executor::block_on(future::join(subsystem_task, test_task));
 	}
 
+	#[test]
+	fn requests_outbound_hrmp_channels_bandwidth() {
+		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
+
+		let relay_parent = [1; 32].into();
+		let para_a = 99.into();
+		let para_b = 66.into();
+		let spawner = tet_core::testing::TaskExecutor::new();
+
+		let para_a_bandwidth = OutboundHrmpBandwidth {
+			max_messages_per_candidate: 2,
+			channels: vec![OutboundHrmpChannelBandwidth {
+				recipient: para_b,
+				messages_remaining: 3,
+				bytes_remaining: 1024,
+				max_message_size: 256,
+			}],
+		};
+
+		let runtime_api = Arc::new({
+			let mut runtime_api = MockRuntimeApi::default();
+			runtime_api.hrmp_outbound_bandwidth.insert(para_a, para_a_bandwidth.clone());
+			runtime_api
+		});
+
+		let subsystem = RuntimeApiSubsystem::new(runtime_api.clone(), Metrics(None), spawner);
+		let subsystem_task = run(ctx, subsystem).map(|x| x.unwrap());
+		let test_task = async move {
+			let (tx, rx) = oneshot::channel();
+			ctx_handle
+				.send(FromOverseer::Communication {
+					msg: RuntimeApiMessage::Request(
+						relay_parent,
+						Request::OutboundHrmpChannelsBandwidth(para_a, tx),
+					),
+				})
+				.await;
+			assert_eq!(rx.await.unwrap().unwrap(), para_a_bandwidth);
+
+			let (tx, rx) = oneshot::channel();
+			ctx_handle
+				.send(FromOverseer::Communication {
+					msg: RuntimeApiMessage::Request(
+						relay_parent,
+						Request::OutboundHrmpChannelsBandwidth(para_b, tx),
+					),
+				})
+				.await;
+			assert_eq!(rx.await.unwrap().unwrap(), OutboundHrmpBandwidth::default());
+
+			ctx_handle
+				.send(FromOverseer::Signal(OverseerSignal::Conclude))
+				.await;
+		};
+		futures::executor::block_on(future::join(subsystem_task, test_task));
+	}
+
 	#[test]
 	fn requests_historical_code() {
 		let (ctx, mut ctx_handle) = test_helpers::make_subsystem_context(TaskExecutor::new());
@@ -28,6 +28,9 @@ pub enum Error {
 	#[error("failed to query a chunk from store")]
 	CanceledQueryChunk(#[source] oneshot::Canceled),
 
+	#[error("failed to query the full available data from store")]
+	CanceledQueryFullData(#[source] oneshot::Canceled),
+
 	#[error("failed to query session info")]
 	CanceledSessionInfo(#[source] oneshot::Canceled),
 
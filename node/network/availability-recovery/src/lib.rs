@@ -19,7 +19,7 @@
 #![warn(missing_docs)]
 
 use std::collections::HashMap;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::pin::Pin;
 
 use futures::{channel::{oneshot, mpsc}, prelude::*, stream::FuturesUnordered};
@@ -30,7 +30,7 @@ use streamunordered::{StreamUnordered, StreamYield};
 
 use tetcoin_primitives::v1::{
 	AuthorityDiscoveryId, AvailableData, CandidateReceipt, CandidateHash,
-	Hash, ErasureChunk, ValidatorId, ValidatorIndex,
+	Hash, ErasureChunk, GroupIndex, ValidatorId, ValidatorIndex,
 	SessionInfo, SessionIndex, BlakeTwo256, HashT,
 };
 use tetcoin_subsystem::{
@@ -58,6 +58,9 @@ const LOG_TARGET: &str = "availability_recovery";
 
 const COST_MERKLE_PROOF_INVALID: Rep = Rep::new(-100, "Merkle proof was invalid");
 const COST_UNEXPECTED_CHUNK: Rep = Rep::new(-100, "Peer has sent an unexpected chunk");
+const COST_INVALID_AVAILABLE_DATA: Rep = Rep::new(-100, "Available data did not match the erasure root");
+const COST_UNEXPECTED_FULL_DATA: Rep = Rep::new(-100, "Peer has sent unexpected available data");
+const COST_FULL_DATA_FLOOD: Rep = Rep::new(-10, "Peer has requested too much available data");
 
 // How many parallel requests interaction should have going at once.
 const N_PARALLEL: usize = 50;
@@ -68,6 +71,17 @@ const LRU_SIZE: usize = 16;
 // A timeout for a chunk request.
 const CHUNK_REQUEST_TIMEOUT: Duration = Duration::from_secs(3);
 
+// A timeout for a request of the full available data from a backing validator.
+const FULL_DATA_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+// A timeout for requesting the full available data from the whole backing group,
+// after which we fall back to recovering from chunks.
+const FULL_DATA_RECOVERY_TIMEOUT: Duration = Duration::from_secs(4);
+
+// How many full available data requests we serve to a single peer
+// per `AWAITED_CHUNKS_CLEANUP_INTERVAL`. The rest are answered with `None`.
+const MAX_FULL_DATA_RESPONSES_PER_PEER: usize = 4;
+
 // A period to poll and clean AwaitedChunks.
 const AWAITED_CHUNKS_CLEANUP_INTERVAL: Duration = Duration::from_secs(1);
 
@@ -76,6 +90,8 @@ pub struct AvailabilityRecoverySubsystem;
 
 type ChunkResponse = Result<(PeerId, ErasureChunk), RecoveryError>;
 
+type FullDataResponse = Result<(PeerId, AvailableData), RecoveryError>;
+
 /// Data we keep around for every chunk that we are awaiting.
 struct AwaitedChunk {
 	/// Index of the validator we have requested this chunk from.
@@ -91,6 +107,18 @@ struct AwaitedChunk {
 	response: oneshot::Sender<ChunkResponse>,
 }
 
+/// Data we keep around for every full available data request that we are awaiting.
+struct AwaitedFullData {
+	/// The hash of the candidate the data belongs to.
+	candidate_hash: CandidateHash,
+
+	/// Token to cancel the connection request to the validator.
+	token: usize,
+
+	/// Result sender.
+	response: oneshot::Sender<FullDataResponse>,
+}
+
 /// Accumulate all awaiting sides for some particular `AvailableData`.
 struct InteractionHandle {
 	awaiting: Vec<oneshot::Sender<Result<AvailableData, RecoveryError>>>,
@@ -110,6 +138,13 @@ enum FromInteraction {
 		oneshot::Sender<ChunkResponse>,
 	),
 
+	/// Make a request of the full available data from a particular validator.
+	MakeFullDataRequest(
+		AuthorityDiscoveryId,
+		CandidateHash,
+		oneshot::Sender<FullDataResponse>,
+	),
+
 	/// Report a peer.
 	ReportPeer(
 		PeerId,
@@ -132,6 +167,10 @@ struct Interaction {
 	/// to the validators and request the chunk from them.
 	shuffling: Vec<ValidatorIndex>,
 
+	/// A random shuffling of the validators that backed the candidate. These are asked
+	/// for the full available data one by one before we fall back to requesting chunks.
+	backing_group: Vec<ValidatorIndex>,
+
 	/// The number of pieces needed.
 	threshold: usize,
 
@@ -158,6 +197,67 @@ const fn is_unavailable(
 }
 
 impl Interaction {
+	async fn request_from_backing_group(&mut self) -> error::Result<Option<AvailableData>> {
+		let deadline = Instant::now() + FULL_DATA_RECOVERY_TIMEOUT;
+
+		while let Some(validator_index) = self.backing_group.pop() {
+			// Validators are asked one at a time, so bound the time spent on all of them.
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			if remaining == Duration::from_secs(0) {
+				tracing::debug!(
+					target: LOG_TARGET,
+					"Ran out of time requesting the full data from the backing group",
+				);
+				break;
+			}
+
+			let (tx, rx) = oneshot::channel();
+
+			self.to_state.send(FromInteraction::MakeFullDataRequest(
+				self.validator_authority_keys[validator_index as usize].clone(),
+				self.candidate_hash.clone(),
+				tx,
+			)).await.map_err(error::Error::ClosedToState)?;
+
+			match rx.timeout(std::cmp::min(FULL_DATA_REQUEST_TIMEOUT, remaining)).await {
+				Some(Ok(Ok((peer_id, data)))) => {
+					// The data is not covered by any proof, so re-encode it and check
+					// it against the erasure root just like reconstructed data.
+					if reconstructed_data_matches_root(self.validators.len(), &self.erasure_root, &data) {
+						return Ok(Some(data));
+					}
+
+					self.to_state.send(FromInteraction::ReportPeer(
+						peer_id,
+						COST_INVALID_AVAILABLE_DATA,
+					)).await.map_err(error::Error::ClosedToState)?;
+				}
+				Some(Err(e)) => {
+					tracing::debug!(
+						target: LOG_TARGET,
+						err = ?e,
+						"A response channel was canceled while waiting for the full data",
+					);
+				}
+				Some(Ok(Err(e))) => {
+					tracing::debug!(
+						target: LOG_TARGET,
+						err = ?e,
+						"A full data request ended with an error",
+					);
+				}
+				None => {
+					tracing::debug!(
+						target: LOG_TARGET,
+						"A full data request has timed out",
+					);
+				}
+			}
+		}
+
+		Ok(None)
+	}
+
 	async fn launch_parallel_requests(&mut self) -> error::Result<()> {
 		while self.requesting_chunks.len() < N_PARALLEL {
 			if let Some(validator_index) = self.shuffling.pop() {
@@ -242,6 +342,15 @@ impl Interaction {
 	}
 
 	async fn run(mut self) -> error::Result<()> {
+		if let Some(data) = self.request_from_backing_group().await? {
+			self.to_state.send(FromInteraction::Concluded(
+				self.candidate_hash,
+				Ok(data),
+			)).await.map_err(error::Error::ClosedToState)?;
+
+			return Ok(());
+		}
+
 		loop {
 			if is_unavailable(
 				self.received_chunks.len(),
@@ -331,6 +440,18 @@ struct State {
 	/// about the chunks we are interested in.
 	live_chunk_requests: HashMap<RequestId, (PeerId, AwaitedChunk)>,
 
+	/// We are waiting for these validators to connect and as soon as they
+	/// do to request the full available data we are awaiting for.
+	discovering_full_data: HashMap<AuthorityDiscoveryId, Vec<AwaitedFullData>>,
+
+	/// Requests that we have issued to the already connected validators
+	/// about the full available data we are interested in.
+	live_full_data_requests: HashMap<RequestId, (PeerId, AwaitedFullData)>,
+
+	/// How many full available data requests we have served to each peer
+	/// since the last cleanup.
+	served_full_data: HashMap<PeerId, usize>,
+
 	/// Derive request ids from this.
 	next_request_id: RequestId,
 
@@ -357,6 +478,9 @@ impl Default for State {
 			live_block_hash: Hash::default(),
 			discovering_validators: HashMap::new(),
 			live_chunk_requests: HashMap::new(),
+			discovering_full_data: HashMap::new(),
+			live_full_data_requests: HashMap::new(),
+			served_full_data: HashMap::new(),
 			next_request_id: 0,
 			connecting_validators: StreamUnordered::new(),
 			availability_lru: LruCache::new(LRU_SIZE),
@@ -414,6 +538,7 @@ async fn launch_interaction(
 	session_index: SessionIndex,
 	session_info: SessionInfo,
 	receipt: CandidateReceipt,
+	backing_group: Option<GroupIndex>,
 	response_sender: oneshot::Sender<Result<AvailableData, RecoveryError>>,
 ) -> error::Result<()> {
 	let threshold = recovery_threshold(session_info.validators.len())?;
@@ -423,6 +548,10 @@ async fn launch_interaction(
 	let validators = session_info.validators.clone();
 	let validator_authority_keys = session_info.discovery_keys.clone();
 	let mut shuffling: Vec<_> = (0..validators.len() as ValidatorIndex).collect();
+	let mut backing_group = backing_group
+		.and_then(|g| session_info.validator_groups.get(g.0 as usize))
+		.cloned()
+		.unwrap_or_default();
 
 	state.interactions.insert(
 		candidate_hash.clone(),
@@ -435,6 +564,7 @@ async fn launch_interaction(
 		// make borrow checker happy.
 		let mut rng = thread_rng();
 		shuffling.shuffle(&mut rng);
+		backing_group.shuffle(&mut rng);
 	}
 
 	let interaction = Interaction {
@@ -442,6 +572,7 @@ async fn launch_interaction(
 		validator_authority_keys,
 		validators,
 		shuffling,
+		backing_group,
 		threshold,
 		candidate_hash,
 		erasure_root,
@@ -477,6 +608,7 @@ async fn handle_recover(
 	ctx: &mut impl SubsystemContext<Message = AvailabilityRecoveryMessage>,
 	receipt: CandidateReceipt,
 	session_index: SessionIndex,
+	backing_group: Option<GroupIndex>,
 	response_sender: oneshot::Sender<Result<AvailableData, RecoveryError>>,
 ) -> error::Result<()> {
 	let candidate_hash = receipt.hash();
@@ -511,6 +643,7 @@ async fn handle_recover(
 				session_index,
 				session_info,
 				receipt,
+				backing_group,
 				response_sender,
 			).await
 		}
@@ -542,6 +675,20 @@ async fn query_chunk(
 	Ok(rx.await.map_err(error::Error::CanceledQueryChunk)?)
 }
 
+/// Queries the full available data from av-store.
+#[tracing::instrument(level = "trace", skip(ctx), fields(subsystem = LOG_TARGET))]
+async fn query_full_data(
+	ctx: &mut impl SubsystemContext<Message = AvailabilityRecoveryMessage>,
+	candidate_hash: CandidateHash,
+) -> error::Result<Option<AvailableData>> {
+	let (tx, rx) = oneshot::channel();
+	ctx.send_message(AllMessages::AvailabilityStore(
+		AvailabilityStoreMessage::QueryAvailableData(candidate_hash, tx),
+	)).await;
+
+	Ok(rx.await.map_err(error::Error::CanceledQueryFullData)?)
+}
+
 /// Handles message from interaction.
 #[tracing::instrument(level = "trace", skip(ctx, state), fields(subsystem = LOG_TARGET))]
 async fn handle_from_interaction(
@@ -592,6 +739,24 @@ async fn handle_from_interaction(
 				response,
 			});
 		}
+		FromInteraction::MakeFullDataRequest(id, candidate_hash, response) => {
+			let (tx, rx) = mpsc::channel(2);
+
+			let message = NetworkBridgeMessage::ConnectToValidators {
+				validator_ids: vec![id.clone()],
+				connected: tx,
+			};
+
+			ctx.send_message(AllMessages::NetworkBridge(message)).await;
+
+			let token = state.connecting_validators.push(rx);
+
+			state.discovering_full_data.entry(id).or_default().push(AwaitedFullData {
+				candidate_hash,
+				token,
+				response,
+			});
+		}
 		FromInteraction::ReportPeer(peer_id, rep) => {
 			report_peer(ctx, peer_id, rep).await;
 		}
@@ -660,6 +825,60 @@ async fn handle_network_update(
 						}
 					}
 				}
+				protocol_v1::AvailabilityRecoveryMessage::RequestFullData(
+					request_id,
+					candidate_hash,
+				) => {
+					// Serving the full data is expensive, so limit how much of it
+					// a single peer can get out of us.
+					let served = state.served_full_data.entry(peer.clone()).or_default();
+					*served += 1;
+
+					let data = if *served > MAX_FULL_DATA_RESPONSES_PER_PEER {
+						report_peer(ctx, peer.clone(), COST_FULL_DATA_FLOOD).await;
+						None
+					} else {
+						query_full_data(ctx, candidate_hash).await?
+					};
+
+					let wire_message = protocol_v1::AvailabilityRecoveryMessage::FullData(
+						request_id,
+						data,
+					);
+
+					ctx.send_message(AllMessages::NetworkBridge(
+						NetworkBridgeMessage::SendValidationMessage(
+							vec![peer],
+							protocol_v1::ValidationProtocol::AvailabilityRecovery(wire_message),
+						),
+					)).await;
+				}
+				protocol_v1::AvailabilityRecoveryMessage::FullData(request_id, data) => {
+					match state.live_full_data_requests.remove(&request_id) {
+						None => {
+							report_peer(ctx, peer, COST_UNEXPECTED_FULL_DATA).await;
+						}
+						Some((peer_id, awaited_full_data)) if peer_id == peer => {
+							// A peer not having the data is not worth waiting for the timeout,
+							// let the interaction move on to the next one right away.
+							let response = match data {
+								Some(data) => Ok((peer_id, data)),
+								None => Err(RecoveryError::Unavailable),
+							};
+
+							if awaited_full_data.response.send(response).is_err() {
+								tracing::debug!(
+									target: LOG_TARGET,
+									"A sending side of the recovery request is closed",
+								);
+							}
+						}
+						Some(a) => {
+							state.live_full_data_requests.insert(request_id, a);
+							report_peer(ctx, peer, COST_UNEXPECTED_FULL_DATA).await;
+						}
+					}
+				}
 			}
 		}
 		// We do not really need to track the peers' views in this subsystem
@@ -702,6 +921,33 @@ async fn issue_chunk_request(
 	Ok(())
 }
 
+/// Issues a full data request to the validator we've been waiting for to connect to us.
+async fn issue_full_data_request(
+	state: &mut State,
+	ctx: &mut impl SubsystemContext<Message = AvailabilityRecoveryMessage>,
+	peer_id: PeerId,
+	awaited_full_data: AwaitedFullData,
+) -> error::Result<()> {
+	let request_id = state.next_request_id;
+	state.next_request_id += 1;
+
+	let wire_message = protocol_v1::AvailabilityRecoveryMessage::RequestFullData(
+		request_id,
+		awaited_full_data.candidate_hash,
+	);
+
+	ctx.send_message(AllMessages::NetworkBridge(
+		NetworkBridgeMessage::SendValidationMessage(
+			vec![peer_id.clone()],
+			protocol_v1::ValidationProtocol::AvailabilityRecovery(wire_message),
+		),
+	)).await;
+
+	state.live_full_data_requests.insert(request_id, (peer_id, awaited_full_data));
+
+	Ok(())
+}
+
 /// Handles a newly connected validator in the context of some relay leaf.
 async fn handle_validator_connected(
 	state: &mut State,
@@ -715,6 +961,12 @@ async fn handle_validator_connected(
 		}
 	}
 
+	if let Some(discovering) = state.discovering_full_data.remove(&authority_id) {
+		for awaited_full_data in discovering {
+			issue_full_data_request(state, ctx, peer_id.clone(), awaited_full_data).await?;
+		}
+	}
+
 	Ok(())
 }
 
@@ -733,12 +985,24 @@ fn cleanup_awaited_chunks(state: &mut State) {
 		});
 	}
 
+	for (_, v) in state.discovering_full_data.iter_mut() {
+		v.retain(|e| if e.response.is_canceled() {
+			removed_tokens.push(e.token);
+			false
+		} else {
+			true
+		});
+	}
+
 	for token in removed_tokens {
 		Pin::new(&mut state.connecting_validators).remove(token);
 	}
 
 	state.discovering_validators.retain(|_, v| !v.is_empty());
 	state.live_chunk_requests.retain(|_, v| !v.1.response.is_canceled());
+	state.discovering_full_data.retain(|_, v| !v.is_empty());
+	state.live_full_data_requests.retain(|_, v| !v.1.response.is_canceled());
+	state.served_full_data.clear();
 }
 
 impl AvailabilityRecoverySubsystem {
@@ -800,6 +1064,7 @@ impl AvailabilityRecoverySubsystem {
 								AvailabilityRecoveryMessage::RecoverAvailableData(
 									receipt,
 									session_index,
+									backing_group,
 									response_sender,
 								) => {
 									if let Err(e) = handle_recover(
@@ -807,6 +1072,7 @@ impl AvailabilityRecoverySubsystem {
 										&mut ctx,
 										receipt,
 										session_index,
+										backing_group,
 										response_sender,
 									).await {
 										tracing::warn!(
@@ -121,6 +121,7 @@ struct TestState {
 	current: Hash,
 	candidate: CandidateReceipt,
 	session_index: SessionIndex,
+	validator_groups: Vec<Vec<ValidatorIndex>>,
 
 
 	persisted_validation_data: PersistedValidationData,
@@ -149,6 +150,7 @@ impl TestState {
 				tx.send(Ok(Some(SessionInfo {
 					validators: self.validator_public.clone(),
 					discovery_keys: self.validator_authority_id.clone(),
+					validator_groups: self.validator_groups.clone(),
 					..Default::default()
 				}))).unwrap();
 			}
@@ -197,6 +199,69 @@ impl TestState {
 		}
 	}
 
+	async fn test_full_data_request(
+		&self,
+		candidate_hash: CandidateHash,
+		virtual_overseer: &mut VirtualOverseer,
+		data: Option<AvailableData>,
+	) -> PeerId {
+		// Full data is requested from one backing validator at a time.
+		let peer_id = assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::ConnectToValidators {
+					validator_ids,
+					mut connected,
+					..
+				}
+			) => {
+				assert_eq!(validator_ids.len(), 1);
+
+				let idx = self.validator_authority_id
+					.iter()
+					.position(|x| *x == validator_ids[0])
+					.unwrap();
+
+				assert!(self.validator_groups.iter().any(|g| g.contains(&(idx as ValidatorIndex))));
+
+				let peer_id = self.validator_peer_id[idx].clone();
+				connected.send((validator_ids[0].clone(), peer_id.clone())).await.unwrap();
+
+				peer_id
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(virtual_overseer).await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::SendValidationMessage(
+					peers,
+					protocol_v1::ValidationProtocol::AvailabilityRecovery(
+						protocol_v1::AvailabilityRecoveryMessage::RequestFullData(
+							request_id,
+							candidate_hash_recvd,
+						)
+					),
+				)
+			) => {
+				assert_eq!(peers, vec![peer_id.clone()]);
+				assert_eq!(candidate_hash_recvd, candidate_hash);
+
+				overseer_send(
+					virtual_overseer,
+					AvailabilityRecoveryMessage::NetworkBridgeUpdateV1(
+						NetworkBridgeEvent::PeerMessage(
+							peer_id.clone(),
+							protocol_v1::AvailabilityRecoveryMessage::FullData(request_id, data),
+						)
+					)
+				).await;
+			}
+		);
+
+		peer_id
+	}
+
 	async fn test_chunk_requests(
 		&self,
 		candidate_hash: CandidateHash,
@@ -394,6 +459,7 @@ impl Default for TestState {
 			current,
 			candidate,
 			session_index,
+			validator_groups: vec![vec![1, 2], vec![3, 4]],
 			persisted_validation_data,
 			available_data,
 			chunks,
@@ -423,6 +489,7 @@ fn availability_is_recovered() {
 			AvailabilityRecoveryMessage::RecoverAvailableData(
 				test_state.candidate.clone(),
 				test_state.session_index,
+				None,
 				tx,
 			)
 		).await;
@@ -450,6 +517,7 @@ fn availability_is_recovered() {
 			AvailabilityRecoveryMessage::RecoverAvailableData(
 				new_candidate,
 				test_state.session_index,
+				None,
 				tx,
 			)
 		).await;
@@ -485,6 +553,7 @@ fn a_faulty_chunk_leads_to_recovery_error() {
 			AvailabilityRecoveryMessage::RecoverAvailableData(
 				test_state.candidate.clone(),
 				test_state.session_index,
+				None,
 				tx,
 			)
 		).await;
@@ -535,6 +604,7 @@ fn a_wrong_chunk_leads_to_recovery_error() {
 			AvailabilityRecoveryMessage::RecoverAvailableData(
 				test_state.candidate.clone(),
 				test_state.session_index,
+				None,
 				tx,
 			)
 		).await;
@@ -563,3 +633,246 @@ fn a_wrong_chunk_leads_to_recovery_error() {
 		assert_eq!(rx.await.unwrap().unwrap_err(), RecoveryError::Invalid);
 	});
 }
+
+#[test]
+fn availability_is_recovered_from_backing_group() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+				activated: smallvec![(test_state.current.clone(), Arc::new(JaegerSpan::Disabled))],
+				deactivated: smallvec![],
+			}),
+		).await;
+
+		let (tx, rx) = oneshot::channel();
+
+		overseer_send(
+			&mut virtual_overseer,
+			AvailabilityRecoveryMessage::RecoverAvailableData(
+				test_state.candidate.clone(),
+				test_state.session_index,
+				Some(GroupIndex(0)),
+				tx,
+			)
+		).await;
+
+		test_state.test_runtime_api(&mut virtual_overseer).await;
+
+		let candidate_hash = test_state.candidate.hash();
+
+		test_state.test_full_data_request(
+			candidate_hash,
+			&mut virtual_overseer,
+			Some(test_state.available_data.clone()),
+		).await;
+
+		// The data is recovered without requesting any chunks.
+		assert_eq!(rx.await.unwrap().unwrap(), test_state.available_data);
+	});
+}
+
+#[test]
+fn invalid_full_data_falls_back_to_chunks() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		overseer_signal(
+			&mut virtual_overseer,
+			OverseerSignal::ActiveLeaves(ActiveLeavesUpdate {
+				activated: smallvec![(test_state.current.clone(), Arc::new(JaegerSpan::Disabled))],
+				deactivated: smallvec![],
+			}),
+		).await;
+
+		let (tx, rx) = oneshot::channel();
+
+		overseer_send(
+			&mut virtual_overseer,
+			AvailabilityRecoveryMessage::RecoverAvailableData(
+				test_state.candidate.clone(),
+				test_state.session_index,
+				Some(GroupIndex(0)),
+				tx,
+			)
+		).await;
+
+		test_state.test_runtime_api(&mut virtual_overseer).await;
+
+		let candidate_hash = test_state.candidate.hash();
+
+		// The first backing validator responds with data not matching the erasure root.
+		let bogus_data = AvailableData {
+			validation_data: test_state.persisted_validation_data.clone(),
+			pov: Arc::new(PoV {
+				block_data: BlockData(vec![43; 64]),
+			}),
+		};
+
+		let bogus_peer = test_state.test_full_data_request(
+			candidate_hash,
+			&mut virtual_overseer,
+			Some(bogus_data),
+		).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, bogus_peer);
+				assert_eq!(rep, COST_INVALID_AVAILABLE_DATA);
+			}
+		);
+
+		// The second one does not have the data.
+		test_state.test_full_data_request(
+			candidate_hash,
+			&mut virtual_overseer,
+			None,
+		).await;
+
+		test_state.test_connect_to_validators(&mut virtual_overseer).await;
+
+		test_state.test_chunk_requests(candidate_hash, &mut virtual_overseer).await;
+
+		assert_eq!(rx.await.unwrap().unwrap(), test_state.available_data);
+	});
+}
+
+#[test]
+fn full_data_is_served_from_store() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let candidate_hash = test_state.candidate.hash();
+		let peer = test_state.validator_peer_id[1].clone();
+
+		overseer_send(
+			&mut virtual_overseer,
+			AvailabilityRecoveryMessage::NetworkBridgeUpdateV1(
+				NetworkBridgeEvent::PeerMessage(
+					peer.clone(),
+					protocol_v1::AvailabilityRecoveryMessage::RequestFullData(7, candidate_hash),
+				)
+			)
+		).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::AvailabilityStore(
+				AvailabilityStoreMessage::QueryAvailableData(hash, tx)
+			) => {
+				assert_eq!(hash, candidate_hash);
+				tx.send(Some(test_state.available_data.clone())).unwrap();
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::SendValidationMessage(
+					peers,
+					protocol_v1::ValidationProtocol::AvailabilityRecovery(
+						protocol_v1::AvailabilityRecoveryMessage::FullData(request_id, data)
+					),
+				)
+			) => {
+				assert_eq!(peers, vec![peer]);
+				assert_eq!(request_id, 7);
+				assert_eq!(data, Some(test_state.available_data));
+			}
+		);
+	});
+}
+
+#[test]
+fn full_data_serving_is_rate_limited() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let candidate_hash = test_state.candidate.hash();
+		let peer = test_state.validator_peer_id[1].clone();
+
+		for request_id in 0..MAX_FULL_DATA_RESPONSES_PER_PEER as RequestId {
+			overseer_send(
+				&mut virtual_overseer,
+				AvailabilityRecoveryMessage::NetworkBridgeUpdateV1(
+					NetworkBridgeEvent::PeerMessage(
+						peer.clone(),
+						protocol_v1::AvailabilityRecoveryMessage::RequestFullData(request_id, candidate_hash),
+					)
+				)
+			).await;
+
+			assert_matches!(
+				overseer_recv(&mut virtual_overseer).await,
+				AllMessages::AvailabilityStore(
+					AvailabilityStoreMessage::QueryAvailableData(_, tx)
+				) => {
+					tx.send(Some(test_state.available_data.clone())).unwrap();
+				}
+			);
+
+			assert_matches!(
+				overseer_recv(&mut virtual_overseer).await,
+				AllMessages::NetworkBridge(
+					NetworkBridgeMessage::SendValidationMessage(
+						_,
+						protocol_v1::ValidationProtocol::AvailabilityRecovery(
+							protocol_v1::AvailabilityRecoveryMessage::FullData(_, data)
+						),
+					)
+				) => {
+					assert_eq!(data, Some(test_state.available_data.clone()));
+				}
+			);
+		}
+
+		overseer_send(
+			&mut virtual_overseer,
+			AvailabilityRecoveryMessage::NetworkBridgeUpdateV1(
+				NetworkBridgeEvent::PeerMessage(
+					peer.clone(),
+					protocol_v1::AvailabilityRecoveryMessage::RequestFullData(42, candidate_hash),
+				)
+			)
+		).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::ReportPeer(p, rep)
+			) => {
+				assert_eq!(p, peer);
+				assert_eq!(rep, COST_FULL_DATA_FLOOD);
+			}
+		);
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridge(
+				NetworkBridgeMessage::SendValidationMessage(
+					peers,
+					protocol_v1::ValidationProtocol::AvailabilityRecovery(
+						protocol_v1::AvailabilityRecoveryMessage::FullData(request_id, data)
+					),
+				)
+			) => {
+				assert_eq!(peers, vec![peer]);
+				assert_eq!(request_id, 42);
+				assert_eq!(data, None);
+			}
+		);
+	});
+}
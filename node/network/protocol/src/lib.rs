@@ -283,6 +283,7 @@ pub mod v1 {
 	use tetcoin_primitives::v1::{
 		Hash, CollatorId, Id as ParaId, ErasureChunk, CandidateReceipt,
 		SignedAvailabilityBitfield, PoV, CandidateHash, ValidatorIndex, CandidateIndex,
+		AvailableData,
 	};
 	use tetcoin_node_primitives::{
 		SignedFullStatement,
@@ -308,6 +309,11 @@ pub mod v1 {
 		/// Respond with chunk for a given candidate hash and validator index.
 		/// The response may be `None` if the requestee does not have the chunk.
 		Chunk(RequestId, Option<ErasureChunk>),
+		/// Request the full available data for a given candidate hash.
+		RequestFullData(RequestId, CandidateHash),
+		/// Respond with the full available data for a given candidate hash.
+		/// The response may be `None` if the requestee does not have the data.
+		FullData(RequestId, Option<AvailableData>),
 	}
 
 	/// Network messages used by the bitfield distribution subsystem.
@@ -67,7 +67,8 @@ impl PeerSet {
 	/// Get the protocol name associated with each peer set as static str.
 	pub const fn get_protocol_name_static(self) -> &'static str {
 		match self {
-			PeerSet::Validation => "/tetcoin/validation/1",
+			// Version 2 added the availability recovery full data messages.
+			PeerSet::Validation => "/tetcoin/validation/2",
 			PeerSet::Collation => "/tetcoin/collation/1",
 		}
 	}
@@ -27,6 +27,7 @@ use futures::{
 use futures_timer::Delay;
 
 use tetcoin_primitives::v1::{BlockData, PoV};
+use tetcoin_node_primitives::ValidationPriority;
 use tetcoin_overseer::{Overseer, AllSubsystems};
 
 use tetcoin_subsystem::{Subsystem, SubsystemContext, SpawnedSubsystem, FromOverseer};
@@ -62,6 +63,7 @@ impl Subsystem1 {
 					PoV {
 						block_data: BlockData(Vec::new()),
 					}.into(),
+					ValidationPriority::Backing,
 					tx,
 				)
 			)).await;
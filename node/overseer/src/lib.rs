@@ -1962,7 +1962,7 @@ mod tests {
 
 	use tetcoin_primitives::v1::{BlockData, CollatorPair, PoV, CandidateHash};
 	use tetcoin_subsystem::{messages::RuntimeApiRequest, JaegerSpan};
-	use tetcoin_node_primitives::{Collation, CollationGenerationConfig};
+	use tetcoin_node_primitives::{Collation, CollationGenerationConfig, ValidationPriority};
 	use tetcoin_node_network_protocol::{PeerId, ReputationChange, NetworkBridgeEvent};
 	use tetcoin_node_subsystem_util::metered;
 
@@ -2020,6 +2020,7 @@ mod tests {
 										PoV {
 											block_data: BlockData(Vec::new()),
 										}.into(),
+										ValidationPriority::Backing,
 										tx,
 									)
 								)
@@ -2604,7 +2605,12 @@ mod tests {
 	fn test_candidate_validation_msg() -> CandidateValidationMessage {
 		let (sender, _) = oneshot::channel();
 		let pov = Arc::new(PoV { block_data: BlockData(Vec::new()) });
-		CandidateValidationMessage::ValidateFromChainState(Default::default(), pov, sender)
+		CandidateValidationMessage::ValidateFromChainState(
+			Default::default(),
+			pov,
+			ValidationPriority::Backing,
+			sender,
+		)
 	}
 
 	fn test_candidate_backing_msg() -> CandidateBackingMessage {
@@ -2624,7 +2630,7 @@ mod tests {
 	fn test_collator_generation_msg() -> CollationGenerationMessage {
 		CollationGenerationMessage::Initialize(CollationGenerationConfig {
 			key: CollatorPair::generate().0,
-			collator: Box::new(|_, _| TestCollator.boxed()),
+			collator: Box::new(|_, _, _| TestCollator.boxed()),
 			para_id: Default::default(),
 		})
 	}
@@ -2661,6 +2667,7 @@ mod tests {
 		AvailabilityRecoveryMessage::RecoverAvailableData(
 			Default::default(),
 			Default::default(),
+			None,
 			sender,
 		)
 	}
@@ -26,12 +26,13 @@ use futures::Future;
 use tetsy_scale_codec::{Decode, Encode};
 use tetcoin_primitives::v1::{
 	CandidateCommitments, CandidateHash, CollatorPair, CommittedCandidateReceipt, CompactStatement,
-	EncodeAs, Hash, HeadData, Id as ParaId, OutboundHrmpMessage, PersistedValidationData, PoV,
-	Signed, UpwardMessage, ValidationCode,
+	EncodeAs, Hash, HeadData, Id as ParaId, OutboundHrmpMessage, OutboundHrmpBandwidth,
+	PersistedValidationData, PoV, Signed, UpwardMessage, ValidationCode,
 };
 use std::pin::Pin;
 
 pub use tet_core::traits::SpawnNamed;
+pub use tetcoin_parachain::wasm_executor::ValidationPriority;
 
 pub mod approval;
 
@@ -106,6 +107,10 @@ pub enum InvalidCandidate {
 	InvalidOutputs,
 	/// Execution timeout.
 	Timeout,
+	/// The validation function exceeded its heap.
+	OutOfMemory,
+	/// The validation function trapped.
+	Trap(String),
 	/// Validation input is over the limit.
 	ParamsTooLarge(u64),
 	/// Code size is over the limit.
@@ -161,8 +166,13 @@ pub struct Collation<BlockNumber = tetcoin_primitives::v1::BlockNumber> {
 /// Will be called with the hash of the relay chain block the parachain
 /// block should be build on and the [`ValidationData`] that provides
 /// information about the state of the parachain on the relay chain.
+///
+/// It is also given the [`OutboundHrmpBandwidth`] left to the parachain as of that relay chain
+/// block. The horizontal messages of the collation must fit into it, otherwise the candidate is
+/// rejected by the relay chain.
 pub type CollatorFn = Box<
-	dyn Fn(Hash, &PersistedValidationData) -> Pin<Box<dyn Future<Output = Option<Collation>> + Send>>
+	dyn Fn(Hash, &PersistedValidationData, &OutboundHrmpBandwidth)
+		-> Pin<Box<dyn Future<Output = Option<Collation>> + Send>>
 		+ Send
 		+ Sync,
 >;
@@ -434,6 +434,7 @@ where
 		),
 		candidate_validation: CandidateValidationSubsystem::new(
 			spawner.clone(),
+			keystore.clone(),
 			Metrics::register(registry)?,
 			isolation_strategy,
 		),
@@ -988,6 +989,7 @@ pub fn build_full(
 	is_collator: IsCollator,
 	grandpa_pause: Option<(u32, u32)>,
 	jaeger_agent: Option<std::net::SocketAddr>,
+	isolation_strategy: IsolationStrategy,
 ) -> Result<NewFull<Client>, Error> {
 	if config.chain_spec.is_rococo() {
 		new_full::<rococo_runtime::RuntimeApi, RococoExecutor>(
@@ -995,7 +997,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			jaeger_agent,
-			Default::default(),
+			isolation_strategy,
 		).map(|full| full.with_client(Client::Rococo))
 	} else if config.chain_spec.is_metrocoin() {
 		new_full::<metrocoin_runtime::RuntimeApi, MetrocoinExecutor>(
@@ -1003,7 +1005,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			jaeger_agent,
-			Default::default(),
+			isolation_strategy,
 		).map(|full| full.with_client(Client::Metrocoin))
 	} else if config.chain_spec.is_westend() {
 		new_full::<westend_runtime::RuntimeApi, WestendExecutor>(
@@ -1011,7 +1013,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			jaeger_agent,
-			Default::default(),
+			isolation_strategy,
 		).map(|full| full.with_client(Client::Westend))
 	} else {
 		new_full::<tetcoin_runtime::RuntimeApi, TetcoinExecutor>(
@@ -1019,7 +1021,7 @@ pub fn build_full(
 			is_collator,
 			grandpa_pause,
 			jaeger_agent,
-			Default::default(),
+			isolation_strategy,
 		).map(|full| full.with_client(Client::Tetcoin))
 	}
 }
@@ -38,6 +38,7 @@ use tetcoin_primitives::v1::{
 	CandidateEvent, CommittedCandidateReceipt, CoreState, EncodeAs, PersistedValidationData,
 	GroupRotationInfo, Hash, Id as ParaId, OccupiedCoreAssumption,
 	SessionIndex, Signed, SigningContext, ValidationCode, ValidatorId, ValidatorIndex, SessionInfo,
+	OutboundHrmpBandwidth, ParaLifecycle, PvfExecutionLimits,
 };
 use tet_core::{traits::SpawnNamed, Public};
 use tet_application_crypto::AppKey;
@@ -50,6 +51,7 @@ use streamunordered::{StreamUnordered, StreamYield};
 use thiserror::Error;
 
 pub mod validator_discovery;
+pub mod rolling_session_window;
 pub use metered_channel as metered;
 
 /// These reexports are required so that external crates can use the `delegated_subsystem` macro properly.
@@ -94,6 +96,9 @@ pub enum Error {
 	/// Already forwarding errors to another sender
 	#[error("AlreadyForwarding")]
 	AlreadyForwarding,
+	/// The session info of a session is not stored by the runtime.
+	#[error("Session info unavailable for session {0}")]
+	SessionInfoUnavailable(SessionIndex),
 }
 
 /// A type alias for Runtime API receivers.
@@ -175,7 +180,11 @@ specialize_requests! {
 	fn request_validation_code(para_id: ParaId, assumption: OccupiedCoreAssumption) -> Option<ValidationCode>; ValidationCode;
 	fn request_candidate_pending_availability(para_id: ParaId) -> Option<CommittedCandidateReceipt>; CandidatePendingAvailability;
 	fn request_candidate_events() -> Vec<CandidateEvent>; CandidateEvents;
+	fn request_para_lifecycle(para_id: ParaId) -> Option<ParaLifecycle>; ParaLifecycle;
 	fn request_session_info(index: SessionIndex) -> Option<SessionInfo>; SessionInfo;
+	fn request_earliest_stored_session() -> SessionIndex; EarliestStoredSession;
+	fn request_outbound_hrmp_channels_bandwidth(para_id: ParaId) -> OutboundHrmpBandwidth; OutboundHrmpChannelsBandwidth;
+	fn request_pvf_execution_limits() -> PvfExecutionLimits; PvfExecutionLimits;
 }
 
 /// Request some data from the `RuntimeApi` via a SubsystemContext.
@@ -256,7 +265,11 @@ specialize_requests_ctx! {
 	fn request_validation_code_ctx(para_id: ParaId, assumption: OccupiedCoreAssumption) -> Option<ValidationCode>; ValidationCode;
 	fn request_candidate_pending_availability_ctx(para_id: ParaId) -> Option<CommittedCandidateReceipt>; CandidatePendingAvailability;
 	fn request_candidate_events_ctx() -> Vec<CandidateEvent>; CandidateEvents;
+	fn request_para_lifecycle_ctx(para_id: ParaId) -> Option<ParaLifecycle>; ParaLifecycle;
 	fn request_session_info_ctx(index: SessionIndex) -> Option<SessionInfo>; SessionInfo;
+	fn request_earliest_stored_session_ctx() -> SessionIndex; EarliestStoredSession;
+	fn request_outbound_hrmp_channels_bandwidth_ctx(para_id: ParaId) -> OutboundHrmpBandwidth; OutboundHrmpChannelsBandwidth;
+	fn request_pvf_execution_limits_ctx() -> PvfExecutionLimits; PvfExecutionLimits;
 }
 
 /// From the given set of validators, find the first key we can sign with, if any.
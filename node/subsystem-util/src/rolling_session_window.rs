@@ -0,0 +1,336 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A rolling window of sessions and cached session info, updated by the state of newly imported blocks.
+//!
+//! This is useful for subsystems which need to stay up-to-date about recent sessions, but don't care
+//! about the state of particular blocks. The session infos are fetched only once per session.
+
+use tetcoin_primitives::v1::{Hash, SessionIndex, SessionInfo};
+use tetcoin_node_subsystem::SubsystemContext;
+
+use crate::{
+	Error, request_session_index_for_child_ctx, request_earliest_stored_session_ctx,
+	request_session_info_ctx,
+};
+
+/// An indicated update of the rolling session window.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SessionWindowUpdate {
+	/// The session window was just initialized to the current values.
+	Initialized {
+		/// The start of the window (inclusive).
+		window_start: SessionIndex,
+		/// The end of the window (inclusive).
+		window_end: SessionIndex,
+	},
+	/// The session window was updated.
+	Advanced {
+		/// The previous start of the window (inclusive).
+		prev_window_start: SessionIndex,
+		/// The previous end of the window (inclusive).
+		prev_window_end: SessionIndex,
+		/// The new start of the window (inclusive).
+		new_window_start: SessionIndex,
+		/// The new end of the window (inclusive).
+		new_window_end: SessionIndex,
+	},
+	/// The session window was unchanged.
+	Unchanged,
+}
+
+/// A rolling window of sessions and their infos.
+///
+/// The window covers at most `window_size` sessions, ending at the latest session observed. It never
+/// reaches back further than the earliest session the runtime still stores info for.
+#[derive(Debug)]
+pub struct RollingSessionWindow {
+	earliest_session: Option<SessionIndex>,
+	session_info: Vec<SessionInfo>,
+	window_size: SessionIndex,
+}
+
+impl RollingSessionWindow {
+	/// Create a new, empty, session window of the given size.
+	///
+	/// # Panics
+	///
+	/// Panics if `window_size` is zero.
+	pub fn new(window_size: SessionIndex) -> Self {
+		assert!(window_size != 0, "the session window must cover at least one session");
+
+		RollingSessionWindow {
+			earliest_session: None,
+			session_info: Vec::new(),
+			window_size,
+		}
+	}
+
+	/// Access the session info for the given session index, if stored within the window.
+	pub fn session_info(&self, index: SessionIndex) -> Option<&SessionInfo> {
+		self.earliest_session.and_then(|earliest| {
+			if index < earliest {
+				None
+			} else {
+				self.session_info.get((index - earliest) as usize)
+			}
+		})
+	}
+
+	/// Access the index of the earliest session, if the window is not empty.
+	pub fn earliest_session(&self) -> Option<SessionIndex> {
+		self.earliest_session
+	}
+
+	/// Access the index of the latest session, if the window is not empty.
+	pub fn latest_session(&self) -> Option<SessionIndex> {
+		self.earliest_session
+			.map(|earliest| earliest + (self.session_info.len() as SessionIndex).saturating_sub(1))
+	}
+
+	/// When inspecting a new import notification, updates the session info cache to match
+	/// the session of the imported block.
+	///
+	/// This only needs to be called on heads where we are directly notified about import, as sessions do
+	/// not change often and import notifications are expected to be typically increasing in session number.
+	///
+	/// Some backwards drift in session index is acceptable. The window is left untouched if fetching any
+	/// of the session infos fails.
+	pub async fn cache_session_info_for_head(
+		&mut self,
+		ctx: &mut impl SubsystemContext,
+		block_hash: Hash,
+	) -> Result<SessionWindowUpdate, Error> {
+		let session_index = request_session_index_for_child_ctx(block_hash, ctx).await?.await??;
+
+		let old_window = self.earliest_session.zip(self.latest_session());
+		if let Some((_, old_window_end)) = old_window {
+			if session_index <= old_window_end {
+				return Ok(SessionWindowUpdate::Unchanged)
+			}
+		}
+
+		let earliest_stored_session = request_earliest_stored_session_ctx(block_hash, ctx).await?.await??;
+		let window_start = std::cmp::max(
+			session_index.saturating_sub(self.window_size - 1),
+			earliest_stored_session,
+		);
+
+		match old_window {
+			None => {
+				let fresh = load_all_sessions(ctx, block_hash, window_start, session_index).await?;
+
+				self.earliest_session = Some(window_start);
+				self.session_info = fresh;
+
+				Ok(SessionWindowUpdate::Initialized {
+					window_start,
+					window_end: session_index,
+				})
+			}
+			Some((old_window_start, old_window_end)) => {
+				// Only the sessions which are not in the window yet need to be fetched.
+				let fetch_start = std::cmp::max(window_start, old_window_end + 1);
+				let fresh = load_all_sessions(ctx, block_hash, fetch_start, session_index).await?;
+
+				// A window start behind the old one means that the runtime started storing older sessions,
+				// which can't happen. Keep the old start in that case.
+				let window_start = std::cmp::max(window_start, old_window_start);
+				if window_start > old_window_end {
+					self.session_info = fresh;
+				} else {
+					self.session_info.drain(..(window_start - old_window_start) as usize);
+					self.session_info.extend(fresh);
+				}
+				self.earliest_session = Some(window_start);
+
+				Ok(SessionWindowUpdate::Advanced {
+					prev_window_start: old_window_start,
+					prev_window_end: old_window_end,
+					new_window_start: window_start,
+					new_window_end: session_index,
+				})
+			}
+		}
+	}
+}
+
+async fn load_all_sessions(
+	ctx: &mut impl SubsystemContext,
+	block_hash: Hash,
+	start: SessionIndex,
+	end_inclusive: SessionIndex,
+) -> Result<Vec<SessionInfo>, Error> {
+	let mut v = Vec::new();
+	for i in start..=end_inclusive {
+		match request_session_info_ctx(block_hash, i, ctx).await?.await?? {
+			Some(info) => v.push(info),
+			None => return Err(Error::SessionInfoUnavailable(i)),
+		}
+	}
+
+	Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use assert_matches::assert_matches;
+	use futures::{executor, future};
+	use tetcoin_node_subsystem::messages::{AllMessages, RuntimeApiMessage, RuntimeApiRequest};
+	use tetcoin_node_subsystem_test_helpers::{make_subsystem_context, TestSubsystemContextHandle};
+	use tet_core::testing::TaskExecutor;
+
+	const SESSION_WINDOW_SIZE: SessionIndex = 6;
+
+	fn dummy_session_info(index: SessionIndex) -> SessionInfo {
+		SessionInfo {
+			n_cores: index,
+			..Default::default()
+		}
+	}
+
+	async fn answer_requests(
+		handle: &mut TestSubsystemContextHandle<()>,
+		hash: Hash,
+		session: SessionIndex,
+		earliest_stored_session: SessionIndex,
+		expect_requests_from: Option<SessionIndex>,
+	) {
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				h,
+				RuntimeApiRequest::SessionIndexForChild(s_tx),
+			)) => {
+				assert_eq!(h, hash);
+				let _ = s_tx.send(Ok(session));
+			}
+		);
+
+		let expect_requests_from = match expect_requests_from {
+			Some(start) => start,
+			None => return,
+		};
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				h,
+				RuntimeApiRequest::EarliestStoredSession(s_tx),
+			)) => {
+				assert_eq!(h, hash);
+				let _ = s_tx.send(Ok(earliest_stored_session));
+			}
+		);
+
+		for i in expect_requests_from..=session {
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					h,
+					RuntimeApiRequest::SessionInfo(j, s_tx),
+				)) => {
+					assert_eq!(h, hash);
+					assert_eq!(i, j);
+					let _ = s_tx.send(Ok(Some(dummy_session_info(i))));
+				}
+			);
+		}
+	}
+
+	fn cache_session_info_test(
+		window: &mut RollingSessionWindow,
+		session: SessionIndex,
+		earliest_stored_session: SessionIndex,
+		expect_requests_from: Option<SessionIndex>,
+	) -> SessionWindowUpdate {
+		let hash = Hash::repeat_byte(session as u8);
+		let (mut ctx, mut handle) = make_subsystem_context::<(), _>(TaskExecutor::new());
+
+		let test_fut = async move {
+			window.cache_session_info_for_head(&mut ctx, hash).await.unwrap()
+		};
+		let aux_fut = answer_requests(&mut handle, hash, session, earliest_stored_session, expect_requests_from);
+
+		executor::block_on(future::join(test_fut, aux_fut)).0
+	}
+
+	#[test]
+	fn cache_session_info_first_early() {
+		let mut window = RollingSessionWindow::new(SESSION_WINDOW_SIZE);
+
+		let update = cache_session_info_test(&mut window, 1, 0, Some(0));
+
+		assert_eq!(update, SessionWindowUpdate::Initialized { window_start: 0, window_end: 1 });
+		assert_eq!(window.earliest_session(), Some(0));
+		assert_eq!(window.latest_session(), Some(1));
+		assert_eq!(window.session_info(1), Some(&dummy_session_info(1)));
+	}
+
+	#[test]
+	fn cache_session_info_is_bounded_by_window_and_runtime() {
+		let mut window = RollingSessionWindow::new(SESSION_WINDOW_SIZE);
+		let update = cache_session_info_test(&mut window, 20, 0, Some(20 - (SESSION_WINDOW_SIZE - 1)));
+		assert_eq!(update, SessionWindowUpdate::Initialized { window_start: 15, window_end: 20 });
+
+		// The runtime doesn't store anything before session 18.
+		let mut window = RollingSessionWindow::new(SESSION_WINDOW_SIZE);
+		let update = cache_session_info_test(&mut window, 20, 18, Some(18));
+		assert_eq!(update, SessionWindowUpdate::Initialized { window_start: 18, window_end: 20 });
+		assert!(window.session_info(17).is_none());
+		assert_eq!(window.session_info(18), Some(&dummy_session_info(18)));
+	}
+
+	#[test]
+	fn cache_session_info_advances_and_fetches_only_new_sessions() {
+		let mut window = RollingSessionWindow::new(SESSION_WINDOW_SIZE);
+		cache_session_info_test(&mut window, 10, 0, Some(5));
+
+		let update = cache_session_info_test(&mut window, 12, 0, Some(11));
+		assert_eq!(update, SessionWindowUpdate::Advanced {
+			prev_window_start: 5,
+			prev_window_end: 10,
+			new_window_start: 7,
+			new_window_end: 12,
+		});
+		assert!(window.session_info(6).is_none());
+		for i in 7..=12 {
+			assert_eq!(window.session_info(i), Some(&dummy_session_info(i)));
+		}
+
+		// A jump beyond the window replaces all of it.
+		let update = cache_session_info_test(&mut window, 30, 0, Some(25));
+		assert_eq!(update, SessionWindowUpdate::Advanced {
+			prev_window_start: 7,
+			prev_window_end: 12,
+			new_window_start: 25,
+			new_window_end: 30,
+		});
+		assert_eq!(window.earliest_session(), Some(25));
+		assert_eq!(window.session_info(25), Some(&dummy_session_info(25)));
+	}
+
+	#[test]
+	fn cache_session_info_unchanged_for_known_sessions() {
+		let mut window = RollingSessionWindow::new(SESSION_WINDOW_SIZE);
+		cache_session_info_test(&mut window, 10, 0, Some(5));
+
+		assert_eq!(cache_session_info_test(&mut window, 10, 0, None), SessionWindowUpdate::Unchanged);
+		assert_eq!(cache_session_info_test(&mut window, 9, 0, None), SessionWindowUpdate::Unchanged);
+		assert_eq!(window.latest_session(), Some(10));
+	}
+}
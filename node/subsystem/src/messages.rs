@@ -28,18 +28,19 @@ use tetcoin_node_network_protocol::{
 	v1 as protocol_v1, NetworkBridgeEvent, ReputationChange, PeerId,
 };
 use tetcoin_node_primitives::{
-	CollationGenerationConfig, SignedFullStatement, ValidationResult,
+	CollationGenerationConfig, SignedFullStatement, ValidationResult, ValidationPriority,
 	approval::{BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote},
 };
 use tetcoin_primitives::v1::{
 	AuthorityDiscoveryId, AvailableData, BackedCandidate, BlockNumber, SessionInfo,
 	Header as BlockHeader, CandidateDescriptor, CandidateEvent, CandidateReceipt,
 	CollatorId, CommittedCandidateReceipt, CoreState, ErasureChunk,
-	GroupRotationInfo, Hash, Id as ParaId, OccupiedCoreAssumption,
+	GroupIndex, GroupRotationInfo, Hash, Id as ParaId, OccupiedCoreAssumption,
 	PersistedValidationData, PoV, SessionIndex, SignedAvailabilityBitfield,
 	ValidationCode, ValidatorId, CandidateHash,
 	ValidatorIndex, ValidatorSignature, InboundDownwardMessage, InboundHrmpMessage,
-	CandidateIndex,
+	CandidateIndex, PvfCheckStatement, OutboundHrmpBandwidth, ParaLifecycle,
+	PvfExecutionLimits,
 };
 use tetcoin_statement_table::v1::Misbehavior;
 use std::{sync::Arc, collections::btree_map::BTreeMap};
@@ -123,25 +124,32 @@ pub enum CandidateValidationMessage {
 	///
 	/// If there is no state available which can provide this data or the core for
 	/// the para is not free at the relay-parent, an error is returned.
+	///
+	/// The priority determines which validation work this request may be delayed by.
 	ValidateFromChainState(
 		CandidateDescriptor,
 		Arc<PoV>,
+		ValidationPriority,
 		oneshot::Sender<Result<ValidationResult, ValidationFailed>>,
 	),
 	/// Validate a candidate with provided, exhaustive parameters for validation.
 	///
-	/// Explicitly provide the `PersistedValidationData` and `ValidationCode` so this can do full
-	/// validation without needing to access the state of the relay-chain.
+	/// Explicitly provide the `PersistedValidationData`, `ValidationCode` and `PvfExecutionLimits`
+	/// so this can do full validation without needing to access the state of the relay-chain.
 	///
 	/// This request doesn't involve acceptance criteria checking, therefore only useful for the
 	/// cases where the validity of the candidate is established. This is the case for the typical
 	/// use-case: secondary checkers would use this request relying on the full prior checks
 	/// performed by the relay-chain.
+	///
+	/// The priority determines which validation work this request may be delayed by.
 	ValidateFromExhaustive(
 		PersistedValidationData,
 		ValidationCode,
+		PvfExecutionLimits,
 		CandidateDescriptor,
 		Arc<PoV>,
+		ValidationPriority,
 		oneshot::Sender<Result<ValidationResult, ValidationFailed>>,
 	),
 }
@@ -150,8 +158,8 @@ impl CandidateValidationMessage {
 	/// If the current variant contains the relay parent hash, return it.
 	pub fn relay_parent(&self) -> Option<Hash> {
 		match self {
-			Self::ValidateFromChainState(_, _, _) => None,
-			Self::ValidateFromExhaustive(_, _, _, _, _) => None,
+			Self::ValidateFromChainState(_, _, _, _) => None,
+			Self::ValidateFromExhaustive(_, _, _, _, _, _, _) => None,
 		}
 	}
 }
@@ -252,9 +260,13 @@ pub enum AvailabilityDistributionMessage {
 #[derive(Debug)]
 pub enum AvailabilityRecoveryMessage {
 	/// Recover available data from validators on the network.
+	///
+	/// If the index of the group that backed the candidate is known, the full data is first
+	/// requested from the validators of that group, before falling back to chunk recovery.
 	RecoverAvailableData(
 		CandidateReceipt,
 		SessionIndex,
+		Option<GroupIndex>,
 		oneshot::Sender<Result<AvailableData, crate::errors::RecoveryError>>,
 	),
 	/// Event from the network bridge.
@@ -446,8 +458,12 @@ pub enum RuntimeApiRequest {
 	/// Get all events concerning candidates (backing, inclusion, time-out) in the parent of
 	/// the block in whose state this request is executed.
 	CandidateEvents(RuntimeApiSender<Vec<CandidateEvent>>),
+	/// Get the lifecycle state of a para, if it is known to the relay chain.
+	ParaLifecycle(ParaId, RuntimeApiSender<Option<ParaLifecycle>>),
 	/// Get the session info for the given session, if stored.
 	SessionInfo(SessionIndex, RuntimeApiSender<Option<SessionInfo>>),
+	/// Get the index of the earliest session for which the session info is still stored.
+	EarliestStoredSession(RuntimeApiSender<SessionIndex>),
 	/// Get all the pending inbound messages in the downward message queue for a para.
 	DmqContents(
 		ParaId,
@@ -459,6 +475,19 @@ pub enum RuntimeApiRequest {
 		ParaId,
 		RuntimeApiSender<BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>>>,
 	),
+	/// Get the remaining bandwidth of all outbound channels of the given sender.
+	OutboundHrmpChannelsBandwidth(
+		ParaId,
+		RuntimeApiSender<OutboundHrmpBandwidth>,
+	),
+	/// Get the limits under which parachain validation functions are executed.
+	PvfExecutionLimits(RuntimeApiSender<PvfExecutionLimits>),
+	/// Get the hashes of the validation code awaiting pre-checking votes from the validators.
+	PvfsRequirePrecheck(RuntimeApiSender<Vec<Hash>>),
+	/// Get the validation code under pre-checking by its hash.
+	ValidationCodeByHash(Hash, RuntimeApiSender<Option<ValidationCode>>),
+	/// Submit a signed pre-checking statement of the local validator to the transaction pool.
+	SubmitPvfCheckStatement(PvfCheckStatement, ValidatorSignature, RuntimeApiSender<()>),
 }
 
 /// A message to the Runtime API subsystem.
@@ -55,6 +55,32 @@ impl HeadData {
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize, Hash, MallocSizeOf))]
 pub struct ValidationCode(#[cfg_attr(feature = "std", serde(with="bytes"))] pub Vec<u8>);
 
+/// The limits under which parachain validation functions are executed.
+///
+/// These are set by the relay chain, so that all the validators check a candidate under the same
+/// limits.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(MallocSizeOf))]
+pub struct PvfExecutionLimits {
+	/// The time the execution may take, in milliseconds.
+	pub timeout_ms: u32,
+	/// The number of wasm pages, of 64 KiB each, the validation function may use for its heap.
+	pub max_memory_pages: u32,
+	/// The maximum height of the value stack of the validation function, counted in values
+	/// rather than bytes so that exceeding it traps deterministically with every executor.
+	pub max_stack_height: u32,
+}
+
+impl Default for PvfExecutionLimits {
+	fn default() -> Self {
+		PvfExecutionLimits {
+			timeout_ms: 5_000,
+			max_memory_pages: 1024,
+			max_stack_height: 64 * 1024,
+		}
+	}
+}
+
 /// Parachain block data.
 ///
 /// Contains everything required to validate para-block, may contain block and witness data.
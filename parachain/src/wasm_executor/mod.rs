@@ -20,17 +20,25 @@
 //! Assuming the parameters are correct, this module provides a wrapper around
 //! a WASM VM for re-execution of a parachain candidate.
 
-use std::{any::{TypeId, Any}, path::PathBuf};
-use crate::primitives::{ValidationParams, ValidationResult};
+use std::{any::{TypeId, Any}, cell::{Cell, RefCell}, path::PathBuf, time::{Duration, Instant}};
+use crate::primitives::{PvfExecutionLimits, ValidationParams, ValidationResult};
 use tetsy_scale_codec::{Decode, Encode};
 use tet_core::{storage::{ChildInfo, TrackedStorageKey}, traits::{CallInWasm, SpawnNamed}};
 use externalities::Extensions;
-use tetcore_wasm_interface::HostFunctions as _;
+use tetcore_wasm_interface::{
+	Function, FunctionContext, HostFunctions as _, Signature, Value, ValueType,
+};
 
 #[cfg(not(any(target_os = "android", target_os = "unknown")))]
-pub use validation_host::{run_worker, ValidationPool, EXECUTION_TIMEOUT_SEC, WORKER_ARGS};
+pub use validation_host::{
+	run_worker, ValidationPool, ValidationPoolConfig, WORKER_ARGS,
+};
+
+#[cfg(not(any(target_os = "android", target_os = "unknown")))]
+pub use sandbox::{check_sandbox, SandboxMode};
 
 mod validation_host;
+mod sandbox;
 
 // maximum memory in bytes
 const MAX_RUNTIME_MEM: usize = 1024 * 1024 * 1024; // 1 GiB
@@ -58,7 +66,8 @@ const MAX_VALIDATION_RESULT_HEADER_MEM: usize = MAX_CODE_MEM + 1024; // 16.001 M
 ///
 /// On top of that, execution in a separate process helps to minimize impact of (c) if exploited.
 /// It's not only the risk of miscompilation, but it also includes risk of JIT-bombs, i.e. cases
-/// of specially crafted code that take enourmous amounts of time and memory to compile.
+/// of specially crafted code that take enourmous amounts of time and memory to compile. To that
+/// end, the workers also sandbox themselves, see [`SandboxMode`].
 ///
 /// At the same time, since PVF validates self-contained candidates, validation workers don't require
 /// extensive communication with tetcoin host, therefore there should be no observable performance penalty
@@ -105,6 +114,28 @@ impl Default for IsolationStrategy {
 	}
 }
 
+#[cfg(not(any(target_os = "android", target_os = "unknown")))]
+impl IsolationStrategy {
+	/// The default strategy, with a validation pool of the given configuration.
+	pub fn with_pool_config(config: ValidationPoolConfig) -> Self {
+		Self::ExternalProcessSelfHost(ValidationPool::with_config(config))
+	}
+}
+
+/// The reason a candidate is validated for.
+///
+/// The variants are ordered from the most to the least urgent. Check out [`ValidationPool`] for how
+/// the priority affects the scheduling of the validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationPriority {
+	/// The candidate is validated in order to be backed, which needs to happen within a few blocks.
+	Backing,
+	/// The candidate is validated in order to participate in a dispute.
+	Dispute,
+	/// The candidate is validated in order to be approved.
+	Approval,
+}
+
 #[derive(Debug, thiserror::Error)]
 /// Candidate validation error.
 pub enum ValidationError {
@@ -133,13 +164,40 @@ pub enum InvalidCandidate {
 	BadReturn,
 	#[error("Validation function timeout.")]
 	Timeout,
+	/// The validation function exceeded the heap it is allowed to use.
+	#[error("Validation function ran out of memory.")]
+	OutOfMemory,
+	/// The validation function trapped, e.g. by reaching an `unreachable` instruction or by
+	/// exceeding its stack height limit.
+	#[error("Validation function trapped: {0}")]
+	Trap(String),
 	#[error("External WASM execution error: {0}")]
 	ExternalWasmExecutor(String),
+	/// The validation code is not a well-formed wasm module.
+	#[error("Validation code is not a valid WASM module: {0}")]
+	InvalidModule(parity_wasm::elements::Error),
+	/// The validation code exports a function under a name reserved for the host.
+	#[error("Validation code exports the reserved name {0}")]
+	ReservedExport(&'static str),
+	/// The validation code could not be prepared for execution.
+	#[error("Validation code could not be prepared: {0}")]
+	Preparation(String),
 }
 
+/// Fragments of the executor errors which mean the validation function trapped.
+///
+/// Either way the candidate is invalid, so this only decides how the failure is reported.
+const TRAP_ERRORS: &[&str] = &["Trap", "trapped", "unreachable", "panicked", "aborted due to panic"];
+
 impl core::convert::From<String> for InvalidCandidate {
 	fn from(s: String) -> Self {
-		Self::ExternalWasmExecutor(s)
+		// The executor only reports errors as strings, so this is the best we can do to tell them
+		// apart.
+		if TRAP_ERRORS.iter().any(|e| s.contains(e)) {
+			Self::Trap(s)
+		} else {
+			Self::ExternalWasmExecutor(s)
+		}
 	}
 }
 
@@ -158,61 +216,269 @@ pub enum InternalError {
 
 	#[error("WASM worker error: {0}")]
 	WasmWorker(String),
+
+	#[error("Validation thread terminated unexpectedly")]
+	ValidationThreadDied,
 }
 
 /// A cache of executors for different parachain Wasm instances.
 ///
 /// This should be reused across candidate validation instances.
+#[derive(Clone)]
 pub struct ExecutorCache(tc_executor::WasmExecutor);
 
-impl Default for ExecutorCache {
-	fn default() -> Self {
+thread_local! {
+	/// The executor used for validation by the worker on this thread, along with the number of
+	/// heap pages it was created with.
+	///
+	/// The executor keeps the modules it compiled around, keyed by the code hash, so reusing it
+	/// spares recompiling the same validation code for every candidate.
+	static EXECUTOR: RefCell<Option<(u32, ExecutorCache)>> = RefCell::new(None);
+}
+
+impl ExecutorCache {
+	/// Creates an executor which gives the validation functions the given number of heap pages.
+	pub fn new(max_memory_pages: u32) -> Self {
 		ExecutorCache(tc_executor::WasmExecutor::new(
 			#[cfg(all(feature = "wasmtime", not(any(target_os = "android", target_os = "unknown"))))]
 			tc_executor::WasmExecutionMethod::Compiled,
 			#[cfg(any(not(feature = "wasmtime"), target_os = "android", target_os = "unknown"))]
 			tc_executor::WasmExecutionMethod::Interpreted,
-			// TODO: Make sure we don't use more than 1GB: https://github.com/tetcoin/tetcoin/issues/699
-			Some(1024),
-			HostFunctions::host_functions(),
+			Some(max_memory_pages as u64),
+			host_functions(),
 			8
 		))
 	}
 }
 
+impl Default for ExecutorCache {
+	fn default() -> Self {
+		Self::new(PvfExecutionLimits::default().max_memory_pages)
+	}
+}
+
+/// Returns the executor of this thread for the given number of heap pages.
+///
+/// The executor is only recreated, losing the modules compiled so far, if the number of heap
+/// pages changed.
+fn thread_executor(max_memory_pages: u32) -> ExecutorCache {
+	EXECUTOR.with(|executor| {
+		let mut executor = executor.borrow_mut();
+		match &*executor {
+			Some((pages, cache)) if *pages == max_memory_pages => cache.clone(),
+			_ => {
+				let cache = ExecutorCache::new(max_memory_pages);
+				*executor = Some((max_memory_pages, cache.clone()));
+				cache
+			}
+		}
+	})
+}
+
 /// Validate a candidate under the given validation code.
 ///
+/// The execution is subject to the given limits with every isolation strategy. The priority is
+/// only taken into account by the validation pools of the external process strategies.
+///
 /// This will fail if the validation code is not a proper parachain validation module.
 pub fn validate_candidate(
 	validation_code: &[u8],
 	params: ValidationParams,
 	isolation_strategy: &IsolationStrategy,
+	limits: PvfExecutionLimits,
+	priority: ValidationPriority,
 	spawner: impl SpawnNamed + 'static,
 ) -> Result<ValidationResult, ValidationError> {
 	match isolation_strategy {
 		IsolationStrategy::InProcess => {
-			validate_candidate_internal(
-				&ExecutorCache::default(),
-				validation_code,
-				&params.encode(),
+			validate_on_native_stack(
+				ExecutorCache::new(limits.max_memory_pages),
+				validation_code.to_vec(),
+				params.encode(),
+				limits,
 				spawner,
 			)
 		},
 		#[cfg(not(any(target_os = "android", target_os = "unknown")))]
 		IsolationStrategy::ExternalProcessSelfHost(pool) => {
-			pool.validate_candidate(validation_code, params)
+			pool.validate_candidate(validation_code, params, limits, priority)
 		},
 		#[cfg(not(any(target_os = "android", target_os = "unknown")))]
 		IsolationStrategy::ExternalProcessCustomHost { pool, binary, args } => {
 			let args: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
-			pool.validate_candidate_custom(validation_code, params, binary, &args)
+			pool.validate_candidate_custom(validation_code, params, limits, priority, binary, &args)
 		},
 	}
 }
 
+/// The size of the native stack validation functions are executed on, in bytes.
+///
+/// This leaves enough room for any stack height the relay chain allows, so that a validation
+/// function exceeding its stack height limit traps before the native stack overflows.
+const NATIVE_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Validate a candidate on a new thread with a native stack of [`NATIVE_STACK_SIZE`], waiting for
+/// the validation to finish.
+fn validate_on_native_stack(
+	executor: ExecutorCache,
+	validation_code: Vec<u8>,
+	encoded_call_data: Vec<u8>,
+	limits: PvfExecutionLimits,
+	spawner: impl SpawnNamed + 'static,
+) -> Result<ValidationResult, ValidationError> {
+	on_native_stack(move || validate_candidate_internal(
+		&executor,
+		&validation_code,
+		&encoded_call_data,
+		limits,
+		spawner,
+	))
+}
+
+/// Run the given work with the validation code on a new thread with a native stack of
+/// [`NATIVE_STACK_SIZE`], waiting for it to finish.
+fn on_native_stack<R: Send + 'static>(
+	work: impl FnOnce() -> Result<R, ValidationError> + Send + 'static,
+) -> Result<R, ValidationError> {
+	std::thread::Builder::new()
+		.name("pvf-validation".into())
+		.stack_size(NATIVE_STACK_SIZE)
+		.spawn(work)
+		.map_err(|e| ValidationError::Internal(e.into()))?
+		.join()
+		.unwrap_or_else(|_| Err(ValidationError::Internal(InternalError::ValidationThreadDied)))
+}
+
 /// The host functions provided by the wasm executor to the parachain wasm blob.
 type HostFunctions = tet_io::TetcoreHostFunctions;
 
+/// The name of the host function through which the validation function allocates its heap.
+const MALLOC: &str = "ext_allocator_malloc_version_1";
+
+/// The name of the host function the validation code is instrumented to call with the cost of
+/// every block of instructions before executing it.
+const GAS: &str = "gas";
+
+/// The cost of the instructions the validation function executes between two looks at the clock.
+const GAS_PER_CLOCK_CHECK: u64 = 100_000;
+
+thread_local! {
+	/// Whether the validation function running on this thread was refused a heap allocation.
+	static OUT_OF_MEMORY: Cell<bool> = Cell::new(false);
+
+	/// The time by which the validation function running on this thread must be done.
+	static DEADLINE: Cell<Option<Instant>> = Cell::new(None);
+
+	/// The cost of the instructions executed on this thread since the clock was last looked at.
+	static GAS_SINCE_CLOCK_CHECK: Cell<u64> = Cell::new(0);
+
+	/// Whether the validation function running on this thread was interrupted at its deadline.
+	static TIMED_OUT: Cell<bool> = Cell::new(false);
+}
+
+/// The allocator's `malloc` host function, which records the validation function running out of
+/// heap in [`OUT_OF_MEMORY`] before trapping.
+///
+/// The heap has a fixed size, so whether an allocation fits does not depend on the machine.
+struct TrackedMalloc;
+
+impl Function for TrackedMalloc {
+	fn name(&self) -> &str {
+		MALLOC
+	}
+
+	fn signature(&self) -> Signature {
+		Signature::new(vec![ValueType::I32], Some(ValueType::I32))
+	}
+
+	fn execute(
+		&self,
+		context: &mut dyn FunctionContext,
+		args: &mut dyn Iterator<Item = Value>,
+	) -> tetcore_wasm_interface::Result<Option<Value>> {
+		let size = match args.next() {
+			Some(Value::I32(size)) => size as u32,
+			_ => return Err(format!("{} expects a single i32 argument", MALLOC)),
+		};
+
+		match context.allocate_memory(size) {
+			Ok(ptr) => Ok(Some(Value::I32(u32::from(ptr) as i32))),
+			Err(e) => {
+				OUT_OF_MEMORY.with(|out_of_memory| out_of_memory.set(true));
+				Err(e)
+			}
+		}
+	}
+}
+
+/// The `gas` host function, which interrupts the validation function once it runs past the
+/// [`DEADLINE`], recording that in [`TIMED_OUT`].
+///
+/// The validation code is instrumented to call it for every block of instructions, so the
+/// execution can't escape the deadline, whatever the executor.
+struct TimeoutCheck;
+
+impl Function for TimeoutCheck {
+	fn name(&self) -> &str {
+		GAS
+	}
+
+	fn signature(&self) -> Signature {
+		Signature::new(vec![ValueType::I32], None)
+	}
+
+	fn execute(
+		&self,
+		_context: &mut dyn FunctionContext,
+		args: &mut dyn Iterator<Item = Value>,
+	) -> tetcore_wasm_interface::Result<Option<Value>> {
+		let cost = match args.next() {
+			Some(Value::I32(cost)) => cost as u32 as u64,
+			_ => return Err(format!("{} expects a single i32 argument", GAS)),
+		};
+
+		// looking at the clock for every block of instructions would slow the execution down.
+		let gas = GAS_SINCE_CLOCK_CHECK.with(|gas| gas.get()).saturating_add(cost);
+		if gas < GAS_PER_CLOCK_CHECK {
+			GAS_SINCE_CLOCK_CHECK.with(|since_check| since_check.set(gas));
+			return Ok(None);
+		}
+		GAS_SINCE_CLOCK_CHECK.with(|since_check| since_check.set(0));
+
+		match DEADLINE.with(|deadline| deadline.get()) {
+			Some(deadline) if Instant::now() >= deadline => {
+				TIMED_OUT.with(|timed_out| timed_out.set(true));
+				Err("Validation function ran past its deadline".into())
+			}
+			_ => Ok(None),
+		}
+	}
+}
+
+/// The host functions given to the validation function, with `malloc` replaced by
+/// [`TrackedMalloc`] and with [`TimeoutCheck`] added.
+fn host_functions() -> Vec<&'static dyn Function> {
+	HostFunctions::host_functions()
+		.into_iter()
+		.map(|f| if f.name() == MALLOC { &TrackedMalloc as &'static dyn Function } else { f })
+		.chain(std::iter::once(&TimeoutCheck as &'static dyn Function))
+		.collect()
+}
+
+/// Instruments the validation code so that it calls [`TimeoutCheck`] for every block of
+/// instructions, and so that it traps as soon as the height of its value stack exceeds the given
+/// one.
+fn instrument(validation_code: &[u8], max_stack_height: u32) -> Result<Vec<u8>, InvalidCandidate> {
+	let module = parity_wasm::deserialize_buffer(validation_code)
+		.map_err(InvalidCandidate::InvalidModule)?;
+	let module = pwasm_utils::inject_gas_counter(module, &Default::default())
+		.map_err(|_| InvalidCandidate::Preparation("Failed to inject the timeout checks".into()))?;
+	let module = pwasm_utils::stack_height::inject_limiter(module, max_stack_height)
+		.map_err(|e| InvalidCandidate::Preparation(format!("{:?}", e)))?;
+
+	parity_wasm::serialize(module).map_err(InvalidCandidate::InvalidModule)
+}
+
 /// Validate a candidate under the given validation code.
 ///
 /// This will fail if the validation code is not a proper parachain validation module.
@@ -220,10 +486,13 @@ pub fn validate_candidate_internal(
 	executor: &ExecutorCache,
 	validation_code: &[u8],
 	encoded_call_data: &[u8],
+	limits: PvfExecutionLimits,
 	spawner: impl SpawnNamed + 'static,
 ) -> Result<ValidationResult, ValidationError> {
 	let executor = &executor.0;
 
+	let validation_code = instrument(validation_code, limits.max_stack_height)?;
+
 	let mut extensions = Extensions::new();
 	extensions.register(tet_core::traits::TaskExecutorExt::new(spawner));
 	extensions.register(tet_core::traits::CallInWasmExt::new(executor.clone()));
@@ -234,22 +503,159 @@ pub fn validate_candidate_internal(
 	// And we need this hash to access the `tc_executor` cache.
 	let code_hash = {
 		use tetcoin_core_primitives::{BlakeTwo256, HashT};
-		BlakeTwo256::hash(validation_code)
+		BlakeTwo256::hash(&validation_code)
 	};
 
-	let res = executor.call_in_wasm(
-		validation_code,
+	let res = call_instrumented(
+		executor,
+		&validation_code,
 		Some(code_hash.as_bytes().to_vec()),
 		"validate_block",
 		encoded_call_data,
 		&mut ext,
-		tet_core::traits::MissingHostFunctions::Allow,
-	).map_err(|e| ValidationError::InvalidCandidate(e.into()))?;
+		limits.timeout_ms,
+	)?;
 
 	ValidationResult::decode(&mut &res[..])
 		.map_err(|_| ValidationError::InvalidCandidate(InvalidCandidate::BadReturn).into())
 }
 
+/// Calls the given function of the [instrumented](instrument) validation code on this thread,
+/// interrupting it once it ran for the given number of milliseconds.
+///
+/// Running out of heap and running past the deadline are told apart from other failures.
+fn call_instrumented(
+	executor: &tc_executor::WasmExecutor,
+	validation_code: &[u8],
+	code_hash: Option<Vec<u8>>,
+	method: &str,
+	call_data: &[u8],
+	ext: &mut ValidationExternalities,
+	timeout_ms: u32,
+) -> Result<Vec<u8>, InvalidCandidate> {
+	OUT_OF_MEMORY.with(|out_of_memory| out_of_memory.set(false));
+	TIMED_OUT.with(|timed_out| timed_out.set(false));
+	GAS_SINCE_CLOCK_CHECK.with(|since_check| since_check.set(0));
+	DEADLINE.with(|deadline| deadline.set(Some(Instant::now() + Duration::from_millis(timeout_ms as u64))));
+
+	let res = executor.call_in_wasm(
+		validation_code,
+		code_hash,
+		method,
+		call_data,
+		ext,
+		tet_core::traits::MissingHostFunctions::Allow,
+	);
+	DEADLINE.with(|deadline| deadline.set(None));
+
+	res.map_err(|e| if OUT_OF_MEMORY.with(|out_of_memory| out_of_memory.replace(false)) {
+		InvalidCandidate::OutOfMemory
+	} else if TIMED_OUT.with(|timed_out| timed_out.replace(false)) {
+		InvalidCandidate::Timeout
+	} else {
+		e.into()
+	})
+}
+
+/// The name of the export added to the validation code during pre-checking. Validation code must
+/// not export a function under this name itself.
+const PRECHECK_PROBE_EXPORT: &str = "__tetcoin_pvf_precheck_probe";
+
+/// Pre-check the given validation code, i.e. check that it can be prepared for execution.
+///
+/// A function which returns right away is added to the code and exported as
+/// [`PRECHECK_PROBE_EXPORT`]. The code passes if the wasm executor manages to compile and
+/// instantiate it and call that function, subject to the given limits.
+///
+/// The code is untrusted, so this is isolated just like validation is, on the least urgent hosts
+/// of the validation pool of the external process strategies. This blocks until pre-checking
+/// finishes, so it should be run on a blocking task.
+pub fn precheck(
+	validation_code: &[u8],
+	isolation_strategy: &IsolationStrategy,
+	limits: PvfExecutionLimits,
+) -> Result<(), ValidationError> {
+	match isolation_strategy {
+		IsolationStrategy::InProcess => {
+			let validation_code = validation_code.to_vec();
+			on_native_stack(move || precheck_internal(&validation_code, limits))
+		},
+		#[cfg(not(any(target_os = "android", target_os = "unknown")))]
+		IsolationStrategy::ExternalProcessSelfHost(pool) => {
+			pool.precheck(validation_code, limits)
+		},
+		#[cfg(not(any(target_os = "android", target_os = "unknown")))]
+		IsolationStrategy::ExternalProcessCustomHost { pool, binary, args } => {
+			let args: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
+			pool.precheck_custom(validation_code, limits, binary, &args)
+		},
+	}
+}
+
+/// Pre-check the given validation code on this thread.
+fn precheck_internal(validation_code: &[u8], limits: PvfExecutionLimits) -> Result<(), ValidationError> {
+	if validation_code.len() > MAX_CODE_MEM {
+		return Err(InvalidCandidate::CodeTooLarge(validation_code.len()).into());
+	}
+
+	// the code is executed instrumented, so the instrumentation must succeed as well.
+	let probed_code = instrument(&with_precheck_probe(validation_code)?, limits.max_stack_height)?;
+
+	let executor = ExecutorCache::new(limits.max_memory_pages);
+	let mut ext = ValidationExternalities(Extensions::new());
+
+	call_instrumented(
+		&executor.0,
+		&probed_code,
+		None,
+		PRECHECK_PROBE_EXPORT,
+		&[],
+		&mut ext,
+		limits.timeout_ms,
+	)
+		.map(|_| ())
+		.map_err(|e| InvalidCandidate::Preparation(e.to_string()).into())
+}
+
+/// Adds the pre-checking probe to the given validation code, returning the modified code.
+fn with_precheck_probe(validation_code: &[u8]) -> Result<Vec<u8>, InvalidCandidate> {
+	use parity_wasm::{builder, elements::{Instruction, Instructions, ValueType}};
+
+	let module: parity_wasm::elements::Module = parity_wasm::deserialize_buffer(validation_code)
+		.map_err(InvalidCandidate::InvalidModule)?;
+
+	let reserved = module.export_section().map_or(false, |exports| {
+		exports.entries().iter().any(|e| e.field() == PRECHECK_PROBE_EXPORT)
+	});
+	if reserved {
+		return Err(InvalidCandidate::ReservedExport(PRECHECK_PROBE_EXPORT));
+	}
+
+	let n_imported_functions = module.import_count(parity_wasm::elements::ImportCountType::Function);
+	let mut module = builder::from_module(module);
+
+	// the probe has the signature of any other runtime export and returns an empty result.
+	let probe = builder::function()
+		.signature()
+			.with_params(vec![ValueType::I32, ValueType::I32])
+			.with_result(ValueType::I64)
+			.build()
+		.body()
+			.with_instructions(Instructions::new(vec![Instruction::I64Const(0), Instruction::End]))
+			.build()
+		.build();
+	let location = module.push_function(probe);
+
+	let module = module
+		.export()
+			.field(PRECHECK_PROBE_EXPORT)
+			.internal().func(n_imported_functions as u32 + location.body)
+			.build()
+		.build();
+
+	parity_wasm::serialize(module).map_err(InvalidCandidate::InvalidModule)
+}
+
 /// The validation externalities that will panic on any storage related access. They just provide
 /// access to the parachain extension.
 struct ValidationExternalities(Extensions);
@@ -0,0 +1,706 @@
+// Copyright 2021 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sandboxing of the validation worker processes.
+//!
+//! A validation worker executes untrusted code with an optimizing compiler, so a bug in the
+//! compiler could let a parachain take over the worker. Before touching any candidate, the worker
+//! therefore locks itself down, so that it can do little more than compute with the memory it
+//! already has:
+//!
+//! - it can't gain privileges anymore and drops all of its capabilities,
+//! - landlock denies it any access to the file system,
+//! - a seccomp filter denies it the system calls for networking, spawning programs, inspecting or
+//!   signalling other processes and threads, creating user namespaces, io_uring and reconfiguring
+//!   the system,
+//! - rlimits keep it from writing files, dumping its core and opening many descriptors.
+//!
+//! The restrictions only apply to the calling thread and the threads it spawns afterwards, so
+//! [`lock_down`] must be called before the worker spawns any threads. After applying them, a
+//! self-test checks that they are actually in effect.
+//!
+//! Sandboxing is only supported on Linux on x86_64 and aarch64. Elsewhere, no restriction can be
+//! put in place, so workers only validate in [`SandboxMode::Degraded`].
+
+#![cfg(not(any(target_os = "android", target_os = "unknown")))]
+
+use log::warn;
+
+const LOG_TARGET: &'static str = "validation-worker";
+
+/// How a validation worker deals with sandboxing features missing in the kernel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SandboxMode {
+	/// Refuse to validate anything unless all of the restrictions are in effect.
+	Strict,
+	/// Apply the restrictions the kernel supports and validate anyway, warning about the rest.
+	///
+	/// A worker running like this offers less protection from a compromised validation function.
+	Degraded,
+}
+
+impl Default for SandboxMode {
+	fn default() -> Self {
+		SandboxMode::Strict
+	}
+}
+
+/// Restricts the calling thread, and any thread it spawns afterwards, to the bare minimum a
+/// validation worker needs, and checks that the restrictions are in effect.
+///
+/// Fails if some restriction couldn't be put in place, unless the mode is
+/// [`SandboxMode::Degraded`].
+pub fn lock_down(mode: SandboxMode) -> Result<(), String> {
+	let problems = imp::lock_down();
+
+	if problems.is_empty() {
+		return Ok(());
+	}
+
+	let problems = problems.join("; ");
+	match mode {
+		SandboxMode::Strict => Err(format!("Failed to sandbox the validation worker: {}", problems)),
+		SandboxMode::Degraded => {
+			warn!(
+				target: LOG_TARGET,
+				"{} Running the validation worker with a degraded sandbox: {}",
+				std::process::id(),
+				problems,
+			);
+			Ok(())
+		}
+	}
+}
+
+/// Checks that validation workers are able to sandbox themselves, by locking down a forked child
+/// process.
+///
+/// This is meant to be run once when the node starts, before it spawns any threads, so that a
+/// kernel lacking some sandboxing feature is reported right away instead of by every worker.
+pub fn check_sandbox() -> Result<(), String> {
+	let problems = imp::in_child(imp::lock_down)
+		.map_err(|e| format!("Failed to check the validation worker sandbox: {}", e))?;
+
+	if problems.is_empty() {
+		Ok(())
+	} else {
+		Err(problems.join("; "))
+	}
+}
+
+#[cfg(not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64"))))]
+mod imp {
+	pub fn lock_down() -> Vec<String> {
+		vec!["sandboxing is only supported on Linux on x86_64 and aarch64".into()]
+	}
+
+	/// Runs the given function in the calling process, since [`lock_down`] doesn't restrict
+	/// anything here.
+	pub fn in_child(f: fn() -> Vec<String>) -> std::io::Result<Vec<String>> {
+		Ok(f())
+	}
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod imp {
+	use std::{fs::File, io::{self, Read, Write}, os::unix::io::FromRawFd};
+
+	/// The number of file descriptors the worker may have open.
+	const MAX_OPEN_FILES: libc::rlim_t = 64;
+
+	/// Applies all the restrictions and runs the self-test, returning what went wrong.
+	pub fn lock_down() -> Vec<String> {
+		// the order matters: the bounding set can only be dropped while the worker still has its
+		// capabilities, and both landlock and seccomp require `no_new_privs`.
+		let steps: &[(&str, fn() -> io::Result<()>)] = &[
+			("rlimits", set_rlimits),
+			("no_new_privs", set_no_new_privs),
+			("capabilities", drop_capabilities),
+			("landlock", restrict_file_system),
+			("seccomp", filter_syscalls),
+		];
+
+		let mut problems: Vec<String> = steps.iter()
+			.filter_map(|(name, step)| step().err().map(|e| format!("{}: {}", name, e)))
+			.collect();
+
+		// a restriction which couldn't be applied is bound to fail the self-test as well.
+		for problem in self_test() {
+			let feature = problem.split(':').next().unwrap_or_default();
+			if !problems.iter().any(|p| p.starts_with(feature)) {
+				problems.push(problem);
+			}
+		}
+
+		problems
+	}
+
+	/// Runs the given function in a forked child process, returning the lines it produced.
+	///
+	/// The calling process must not have spawned any threads, since only the calling thread is
+	/// carried over to the child.
+	pub fn in_child(f: fn() -> Vec<String>) -> io::Result<Vec<String>> {
+		let mut fds = [0; 2];
+		check(unsafe { libc::pipe(fds.as_mut_ptr()) } as _)?;
+		let (read_fd, write_fd) = (fds[0], fds[1]);
+
+		let pid = match check(unsafe { libc::fork() } as _) {
+			Ok(0) => {
+				unsafe { libc::close(read_fd) };
+				let mut pipe = unsafe { File::from_raw_fd(write_fd) };
+				let _ = pipe.write_all(f().join("\n").as_bytes());
+				unsafe { libc::_exit(0) }
+			}
+			Ok(pid) => pid as libc::pid_t,
+			Err(e) => {
+				unsafe {
+					libc::close(read_fd);
+					libc::close(write_fd);
+				}
+				return Err(e);
+			}
+		};
+
+		unsafe { libc::close(write_fd) };
+		let mut output = String::new();
+		let read = unsafe { File::from_raw_fd(read_fd) }.read_to_string(&mut output);
+
+		let mut status = 0;
+		check(unsafe { libc::waitpid(pid, &mut status, 0) } as _)?;
+		read?;
+
+		if !libc::WIFEXITED(status) || libc::WEXITSTATUS(status) != 0 {
+			return Err(io::Error::new(io::ErrorKind::Other, "the child process died"));
+		}
+
+		Ok(output.lines().map(Into::into).collect())
+	}
+
+	fn check(ret: libc::c_long) -> io::Result<libc::c_long> {
+		if ret < 0 {
+			Err(io::Error::last_os_error())
+		} else {
+			Ok(ret)
+		}
+	}
+
+	fn prctl(option: libc::c_int, arg2: libc::c_ulong, arg3: libc::c_ulong) -> io::Result<libc::c_long> {
+		check(unsafe { libc::prctl(option, arg2, arg3, 0 as libc::c_ulong, 0 as libc::c_ulong) } as _)
+	}
+
+	fn set_rlimits() -> io::Result<()> {
+		// the address space isn't limited, since the executor reserves large amounts of virtual
+		// memory up front. The heap of the validation function is limited by the executor instead.
+		let limits = [
+			(libc::RLIMIT_CORE, 0),
+			(libc::RLIMIT_FSIZE, 0),
+			(libc::RLIMIT_NOFILE, MAX_OPEN_FILES),
+		];
+
+		for (resource, limit) in limits.iter() {
+			let rlimit = libc::rlimit { rlim_cur: *limit, rlim_max: *limit };
+			check(unsafe { libc::setrlimit(*resource, &rlimit) } as _)?;
+		}
+
+		Ok(())
+	}
+
+	fn set_no_new_privs() -> io::Result<()> {
+		prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0).map(|_| ())
+	}
+
+	const LINUX_CAPABILITY_VERSION_3: u32 = 0x20080522;
+
+	#[repr(C)]
+	struct CapUserHeader {
+		version: u32,
+		pid: libc::c_int,
+	}
+
+	#[repr(C)]
+	#[derive(Default, Clone, Copy)]
+	struct CapUserData {
+		effective: u32,
+		permitted: u32,
+		inheritable: u32,
+	}
+
+	fn drop_capabilities() -> io::Result<()> {
+		// dropping capabilities from the bounding set requires `CAP_SETPCAP`. Without it there is
+		// nothing to drop, since `no_new_privs` keeps the worker from gaining capabilities.
+		for cap in 0.. {
+			match prctl(libc::PR_CAPBSET_DROP, cap, 0) {
+				Ok(_) => {},
+				Err(e) if e.raw_os_error() == Some(libc::EINVAL) => break,
+				Err(e) if e.raw_os_error() == Some(libc::EPERM) => break,
+				Err(e) => return Err(e),
+			}
+		}
+
+		prctl(libc::PR_CAP_AMBIENT, libc::PR_CAP_AMBIENT_CLEAR_ALL as libc::c_ulong, 0)?;
+
+		let mut header = CapUserHeader { version: LINUX_CAPABILITY_VERSION_3, pid: 0 };
+		let data = [CapUserData::default(); 2];
+		check(unsafe {
+			libc::syscall(libc::SYS_capset, &mut header as *mut CapUserHeader, data.as_ptr())
+		}).map(|_| ())
+	}
+
+	fn capabilities() -> io::Result<[CapUserData; 2]> {
+		let mut header = CapUserHeader { version: LINUX_CAPABILITY_VERSION_3, pid: 0 };
+		let mut data = [CapUserData::default(); 2];
+		check(unsafe {
+			libc::syscall(libc::SYS_capget, &mut header as *mut CapUserHeader, data.as_mut_ptr())
+		})?;
+		Ok(data)
+	}
+
+	// The landlock system calls have the same numbers on all architectures.
+	const SYS_LANDLOCK_CREATE_RULESET: libc::c_long = 444;
+	const SYS_LANDLOCK_RESTRICT_SELF: libc::c_long = 446;
+	const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+
+	/// All the file system accesses known to the first version of landlock.
+	const LANDLOCK_ACCESS_FS_V1: u64 = (1 << 13) - 1;
+	const LANDLOCK_ACCESS_FS_REFER: u64 = 1 << 13;
+	const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+	#[repr(C)]
+	struct LandlockRulesetAttr {
+		handled_access_fs: u64,
+	}
+
+	fn restrict_file_system() -> io::Result<()> {
+		let abi = check(unsafe {
+			libc::syscall(
+				SYS_LANDLOCK_CREATE_RULESET,
+				std::ptr::null::<LandlockRulesetAttr>(),
+				0usize,
+				LANDLOCK_CREATE_RULESET_VERSION,
+			)
+		})?;
+
+		let mut handled_access_fs = LANDLOCK_ACCESS_FS_V1;
+		if abi >= 2 {
+			handled_access_fs |= LANDLOCK_ACCESS_FS_REFER;
+		}
+		if abi >= 3 {
+			handled_access_fs |= LANDLOCK_ACCESS_FS_TRUNCATE;
+		}
+
+		// a ruleset without any rules denies every access it handles.
+		let attr = LandlockRulesetAttr { handled_access_fs };
+		let ruleset = check(unsafe {
+			libc::syscall(
+				SYS_LANDLOCK_CREATE_RULESET,
+				&attr as *const LandlockRulesetAttr,
+				std::mem::size_of::<LandlockRulesetAttr>(),
+				0u32,
+			)
+		})?;
+
+		let res = check(unsafe { libc::syscall(SYS_LANDLOCK_RESTRICT_SELF, ruleset, 0u32) });
+		unsafe { libc::close(ruleset as libc::c_int) };
+		res.map(|_| ())
+	}
+
+	#[repr(C)]
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub(super) struct SockFilter {
+		pub(super) code: u16,
+		pub(super) jt: u8,
+		pub(super) jf: u8,
+		pub(super) k: u32,
+	}
+
+	#[repr(C)]
+	struct SockFprog {
+		len: libc::c_ushort,
+		filter: *const SockFilter,
+	}
+
+	const BPF_LD_W_ABS: u16 = 0x00 | 0x00 | 0x20;
+	pub(super) const BPF_JMP_JEQ_K: u16 = 0x05 | 0x10 | 0x00;
+	#[cfg(target_arch = "x86_64")]
+	const BPF_JMP_JGE_K: u16 = 0x05 | 0x30 | 0x00;
+	const BPF_JMP_JSET_K: u16 = 0x05 | 0x40 | 0x00;
+	const BPF_RET_K: u16 = 0x06 | 0x00;
+
+	const SECCOMP_MODE_FILTER: libc::c_ulong = 2;
+	const SECCOMP_RET_KILL_PROCESS: u32 = 0x8000_0000;
+	pub(super) const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+	pub(super) const SECCOMP_RET_ALLOW: u32 = 0x7fff_0000;
+
+	// offsets into `struct seccomp_data`. Both supported architectures are little-endian, so the
+	// lower half of the first argument comes first.
+	const SECCOMP_DATA_NR: u32 = 0;
+	const SECCOMP_DATA_ARCH: u32 = 4;
+	const SECCOMP_DATA_ARG0: u32 = 16;
+
+	// The system calls added since Linux 5.1 have the same numbers on all architectures.
+	pub(super) const SYS_PIDFD_SEND_SIGNAL: libc::c_long = 424;
+	pub(super) const SYS_IO_URING_SETUP: libc::c_long = 425;
+	pub(super) const SYS_IO_URING_ENTER: libc::c_long = 426;
+	pub(super) const SYS_IO_URING_REGISTER: libc::c_long = 427;
+	pub(super) const SYS_PIDFD_OPEN: libc::c_long = 434;
+	pub(super) const SYS_CLONE3: libc::c_long = 435;
+	pub(super) const SYS_PIDFD_GETFD: libc::c_long = 438;
+
+	const CLONE_NEWUSER: u32 = 0x1000_0000;
+
+	#[cfg(target_arch = "x86_64")]
+	pub(super) const AUDIT_ARCH: u32 = 0xc000_003e;
+	#[cfg(target_arch = "aarch64")]
+	pub(super) const AUDIT_ARCH: u32 = 0xc000_00b7;
+
+	/// System calls with this bit set use the x32 ABI, which has a table of its own.
+	#[cfg(target_arch = "x86_64")]
+	const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+	/// The system calls a validation worker has no business making.
+	pub(super) fn denied_syscalls() -> Vec<libc::c_long> {
+		let mut denied = vec![
+			// networking
+			libc::SYS_socket,
+			libc::SYS_socketpair,
+			libc::SYS_connect,
+			libc::SYS_bind,
+			libc::SYS_listen,
+			libc::SYS_accept,
+			libc::SYS_accept4,
+			// running programs
+			libc::SYS_execve,
+			libc::SYS_execveat,
+			// inspecting other processes
+			libc::SYS_ptrace,
+			libc::SYS_process_vm_readv,
+			libc::SYS_process_vm_writev,
+			SYS_PIDFD_OPEN,
+			SYS_PIDFD_GETFD,
+			// signalling other processes. `tkill` takes a thread id, which may be that of a thread
+			// of another process, and no pidfd of the worker itself can be opened anyway.
+			libc::SYS_tkill,
+			SYS_PIDFD_SEND_SIGNAL,
+			// io_uring performs system calls on behalf of the worker, out of reach of the filter
+			SYS_IO_URING_SETUP,
+			SYS_IO_URING_ENTER,
+			SYS_IO_URING_REGISTER,
+			// changing credentials and namespaces
+			libc::SYS_setuid,
+			libc::SYS_setgid,
+			libc::SYS_setreuid,
+			libc::SYS_setregid,
+			libc::SYS_setresuid,
+			libc::SYS_setresgid,
+			libc::SYS_setgroups,
+			libc::SYS_capset,
+			libc::SYS_unshare,
+			libc::SYS_setns,
+			libc::SYS_mount,
+			libc::SYS_umount2,
+			libc::SYS_pivot_root,
+			libc::SYS_chroot,
+			// reconfiguring the kernel
+			libc::SYS_init_module,
+			libc::SYS_finit_module,
+			libc::SYS_delete_module,
+			libc::SYS_kexec_load,
+			libc::SYS_reboot,
+			libc::SYS_swapon,
+			libc::SYS_swapoff,
+			libc::SYS_bpf,
+			libc::SYS_perf_event_open,
+			libc::SYS_userfaultfd,
+			libc::SYS_keyctl,
+			libc::SYS_add_key,
+			libc::SYS_request_key,
+			libc::SYS_personality,
+		];
+
+		#[cfg(target_arch = "x86_64")]
+		denied.extend_from_slice(&[libc::SYS_fork, libc::SYS_vfork]);
+
+		denied
+	}
+
+	/// The condition on the first argument of a system call under which it is denied.
+	#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+	pub(super) enum DeniedIf {
+		/// Any of these bits is set.
+		AnySet(u32),
+		/// The argument is not this one.
+		NotEqual(u32),
+	}
+
+	/// The system calls a validation worker may only make with some arguments, given the id of the
+	/// worker process.
+	pub(super) fn restricted_syscalls(pid: u32) -> Vec<(libc::c_long, DeniedIf)> {
+		vec![
+			// creating user namespaces, which are the way to most of the kernel attack surface.
+			// `clone3` can't be restricted this way, since its flags are passed in memory.
+			(libc::SYS_clone, DeniedIf::AnySet(CLONE_NEWUSER)),
+			// signalling other processes. The worker may still signal itself, e.g. to abort.
+			(libc::SYS_kill, DeniedIf::NotEqual(pid)),
+			(libc::SYS_tgkill, DeniedIf::NotEqual(pid)),
+			(libc::SYS_rt_sigqueueinfo, DeniedIf::NotEqual(pid)),
+			(libc::SYS_rt_tgsigqueueinfo, DeniedIf::NotEqual(pid)),
+		]
+	}
+
+	/// Builds a seccomp filter which kills the worker on any foreign system call ABI, makes the
+	/// denied system calls fail with `EPERM`, as well as the restricted ones called with the
+	/// denied arguments, and allows everything else.
+	///
+	/// `clone3` fails with `ENOSYS`, which makes the C library fall back to `clone`.
+	pub(super) fn seccomp_filter(pid: u32) -> Vec<SockFilter> {
+		let stmt = |code, k| SockFilter { code, jt: 0, jf: 0, k };
+		let jump = |code, k, jt, jf| SockFilter { code, jt, jf, k };
+
+		let mut filter = vec![
+			stmt(BPF_LD_W_ABS, SECCOMP_DATA_ARCH),
+			jump(BPF_JMP_JEQ_K, AUDIT_ARCH, 1, 0),
+			stmt(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+			stmt(BPF_LD_W_ABS, SECCOMP_DATA_NR),
+		];
+
+		#[cfg(target_arch = "x86_64")]
+		filter.extend_from_slice(&[
+			jump(BPF_JMP_JGE_K, X32_SYSCALL_BIT, 0, 1),
+			stmt(BPF_RET_K, SECCOMP_RET_KILL_PROCESS),
+		]);
+
+		for syscall in denied_syscalls() {
+			filter.push(jump(BPF_JMP_JEQ_K, syscall as u32, 0, 1));
+			filter.push(stmt(BPF_RET_K, SECCOMP_RET_ERRNO | libc::EPERM as u32));
+		}
+
+		filter.push(jump(BPF_JMP_JEQ_K, SYS_CLONE3 as u32, 0, 1));
+		filter.push(stmt(BPF_RET_K, SECCOMP_RET_ERRNO | libc::ENOSYS as u32));
+
+		// each check returns either way once the system call matches, so the following ones still
+		// find the system call number in the accumulator.
+		for (syscall, denied_if) in restricted_syscalls(pid) {
+			filter.push(jump(BPF_JMP_JEQ_K, syscall as u32, 0, 4));
+			filter.push(stmt(BPF_LD_W_ABS, SECCOMP_DATA_ARG0));
+			filter.push(match denied_if {
+				DeniedIf::AnySet(bits) => jump(BPF_JMP_JSET_K, bits, 0, 1),
+				DeniedIf::NotEqual(value) => jump(BPF_JMP_JEQ_K, value, 1, 0),
+			});
+			filter.push(stmt(BPF_RET_K, SECCOMP_RET_ERRNO | libc::EPERM as u32));
+			filter.push(stmt(BPF_RET_K, SECCOMP_RET_ALLOW));
+		}
+
+		filter.push(stmt(BPF_RET_K, SECCOMP_RET_ALLOW));
+		filter
+	}
+
+	fn filter_syscalls() -> io::Result<()> {
+		let filter = seccomp_filter(std::process::id());
+		let prog = SockFprog { len: filter.len() as libc::c_ushort, filter: filter.as_ptr() };
+
+		prctl(libc::PR_SET_SECCOMP, SECCOMP_MODE_FILTER, &prog as *const SockFprog as libc::c_ulong)
+			.map(|_| ())
+	}
+
+	/// Checks that the restrictions are in effect, returning the ones that aren't.
+	fn self_test() -> Vec<String> {
+		let mut failures = Vec::new();
+
+		let mut rlimit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+		match check(unsafe { libc::getrlimit(libc::RLIMIT_FSIZE, &mut rlimit) } as _) {
+			Ok(_) if rlimit.rlim_max == 0 => {},
+			Ok(_) => failures.push("rlimits: files can still be written".into()),
+			Err(e) => failures.push(format!("rlimits: self-test failed: {}", e)),
+		}
+
+		match prctl(libc::PR_GET_NO_NEW_PRIVS, 0, 0) {
+			Ok(1) => {},
+			Ok(_) => failures.push("no_new_privs: not set".into()),
+			Err(e) => failures.push(format!("no_new_privs: self-test failed: {}", e)),
+		}
+
+		match capabilities() {
+			Ok(data) if data.iter().all(|d| d.effective == 0 && d.permitted == 0 && d.inheritable == 0) => {},
+			Ok(_) => failures.push("capabilities: some are still held".into()),
+			Err(e) => failures.push(format!("capabilities: self-test failed: {}", e)),
+		}
+
+		match std::fs::read_dir("/") {
+			Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {},
+			Err(e) => failures.push(format!("landlock: self-test failed: {}", e)),
+			Ok(_) => failures.push("landlock: the file system is still accessible".into()),
+		}
+
+		let socket = unsafe { libc::socket(libc::AF_UNIX, libc::SOCK_STREAM, 0) };
+		if socket >= 0 {
+			unsafe { libc::close(socket) };
+			failures.push("seccomp: sockets can still be created".into());
+		} else if io::Error::last_os_error().raw_os_error() != Some(libc::EPERM) {
+			failures.push(format!("seccomp: self-test failed: {}", io::Error::last_os_error()));
+		}
+
+		failures
+	}
+}
+
+#[cfg(all(test, target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod tests {
+	use super::imp::*;
+
+	#[test]
+	fn seccomp_filter_denies_listed_syscalls_only() {
+		let filter = seccomp_filter(42);
+		let eperm = SECCOMP_RET_ERRNO | libc::EPERM as u32;
+
+		// the architecture is checked before anything else.
+		assert_eq!(filter[0].k, 4);
+		assert_eq!(filter[1].k, AUDIT_ARCH);
+
+		let denied = denied_syscalls();
+		let checked: Vec<_> = filter.windows(2)
+			.filter(|w| w[0].code == BPF_JMP_JEQ_K && w[0].jf == 1 && w[1].k == eperm)
+			.map(|w| w[0].k as libc::c_long)
+			.collect();
+		assert_eq!(checked, denied);
+		assert!(!denied.contains(&libc::SYS_read));
+		assert!(!denied.contains(&libc::SYS_mmap));
+		assert!(!denied.contains(&libc::SYS_clone));
+		assert!(denied.contains(&libc::SYS_tkill));
+		assert!(denied.contains(&SYS_PIDFD_SEND_SIGNAL));
+
+		let clone3 = filter.iter().position(|f| f.k == SYS_CLONE3 as u32).unwrap();
+		assert_eq!(filter[clone3 + 1].k, SECCOMP_RET_ERRNO | libc::ENOSYS as u32);
+
+		// the system calls signalling processes are denied for any but the given process id.
+		for syscall in &[
+			libc::SYS_kill,
+			libc::SYS_tgkill,
+			libc::SYS_rt_sigqueueinfo,
+			libc::SYS_rt_tgsigqueueinfo,
+		] {
+			let check = filter.iter().position(|f| f.k == *syscall as u32 && f.jf == 4).unwrap();
+			assert_eq!(filter[check + 2], SockFilter { code: BPF_JMP_JEQ_K, jt: 1, jf: 0, k: 42 });
+			assert_eq!(filter[check + 3].k, eperm);
+			assert_eq!(filter[check + 4].k, SECCOMP_RET_ALLOW);
+		}
+
+		assert_eq!(filter.last().unwrap().k, SECCOMP_RET_ALLOW);
+	}
+
+	/// Locks down the calling process and then checks the restrictions beyond those covered by the
+	/// self-test.
+	fn lock_down_and_probe() -> Vec<String> {
+		let mut problems = lock_down();
+		let errno = || std::io::Error::last_os_error().raw_os_error();
+		let mut expect_failure = |what: &str, ret: libc::c_long, expected: libc::c_int| {
+			if ret >= 0 || errno() != Some(expected) {
+				problems.push(format!("{} was not denied", what));
+			}
+		};
+
+		expect_failure(
+			"signalling the parent",
+			unsafe { libc::kill(libc::getppid(), 0) } as _,
+			libc::EPERM,
+		);
+		expect_failure(
+			"io_uring",
+			unsafe { libc::syscall(SYS_IO_URING_SETUP, 1, std::ptr::null_mut::<libc::c_void>()) },
+			libc::EPERM,
+		);
+		expect_failure(
+			"signalling a thread by id",
+			unsafe { libc::syscall(libc::SYS_tkill, libc::getppid(), 0) },
+			libc::EPERM,
+		);
+		expect_failure(
+			"queueing a signal to the parent",
+			unsafe {
+				libc::syscall(
+					libc::SYS_rt_sigqueueinfo,
+					libc::getppid(),
+					0,
+					std::ptr::null_mut::<libc::c_void>(),
+				)
+			},
+			libc::EPERM,
+		);
+		expect_failure(
+			"pidfd_open",
+			unsafe { libc::syscall(SYS_PIDFD_OPEN, libc::getppid(), 0) },
+			libc::EPERM,
+		);
+		expect_failure(
+			"clone3",
+			unsafe { libc::syscall(SYS_CLONE3, std::ptr::null_mut::<libc::c_void>(), 0) },
+			libc::ENOSYS,
+		);
+
+		let ret = unsafe {
+			libc::syscall(libc::SYS_clone, (libc::CLONE_NEWUSER | libc::SIGCHLD) as libc::c_ulong, 0, 0, 0, 0)
+		};
+		if ret == 0 {
+			// the clone went through, and this is the new process.
+			unsafe { libc::_exit(0) };
+		}
+		expect_failure("creating a user namespace", ret, libc::EPERM);
+
+		if unsafe { libc::kill(libc::getpid(), 0) } != 0 {
+			problems.push("signalling itself was denied".into());
+		}
+
+		problems
+	}
+
+	/// Set in the environment of the test process started to be locked down.
+	const LOCKED_DOWN_CHILD: &str = "TETCOIN_SANDBOX_LOCKED_DOWN_CHILD";
+
+	/// Starts the lines of the output of the locked down test process reporting a problem.
+	const PROBLEM: &str = "sandbox problem: ";
+
+	#[test]
+	fn lock_down_restricts_a_child_process() {
+		if std::env::var_os(LOCKED_DOWN_CHILD).is_some() {
+			for problem in lock_down_and_probe() {
+				println!("{}{}", PROBLEM, problem);
+			}
+			return;
+		}
+
+		// the restrictions can't be lifted, and forking the test harness isn't safe since it runs
+		// tests in threads of their own, so this test is run again in a process of its own, which
+		// is locked down.
+		let test_name = module_path!().splitn(2, "::").nth(1).unwrap().to_owned()
+			+ "::lock_down_restricts_a_child_process";
+		let output = std::process::Command::new(std::env::current_exe().unwrap())
+			.args(&["--exact", &test_name, "--nocapture", "--test-threads=1"])
+			.env(LOCKED_DOWN_CHILD, "1")
+			.output()
+			.unwrap();
+		let stdout = String::from_utf8_lossy(&output.stdout);
+		assert!(output.status.success(), "{}", stdout);
+
+		// landlock is too recent to be found on every kernel running the tests.
+		let problems: Vec<_> = stdout.lines()
+			.filter_map(|line| line.strip_prefix(PROBLEM))
+			.filter(|p| !p.starts_with("landlock"))
+			.collect();
+		assert!(problems.is_empty(), "{:?}", problems);
+
+		// the test process itself is not restricted.
+		assert!(std::fs::read_dir("/").is_ok());
+	}
+}
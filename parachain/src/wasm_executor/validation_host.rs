@@ -16,31 +16,35 @@
 
 #![cfg(not(any(target_os = "android", target_os = "unknown")))]
 
-use std::{process, env, sync::Arc, sync::atomic, path::PathBuf};
+use std::{cmp, process, env, sync::Arc, sync::atomic, path::PathBuf};
 use tetsy_scale_codec::{Decode, Encode};
-use crate::primitives::{ValidationParams, ValidationResult};
+use crate::primitives::{PvfExecutionLimits, ValidationParams, ValidationResult};
 use super::{
-	validate_candidate_internal, ValidationError, InvalidCandidate, InternalError,
-	MAX_CODE_MEM, MAX_RUNTIME_MEM, MAX_VALIDATION_RESULT_HEADER_MEM,
+	sandbox::{self, SandboxMode}, thread_executor, validate_on_native_stack, on_native_stack, precheck_internal,
+	ValidationError,
+	ValidationPriority, InvalidCandidate, InternalError, MAX_CODE_MEM, MAX_RUNTIME_MEM,
+	MAX_VALIDATION_RESULT_HEADER_MEM,
 };
 use shared_memory::{SharedMem, SharedMemConf, EventState, WriteLockable, EventWait, EventSet};
-use parking_lot::Mutex;
-use log::{debug, trace};
+use parking_lot::{Condvar, Mutex};
+use log::{debug, error, trace};
 use futures::executor::ThreadPool;
 use tet_core::traits::SpawnNamed;
 
 const WORKER_ARG: &'static str = "validation-worker";
 /// CLI Argument to start in validation worker mode.
 pub const WORKER_ARGS: &[&'static str] = &[WORKER_ARG];
+/// CLI Argument to run the validation worker in [`SandboxMode::Degraded`].
+const DEGRADED_SANDBOX_ARG: &'static str = "--degraded-sandbox";
 
 const LOG_TARGET: &'static str = "validation-worker";
 
-/// Execution timeout in seconds;
+/// The time a freshly started worker has to signal it is ready, in seconds.
 #[cfg(debug_assertions)]
-pub const EXECUTION_TIMEOUT_SEC: u64 =  30;
+const WORKER_READY_TIMEOUT_SEC: usize = 30;
 
 #[cfg(not(debug_assertions))]
-pub const EXECUTION_TIMEOUT_SEC: u64 =  5;
+const WORKER_READY_TIMEOUT_SEC: usize = 5;
 
 enum Event {
 	CandidateReady = 0,
@@ -67,41 +71,143 @@ impl SpawnNamed for TaskExecutor {
 	}
 }
 
+const DEFAULT_BACKING_HOSTS: usize = 4;
+const DEFAULT_DISPUTE_HOSTS: usize = 1;
+const DEFAULT_APPROVAL_HOSTS: usize = 8;
+
+/// The configuration of a [`ValidationPool`].
+///
+/// At least one host is reserved for every priority, even if configured otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationPoolConfig {
+	/// The number of hosts reserved for backing.
+	pub backing_hosts: usize,
+	/// The number of hosts reserved for disputes.
+	pub dispute_hosts: usize,
+	/// The number of hosts reserved for approval checking.
+	pub approval_hosts: usize,
+	/// How the workers started with [`ValidationPool::validate_candidate`] sandbox themselves.
+	/// Custom workers are responsible for their own sandboxing.
+	pub sandbox_mode: SandboxMode,
+}
+
+impl Default for ValidationPoolConfig {
+	fn default() -> Self {
+		ValidationPoolConfig {
+			backing_hosts: DEFAULT_BACKING_HOSTS,
+			dispute_hosts: DEFAULT_DISPUTE_HOSTS,
+			approval_hosts: DEFAULT_APPROVAL_HOSTS,
+			sandbox_mode: SandboxMode::Strict,
+		}
+	}
+}
+
 /// A pool of hosts.
+///
+/// Every [`ValidationPriority`] has hosts of its own. A validation runs on a free host of its own
+/// priority, or else on a free host of a less urgent priority. If there is none, it waits for the
+/// first of these hosts to be freed. This way, less urgent work never delays more urgent work, e.g. a flood of
+/// approval checks can't keep the node from backing candidates in time.
 #[derive(Clone, Debug)]
 pub struct ValidationPool {
-	hosts: Arc<Vec<Mutex<ValidationHost>>>,
+	/// The hosts of every priority, indexed by priority, from the most to the least urgent.
+	hosts: Arc<Vec<Vec<Mutex<ValidationHost>>>>,
+	/// Notified whenever a host is freed, so that the validations waiting for a host look again.
+	host_freed: Arc<(Mutex<()>, Condvar)>,
+	sandbox_mode: SandboxMode,
 }
 
-const DEFAULT_NUM_HOSTS: usize = 8;
-
 impl ValidationPool {
 	/// Creates a validation pool with the default configuration.
 	pub fn new() -> ValidationPool {
+		Self::with_config(Default::default())
+	}
+
+	/// Creates a validation pool with the given number of hosts for each priority.
+	pub fn with_config(config: ValidationPoolConfig) -> ValidationPool {
+		let hosts = |n: usize| (0..cmp::max(n, 1)).map(|_| Default::default()).collect();
+
 		ValidationPool {
-			hosts: Arc::new((0..DEFAULT_NUM_HOSTS).map(|_| Default::default()).collect()),
+			hosts: Arc::new(vec![
+				hosts(config.backing_hosts),
+				hosts(config.dispute_hosts),
+				hosts(config.approval_hosts),
+			]),
+			host_freed: Default::default(),
+			sandbox_mode: config.sandbox_mode,
 		}
 	}
 
-	/// Validate a candidate under the given validation code using the next free validation host.
+	/// The number of hosts of the given priority which are validating a candidate right now.
+	pub fn busy_hosts(&self, priority: ValidationPriority) -> usize {
+		self.hosts[priority as usize].iter().filter(|host| host.is_locked()).count()
+	}
+
+	/// Validate a candidate under the given validation code using the next free validation host
+	/// available to the given priority.
 	///
 	/// This will fail if the validation code is not a proper parachain validation module.
 	///
-	/// This function will use `std::env::current_exe()` with the default arguments [`WORKER_ARGS`] to run the worker.
+	/// This function will use `std::env::current_exe()` with the default arguments [`WORKER_ARGS`] to run the worker,
+	/// along with the argument selecting the configured [`SandboxMode`].
 	pub fn validate_candidate(
 		&self,
 		validation_code: &[u8],
 		params: ValidationParams,
+		limits: PvfExecutionLimits,
+		priority: ValidationPriority,
 	) -> Result<ValidationResult, ValidationError> {
 		self.validate_candidate_custom(
 			validation_code,
 			params,
+			limits,
+			priority,
+			&env::current_exe().map_err(|err| ValidationError::Internal(err.into()))?,
+			&self.worker_args(),
+		)
+	}
+
+	/// Pre-check the given validation code using the next free validation host available to
+	/// approval checking, the least urgent priority.
+	///
+	/// This function will use `std::env::current_exe()` with the default arguments [`WORKER_ARGS`] to run the worker,
+	/// along with the argument selecting the configured [`SandboxMode`].
+	pub fn precheck(&self, validation_code: &[u8], limits: PvfExecutionLimits) -> Result<(), ValidationError> {
+		self.precheck_custom(
+			validation_code,
+			limits,
 			&env::current_exe().map_err(|err| ValidationError::Internal(err.into()))?,
-			WORKER_ARGS,
+			&self.worker_args(),
 		)
 	}
 
-	/// Validate a candidate under the given validation code using the next free validation host.
+	/// Pre-check the given validation code using the next free validation host available to
+	/// approval checking, the least urgent priority.
+	///
+	/// This function will use the command and the arguments provided in the function's arguments to run the worker.
+	pub fn precheck_custom(
+		&self,
+		validation_code: &[u8],
+		limits: PvfExecutionLimits,
+		command: &PathBuf,
+		args: &[&str],
+	) -> Result<(), ValidationError> {
+		self.with_free_host(ValidationPriority::Approval, |host| {
+			host.run_task(WorkerTask::Precheck, validation_code, Vec::new(), limits, command, args)
+		}).map(|_| ())
+	}
+
+	/// The arguments [`WORKER_ARGS`], along with the one selecting the configured [`SandboxMode`].
+	fn worker_args(&self) -> Vec<&'static str> {
+		let mut args = WORKER_ARGS.to_vec();
+		if self.sandbox_mode == SandboxMode::Degraded {
+			args.push(DEGRADED_SANDBOX_ARG);
+		}
+		args
+	}
+
+	/// Validate a candidate under the given validation code using the next free validation host
+	/// available to the given priority.
 	///
 	/// This will fail if the validation code is not a proper parachain validation module.
 	///
@@ -110,23 +216,54 @@ impl ValidationPool {
 		&self,
 		validation_code: &[u8],
 		params: ValidationParams,
+		limits: PvfExecutionLimits,
+		priority: ValidationPriority,
 		command: &PathBuf,
 		args: &[&str],
 	) -> Result<ValidationResult, ValidationError> {
-		for host in self.hosts.iter() {
-			if let Some(mut host) = host.try_lock() {
-				return host.validate_candidate(validation_code, params, command, args)
-			}
+		let encoded_params = params.encode();
+		if encoded_params.len() >= MAX_RUNTIME_MEM {
+			return Err(ValidationError::InvalidCandidate(InvalidCandidate::ParamsTooLarge(MAX_RUNTIME_MEM)));
 		}
 
-		// all workers are busy, just wait for the first one
-		self.hosts[0].lock().validate_candidate(validation_code, params, command, args)
+		self.with_free_host(priority, |host| {
+			host.run_task(WorkerTask::Validate, validation_code, encoded_params, limits, command, args)
+		})?.ok_or_else(|| ValidationError::Internal(InternalError::WasmWorker("Worker returned no result".into())))
+	}
+
+	/// Runs the given work on the next free host available to the given priority.
+	fn with_free_host<R>(&self, priority: ValidationPriority, work: impl FnOnce(&mut ValidationHost) -> R) -> R {
+		let own = priority as usize;
+		let (lock, host_freed) = &*self.host_freed;
+
+		// a host freed after looking at it is only announced once we wait, so it can't be missed.
+		let mut waiting = lock.lock();
+		loop {
+			// hosts of less urgent priorities come after our own ones.
+			for host in self.hosts[own..].iter().flatten() {
+				if let Some(mut host) = host.try_lock() {
+					drop(waiting);
+					let res = work(&mut host);
+					drop(host);
+
+					let _waiting = lock.lock();
+					host_freed.notify_all();
+					return res
+				}
+			}
+
+			// all the hosts we may use are busy, wait for any of them to be freed.
+			host_freed.wait(&mut waiting);
+		}
 	}
 }
 
 /// Validation worker process entry point. Runs a loop waiting for candidates to validate
 /// and sends back results via shared memory.
-pub fn run_worker(mem_id: &str) -> Result<(), String> {
+///
+/// The worker sandboxes itself according to the given mode before doing anything else, so this
+/// must be called before the calling thread spawns any other threads.
+pub fn run_worker(mem_id: &str, sandbox_mode: SandboxMode) -> Result<(), String> {
 	let mut memory = match SharedMem::open(mem_id) {
 		Ok(memory) => memory,
 		Err(e) => {
@@ -135,6 +272,11 @@ pub fn run_worker(mem_id: &str) -> Result<(), String> {
 		}
 	};
 
+	if let Err(e) = sandbox::lock_down(sandbox_mode) {
+		error!(target: LOG_TARGET, "{} {}", process::id(), e);
+		return Err(e);
+	}
+
 	let exit = Arc::new(atomic::AtomicBool::new(false));
 	let task_executor = TaskExecutor::new()?;
 	// spawn parent monitor thread
@@ -151,8 +293,6 @@ pub fn run_worker(mem_id: &str) -> Result<(), String> {
 	memory.set(Event::WorkerReady as usize, EventState::Signaled)
 		.map_err(|e| format!("{} Error setting shared event: {:?}", process::id(), e))?;
 
-	let executor = super::ExecutorCache::default();
-
 	loop {
 		if watch_exit.load(atomic::Ordering::Relaxed) {
 			break;
@@ -186,13 +326,34 @@ pub fn run_worker(mem_id: &str) -> Result<(), String> {
 				let (call_data, _) = rest.split_at_mut(MAX_RUNTIME_MEM);
 				let (call_data, _) = call_data.split_at_mut(header.params_size as usize);
 
-				let result = validate_candidate_internal(&executor, code, call_data, task_executor.clone());
-				debug!(target: LOG_TARGET, "{} Candidate validated: {:?}", process::id(), result);
+				// the task interrupts itself at the timeout, and the host kills us if it doesn't.
+				let result = match header.task {
+					WorkerTask::Validate => validate_on_native_stack(
+						thread_executor(header.limits.max_memory_pages),
+						code.to_vec(),
+						call_data.to_vec(),
+						header.limits,
+						task_executor.clone(),
+					).map(Some),
+					WorkerTask::Precheck => {
+						let code = code.to_vec();
+						let limits = header.limits;
+						on_native_stack(move || precheck_internal(&code, limits)).map(|()| None)
+					}
+				};
+				debug!(target: LOG_TARGET, "{} Task finished: {:?}", process::id(), result);
 
 				match result {
-					Ok(r) => ValidationResultHeader::Ok(r),
+					Ok(Some(r)) => ValidationResultHeader::Ok(r),
+					Ok(None) => ValidationResultHeader::Prechecked,
 					Err(ValidationError::Internal(e)) =>
 						ValidationResultHeader::Error(WorkerValidationError::InternalError(e.to_string())),
+					Err(ValidationError::InvalidCandidate(InvalidCandidate::OutOfMemory)) =>
+						ValidationResultHeader::Error(WorkerValidationError::OutOfMemory),
+					Err(ValidationError::InvalidCandidate(InvalidCandidate::Timeout)) =>
+						ValidationResultHeader::Error(WorkerValidationError::Timeout),
+					Err(ValidationError::InvalidCandidate(InvalidCandidate::Trap(e))) =>
+						ValidationResultHeader::Error(WorkerValidationError::Trap(e)),
 					Err(ValidationError::InvalidCandidate(e)) =>
 						ValidationResultHeader::Error(WorkerValidationError::ValidationError(e.to_string())),
 				}
@@ -207,22 +368,37 @@ pub fn run_worker(mem_id: &str) -> Result<(), String> {
 	Ok(())
 }
 
+/// What a worker is asked to do with the validation code.
+#[derive(Encode, Decode, Debug, Clone, Copy)]
+enum WorkerTask {
+	/// Validate a candidate with the params following the code.
+	Validate,
+	/// Pre-check the code. There are no params.
+	Precheck,
+}
+
 /// Params header in shared memory. All offsets should be aligned to WASM page size.
 #[derive(Encode, Decode, Debug)]
 struct ValidationHeader {
+	task: WorkerTask,
 	code_size: u64,
 	params_size: u64,
+	limits: PvfExecutionLimits,
 }
 
 #[derive(Encode, Decode, Debug)]
 enum WorkerValidationError {
 	InternalError(String),
 	ValidationError(String),
+	OutOfMemory,
+	Timeout,
+	Trap(String),
 }
 
 #[derive(Encode, Decode, Debug)]
 enum ValidationResultHeader {
 	Ok(ValidationResult),
+	Prechecked,
 	Error(WorkerValidationError),
 }
 
@@ -304,24 +480,33 @@ impl ValidationHost {
 		self.id = worker.id();
 		self.worker = Some(worker);
 
-		memory.wait(
+		if let Err(e) = memory.wait(
 			Event::WorkerReady as usize,
-			shared_memory::Timeout::Sec(EXECUTION_TIMEOUT_SEC as usize),
-		)?;
+			shared_memory::Timeout::Sec(WORKER_READY_TIMEOUT_SEC),
+		) {
+			// e.g. the worker couldn't sandbox itself.
+			if let Some(Ok(Some(status))) = self.worker.as_mut().map(|w| w.try_wait()) {
+				return Err(InternalError::WasmWorker(format!("Worker exited during startup: {}", status)));
+			}
+			return Err(e.into());
+		}
 		self.memory = Some(ValidationHostMemory(memory));
 		Ok(())
 	}
 
-	/// Validate a candidate under the given validation code.
+	/// Run the given task with the validation code and the encoded params on the worker, returning
+	/// the result of the validation, if any.
 	///
 	/// This will fail if the validation code is not a proper parachain validation module.
-	pub fn validate_candidate(
+	fn run_task(
 		&mut self,
+		task: WorkerTask,
 		validation_code: &[u8],
-		params: ValidationParams,
+		encoded_params: Vec<u8>,
+		limits: PvfExecutionLimits,
 		binary: &PathBuf,
 		args: &[&str],
-	) -> Result<ValidationResult, ValidationError> {
+	) -> Result<Option<ValidationResult>, ValidationError> {
 		if validation_code.len() > MAX_CODE_MEM {
 			return Err(ValidationError::InvalidCandidate(InvalidCandidate::CodeTooLarge(validation_code.len())));
 		}
@@ -338,15 +523,13 @@ impl ValidationHost {
 			let (code, _) = code.split_at_mut(validation_code.len());
 			let (call_data, _) = rest.split_at_mut(MAX_RUNTIME_MEM);
 			code[..validation_code.len()].copy_from_slice(validation_code);
-			let encoded_params = params.encode();
-			if encoded_params.len() >= MAX_RUNTIME_MEM {
-				return Err(ValidationError::InvalidCandidate(InvalidCandidate::ParamsTooLarge(MAX_RUNTIME_MEM)));
-			}
 			call_data[..encoded_params.len()].copy_from_slice(&encoded_params);
 
 			let header = ValidationHeader {
+				task,
 				code_size: validation_code.len() as u64,
 				params_size: encoded_params.len() as u64,
+				limits,
 			};
 
 			header.encode_to(&mut header_buf);
@@ -357,10 +540,18 @@ impl ValidationHost {
 			.map_err(|e| ValidationError::Internal(e.into()))?;
 
 		debug!(target: LOG_TARGET, "{} Waiting for results", self.id);
-		match memory.wait(Event::ResultReady as usize, shared_memory::Timeout::Sec(EXECUTION_TIMEOUT_SEC as usize)) {
+		let timeout = shared_memory::Timeout::Milli(limits.timeout_ms as usize);
+		match memory.wait(Event::ResultReady as usize, timeout) {
 			Err(e) => {
 				debug!(target: LOG_TARGET, "Worker timeout: {:?}", e);
 				if let Some(mut worker) = self.worker.take() {
+					// a worker that died on its own, e.g. due to a native stack overflow, says
+					// nothing about the candidate.
+					if let Ok(Some(status)) = worker.try_wait() {
+						return Err(ValidationError::Internal(InternalError::WasmWorker(
+							format!("Worker exited unexpectedly: {}", status),
+						)));
+					}
 					worker.kill().ok();
 				}
 				return Err(ValidationError::InvalidCandidate(InvalidCandidate::Timeout));
@@ -383,7 +574,8 @@ impl ValidationHost {
 					)
 				)?;
 			match header {
-				ValidationResultHeader::Ok(result) => Ok(result),
+				ValidationResultHeader::Ok(result) => Ok(Some(result)),
+				ValidationResultHeader::Prechecked => Ok(None),
 				ValidationResultHeader::Error(WorkerValidationError::InternalError(e)) => {
 					debug!(target: LOG_TARGET, "{} Internal validation error: {}", self.id, e);
 					Err(ValidationError::Internal(InternalError::WasmWorker(e)))
@@ -391,7 +583,19 @@ impl ValidationHost {
 				ValidationResultHeader::Error(WorkerValidationError::ValidationError(e)) => {
 					debug!(target: LOG_TARGET, "{} External validation error: {}", self.id, e);
 					Err(ValidationError::InvalidCandidate(InvalidCandidate::ExternalWasmExecutor(e)))
-				}
+				},
+				ValidationResultHeader::Error(WorkerValidationError::OutOfMemory) => {
+					debug!(target: LOG_TARGET, "{} Validation ran out of memory", self.id);
+					Err(ValidationError::InvalidCandidate(InvalidCandidate::OutOfMemory))
+				},
+				ValidationResultHeader::Error(WorkerValidationError::Timeout) => {
+					debug!(target: LOG_TARGET, "{} Validation timed out", self.id);
+					Err(ValidationError::InvalidCandidate(InvalidCandidate::Timeout))
+				},
+				ValidationResultHeader::Error(WorkerValidationError::Trap(e)) => {
+					debug!(target: LOG_TARGET, "{} Validation trapped: {}", self.id, e);
+					Err(ValidationError::InvalidCandidate(InvalidCandidate::Trap(e)))
+				},
 			}
 		}
 	}
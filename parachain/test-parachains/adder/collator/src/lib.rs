@@ -147,7 +147,7 @@ impl Collator {
 
 		let state = self.state.clone();
 
-		Box::new(move |relay_parent, validation_data| {
+		Box::new(move |relay_parent, validation_data, _hrmp_bandwidth| {
 			let parent = HeadData::decode(&mut &validation_data.parent_head.0[..])
 				.expect("Decodes parent head");
 
@@ -195,7 +195,10 @@ mod tests {
 	use super::*;
 
 	use futures::executor::block_on;
-	use tetcoin_parachain::{primitives::ValidationParams, wasm_executor::IsolationStrategy};
+	use tetcoin_parachain::{
+		primitives::ValidationParams,
+		wasm_executor::{IsolationStrategy, ValidationPriority},
+	};
 	use tetcoin_primitives::v1::PersistedValidationData;
 
 	#[test]
@@ -219,7 +222,7 @@ mod tests {
 			};
 
 			let collation =
-				block_on(collation_function(Default::default(), &validation_data)).unwrap();
+				block_on(collation_function(Default::default(), &validation_data, &Default::default())).unwrap();
 			validate_collation(&collator, (*parent_head).clone(), collation);
 		}
 	}
@@ -236,6 +239,8 @@ mod tests {
 				dmq_mqc_head: Default::default(),
 			},
 			&IsolationStrategy::InProcess,
+			Default::default(),
+			ValidationPriority::Backing,
 			tet_core::testing::TaskExecutor::new(),
 		)
 		.unwrap();
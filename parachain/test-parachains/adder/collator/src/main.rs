@@ -63,6 +63,7 @@ fn main() -> Result<()> {
 							tetcoin_service::IsCollator::Yes(collator.collator_id()),
 							None,
 							None,
+							Default::default(),
 						).map_err(|e| e.to_string())?;
 						let mut overseer_handler = full_node
 							.overseer_handler
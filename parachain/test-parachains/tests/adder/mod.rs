@@ -25,7 +25,7 @@ use parachain::{
 		HeadData as GenericHeadData,
 		ValidationParams,
 	},
-	wasm_executor::{ValidationPool, IsolationStrategy}
+	wasm_executor::{ValidationPool, IsolationStrategy, ValidationPriority}
 };
 use tetsy_scale_codec::{Decode, Encode};
 use adder::{HeadData, BlockData, hash_state};
@@ -73,6 +73,8 @@ fn execute_good_on_parent(isolation_strategy: IsolationStrategy) {
 			dmq_mqc_head: Default::default(),
 		},
 		&isolation_strategy,
+		Default::default(),
+		ValidationPriority::Backing,
 		tet_core::testing::TaskExecutor::new(),
 	).unwrap();
 
@@ -113,6 +115,8 @@ fn execute_good_chain_on_parent() {
 				dmq_mqc_head: Default::default(),
 			},
 			&isolation_strategy,
+			Default::default(),
+			ValidationPriority::Backing,
 			tet_core::testing::TaskExecutor::new(),
 		).unwrap();
 
@@ -154,6 +158,8 @@ fn execute_bad_on_parent() {
 			dmq_mqc_head: Default::default(),
 		},
 		&isolation_strategy,
+		Default::default(),
+		ValidationPriority::Backing,
 		tet_core::testing::TaskExecutor::new(),
 	).unwrap_err();
 }
@@ -20,8 +20,11 @@ const WORKER_ARGS_TEST: &[&'static str] = &["--nocapture", "validation_worker"];
 
 use crate::adder;
 use parachain::{
-	primitives::{BlockData, ValidationParams},
-	wasm_executor::{ValidationError, InvalidCandidate, EXECUTION_TIMEOUT_SEC, IsolationStrategy, ValidationPool},
+	primitives::{BlockData, PvfExecutionLimits, ValidationParams},
+	wasm_executor::{
+		ValidationError, InvalidCandidate, IsolationStrategy, ValidationPool, ValidationPoolConfig,
+		ValidationPriority,
+	},
 };
 
 fn isolation_strategy() -> IsolationStrategy {
@@ -32,6 +35,10 @@ fn isolation_strategy() -> IsolationStrategy {
 	}
 }
 
+fn execution_timeout() -> std::time::Duration {
+	std::time::Duration::from_millis(PvfExecutionLimits::default().timeout_ms as u64)
+}
+
 #[test]
 fn terminates_on_timeout() {
 	let isolation_strategy = isolation_strategy();
@@ -47,6 +54,8 @@ fn terminates_on_timeout() {
 			dmq_mqc_head: Default::default(),
 		},
 		&isolation_strategy,
+		Default::default(),
+		ValidationPriority::Backing,
 		tet_core::testing::TaskExecutor::new(),
 	);
 	match result {
@@ -58,6 +67,33 @@ fn terminates_on_timeout() {
 	adder::execute_good_on_parent_with_external_process_validation();
 }
 
+#[test]
+fn terminates_on_configured_timeout_in_process() {
+	let limits = PvfExecutionLimits { timeout_ms: 500, ..Default::default() };
+
+	let start = std::time::Instant::now();
+	let result = parachain::wasm_executor::validate_candidate(
+		halt::wasm_binary_unwrap(),
+		ValidationParams {
+			block_data: BlockData(Vec::new()),
+			parent_head: Default::default(),
+			relay_chain_height: 1,
+			relay_storage_root: Default::default(),
+			hrmp_mqc_heads: Vec::new(),
+			dmq_mqc_head: Default::default(),
+		},
+		&IsolationStrategy::InProcess,
+		limits,
+		ValidationPriority::Backing,
+		tet_core::testing::TaskExecutor::new(),
+	);
+	match result {
+		Err(ValidationError::InvalidCandidate(InvalidCandidate::Timeout)) => {},
+		r => panic!("{:?}", r),
+	}
+	assert!(std::time::Instant::now().duration_since(start) < execution_timeout());
+}
+
 #[test]
 fn parallel_execution() {
 	let isolation_strategy = isolation_strategy();
@@ -76,6 +112,8 @@ fn parallel_execution() {
 			dmq_mqc_head: Default::default(),
 		},
 		&isolation_strategy,
+		Default::default(),
+		ValidationPriority::Backing,
 		tet_core::testing::TaskExecutor::new(),
 	).ok());
 	let _ = parachain::wasm_executor::validate_candidate(
@@ -89,12 +127,104 @@ fn parallel_execution() {
 			dmq_mqc_head: Default::default(),
 		},
 		&isolation_strategy_clone,
+		Default::default(),
+		ValidationPriority::Backing,
 		tet_core::testing::TaskExecutor::new(),
 	);
 	thread.join().unwrap();
-	// total time should be < 2 x EXECUTION_TIMEOUT_SEC
+	// total time should be < 2 x the execution timeout
 	assert!(
 		std::time::Instant::now().duration_since(start)
-		< std::time::Duration::from_secs(EXECUTION_TIMEOUT_SEC * 2)
+		< execution_timeout() * 2
+	);
+}
+
+#[test]
+fn approval_checks_do_not_delay_backing() {
+	let pool = ValidationPool::with_config(ValidationPoolConfig {
+		backing_hosts: 1,
+		dispute_hosts: 1,
+		approval_hosts: 1,
+		..Default::default()
+	});
+	let isolation_strategy = IsolationStrategy::ExternalProcessCustomHost {
+		pool: pool.clone(),
+		binary: std::env::current_exe().unwrap(),
+		args: WORKER_ARGS_TEST.iter().map(|x| x.to_string()).collect(),
+	};
+
+	// one approval check occupies the approval host until it times out, while the other one waits
+	// for it.
+	let approvals: Vec<_> = (0..2).map(|_| {
+		let isolation_strategy = isolation_strategy.clone();
+		std::thread::spawn(move || {
+			parachain::wasm_executor::validate_candidate(
+				halt::wasm_binary_unwrap(),
+				ValidationParams {
+					block_data: BlockData(Vec::new()),
+					parent_head: Default::default(),
+					relay_chain_height: 1,
+					relay_storage_root: Default::default(),
+					hrmp_mqc_heads: Vec::new(),
+					dmq_mqc_head: Default::default(),
+				},
+				&isolation_strategy,
+				Default::default(),
+				ValidationPriority::Approval,
+				tet_core::testing::TaskExecutor::new(),
+			).ok()
+		})
+	}).collect();
+
+	let deadline = std::time::Instant::now() + execution_timeout();
+	while pool.busy_hosts(ValidationPriority::Approval) == 0 {
+		assert!(std::time::Instant::now() < deadline, "approval checks didn't start");
+		std::thread::sleep(std::time::Duration::from_millis(10));
+	}
+
+	// the empty parent head can't be decoded, so the validation fails right away.
+	let result = parachain::wasm_executor::validate_candidate(
+		::adder::wasm_binary_unwrap(),
+		ValidationParams {
+			block_data: BlockData(Vec::new()),
+			parent_head: Default::default(),
+			relay_chain_height: 1,
+			relay_storage_root: Default::default(),
+			hrmp_mqc_heads: Vec::new(),
+			dmq_mqc_head: Default::default(),
+		},
+		&isolation_strategy,
+		Default::default(),
+		ValidationPriority::Backing,
+		tet_core::testing::TaskExecutor::new(),
 	);
+	match result {
+		Err(ValidationError::InvalidCandidate(InvalidCandidate::Timeout)) => panic!("backing timed out"),
+		_ => {},
+	}
+	// the backing validation didn't wait for the approval host, which is still busy.
+	assert_eq!(pool.busy_hosts(ValidationPriority::Approval), 1);
+
+	for approval in approvals {
+		approval.join().unwrap();
+	}
+}
+
+#[test]
+fn precheck_accepts_compilable_code_only() {
+	// a valid wasm header followed by garbage.
+	let mut malformed = vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+	malformed.extend_from_slice(&[0xff; 32]);
+
+	for isolation_strategy in vec![IsolationStrategy::InProcess, isolation_strategy()] {
+		let precheck = |code: &[u8]| parachain::wasm_executor::precheck(
+			code,
+			&isolation_strategy,
+			Default::default(),
+		);
+
+		assert!(precheck(::adder::wasm_binary_unwrap()).is_ok());
+		assert!(precheck(halt::wasm_binary_unwrap()).is_ok());
+		assert!(matches!(precheck(&malformed), Err(ValidationError::InvalidCandidate(_))));
+	}
 }
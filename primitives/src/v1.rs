@@ -39,6 +39,7 @@ pub use tetcoin_core_primitives::v1::{
 // Export some tetcoin-parachain primitives
 pub use tetcoin_parachain::primitives::{
 	Id, LOWEST_USER_ID, HrmpChannelId, UpwardMessage, HeadData, BlockData, ValidationCode,
+	PvfExecutionLimits,
 };
 
 // Export some basic parachain primitives from v0.
@@ -139,6 +140,9 @@ pub mod well_known_keys {
 /// Unique identifier for the Inclusion Inherent
 pub const INCLUSION_INHERENT_IDENTIFIER: InherentIdentifier = *b"inclusn0";
 
+/// The engine ID of the consensus digests deposited by the parachains runtime.
+pub const TETCOIN_ENGINE_ID: runtime_primitives::ConsensusEngineId = *b"TET1";
+
 /// The key type ID for parachain assignment key.
 pub const ASSIGNMENT_KEY_TYPE_ID: KeyTypeId = KeyTypeId(*b"asgn");
 
@@ -568,7 +572,7 @@ pub enum CoreOccupied {
 
 /// This is the data we keep available for each candidate included in the relay chain.
 #[cfg(feature = "std")]
-#[derive(Clone, Encode, Decode, PartialEq, Debug)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, Debug)]
 pub struct AvailableData {
 	/// The Proof-of-Validation of the candidate.
 	pub pov: std::sync::Arc<PoV>,
@@ -738,6 +742,175 @@ pub enum CandidateEvent<H = Hash> {
 	CandidateTimedOut(CandidateReceipt<H>, HeadData),
 }
 
+/// A statement from a validator on whether it was able to prepare a validation code blob for
+/// execution, i.e. whether the code passed pre-checking.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct PvfCheckStatement {
+	/// `true` if the subject passed pre-checking and `false` otherwise.
+	pub accept: bool,
+	/// The blake2-256 hash of the validation code being checked.
+	pub subject: Hash,
+	/// The index of the session during which this statement is made.
+	pub session_index: SessionIndex,
+	/// The index of the validator in the validator set of the session.
+	pub validator_index: ValidatorIndex,
+}
+
+impl PvfCheckStatement {
+	/// Produce the payload used for signing this type of statement.
+	///
+	/// It is prefixed with a magic number so that it can never be confused with any other
+	/// payload signed by a validator key.
+	pub fn signing_payload(&self) -> Vec<u8> {
+		const MAGIC: [u8; 4] = *b"VCPC"; // for "validation code pre-checking"
+		(MAGIC, self.accept, self.subject, self.session_index, self.validator_index).encode()
+	}
+}
+
+/// A consensus log item deposited by the parachains runtime.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub enum ConsensusLog {
+	/// A dispute concluded against a parachain block that was included in the given relay-chain
+	/// block. That block and all of its descendants should be reverted.
+	#[codec(index = 0)]
+	Revert(BlockNumber),
+}
+
+impl ConsensusLog {
+	/// Attempt to convert a reference to a generic digest item into a consensus log.
+	pub fn from_digest_item<H>(digest_item: &runtime_primitives::DigestItem<H>)
+		-> Result<Option<Self>, tetsy_scale_codec::Error>
+	{
+		match digest_item {
+			runtime_primitives::DigestItem::Consensus(id, encoded) if id == &TETCOIN_ENGINE_ID =>
+				Ok(Some(Self::decode(&mut &encoded[..])?)),
+			_ => Ok(None),
+		}
+	}
+}
+
+impl<H> From<ConsensusLog> for runtime_primitives::DigestItem<H> {
+	fn from(c: ConsensusLog) -> runtime_primitives::DigestItem<H> {
+		Self::Consensus(TETCOIN_ENGINE_ID, c.encode())
+	}
+}
+
+/// An explicit statement of a validator on the validity of a candidate, issued as part of a
+/// dispute.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct ExplicitDisputeStatement {
+	/// Whether the candidate is valid.
+	pub valid: bool,
+	/// The hash of the candidate under dispute.
+	pub candidate_hash: CandidateHash,
+	/// The index of the session the candidate was backed in.
+	pub session: SessionIndex,
+}
+
+impl ExplicitDisputeStatement {
+	/// Produce the payload used for signing this type of statement.
+	///
+	/// It is prefixed with a magic number so that it can never be confused with any other
+	/// payload signed by a validator key.
+	pub fn signing_payload(&self) -> Vec<u8> {
+		const MAGIC: [u8; 4] = *b"DISP";
+		(MAGIC, self.valid, self.candidate_hash, self.session).encode()
+	}
+}
+
+/// A set of signed dispute statements on a single candidate.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub struct DisputeStatementSet {
+	/// The hash of the candidate under dispute.
+	pub candidate_hash: CandidateHash,
+	/// The index of the session the candidate was backed in.
+	pub session: SessionIndex,
+	/// The statements, each being the validity vote, the index of the validator in the
+	/// validator set of the session and the signature of the validator.
+	pub statements: Vec<(bool, ValidatorIndex, ValidatorSignature)>,
+}
+
+/// Dispute statement sets on any number of candidates.
+pub type MultiDisputeStatementSet = Vec<DisputeStatementSet>;
+
+/// The remaining bandwidth of an outbound HRMP channel as of some relay-chain block.
+///
+/// A candidate whose horizontal messages exceed these limits is rejected by the relay chain.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(MallocSizeOf))]
+pub struct OutboundHrmpChannelBandwidth {
+	/// The recipient of the channel.
+	pub recipient: Id,
+	/// The number of messages which can still be put into the channel.
+	pub messages_remaining: u32,
+	/// The total size in bytes of the message payloads which can still be put into the channel.
+	pub bytes_remaining: u32,
+	/// The maximum size of a single message sent over the channel.
+	pub max_message_size: u32,
+}
+
+/// The remaining bandwidth of all outbound HRMP channels of a sender as of some relay-chain block.
+///
+/// This is what the next candidate of the sender can send without being rejected by the relay chain.
+#[derive(Clone, Default, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(MallocSizeOf))]
+pub struct OutboundHrmpBandwidth {
+	/// The maximum number of horizontal messages a single candidate may send, across all channels.
+	pub max_messages_per_candidate: u32,
+	/// The remaining bandwidth of every outbound channel, sorted ascending by the recipient.
+	pub channels: Vec<OutboundHrmpChannelBandwidth>,
+}
+
+/// The lifecycle of a para, as tracked by the relay chain.
+///
+/// Paras only move between the states at session boundaries. The transitioning states record a change
+/// which is scheduled for the next session.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(MallocSizeOf))]
+pub enum ParaLifecycle {
+	/// The para is registered and will be onboarded at the next session.
+	Onboarding,
+	/// The para is a parathread.
+	Parathread,
+	/// The para is a parachain.
+	Parachain,
+	/// The para is a parathread which will become a parachain at the next session.
+	UpgradingToParachain,
+	/// The para is a parachain which will become a parathread at the next session.
+	DowngradingToParathread,
+	/// The para will be offboarded at the next session.
+	Offboarding,
+}
+
+impl ParaLifecycle {
+	/// Whether the para is registered but not yet onboarded.
+	pub fn is_onboarding(&self) -> bool {
+		matches!(self, ParaLifecycle::Onboarding)
+	}
+
+	/// Whether the para is live and not being offboarded, regardless of whether it is changing kind.
+	pub fn is_stable(&self) -> bool {
+		!matches!(self, ParaLifecycle::Onboarding | ParaLifecycle::Offboarding)
+	}
+
+	/// Whether the para is currently scheduled as a parachain. This includes parachains which are
+	/// being downgraded at the next session.
+	pub fn is_parachain(&self) -> bool {
+		matches!(self, ParaLifecycle::Parachain | ParaLifecycle::DowngradingToParathread)
+	}
+
+	/// Whether the para is currently scheduled as a parathread. This includes parathreads which are
+	/// being upgraded at the next session.
+	pub fn is_parathread(&self) -> bool {
+		matches!(self, ParaLifecycle::Parathread | ParaLifecycle::UpgradingToParachain)
+	}
+
+	/// Whether the para is changing kind at the next session.
+	pub fn is_transitioning(&self) -> bool {
+		matches!(self, ParaLifecycle::UpgradingToParachain | ParaLifecycle::DowngradingToParathread)
+	}
+}
+
 /// Information about validator sets of a session.
 #[derive(Clone, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(PartialEq, Default, MallocSizeOf))]
@@ -770,6 +943,11 @@ pub struct SessionInfo {
 
 tp_api::decl_runtime_apis! {
 	/// The API for querying the state of parachains on-chain.
+	///
+	/// Version 2 added `earliest_stored_session`, `para_lifecycle`, `outbound_hrmp_channels_bandwidth`,
+	/// `pvfs_require_precheck`, `validation_code_by_hash`, `pvf_execution_limits` and
+	/// `submit_pvf_check_statement`.
+	#[api_version(2)]
 	pub trait ParachainHost<H: Decode = Hash, N: Encode + Decode = BlockNumber> {
 		// NOTE: Many runtime API are declared with `#[skip_initialize_block]`. This is because without
 		// this attribute before each runtime call, the `initialize_block` runtime API will be called.
@@ -827,6 +1005,12 @@ tp_api::decl_runtime_apis! {
 		#[skip_initialize_block]
 		fn session_info(index: SessionIndex) -> Option<SessionInfo>;
 
+		/// Get the index of the earliest session for which the session info is still stored.
+		///
+		/// Session infos are pruned as they fall out of the dispute period.
+		#[skip_initialize_block]
+		fn earliest_stored_session() -> SessionIndex;
+
 		/// Fetch the validation code used by a para, making the given `OccupiedCoreAssumption`.
 		///
 		/// Returns `None` if either the para is not registered or the assumption is `Freed`
@@ -853,6 +1037,10 @@ tp_api::decl_runtime_apis! {
 		#[skip_initialize_block]
 		fn candidate_events() -> Vec<CandidateEvent<H>>;
 
+		/// Get the lifecycle state of the given para, if it is known to the relay chain.
+		#[skip_initialize_block]
+		fn para_lifecycle(para_id: Id) -> Option<ParaLifecycle>;
+
 		/// Get all the pending inbound messages in the downward message queue for a para.
 		#[skip_initialize_block]
 		fn dmq_contents(
@@ -863,6 +1051,35 @@ tp_api::decl_runtime_apis! {
 		/// messages in them are also included.
 		#[skip_initialize_block]
 		fn inbound_hrmp_channels_contents(recipient: Id) -> BTreeMap<Id, Vec<InboundHrmpMessage<N>>>;
+
+		/// Get the remaining bandwidth of all outbound channels of the given sender.
+		#[skip_initialize_block]
+		fn outbound_hrmp_channels_bandwidth(sender: Id) -> OutboundHrmpBandwidth;
+
+		/// Returns the hashes of the validation code which is currently subject to pre-checking
+		/// and is awaiting votes from the validators.
+		#[skip_initialize_block]
+		fn pvfs_require_precheck() -> Vec<Hash>;
+
+		/// Get the validation code under pre-checking by its blake2-256 hash.
+		#[skip_initialize_block]
+		fn validation_code_by_hash(hash: Hash) -> Option<ValidationCode>;
+
+		/// Get the limits under which parachain validation functions are executed.
+		#[skip_initialize_block]
+		fn pvf_execution_limits() -> PvfExecutionLimits;
+
+		/// Submits a pre-checking statement of the local validator into the transaction pool.
+		fn submit_pvf_check_statement(stmt: PvfCheckStatement, signature: ValidatorSignature);
+	}
+
+	/// The API for querying the host configuration changes that are yet to take effect.
+	///
+	/// `C` is the host configuration type of the runtime.
+	pub trait ConfigurationApi<C: Encode + Decode> {
+		/// Returns the configurations which are going to become active, each paired with the index
+		/// of the session at which it takes effect, in the order in which they are applied.
+		fn pending_host_configurations() -> Vec<(SessionIndex, C)>;
 	}
 }
 
@@ -26,6 +26,7 @@ pub mod purchase;
 pub mod impls;
 pub mod paras_sudo_wrapper;
 pub mod paras_registrar;
+pub mod xcm_queries;
 
 use primitives::v1::{BlockNumber, ValidatorId, AssignmentId};
 use tp_runtime::{Perquintill, Perbill, FixedPointNumber};
@@ -20,19 +20,21 @@
 use crate::WASM_MAGIC;
 use tetcore_std::{prelude::*, result};
 use fabric_support::{
-	decl_storage, decl_module, decl_error, ensure,
+	decl_storage, decl_module, decl_error, decl_event, ensure,
 	dispatch::DispatchResult,
 	traits::{Get, Currency, ReservableCurrency},
 };
 use fabric_system::{self, ensure_root, ensure_signed};
 use primitives::v1::{
-	Id as ParaId, ValidationCode, HeadData,
+	Id as ParaId, ValidationCode, HeadData, CollatorId, ParathreadClaim, ParaLifecycle,
 };
+use tp_runtime::traits::Saturating;
 use runtime_parachains::{
 	paras::{
 		self,
 		ParaGenesisArgs,
 	},
+	scheduler::{self, OnParathreadClaimRemoved},
 	dmp, ump, hrmp,
 	ensure_parachain,
 	Origin,
@@ -41,7 +43,10 @@ use runtime_parachains::{
 type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as fabric_system::Config>::AccountId>>::Balance;
 
-pub trait Config: paras::Config + dmp::Config + ump::Config + hrmp::Config {
+pub trait Config: paras::Config + scheduler::Config + dmp::Config + ump::Config + hrmp::Config {
+	/// The overarching event type.
+	type Event: From<Event<Self>> + Into<<Self as fabric_system::Config>::Event>;
+
 	/// The aggregated origin type must support the `parachains` origin. We require that we can
 	/// infallibly convert between this origin and the system origin, but in reality, they're the
 	/// same type, we just can't express that to the Rust type system without writing a `where`
@@ -54,6 +59,10 @@ pub trait Config: paras::Config + dmp::Config + ump::Config + hrmp::Config {
 
 	/// The deposit to be paid to run a parathread.
 	type ParathreadDeposit: Get<BalanceOf<Self>>;
+
+	/// The fee for a parathread claim placed into an empty claim queue. Each claim already waiting
+	/// in the queue adds this much again.
+	type ParathreadClaimFee: Get<BalanceOf<Self>>;
 }
 
 decl_storage! {
@@ -69,6 +78,23 @@ decl_storage! {
 
 		/// Users who have paid a parathread's deposit.
 		Debtors: map hasher(twox_64_concat) ParaId => T::AccountId;
+
+		/// The account which placed the pending claim of a parathread and the fee reserved for it.
+		ParathreadClaims: map hasher(twox_64_concat) ParaId => Option<(T::AccountId, BalanceOf<T>)>;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where
+		AccountId = <T as fabric_system::Config>::AccountId,
+		Balance = BalanceOf<T>,
+	{
+		/// A claim was placed for a parathread and its fee reserved. [para, who, fee]
+		ParathreadClaimed(ParaId, AccountId, Balance),
+		/// A candidate of a parathread was included and the fee of its claim charged. [para, who, fee]
+		ParathreadClaimCharged(ParaId, AccountId, Balance),
+		/// The claim of a parathread was dropped and its fee refunded. [para, who, fee]
+		ParathreadClaimRefunded(ParaId, AccountId, Balance),
 	}
 }
 
@@ -88,6 +114,10 @@ decl_error! {
 		ParathreadsRegistrationDisabled,
 		/// The validation code provided doesn't start with the Wasm file magic string.
 		DefinitelyNotWasm,
+		/// The fee for a parathread claim is higher than the maximum given.
+		ClaimFeeTooHigh,
+		/// One of the paras to swap is not a live parachain or parathread.
+		CannotSwap,
 	}
 }
 
@@ -95,6 +125,8 @@ decl_module! {
 	pub struct Module<T: Config> for enum Call where origin: <T as fabric_system::Config>::Origin {
 		type Error = Error<T>;
 
+		fn deposit_event() = default;
+
 		/// Register a parathread with given code for immediate use.
 		///
 		/// Must be sent from a Signed origin that is able to have `ParathreadDeposit` reserved.
@@ -158,6 +190,41 @@ decl_module! {
 			Ok(())
 		}
 
+		/// Place a claim for a parathread to have a block authored by `collator` included.
+		///
+		/// Must be sent from a Signed origin that is able to have the claim fee reserved. The fee
+		/// grows with the number of claims already waiting in the queue and must not exceed
+		/// `max_fee`. It is charged once a candidate of the parathread is included and refunded if
+		/// the claim is dropped before that, e.g. after running out of retries.
+		///
+		/// Reads the para, the claim queue, its index, the configuration and the account; writes
+		/// the account, the claim queue, its index and the claim itself.
+		#[weight = 50_000_000 + T::DbWeight::get().reads_writes(6, 4)]
+		fn claim_parathread(
+			origin,
+			id: ParaId,
+			collator: CollatorId,
+			max_fee: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Paras::get(id) == Some(false), Error::<T>::InvalidThreadId);
+
+			let fee = Self::parathread_claim_fee();
+			ensure!(fee <= max_fee, Error::<T>::ClaimFeeTooHigh);
+
+			<T as Config>::Currency::reserve(&who, fee)?;
+			if let Err(e) = <scheduler::Module<T>>::try_add_parathread_claim(ParathreadClaim(id, collator)) {
+				<T as Config>::Currency::unreserve(&who, fee);
+				return Err(e);
+			}
+
+			<ParathreadClaims<T>>::insert(id, (who.clone(), fee));
+			Self::deposit_event(RawEvent::ParathreadClaimed(id, who, fee));
+
+			Ok(())
+		}
+
 		#[weight = 0]
 		fn enable_parathread_registration(origin) -> DispatchResult {
 			ensure_root(origin)?;
@@ -185,11 +252,32 @@ decl_module! {
 		/// `ParaId` to be a long-term identifier of a notional "parachain". However, their
 		/// scheduling info (i.e. whether they're a parathread or parachain), auction information
 		/// and the auction deposit are switched.
+		///
+		/// Swapping a parachain with a parathread schedules the parachain to be downgraded and the
+		/// parathread to be upgraded at the start of the next session.
 		#[weight = 0]
 		fn swap(origin, other: ParaId) {
 			let id = ensure_parachain(<T as Config>::Origin::from(origin))?;
 
 			if PendingSwap::get(other) == Some(id) {
+				let lifecycles = (
+					<paras::Module<T>>::para_lifecycle(id),
+					<paras::Module<T>>::para_lifecycle(other),
+				);
+				match lifecycles {
+					(Some(ParaLifecycle::Parachain), Some(ParaLifecycle::Parathread)) => {
+						runtime_parachains::schedule_parachain_downgrade::<T>(id)?;
+						runtime_parachains::schedule_parathread_upgrade::<T>(other)?;
+					}
+					(Some(ParaLifecycle::Parathread), Some(ParaLifecycle::Parachain)) => {
+						runtime_parachains::schedule_parathread_upgrade::<T>(id)?;
+						runtime_parachains::schedule_parachain_downgrade::<T>(other)?;
+					}
+					(Some(ParaLifecycle::Parachain), Some(ParaLifecycle::Parachain))
+					| (Some(ParaLifecycle::Parathread), Some(ParaLifecycle::Parathread)) => {}
+					_ => Err(Error::<T>::CannotSwap)?,
+				}
+
 				// Remove intention to swap.
 				PendingSwap::remove(other);
 
@@ -239,6 +327,12 @@ impl<T: Config> Module<T> {
 		Ok(())
 	}
 
+	/// The fee for a parathread claim placed now.
+	pub fn parathread_claim_fee() -> BalanceOf<T> {
+		let queued = <scheduler::Module<T>>::parathread_queue_len();
+		T::ParathreadClaimFee::get().saturating_mul((queued + 1).into())
+	}
+
 	/// Deregister a parachain with the given ID. Must be called by root.
 	pub fn deregister_parachain(id: ParaId) -> DispatchResult {
 		let is_parachain = Paras::take(id).ok_or(Error::<T>::InvalidChainId)?;
@@ -251,6 +345,22 @@ impl<T: Config> Module<T> {
 	}
 }
 
+impl<T: Config> OnParathreadClaimRemoved for Module<T> {
+	fn on_claim_fulfilled(para: ParaId) {
+		if let Some((who, fee)) = <ParathreadClaims<T>>::take(para) {
+			let (_, not_slashed) = <T as Config>::Currency::slash_reserved(&who, fee);
+			Self::deposit_event(RawEvent::ParathreadClaimCharged(para, who, fee.saturating_sub(not_slashed)));
+		}
+	}
+
+	fn on_claim_dropped(para: ParaId) {
+		if let Some((who, fee)) = <ParathreadClaims<T>>::take(para) {
+			let not_unreserved = <T as Config>::Currency::unreserve(&who, fee);
+			Self::deposit_event(RawEvent::ParathreadClaimRefunded(para, who, fee.saturating_sub(not_unreserved)));
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -267,10 +377,10 @@ mod tests {
 	use fabric_system::limits;
 	use fabric_support::{
 		traits::{Randomness, OnInitialize, OnFinalize},
-		impl_outer_origin, impl_outer_dispatch, assert_ok, parameter_types,
+		impl_outer_origin, impl_outer_dispatch, assert_ok, assert_noop, parameter_types,
 	};
 	use keyring::Sr25519Keyring;
-	use runtime_parachains::{initializer, configuration, inclusion, session_info, scheduler, dmp, ump, hrmp};
+	use runtime_parachains::{initializer, configuration, inclusion, session_info, disputes, scheduler, dmp, ump, hrmp};
 	use noble_session::OneSessionHandler;
 
 	impl_outer_origin! {
@@ -430,6 +540,7 @@ mod tests {
 	impl dmp::Config for Test {}
 
 	impl ump::Config for Test {
+		type Event = ();
 		type UmpSink = ();
 	}
 
@@ -469,10 +580,13 @@ mod tests {
 	}
 
 	impl paras::Config for Test {
+		type Event = ();
 		type Origin = Origin;
 	}
 
-	impl configuration::Config for Test { }
+	impl configuration::Config for Test {
+		type CurrentSessionIndex = inclusion::CurrentSession<Test>;
+	}
 
 	pub struct TestRewardValidators;
 
@@ -484,6 +598,7 @@ mod tests {
 	impl inclusion::Config for Test {
 		type Event = ();
 		type RewardValidators = TestRewardValidators;
+		type DisputesHandler = ();
 	}
 
 	impl session_info::AuthorityDiscoveryConfig for Test {
@@ -494,6 +609,11 @@ mod tests {
 
 	impl session_info::Config for Test { }
 
+	impl disputes::Config for Test {
+		type Event = ();
+		type SlashingHandler = ();
+	}
+
 	pub struct TestRandomness;
 
 	impl Randomness<H256> for TestRandomness {
@@ -506,7 +626,9 @@ mod tests {
 		type Randomness = TestRandomness;
 	}
 
-	impl scheduler::Config for Test { }
+	impl scheduler::Config for Test {
+		type OnParathreadClaimRemoved = Registrar;
+	}
 
 	type Extrinsic = TestXt<Call, ()>;
 
@@ -530,14 +652,17 @@ mod tests {
 
 	parameter_types! {
 		pub const ParathreadDeposit: Balance = 10;
+		pub const ParathreadClaimFee: Balance = 3;
 		pub const QueueSize: usize = 2;
 		pub const MaxRetries: u32 = 3;
 	}
 
 	impl Config for Test {
+		type Event = ();
 		type Origin = Origin;
 		type Currency = noble_balances::Module<Test>;
 		type ParathreadDeposit = ParathreadDeposit;
+		type ParathreadClaimFee = ParathreadClaimFee;
 	}
 
 	type Balances = noble_balances::Module<Test>;
@@ -548,6 +673,7 @@ mod tests {
 	type Session = noble_session::Module<Test>;
 	type Staking = noble_staking::Module<Test>;
 	type Initializer = initializer::Module<Test>;
+	type Configuration = configuration::Module<Test>;
 
 	fn new_test_ext() -> TestExternalities {
 		let mut t = fabric_system::GenesisConfig::default().build_storage::<Test>().unwrap();
@@ -705,6 +831,147 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn swap_changes_the_kind_of_the_paras() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert_ok!(Registrar::enable_parathread_registration(
+				Origin::root(),
+			));
+			run_to_block(2);
+
+			assert_ok!(Registrar::register_parathread(
+				Origin::signed(1),
+				8u32.into(),
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			assert_ok!(Registrar::register_parachain(
+				2u32.into(),
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			run_to_block(9);
+
+			assert_eq!(Parachains::parachains(), vec![ParaId::from(2u32)]);
+
+			assert_ok!(Registrar::swap(runtime_parachains::Origin::Parachain(2u32.into()).into(), 8u32.into()));
+			assert_ok!(Registrar::swap(runtime_parachains::Origin::Parachain(8u32.into()).into(), 2u32.into()));
+
+			assert_eq!(
+				Parachains::para_lifecycle(ParaId::from(2u32)),
+				Some(ParaLifecycle::DowngradingToParathread),
+			);
+			assert_eq!(
+				Parachains::para_lifecycle(ParaId::from(8u32)),
+				Some(ParaLifecycle::UpgradingToParachain),
+			);
+
+			run_to_block(12);
+
+			assert_eq!(Parachains::parachains(), vec![ParaId::from(8u32)]);
+			assert_eq!(Parachains::para_lifecycle(ParaId::from(2u32)), Some(ParaLifecycle::Parathread));
+			assert_eq!(Registrar::paras(ParaId::from(2u32)), Some(false));
+			assert_eq!(Registrar::paras(ParaId::from(8u32)), Some(true));
+		});
+	}
+
+	#[test]
+	fn swap_with_a_para_not_yet_onboarded_fails() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert_ok!(Registrar::register_parachain(
+				2u32.into(),
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+			run_to_block(3);
+
+			assert_ok!(Registrar::register_parachain(
+				5u32.into(),
+				vec![1; 3].into(),
+				WASM_MAGIC.to_vec().into(),
+			));
+
+			assert_ok!(Registrar::swap(runtime_parachains::Origin::Parachain(5u32.into()).into(), 2u32.into()));
+			assert_noop!(
+				Registrar::swap(runtime_parachains::Origin::Parachain(2u32.into()).into(), 5u32.into()),
+				Error::<Test>::CannotSwap,
+			);
+		});
+	}
+
+	#[test]
+	fn parathread_claims_are_charged_or_refunded() {
+		new_test_ext().execute_with(|| {
+			run_to_block(1);
+
+			assert_ok!(Registrar::enable_parathread_registration(
+				Origin::root(),
+			));
+			assert_ok!(Configuration::set_parathread_cores(Origin::root(), 1));
+			assert_ok!(Configuration::set_scheduling_lookahead(Origin::root(), 2));
+			run_to_block(2);
+
+			for id in &[8u32, 9] {
+				assert_ok!(Registrar::register_parathread(
+					Origin::signed(1),
+					(*id).into(),
+					vec![1; 3].into(),
+					WASM_MAGIC.to_vec().into(),
+				));
+			}
+
+			// wait for the parathreads to be onboarded and the new configuration to take effect.
+			run_to_block(4);
+
+			let collator = CollatorId::from(Sr25519Keyring::Alice.public());
+			let initial_balance = Balances::free_balance(1);
+			let fee = ParathreadClaimFee::get();
+
+			// can't claim for a para which isn't a registered parathread.
+			assert_noop!(
+				Registrar::claim_parathread(Origin::signed(1), 7u32.into(), collator.clone(), fee),
+				Error::<Test>::InvalidThreadId,
+			);
+
+			assert_eq!(Registrar::parathread_claim_fee(), fee);
+			assert_ok!(Registrar::claim_parathread(Origin::signed(1), 8u32.into(), collator.clone(), fee));
+
+			// the fee grows with the number of queued claims.
+			assert_eq!(Registrar::parathread_claim_fee(), 2 * fee);
+			assert_noop!(
+				Registrar::claim_parathread(Origin::signed(1), 9u32.into(), collator.clone(), fee),
+				Error::<Test>::ClaimFeeTooHigh,
+			);
+			assert_ok!(Registrar::claim_parathread(Origin::signed(1), 9u32.into(), collator.clone(), 2 * fee));
+
+			assert_eq!(Balances::free_balance(1), initial_balance - 3 * fee);
+
+			// the claim of a parathread which goes away is dropped at the next session and refunded.
+			assert_ok!(Registrar::deregister_parathread(
+				runtime_parachains::Origin::Parachain(8u32.into()).into()
+			));
+			run_to_block(7);
+
+			assert_eq!(ParathreadClaims::<Test>::get(ParaId::from(8)), None);
+			assert_eq!(Balances::free_balance(1), initial_balance + ParathreadDeposit::get() - 2 * fee);
+
+			// the fee of a fulfilled claim is charged. That the scheduler reports claims fulfilled
+			// by an included candidate is covered by the tests of inclusion.
+			let reserved = Balances::reserved_balance(1);
+			Registrar::on_claim_fulfilled(9u32.into());
+
+			assert_eq!(ParathreadClaims::<Test>::get(ParaId::from(9)), None);
+			assert_eq!(Balances::reserved_balance(1), reserved - 2 * fee);
+			assert_eq!(Balances::free_balance(1), initial_balance + ParathreadDeposit::get() - 2 * fee);
+		});
+	}
+
 	#[test]
 	fn cannot_register_until_para_is_cleaned_up() {
 		new_test_ext().execute_with(|| {
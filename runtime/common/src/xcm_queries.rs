@@ -0,0 +1,258 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module for keeping track of queries made of other consensus systems over XCM.
+//!
+//! A query is registered with [`Module::new_query`] before sending the message that asks for the response (e.g.
+//! `Xcm::ReportOutcome` or `Order::QueryHolding`) using the returned identifier. The module is then meant to be
+//! used as the `ResponseHandler` of the XCM executor, so that the response is stored against the query once it
+//! arrives. Responses are kept until they are taken with [`Module::take_response`]; queries which received no
+//! response within `QueryTimeout` blocks are dropped.
+
+use tetcore_std::prelude::*;
+use tetsy_scale_codec::{Encode, Decode};
+use fabric_support::{
+	decl_event, decl_module, decl_storage, StorageMap, StorageValue,
+	traits::Get, weights::Weight,
+};
+use tp_runtime::{RuntimeDebug, traits::{One, Saturating}};
+use xcm::v0::{MultiLocation, Response};
+use xcm_executor::traits::OnResponse;
+
+/// The module's configuration trait.
+pub trait Config: fabric_system::Config {
+	/// The overarching event type.
+	type Event: From<Event> + Into<<Self as fabric_system::Config>::Event>;
+
+	/// The number of blocks to wait for a response before a query is dropped.
+	type QueryTimeout: Get<Self::BlockNumber>;
+}
+
+/// The status of a query.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub enum QueryStatus<BlockNumber> {
+	/// The query was made of `responder` and a response is expected before block `timeout`.
+	Pending { responder: MultiLocation, timeout: BlockNumber },
+	/// A `response` was received at block `at`.
+	Ready { response: Response, at: BlockNumber },
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as XcmQueries {
+		/// The number of queries which have been made. Also the identifier of the next query.
+		QueryCount get(fn query_count): u64;
+
+		/// The status of every query which is pending, or has a response which was not taken yet.
+		Queries get(fn query): map hasher(twox_64_concat) u64 => Option<QueryStatus<T::BlockNumber>>;
+
+		/// The queries which time out at the start of a given block.
+		Timeouts: map hasher(twox_64_concat) T::BlockNumber => Vec<u64>;
+	}
+}
+
+decl_event! {
+	pub enum Event {
+		/// A response was received for a pending query. [query_id, response]
+		ResponseReady(u64, Response),
+		/// A response was received which did not match any pending query. [origin, query_id]
+		UnexpectedResponse(MultiLocation, u64),
+		/// No response was received for a query in time. [query_id]
+		QueryTimedOut(u64),
+	}
+}
+
+decl_module! {
+	pub struct Module<T: Config> for enum Call where origin: <T as fabric_system::Config>::Origin {
+		fn deposit_event() = default;
+
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let timed_out = Timeouts::<T>::take(now);
+			let count = timed_out.len() as Weight;
+			for query_id in timed_out {
+				if let Some(QueryStatus::Pending { .. }) = Queries::<T>::get(query_id) {
+					Queries::<T>::remove(query_id);
+					Self::deposit_event(Event::QueryTimedOut(query_id));
+				}
+			}
+			T::DbWeight::get().reads_writes(1 + count, 1 + count)
+		}
+	}
+}
+
+impl<T: Config> Module<T> {
+	/// Register a query for which a response is expected from `responder`, returning its identifier.
+	pub fn new_query(responder: MultiLocation) -> u64 {
+		let now = <fabric_system::Module<T>>::block_number();
+		let timeout = now.saturating_add(T::QueryTimeout::get().max(One::one()));
+		let query_id = QueryCount::mutate(|count| {
+			let query_id = *count;
+			*count = count.saturating_add(1);
+			query_id
+		});
+		Queries::<T>::insert(query_id, QueryStatus::Pending { responder, timeout });
+		Timeouts::<T>::append(timeout, query_id);
+		query_id
+	}
+
+	/// Take the response to `query_id` along with the block it was received at, if it has arrived.
+	pub fn take_response(query_id: u64) -> Option<(Response, T::BlockNumber)> {
+		match Queries::<T>::get(query_id) {
+			Some(QueryStatus::Ready { response, at }) => {
+				Queries::<T>::remove(query_id);
+				Some((response, at))
+			}
+			_ => None,
+		}
+	}
+}
+
+impl<T: Config> OnResponse for Module<T> {
+	fn expecting_response(origin: &MultiLocation, query_id: u64) -> bool {
+		matches!(
+			Queries::<T>::get(query_id),
+			Some(QueryStatus::Pending { ref responder, .. }) if responder == origin
+		)
+	}
+
+	fn on_response(origin: MultiLocation, query_id: u64, response: Response) {
+		if !Self::expecting_response(&origin, query_id) {
+			Self::deposit_event(Event::UnexpectedResponse(origin, query_id));
+			return
+		}
+		let at = <fabric_system::Module<T>>::block_number();
+		Queries::<T>::insert(query_id, QueryStatus::Ready { response: response.clone(), at });
+		Self::deposit_event(Event::ResponseReady(query_id, response));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use tet_core::H256;
+	use tp_runtime::traits::{BlakeTwo256, IdentityLookup};
+	use tp_runtime::testing::Header;
+	use fabric_support::{impl_outer_origin, parameter_types, traits::OnInitialize};
+	use xcm::v0::{Junction, MultiAsset, Outcome};
+
+	impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	parameter_types! {
+		pub const BlockHashCount: u32 = 250;
+	}
+	impl fabric_system::Config for Test {
+		type BaseCallFilter = ();
+		type BlockWeights = ();
+		type BlockLength = ();
+		type DbWeight = ();
+		type Origin = Origin;
+		type Call = ();
+		type Index = u64;
+		type BlockNumber = u64;
+		type Hash = H256;
+		type Hashing = BlakeTwo256;
+		type AccountId = u64;
+		type Lookup = IdentityLookup<u64>;
+		type Header = Header;
+		type Event = ();
+		type BlockHashCount = BlockHashCount;
+		type Version = ();
+		type NobleInfo = ();
+		type AccountData = ();
+		type OnNewAccount = ();
+		type OnKilledAccount = ();
+		type SystemWeightInfo = ();
+		type SS58Prefix = ();
+	}
+
+	parameter_types! {
+		pub const QueryTimeout: u64 = 10;
+	}
+
+	impl Config for Test {
+		type Event = ();
+		type QueryTimeout = QueryTimeout;
+	}
+
+	type System = fabric_system::Module<Test>;
+	type XcmQueries = Module<Test>;
+
+	fn new_test_ext() -> tet_io::TestExternalities {
+		fabric_system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	}
+
+	fn para(id: u32) -> MultiLocation {
+		Junction::Parachain { id }.into()
+	}
+
+	#[test]
+	fn response_is_stored_until_taken() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let query_id = XcmQueries::new_query(para(1));
+			assert_eq!(XcmQueries::new_query(para(2)), query_id + 1);
+			assert!(XcmQueries::expecting_response(&para(1), query_id));
+			assert!(!XcmQueries::expecting_response(&para(2), query_id));
+			assert_eq!(XcmQueries::take_response(query_id), None);
+
+			System::set_block_number(2);
+			let response = Response::ExecutionResult(Outcome::Complete(1_000));
+			XcmQueries::on_response(para(1), query_id, response.clone());
+			assert!(!XcmQueries::expecting_response(&para(1), query_id));
+			assert_eq!(XcmQueries::take_response(query_id), Some((response, 2)));
+			assert_eq!(XcmQueries::query(query_id), None);
+		});
+	}
+
+	#[test]
+	fn unexpected_response_is_ignored() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let query_id = XcmQueries::new_query(para(1));
+			let response = Response::Assets(vec![MultiAsset::All]);
+			XcmQueries::on_response(para(2), query_id, response.clone());
+			XcmQueries::on_response(para(1), query_id + 1, response);
+			assert_eq!(
+				XcmQueries::query(query_id),
+				Some(QueryStatus::Pending { responder: para(1), timeout: 11 }),
+			);
+			assert_eq!(XcmQueries::query(query_id + 1), None);
+		});
+	}
+
+	#[test]
+	fn pending_queries_time_out() {
+		new_test_ext().execute_with(|| {
+			System::set_block_number(1);
+			let answered = XcmQueries::new_query(para(1));
+			let unanswered = XcmQueries::new_query(para(1));
+			XcmQueries::on_response(para(1), answered, Response::Assets(vec![]));
+
+			XcmQueries::on_initialize(10);
+			assert!(XcmQueries::expecting_response(&para(1), unanswered));
+
+			XcmQueries::on_initialize(11);
+			assert!(!XcmQueries::expecting_response(&para(1), unanswered));
+			assert_eq!(XcmQueries::query(unanswered), None);
+			// A response which has already arrived is not affected.
+			assert_eq!(XcmQueries::take_response(answered), Some((Response::Assets(vec![]), 1)));
+		});
+	}
+}
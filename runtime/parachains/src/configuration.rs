@@ -16,22 +16,43 @@
 
 //! Configuration manager for the Tetcoin runtime parachains logic.
 //!
-//! Configuration can change only at session boundaries and is buffered until then.
+//! Configuration can change only at session boundaries and is buffered until then. Changes can also
+//! be scheduled to take effect at a specific future session.
 
 use tetcore_std::prelude::*;
-use primitives::v1::{Balance, ValidatorId, SessionIndex};
+use primitives::v1::{Balance, ValidatorId, SessionIndex, PvfExecutionLimits};
 use fabric_support::{
 	decl_storage, decl_module, decl_error,
 	ensure,
 	dispatch::DispatchResult,
+	traits::Get,
 	weights::{DispatchClass, Weight},
 };
 use tetsy_scale_codec::{Encode, Decode};
 use fabric_system::ensure_root;
 use tp_runtime::traits::Zero;
 
+/// The hard upper bound on `max_code_size`, in bytes, which no configuration may exceed.
+pub const MAX_CODE_SIZE: u32 = 10 * 1024 * 1024;
+
+/// The hard upper bound on `max_head_data_size`, in bytes, which no configuration may exceed.
+pub const MAX_HEAD_DATA_SIZE: u32 = 1024 * 1024;
+
+/// The hard upper bound on `max_pov_size`, in bytes, which no configuration may exceed.
+pub const MAX_POV_SIZE: u32 = 50 * 1024 * 1024;
+
+/// The hard upper bound on `pvf_max_memory_pages`, which no configuration may exceed. This amounts
+/// to 1 GiB.
+pub const MAX_PVF_MEMORY_PAGES: u32 = 16 * 1024;
+
+/// The hard upper bound on `pvf_max_stack_height`, which no configuration may exceed.
+///
+/// The wasm executor only provides enough native stack for this height. Running out of it aborts
+/// the executing process instead of making the validation function trap.
+pub const MAX_PVF_STACK_HEIGHT: u32 = 64 * 1024;
+
 /// All configuration of the runtime with respect to parachains and parathreads.
-#[derive(Clone, Encode, Decode, PartialEq, tet_core::RuntimeDebug)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, tet_core::RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostConfiguration<BlockNumber> {
 	// NOTE: This structure is used by parachains via merkle proofs. Therefore, this struct requires
@@ -161,6 +182,24 @@ pub struct HostConfiguration<BlockNumber> {
 	pub needed_approvals: u32,
 	/// The number of samples to do of the RelayVRFModulo approval assignment criterion.
 	pub relay_vrf_modulo_samples: u32,
+	/// Whether validation code must pass pre-checking by the validators before an upgrade or the
+	/// onboarding of a para using it can be enacted.
+	pub pvf_checking_enabled: bool,
+	/// The number of session changes after which a pre-checking vote which has not concluded is
+	/// rejected.
+	///
+	/// Must be at least 1.
+	pub pvf_voting_ttl: SessionIndex,
+	/// The time the execution of a parachain validation function may take, in milliseconds.
+	///
+	/// Must be at least 1.
+	pub pvf_execution_timeout_ms: u32,
+	/// The number of wasm pages, of 64 KiB each, a parachain validation function may use for its
+	/// heap.
+	pub pvf_max_memory_pages: u32,
+	/// The maximum height of the value stack of a parachain validation function, counted in
+	/// values.
+	pub pvf_max_stack_height: u32,
 }
 
 impl<BlockNumber: Default + From<u32>> Default for HostConfiguration<BlockNumber> {
@@ -185,6 +224,11 @@ impl<BlockNumber: Default + From<u32>> Default for HostConfiguration<BlockNumber
 			zeroth_delay_tranche_width: Default::default(),
 			needed_approvals: Default::default(),
 			relay_vrf_modulo_samples: Default::default(),
+			pvf_checking_enabled: false,
+			pvf_voting_ttl: 2,
+			pvf_execution_timeout_ms: PvfExecutionLimits::default().timeout_ms,
+			pvf_max_memory_pages: PvfExecutionLimits::default().max_memory_pages,
+			pvf_max_stack_height: PvfExecutionLimits::default().max_stack_height,
 			max_upward_queue_count: Default::default(),
 			max_upward_queue_size: Default::default(),
 			max_downward_message_size: Default::default(),
@@ -206,32 +250,127 @@ impl<BlockNumber: Default + From<u32>> Default for HostConfiguration<BlockNumber
 	}
 }
 
+/// The reason for which a `HostConfiguration` is inconsistent.
+#[derive(Clone, Copy, PartialEq, Eq, tet_core::RuntimeDebug)]
+pub enum InconsistentError {
+	/// `group_rotation_frequency` is zero.
+	ZeroGroupRotationFrequency,
+	/// `chain_availability_period` is zero.
+	ZeroChainAvailabilityPeriod,
+	/// `thread_availability_period` is zero.
+	ZeroThreadAvailabilityPeriod,
+	/// `no_show_slots` is zero.
+	ZeroNoShowSlots,
+	/// `pvf_voting_ttl` is zero.
+	ZeroPvfVotingTtl,
+	/// `pvf_execution_timeout_ms` is zero.
+	ZeroPvfExecutionTimeout,
+	/// `max_code_size` exceeds [`MAX_CODE_SIZE`].
+	MaxCodeSizeExceedHardLimit,
+	/// `max_head_data_size` exceeds [`MAX_HEAD_DATA_SIZE`].
+	MaxHeadDataSizeExceedHardLimit,
+	/// `max_pov_size` exceeds [`MAX_POV_SIZE`].
+	MaxPovSizeExceedHardLimit,
+	/// `pvf_max_memory_pages` exceeds [`MAX_PVF_MEMORY_PAGES`].
+	PvfMaxMemoryPagesExceedHardLimit,
+	/// `pvf_max_stack_height` exceeds [`MAX_PVF_STACK_HEIGHT`].
+	PvfMaxStackHeightExceedHardLimit,
+}
+
 impl<BlockNumber: Zero> HostConfiguration<BlockNumber> {
 	/// Checks that this instance is consistent with the requirements on each individual member.
-	///
-	/// # Panic
-	///
-	/// This function panics if any member is not set properly.
-	fn check_consistency(&self) {
+	pub fn check_consistency(&self) -> Result<(), InconsistentError> {
+		use InconsistentError::*;
+
 		if self.group_rotation_frequency.is_zero() {
-			panic!("`group_rotation_frequency` must be non-zero!")
+			return Err(ZeroGroupRotationFrequency)
 		}
 
 		if self.chain_availability_period.is_zero() {
-			panic!("`chain_availability_period` must be at least 1!")
+			return Err(ZeroChainAvailabilityPeriod)
 		}
 
 		if self.thread_availability_period.is_zero() {
-			panic!("`thread_availability_period` must be at least 1!")
+			return Err(ZeroThreadAvailabilityPeriod)
 		}
 
 		if self.no_show_slots.is_zero() {
-			panic!("`no_show_slots` must be at least 1!")
+			return Err(ZeroNoShowSlots)
 		}
+
+		if self.pvf_voting_ttl.is_zero() {
+			return Err(ZeroPvfVotingTtl)
+		}
+
+		if self.pvf_execution_timeout_ms.is_zero() {
+			return Err(ZeroPvfExecutionTimeout)
+		}
+
+		if self.max_code_size > MAX_CODE_SIZE {
+			return Err(MaxCodeSizeExceedHardLimit)
+		}
+
+		if self.max_head_data_size > MAX_HEAD_DATA_SIZE {
+			return Err(MaxHeadDataSizeExceedHardLimit)
+		}
+
+		if self.max_pov_size > MAX_POV_SIZE {
+			return Err(MaxPovSizeExceedHardLimit)
+		}
+
+		if self.pvf_max_memory_pages > MAX_PVF_MEMORY_PAGES {
+			return Err(PvfMaxMemoryPagesExceedHardLimit)
+		}
+
+		if self.pvf_max_stack_height > MAX_PVF_STACK_HEIGHT {
+			return Err(PvfMaxStackHeightExceedHardLimit)
+		}
+
+		Ok(())
 	}
+
+	/// Checks that this instance is consistent with the requirements on each individual member.
+	///
+	/// # Panic
+	///
+	/// This function panics if any member is not set properly.
+	fn panic_if_not_consistent(&self) {
+		if let Err(e) = self.check_consistency() {
+			panic!("`HostConfiguration` is inconsistent: {:?}", e)
+		}
+	}
+}
+
+impl<BlockNumber> HostConfiguration<BlockNumber> {
+	/// The limits under which parachain validation functions are executed.
+	pub fn pvf_execution_limits(&self) -> PvfExecutionLimits {
+		PvfExecutionLimits {
+			timeout_ms: self.pvf_execution_timeout_ms,
+			max_memory_pages: self.pvf_max_memory_pages,
+			max_stack_height: self.pvf_max_stack_height,
+		}
+	}
+}
+
+/// The versions of the storage layout of this module.
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, tet_core::RuntimeDebug)]
+pub enum Releases {
+	/// The configurations lack the PVF pre-checking and execution parameters.
+	V0,
+	/// The configurations have the PVF pre-checking and execution parameters.
+	V1,
 }
 
-pub trait Config: fabric_system::Config { }
+impl Default for Releases {
+	fn default() -> Self {
+		Releases::V0
+	}
+}
+
+pub trait Config: fabric_system::Config {
+	/// The index of the current session, as tracked elsewhere in the runtime.
+	type CurrentSessionIndex: Get<SessionIndex>;
+}
 
 decl_storage! {
 	trait Store for Module<T: Config> as Configuration {
@@ -239,10 +378,15 @@ decl_storage! {
 		ActiveConfig get(fn config) config(): HostConfiguration<T::BlockNumber>;
 		/// Pending configuration (if any) for the next session.
 		PendingConfig: Option<HostConfiguration<T::BlockNumber>>;
+		/// Configurations scheduled for future sessions, sorted ascending by the index of the session
+		/// at which they take effect. There is at most one configuration per session.
+		ScheduledConfigs: Vec<(SessionIndex, HostConfiguration<T::BlockNumber>)>;
+		/// The version of the storage layout. New chains start with the latest one.
+		StorageVersion build(|_: &GenesisConfig<T>| Releases::V1): Releases;
 	}
 	add_extra_genesis {
 		build(|config: &Self| {
-			config.config.check_consistency();
+			config.config.panic_if_not_consistent();
 		})
 	}
 }
@@ -251,6 +395,10 @@ decl_error! {
 	pub enum Error for Module<T: Config> {
 		/// The new value for a configuration parameter is invalid.
 		InvalidNewValue,
+		/// The resulting configuration would be inconsistent.
+		InconsistentConfiguration,
+		/// The session at which the configuration was scheduled to take effect is not in the future.
+		ScheduledSessionNotInFuture,
 	}
 }
 
@@ -259,14 +407,49 @@ decl_module! {
 	pub struct Module<T: Config> for enum Call where origin: <T as fabric_system::Config>::Origin {
 		type Error = Error<T>;
 
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_to_v1()
+		}
+
+		/// Replace the whole configuration at once.
+		///
+		/// If `at` is `None` the configuration takes effect at the next session, superseding any
+		/// pending changes. Otherwise it takes effect at the session `at`, which must be in the
+		/// future, replacing any other configuration scheduled for that session.
+		///
+		/// A scheduled configuration is applied after the pending changes of the same session change
+		/// and thus takes precedence over them.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_config(
+			origin,
+			new: HostConfiguration<T::BlockNumber>,
+			at: Option<SessionIndex>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(new.check_consistency().is_ok(), Error::<T>::InconsistentConfiguration);
+
+			match at {
+				None => <Self as Store>::PendingConfig::set(Some(new)),
+				Some(at) => {
+					ensure!(at > T::CurrentSessionIndex::get(), Error::<T>::ScheduledSessionNotInFuture);
+					<Self as Store>::ScheduledConfigs::mutate(|scheduled| {
+						match scheduled.binary_search_by_key(&at, |(session, _)| *session) {
+							Ok(i) => scheduled[i] = (at, new),
+							Err(i) => scheduled.insert(i, (at, new)),
+						}
+					});
+				}
+			}
+			Ok(())
+		}
+
 		/// Set the validation upgrade frequency.
 		#[weight = (1_000, DispatchClass::Operational)]
 		pub fn set_validation_upgrade_frequency(origin, new: T::BlockNumber) -> DispatchResult {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.validation_upgrade_frequency, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the validation upgrade delay.
@@ -275,8 +458,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.validation_upgrade_delay, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the acceptance period for an included candidate.
@@ -285,8 +467,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.acceptance_period, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the max validation code size for incoming upgrades.
@@ -295,8 +476,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_code_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the max POV block size for incoming upgrades.
@@ -305,8 +485,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_pov_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the max head data size for paras.
@@ -315,8 +494,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_head_data_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the number of parathread execution cores.
@@ -325,8 +503,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.parathread_cores, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the number of retries for a particular parathread.
@@ -335,8 +512,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.parathread_retries, new) != new
-			});
-			Ok(())
+			})
 		}
 
 
@@ -349,8 +525,7 @@ decl_module! {
 
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.group_rotation_frequency, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the availability period for parachains.
@@ -362,8 +537,7 @@ decl_module! {
 
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.chain_availability_period, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the availability period for parathreads.
@@ -375,8 +549,7 @@ decl_module! {
 
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.thread_availability_period, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the scheduling lookahead, in expected number of blocks at peak throughput.
@@ -385,8 +558,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.scheduling_lookahead, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the maximum number of validators to assign to any core.
@@ -395,8 +567,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_validators_per_core, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the dispute period, in number of sessions to keep for disputes.
@@ -405,8 +576,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.dispute_period, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the no show slots, in number of number of consensus slots.
@@ -419,8 +589,7 @@ decl_module! {
 
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.no_show_slots, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the total number of delay tranches.
@@ -429,8 +598,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.n_delay_tranches, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the zeroth delay tranche width.
@@ -439,8 +607,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.zeroth_delay_tranche_width, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the number of validators needed to approve a block.
@@ -449,8 +616,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.needed_approvals, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the number of samples to do of the RelayVRFModulo approval assignment criterion.
@@ -459,8 +625,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.relay_vrf_modulo_samples, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum items that can present in a upward dispatch queue at once.
@@ -469,8 +634,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_upward_queue_count, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum total size of items that can present in a upward dispatch queue at once.
@@ -479,8 +643,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_upward_queue_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Set the critical downward message size.
@@ -489,8 +652,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_downward_message_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the soft limit for the phase of dispatching dispatchable upward messages.
@@ -499,8 +661,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.preferred_dispatchable_upward_messages_step_weight, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum size of an upward message that can be sent by a candidate.
@@ -509,8 +670,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_upward_message_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of messages that a candidate can contain.
@@ -519,8 +679,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.max_upward_message_num_per_candidate, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the number of sessions after which an HRMP open channel request expires.
@@ -529,8 +688,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_open_request_ttl, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the amount of funds that the sender should provide for opening an HRMP channel.
@@ -539,8 +697,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_sender_deposit, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the amount of funds that the recipient should provide for accepting opening an HRMP
@@ -550,8 +707,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_recipient_deposit, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of messages allowed in an HRMP channel at once.
@@ -560,8 +716,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_channel_max_capacity, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum total size of messages in bytes allowed in an HRMP channel at once.
@@ -570,8 +725,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_channel_max_total_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of inbound HRMP channels a parachain is allowed to accept.
@@ -580,8 +734,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_max_parachain_inbound_channels, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of inbound HRMP channels a parathread is allowed to accept.
@@ -590,8 +743,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_max_parathread_inbound_channels, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum size of a message that could ever be put into an HRMP channel.
@@ -600,8 +752,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_channel_max_message_size, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of outbound HRMP channels a parachain is allowed to open.
@@ -610,8 +761,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_max_parachain_outbound_channels, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of outbound HRMP channels a parathread is allowed to open.
@@ -620,8 +770,7 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_max_parathread_outbound_channels, new) != new
-			});
-			Ok(())
+			})
 		}
 
 		/// Sets the maximum number of outbound HRMP messages can be sent by a candidate.
@@ -630,8 +779,55 @@ decl_module! {
 			ensure_root(origin)?;
 			Self::update_config_member(|config| {
 				tetcore_std::mem::replace(&mut config.hrmp_max_message_num_per_candidate, new) != new
-			});
-			Ok(())
+			})
+		}
+
+		/// Enable or disable the pre-checking of validation code.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_pvf_checking_enabled(origin, new: bool) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				tetcore_std::mem::replace(&mut config.pvf_checking_enabled, new) != new
+			})
+		}
+
+		/// Set the number of session changes after which a pre-checking vote is rejected.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_pvf_voting_ttl(origin, new: SessionIndex) -> DispatchResult {
+			ensure_root(origin)?;
+
+			ensure!(!new.is_zero(), Error::<T>::InvalidNewValue);
+
+			Self::update_config_member(|config| {
+				tetcore_std::mem::replace(&mut config.pvf_voting_ttl, new) != new
+			})
+		}
+
+		/// Set the time the execution of a parachain validation function may take, in milliseconds.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_pvf_execution_timeout_ms(origin, new: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				tetcore_std::mem::replace(&mut config.pvf_execution_timeout_ms, new) != new
+			})
+		}
+
+		/// Set the number of wasm pages a parachain validation function may use for its heap.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_pvf_max_memory_pages(origin, new: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				tetcore_std::mem::replace(&mut config.pvf_max_memory_pages, new) != new
+			})
+		}
+
+		/// Set the maximum height of the value stack of a parachain validation function.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn set_pvf_max_stack_height(origin, new: u32) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::update_config_member(|config| {
+				tetcore_std::mem::replace(&mut config.pvf_max_stack_height, new) != new
+			})
 		}
 	}
 }
@@ -646,10 +842,56 @@ impl<T: Config> Module<T> {
 	pub(crate) fn initializer_finalize() { }
 
 	/// Called by the initializer to note that a new session has started.
-	pub(crate) fn initializer_on_new_session(_validators: &[ValidatorId], _queued: &[ValidatorId]) {
+	pub(crate) fn initializer_on_new_session(
+		_validators: &[ValidatorId],
+		_queued: &[ValidatorId],
+		session_index: SessionIndex,
+	) {
 		if let Some(pending) = <Self as Store>::PendingConfig::take() {
 			<Self as Store>::ActiveConfig::set(pending);
 		}
+
+		let mut scheduled = <Self as Store>::ScheduledConfigs::get();
+		let due = scheduled.iter().take_while(|(at, _)| *at <= session_index).count();
+		if due > 0 {
+			// Only the latest of the due configurations matters, the others were superseded.
+			if let Some((_, config)) = scheduled.drain(..due).last() {
+				<Self as Store>::ActiveConfig::set(config);
+			}
+			<Self as Store>::ScheduledConfigs::set(scheduled);
+		}
+	}
+
+	/// Returns the configurations which are going to become active, each paired with the index of the
+	/// session at which it takes effect, in the order in which they are applied.
+	pub fn pending_configs() -> Vec<(SessionIndex, HostConfiguration<T::BlockNumber>)> {
+		let next_session = T::CurrentSessionIndex::get() + 1;
+		<Self as Store>::PendingConfig::get()
+			.map(|config| (next_session, config))
+			.into_iter()
+			.chain(<Self as Store>::ScheduledConfigs::get())
+			.collect()
+	}
+
+	/// Translates the configurations stored before the PVF pre-checking and execution parameters
+	/// were added, giving those parameters their default values.
+	///
+	/// Scheduled configurations were only introduced along with these parameters, so only the active
+	/// and the pending configurations need translating.
+	fn migrate_to_v1() -> Weight {
+		if <Self as Store>::StorageVersion::get() != Releases::V0 {
+			return T::DbWeight::get().reads(1);
+		}
+
+		let _ = <Self as Store>::ActiveConfig::translate(
+			|old: Option<migration::HostConfigurationV0<T::BlockNumber>>| old.map(Into::into),
+		);
+		let _ = <Self as Store>::PendingConfig::translate(
+			|old: Option<migration::HostConfigurationV0<T::BlockNumber>>| old.map(Into::into),
+		);
+		<Self as Store>::StorageVersion::put(Releases::V1);
+
+		T::DbWeight::get().reads_writes(3, 3)
 	}
 
 	// NOTE: Explicitly tell rustc not to inline this because otherwise heuristics note the incoming
@@ -659,22 +901,119 @@ impl<T: Config> Module<T> {
 	#[inline(never)]
 	fn update_config_member(
 		updater: impl FnOnce(&mut HostConfiguration<T::BlockNumber>) -> bool,
-	) {
+	) -> DispatchResult {
 		let pending = <Self as Store>::PendingConfig::get();
 		let mut prev = pending.unwrap_or_else(Self::config);
 
 		if updater(&mut prev) {
+			ensure!(prev.check_consistency().is_ok(), Error::<T>::InconsistentConfiguration);
 			<Self as Store>::PendingConfig::set(Some(prev));
 		}
+
+		Ok(())
+	}
+}
+
+/// The storage layouts of this module before the latest one.
+mod migration {
+	use super::*;
+
+	/// A `HostConfiguration` as stored with [`Releases::V0`].
+	#[derive(Default, Encode, Decode)]
+	pub struct HostConfigurationV0<BlockNumber> {
+		pub max_code_size: u32,
+		pub max_head_data_size: u32,
+		pub max_upward_queue_count: u32,
+		pub max_upward_queue_size: u32,
+		pub max_upward_message_size: u32,
+		pub max_upward_message_num_per_candidate: u32,
+		pub hrmp_max_message_num_per_candidate: u32,
+		pub validation_upgrade_frequency: BlockNumber,
+		pub validation_upgrade_delay: BlockNumber,
+		pub max_pov_size: u32,
+		pub max_downward_message_size: u32,
+		pub preferred_dispatchable_upward_messages_step_weight: Weight,
+		pub hrmp_max_parachain_outbound_channels: u32,
+		pub hrmp_max_parathread_outbound_channels: u32,
+		pub hrmp_open_request_ttl: u32,
+		pub hrmp_sender_deposit: Balance,
+		pub hrmp_recipient_deposit: Balance,
+		pub hrmp_channel_max_capacity: u32,
+		pub hrmp_channel_max_total_size: u32,
+		pub hrmp_max_parachain_inbound_channels: u32,
+		pub hrmp_max_parathread_inbound_channels: u32,
+		pub hrmp_channel_max_message_size: u32,
+		pub acceptance_period: BlockNumber,
+		pub parathread_cores: u32,
+		pub parathread_retries: u32,
+		pub group_rotation_frequency: BlockNumber,
+		pub chain_availability_period: BlockNumber,
+		pub thread_availability_period: BlockNumber,
+		pub scheduling_lookahead: u32,
+		pub max_validators_per_core: Option<u32>,
+		pub dispute_period: SessionIndex,
+		pub no_show_slots: u32,
+		pub n_delay_tranches: u32,
+		pub zeroth_delay_tranche_width: u32,
+		pub needed_approvals: u32,
+		pub relay_vrf_modulo_samples: u32,
+	}
+
+	impl<BlockNumber: Default + From<u32>> From<HostConfigurationV0<BlockNumber>>
+		for HostConfiguration<BlockNumber>
+	{
+		fn from(old: HostConfigurationV0<BlockNumber>) -> Self {
+			HostConfiguration {
+				max_code_size: old.max_code_size,
+				max_head_data_size: old.max_head_data_size,
+				max_upward_queue_count: old.max_upward_queue_count,
+				max_upward_queue_size: old.max_upward_queue_size,
+				max_upward_message_size: old.max_upward_message_size,
+				max_upward_message_num_per_candidate: old.max_upward_message_num_per_candidate,
+				hrmp_max_message_num_per_candidate: old.hrmp_max_message_num_per_candidate,
+				validation_upgrade_frequency: old.validation_upgrade_frequency,
+				validation_upgrade_delay: old.validation_upgrade_delay,
+				max_pov_size: old.max_pov_size,
+				max_downward_message_size: old.max_downward_message_size,
+				preferred_dispatchable_upward_messages_step_weight:
+					old.preferred_dispatchable_upward_messages_step_weight,
+				hrmp_max_parachain_outbound_channels: old.hrmp_max_parachain_outbound_channels,
+				hrmp_max_parathread_outbound_channels: old.hrmp_max_parathread_outbound_channels,
+				hrmp_open_request_ttl: old.hrmp_open_request_ttl,
+				hrmp_sender_deposit: old.hrmp_sender_deposit,
+				hrmp_recipient_deposit: old.hrmp_recipient_deposit,
+				hrmp_channel_max_capacity: old.hrmp_channel_max_capacity,
+				hrmp_channel_max_total_size: old.hrmp_channel_max_total_size,
+				hrmp_max_parachain_inbound_channels: old.hrmp_max_parachain_inbound_channels,
+				hrmp_max_parathread_inbound_channels: old.hrmp_max_parathread_inbound_channels,
+				hrmp_channel_max_message_size: old.hrmp_channel_max_message_size,
+				acceptance_period: old.acceptance_period,
+				parathread_cores: old.parathread_cores,
+				parathread_retries: old.parathread_retries,
+				group_rotation_frequency: old.group_rotation_frequency,
+				chain_availability_period: old.chain_availability_period,
+				thread_availability_period: old.thread_availability_period,
+				scheduling_lookahead: old.scheduling_lookahead,
+				max_validators_per_core: old.max_validators_per_core,
+				dispute_period: old.dispute_period,
+				no_show_slots: old.no_show_slots,
+				n_delay_tranches: old.n_delay_tranches,
+				zeroth_delay_tranche_width: old.zeroth_delay_tranche_width,
+				needed_approvals: old.needed_approvals,
+				relay_vrf_modulo_samples: old.relay_vrf_modulo_samples,
+				..Default::default()
+			}
+		}
 	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use crate::mock::{new_test_ext, Initializer, Configuration, Origin};
+	use crate::mock::{new_test_ext, Initializer, Inclusion, Configuration, Origin};
+	use crate::initializer::SessionChangeNotification;
 
-	use fabric_support::traits::{OnFinalize, OnInitialize};
+	use fabric_support::traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade};
 
 	#[test]
 	fn config_changes_on_session_boundary() {
@@ -694,13 +1033,78 @@ mod tests {
 
 			Initializer::on_finalize(1);
 
-			Configuration::initializer_on_new_session(&[], &[]);
+			Configuration::initializer_on_new_session(&[], &[], 1);
 
 			assert_eq!(Configuration::config(), config);
 			assert!(<Configuration as Store>::PendingConfig::get().is_none());
 		})
 	}
 
+	#[test]
+	fn pvf_execution_limits_follow_config() {
+		let config = HostConfiguration::<u32> {
+			pvf_execution_timeout_ms: 1_500,
+			pvf_max_memory_pages: 512,
+			pvf_max_stack_height: MAX_PVF_STACK_HEIGHT,
+			..Default::default()
+		};
+
+		assert_eq!(config.check_consistency(), Ok(()));
+		assert_eq!(config.pvf_execution_limits(), PvfExecutionLimits {
+			timeout_ms: 1_500,
+			max_memory_pages: 512,
+			max_stack_height: MAX_PVF_STACK_HEIGHT,
+		});
+		assert_eq!(
+			HostConfiguration::<u32>::default().pvf_execution_limits(),
+			PvfExecutionLimits::default(),
+		);
+	}
+
+	#[test]
+	fn configurations_are_migrated() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let active = migration::HostConfigurationV0::<u32> {
+				max_code_size: 42,
+				group_rotation_frequency: 10,
+				..Default::default()
+			};
+			let pending = migration::HostConfigurationV0::<u32> {
+				max_code_size: 43,
+				group_rotation_frequency: 10,
+				..Default::default()
+			};
+			<Configuration as Store>::StorageVersion::put(Releases::V0);
+			fabric_support::storage::unhashed::put(
+				&<Configuration as Store>::ActiveConfig::hashed_key(),
+				&active,
+			);
+			fabric_support::storage::unhashed::put(
+				&<Configuration as Store>::PendingConfig::hashed_key(),
+				&pending,
+			);
+
+			Configuration::on_runtime_upgrade();
+
+			let active = Configuration::config();
+			assert_eq!(active.max_code_size, 42);
+			assert_eq!(active.group_rotation_frequency, 10);
+			assert!(!active.pvf_checking_enabled);
+			assert_eq!(active.pvf_voting_ttl, 2);
+			assert_eq!(active.pvf_execution_limits(), PvfExecutionLimits::default());
+
+			let pending = <Configuration as Store>::PendingConfig::get().unwrap();
+			assert_eq!(pending.max_code_size, 43);
+			assert_eq!(pending.pvf_execution_limits(), PvfExecutionLimits::default());
+			assert_eq!(<Configuration as Store>::StorageVersion::get(), Releases::V1);
+
+			// Migrating again leaves the configurations alone.
+			Configuration::set_pvf_voting_ttl(Origin::root(), 5).unwrap();
+			Configuration::on_runtime_upgrade();
+			assert_eq!(<Configuration as Store>::PendingConfig::get().unwrap().pvf_voting_ttl, 5);
+		});
+	}
+
 	#[test]
 	fn setting_pending_config_members() {
 		new_test_ext(Default::default()).execute_with(|| {
@@ -724,6 +1128,11 @@ mod tests {
 				zeroth_delay_tranche_width: 242,
 				needed_approvals: 242,
 				relay_vrf_modulo_samples: 243,
+				pvf_checking_enabled: true,
+				pvf_voting_ttl: 3,
+				pvf_execution_timeout_ms: 2_000,
+				pvf_max_memory_pages: 2048,
+				pvf_max_stack_height: 32 * 1024,
 				max_upward_queue_count: 1337,
 				max_upward_queue_size: 228,
 				max_downward_message_size: 2048,
@@ -802,6 +1211,21 @@ mod tests {
 			Configuration::set_relay_vrf_modulo_samples(
 				Origin::root(), new_config.relay_vrf_modulo_samples,
 			).unwrap();
+			Configuration::set_pvf_checking_enabled(
+				Origin::root(), new_config.pvf_checking_enabled,
+			).unwrap();
+			Configuration::set_pvf_voting_ttl(
+				Origin::root(), new_config.pvf_voting_ttl,
+			).unwrap();
+			Configuration::set_pvf_execution_timeout_ms(
+				Origin::root(), new_config.pvf_execution_timeout_ms,
+			).unwrap();
+			Configuration::set_pvf_max_memory_pages(
+				Origin::root(), new_config.pvf_max_memory_pages,
+			).unwrap();
+			Configuration::set_pvf_max_stack_height(
+				Origin::root(), new_config.pvf_max_stack_height,
+			).unwrap();
 			Configuration::set_max_upward_queue_count(
 				Origin::root(), new_config.max_upward_queue_count,
 			).unwrap();
@@ -869,6 +1293,126 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn setting_inconsistent_config_members_fails() {
+		new_test_ext(Default::default()).execute_with(|| {
+			assert!(Configuration::set_max_pov_size(Origin::root(), MAX_POV_SIZE + 1).is_err());
+			assert!(Configuration::set_max_code_size(Origin::root(), MAX_CODE_SIZE + 1).is_err());
+			assert!(Configuration::set_pvf_execution_timeout_ms(Origin::root(), 0).is_err());
+			assert!(
+				Configuration::set_pvf_max_memory_pages(Origin::root(), MAX_PVF_MEMORY_PAGES + 1).is_err()
+			);
+			assert!(
+				Configuration::set_pvf_max_stack_height(Origin::root(), MAX_PVF_STACK_HEIGHT + 1).is_err()
+			);
+			assert!(<Configuration as Store>::PendingConfig::get().is_none());
+
+			Configuration::set_max_pov_size(Origin::root(), MAX_POV_SIZE).unwrap();
+			assert_eq!(
+				<Configuration as Store>::PendingConfig::get().map(|c| c.max_pov_size),
+				Some(MAX_POV_SIZE),
+			);
+		});
+	}
+
+	#[test]
+	fn set_config_replaces_pending_config() {
+		new_test_ext(Default::default()).execute_with(|| {
+			Configuration::set_max_code_size(Origin::root(), 100).unwrap();
+
+			let new_config = HostConfiguration {
+				max_pov_size: 1024,
+				needed_approvals: 5,
+				..Default::default()
+			};
+			Configuration::set_config(Origin::root(), new_config.clone(), None).unwrap();
+			assert_eq!(<Configuration as Store>::PendingConfig::get(), Some(new_config.clone()));
+			assert_eq!(Configuration::pending_configs(), vec![(1, new_config.clone())]);
+
+			Configuration::initializer_on_new_session(&[], &[], 1);
+			assert_eq!(Configuration::config(), new_config);
+			assert!(Configuration::pending_configs().is_empty());
+		});
+	}
+
+	#[test]
+	fn set_config_rejects_inconsistent_config() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let oversized_pov = HostConfiguration {
+				max_pov_size: MAX_POV_SIZE + 1,
+				..Default::default()
+			};
+			assert_eq!(
+				oversized_pov.check_consistency(),
+				Err(InconsistentError::MaxPovSizeExceedHardLimit),
+			);
+			assert!(Configuration::set_config(Origin::root(), oversized_pov, None).is_err());
+
+			let no_rotation = HostConfiguration {
+				group_rotation_frequency: 0,
+				..Default::default()
+			};
+			assert!(Configuration::set_config(Origin::root(), no_rotation, Some(5)).is_err());
+
+			assert!(Configuration::pending_configs().is_empty());
+		});
+	}
+
+	#[test]
+	fn scheduled_config_applies_at_its_session() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let old_config = Configuration::config();
+			let config_a = HostConfiguration { max_code_size: 10, ..Default::default() };
+			let config_b = HostConfiguration { max_code_size: 20, ..Default::default() };
+			let config_c = HostConfiguration { max_code_size: 30, ..Default::default() };
+
+			Configuration::set_config(Origin::root(), config_b.clone(), Some(3)).unwrap();
+			Configuration::set_config(Origin::root(), config_c.clone(), Some(5)).unwrap();
+			Configuration::set_config(Origin::root(), config_a.clone(), Some(2)).unwrap();
+			Configuration::set_config(Origin::root(), config_a.clone(), Some(3)).unwrap();
+			assert_eq!(
+				Configuration::pending_configs(),
+				vec![(2, config_a.clone()), (3, config_a.clone()), (5, config_c.clone())],
+			);
+
+			Configuration::initializer_on_new_session(&[], &[], 1);
+			assert_eq!(Configuration::config(), old_config);
+
+			Configuration::initializer_on_new_session(&[], &[], 2);
+			assert_eq!(Configuration::config(), config_a);
+
+			// A session may be skipped, in which case the latest due configuration is applied.
+			Configuration::set_config(Origin::root(), config_b.clone(), Some(4)).unwrap();
+			Configuration::initializer_on_new_session(&[], &[], 4);
+			assert_eq!(Configuration::config(), config_b);
+			assert_eq!(Configuration::pending_configs(), vec![(5, config_c.clone())]);
+
+			// The scheduled configuration takes precedence over the pending changes.
+			Configuration::set_max_code_size(Origin::root(), 40).unwrap();
+			Configuration::initializer_on_new_session(&[], &[], 5);
+			assert_eq!(Configuration::config(), config_c);
+			assert!(Configuration::pending_configs().is_empty());
+		});
+	}
+
+	#[test]
+	fn cannot_schedule_config_for_past_session() {
+		new_test_ext(Default::default()).execute_with(|| {
+			// The session index is the one tracked by the inclusion module.
+			Inclusion::initializer_on_new_session(&SessionChangeNotification {
+				session_index: 3,
+				..Default::default()
+			});
+			Configuration::initializer_on_new_session(&[], &[], 3);
+
+			let config = HostConfiguration::default();
+			for at in 0..=3 {
+				assert!(Configuration::set_config(Origin::root(), config.clone(), Some(at)).is_err());
+			}
+			Configuration::set_config(Origin::root(), config, Some(4)).unwrap();
+		});
+	}
+
 	#[test]
 	fn non_root_cannot_set_config() {
 		new_test_ext(Default::default()).execute_with(|| {
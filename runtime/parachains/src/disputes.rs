@@ -0,0 +1,800 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The disputes module is responsible for tracking the votes of validators on the validity of
+//! disputed candidates and for enacting the outcome of disputes.
+//!
+//! A dispute concludes once a supermajority of the validators of the session the candidate was
+//! backed in voted on either side. The validators on the losing side are reported to a slashing
+//! handler. If the candidate concluded invalid after having been included, the chain is frozen
+//! and a digest instructing the node to revert the block including the candidate is deposited.
+//!
+//! Disputes are tracked for `dispute_period` sessions after the session of the candidate.
+
+use tetcore_std::{prelude::*, collections::btree_set::BTreeSet};
+use primitives::v1::{
+	CandidateHash, SessionIndex, ValidatorIndex, ValidatorSignature, ConsensusLog,
+	DisputeStatementSet, MultiDisputeStatementSet, ExplicitDisputeStatement,
+};
+use tp_runtime::{
+	traits::{One, Saturating, AppVerify, SaturatedConversion},
+	transaction_validity::{
+		TransactionValidity, ValidTransaction, InvalidTransaction, TransactionSource,
+		TransactionLongevity,
+	},
+};
+use fabric_support::{
+	decl_storage, decl_module, decl_error, decl_event, ensure,
+	dispatch::DispatchResult,
+	traits::Get,
+	weights::{DispatchClass, Weight},
+};
+use fabric_system::{ensure_none, ensure_root};
+use tetsy_scale_codec::{Encode, Decode};
+use bitvec::{bitvec, order::Lsb0 as BitOrderLsb0, vec::BitVec};
+use tet_core::RuntimeDebug;
+
+use crate::{configuration, session_info, initializer::SessionChangeNotification};
+
+/// A hook for punishing the validators on the losing side of a dispute.
+pub trait SlashingHandler {
+	/// Punish the validators with the given indices in the validator set of the given session
+	/// for voting for a candidate which concluded invalid. This is expected to be a major
+	/// punishment.
+	fn punish_for_invalid(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		losers: impl IntoIterator<Item = ValidatorIndex>,
+	);
+
+	/// Punish the validators with the given indices in the validator set of the given session
+	/// for voting against a candidate which concluded valid. This is expected to be a minor
+	/// punishment.
+	fn punish_against_valid(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		losers: impl IntoIterator<Item = ValidatorIndex>,
+	);
+}
+
+impl SlashingHandler for () {
+	fn punish_for_invalid(_: SessionIndex, _: CandidateHash, _: impl IntoIterator<Item = ValidatorIndex>) { }
+
+	fn punish_against_valid(_: SessionIndex, _: CandidateHash, _: impl IntoIterator<Item = ValidatorIndex>) { }
+}
+
+/// The interface the disputes module exposes to the modules including candidates.
+pub trait DisputesHandler<BlockNumber> {
+	/// Note that the given candidate was included in the given block.
+	fn note_included(session: SessionIndex, candidate_hash: CandidateHash, included_in: BlockNumber);
+
+	/// Whether the given candidate concluded invalid in a dispute.
+	fn concluded_invalid(session: SessionIndex, candidate_hash: CandidateHash) -> bool;
+
+	/// Whether the chain is frozen due to a dispute concluding against an included candidate.
+	/// No candidates should be backed or included while the chain is frozen.
+	fn is_frozen() -> bool;
+}
+
+impl<BlockNumber> DisputesHandler<BlockNumber> for () {
+	fn note_included(_: SessionIndex, _: CandidateHash, _: BlockNumber) { }
+
+	fn concluded_invalid(_: SessionIndex, _: CandidateHash) -> bool {
+		false
+	}
+
+	fn is_frozen() -> bool {
+		false
+	}
+}
+
+pub trait Config:
+	fabric_system::Config
+	+ configuration::Config
+	+ session_info::Config
+{
+	/// The outer event type.
+	type Event: From<Event<Self>> + Into<<Self as fabric_system::Config>::Event>;
+
+	/// The handler of the validators on the losing side of disputes.
+	type SlashingHandler: SlashingHandler;
+}
+
+/// The state of a dispute on a candidate.
+#[derive(Encode, Decode, Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct DisputeState<N> {
+	/// The validators which voted the candidate valid, by index in the validator set of the
+	/// session of the dispute.
+	pub validators_for: BitVec<BitOrderLsb0, u8>,
+	/// The validators which voted the candidate invalid, by index in the validator set of the
+	/// session of the dispute.
+	pub validators_against: BitVec<BitOrderLsb0, u8>,
+	/// The relay-chain block number at which the dispute was initiated on-chain.
+	pub start: N,
+	/// The relay-chain block number at which the dispute concluded, if it has.
+	pub concluded_at: Option<N>,
+	/// The outcome of the dispute, set when it concludes.
+	pub result: Option<DisputeResult>,
+}
+
+/// The outcome of a concluded dispute.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug)]
+pub enum DisputeResult {
+	/// The candidate was found valid.
+	Valid,
+	/// The candidate was found invalid.
+	Invalid,
+}
+
+decl_storage! {
+	trait Store for Module<T: Config> as ParasDisputes {
+		/// The current session index.
+		CurrentSessionIndex get(fn session_index): SessionIndex;
+		/// The last session whose disputes were pruned, if any.
+		LastPrunedSession: Option<SessionIndex>;
+		/// All ongoing or concluded disputes of the last `dispute_period` sessions.
+		Disputes get(fn disputes):
+			double_map hasher(twox_64_concat) SessionIndex, hasher(blake2_128_concat) CandidateHash
+			=> Option<DisputeState<T::BlockNumber>>;
+		/// All candidates included in the last `dispute_period` sessions, with the relay-chain
+		/// block number they were included in.
+		Included:
+			double_map hasher(twox_64_concat) SessionIndex, hasher(blake2_128_concat) CandidateHash
+			=> Option<T::BlockNumber>;
+		/// Whether the chain is frozen. Starts as `None`. When this is `Some`, the chain will not
+		/// accept any new parachain blocks for backing or inclusion. It holds the number of the
+		/// last known-valid block, the descendants of which should be reverted.
+		Frozen get(fn last_valid_block): Option<T::BlockNumber>;
+	}
+}
+
+decl_event! {
+	pub enum Event<T> where <T as fabric_system::Config>::BlockNumber {
+		/// A dispute on a candidate has been initiated. [candidate_hash, session]
+		DisputeInitiated(CandidateHash, SessionIndex),
+		/// A dispute on a candidate has concluded. [candidate_hash, session, result]
+		DisputeConcluded(CandidateHash, SessionIndex, DisputeResult),
+		/// A dispute concluded against a candidate included in the given block, so the chain
+		/// is to be reverted to the parent of that block. [block_number]
+		Revert(BlockNumber),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// The dispute statement set is for a session older than the dispute period.
+		AncientDisputeStatement,
+		/// The dispute statement set is for a future session.
+		FutureDisputeStatement,
+		/// The validator index of a dispute statement is out of bounds.
+		ValidatorIndexOutOfBounds,
+		/// The signature of a dispute statement is invalid.
+		InvalidSignature,
+		/// The validator has already cast the same vote on the candidate.
+		DuplicateStatement,
+		/// The validator has already voted on the other side of the dispute.
+		DoubleVote,
+		/// A new dispute must contain votes on both sides.
+		SingleSidedDispute,
+		/// The dispute statement set contains no statements.
+		EmptyStatementSet,
+		/// More than one dispute statement set refers to the same candidate.
+		DuplicateStatementSet,
+	}
+}
+
+decl_module! {
+	/// The disputes module.
+	pub struct Module<T: Config> for enum Call where origin: <T as fabric_system::Config>::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Submit signed dispute statements on any number of candidates.
+		///
+		/// The statements are only applied if every set checks out. The weight accounts for
+		/// verifying the signature of every statement.
+		#[weight = (
+			T::DbWeight::get().reads_writes(3, 2)
+				.saturating_mul(statement_sets.len() as Weight)
+				.saturating_add(T::DbWeight::get().reads_writes(2, 0))
+				.saturating_add(
+					SIGNATURE_VERIFICATION_WEIGHT.saturating_mul(
+						statement_sets.iter().map(|set| set.statements.len() as Weight).sum()
+					)
+				),
+			DispatchClass::Operational,
+		)]
+		pub fn submit_dispute_statements(origin, statement_sets: MultiDisputeStatementSet) -> DispatchResult {
+			ensure_none(origin)?;
+
+			let checked = Self::check_statement_sets(&statement_sets)?;
+			let now = <fabric_system::Module<T>>::block_number();
+			for (set, state) in statement_sets.iter().zip(checked) {
+				Self::apply_statement_set(set, state, now);
+			}
+
+			Ok(())
+		}
+
+		/// Unfreeze the chain after a dispute concluded against an included candidate.
+		#[weight = (1_000, DispatchClass::Operational)]
+		pub fn force_unfreeze(origin) -> DispatchResult {
+			ensure_root(origin)?;
+			<Self as Store>::Frozen::kill();
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Module<T> {
+	/// Called by the initializer to initialize the disputes module.
+	pub(crate) fn initializer_initialize(_now: T::BlockNumber) -> Weight {
+		0
+	}
+
+	/// Called by the initializer to finalize the disputes module.
+	pub(crate) fn initializer_finalize() { }
+
+	/// Called by the initializer to note that a new session has started. Prunes the disputes
+	/// of the sessions which left the dispute period.
+	pub(crate) fn initializer_on_new_session(notification: &SessionChangeNotification<T::BlockNumber>) {
+		let config = <configuration::Module<T>>::config();
+
+		<Self as Store>::CurrentSessionIndex::set(notification.session_index);
+
+		if notification.session_index <= config.dispute_period + 1 {
+			return;
+		}
+
+		let pruning_target = notification.session_index - config.dispute_period - 1;
+		let to_prune = match <Self as Store>::LastPrunedSession::get() {
+			// avoid a potentially heavy loop when introduced on a live chain.
+			None => pruning_target..=pruning_target,
+			Some(last_pruned) if last_pruned < pruning_target => (last_pruned + 1)..=pruning_target,
+			Some(_) => return,
+		};
+
+		for session in to_prune {
+			<Self as Store>::Disputes::remove_prefix(session);
+			<Self as Store>::Included::remove_prefix(session);
+		}
+
+		<Self as Store>::LastPrunedSession::put(pruning_target);
+	}
+
+	/// Checks the given statement sets, returning the current state of the dispute each set
+	/// refers to, if there is one.
+	fn check_statement_sets(
+		statement_sets: &[DisputeStatementSet],
+	) -> Result<Vec<Option<DisputeState<T::BlockNumber>>>, Error<T>> {
+		let config = <configuration::Module<T>>::config();
+		let current_session = <Self as Store>::CurrentSessionIndex::get();
+
+		// every set is checked against the stored state of its dispute, so votes on the same
+		// candidate must come in a single set.
+		let mut disputes = BTreeSet::new();
+
+		statement_sets.iter().map(|set| {
+			ensure!(
+				disputes.insert((set.session, set.candidate_hash)),
+				Error::<T>::DuplicateStatementSet,
+			);
+			ensure!(set.session <= current_session, Error::<T>::FutureDisputeStatement);
+			ensure!(
+				set.session + config.dispute_period >= current_session,
+				Error::<T>::AncientDisputeStatement,
+			);
+			ensure!(!set.statements.is_empty(), Error::<T>::EmptyStatementSet);
+
+			let validators = <session_info::Module<T>>::session_info(set.session)
+				.ok_or(Error::<T>::AncientDisputeStatement)?
+				.validators;
+			let n_validators = validators.len();
+
+			let state = <Self as Store>::Disputes::get(set.session, set.candidate_hash);
+			let (mut votes_for, mut votes_against) = match state {
+				Some(ref state) => (state.validators_for.clone(), state.validators_against.clone()),
+				None => (
+					bitvec![BitOrderLsb0, u8; 0; n_validators],
+					bitvec![BitOrderLsb0, u8; 0; n_validators],
+				),
+			};
+
+			for (valid, validator_index, signature) in &set.statements {
+				let validator_public = validators.get(*validator_index as usize)
+					.ok_or(Error::<T>::ValidatorIndexOutOfBounds)?;
+
+				let payload = ExplicitDisputeStatement {
+					valid: *valid,
+					candidate_hash: set.candidate_hash,
+					session: set.session,
+				}.signing_payload();
+				ensure!(
+					signature.verify(&payload[..], validator_public),
+					Error::<T>::InvalidSignature,
+				);
+
+				let (votes, other_votes) = if *valid {
+					(&mut votes_for, &votes_against)
+				} else {
+					(&mut votes_against, &votes_for)
+				};
+				ensure!(!votes[*validator_index as usize], Error::<T>::DuplicateStatement);
+				ensure!(!other_votes[*validator_index as usize], Error::<T>::DoubleVote);
+				votes.set(*validator_index as usize, true);
+			}
+
+			// without votes on both sides, there is nothing to dispute.
+			ensure!(
+				state.is_some() || (votes_for.any() && votes_against.any()),
+				Error::<T>::SingleSidedDispute,
+			);
+
+			Ok(state)
+		}).collect()
+	}
+
+	/// Applies a statement set which has already been checked to the state of its dispute.
+	fn apply_statement_set(
+		set: &DisputeStatementSet,
+		state: Option<DisputeState<T::BlockNumber>>,
+		now: T::BlockNumber,
+	) {
+		let n_validators = <session_info::Module<T>>::session_info(set.session)
+			.map_or(0, |info| info.validators.len());
+
+		let mut state = match state {
+			Some(state) => state,
+			None => {
+				Self::deposit_event(RawEvent::DisputeInitiated(set.candidate_hash, set.session));
+				DisputeState {
+					validators_for: bitvec![BitOrderLsb0, u8; 0; n_validators],
+					validators_against: bitvec![BitOrderLsb0, u8; 0; n_validators],
+					start: now,
+					concluded_at: None,
+					result: None,
+				}
+			}
+		};
+
+		// votes arriving after the conclusion on the losing side are punished right away.
+		let concluded_result = state.result;
+		let mut late_losers = Vec::new();
+
+		for (valid, validator_index, _) in &set.statements {
+			if *valid {
+				state.validators_for.set(*validator_index as usize, true);
+			} else {
+				state.validators_against.set(*validator_index as usize, true);
+			}
+
+			match concluded_result {
+				Some(DisputeResult::Valid) if !*valid => late_losers.push(*validator_index),
+				Some(DisputeResult::Invalid) if *valid => late_losers.push(*validator_index),
+				_ => {}
+			}
+		}
+
+		match concluded_result {
+			Some(DisputeResult::Valid) if !late_losers.is_empty() =>
+				T::SlashingHandler::punish_against_valid(set.session, set.candidate_hash, late_losers),
+			Some(DisputeResult::Invalid) if !late_losers.is_empty() =>
+				T::SlashingHandler::punish_for_invalid(set.session, set.candidate_hash, late_losers),
+			Some(_) => {}
+			None => {
+				let threshold = supermajority_threshold(n_validators);
+				let result = if state.validators_for.count_ones() >= threshold {
+					Some(DisputeResult::Valid)
+				} else if state.validators_against.count_ones() >= threshold {
+					Some(DisputeResult::Invalid)
+				} else {
+					None
+				};
+
+				if let Some(result) = result {
+					state.concluded_at = Some(now);
+					state.result = Some(result);
+					Self::enact_result(set.session, set.candidate_hash, &state, result);
+				}
+			}
+		}
+
+		<Self as Store>::Disputes::insert(set.session, set.candidate_hash, state);
+	}
+
+	/// Punishes the losing side of a dispute which just concluded and, if the candidate was found
+	/// invalid after having been included, freezes the chain and requests a revert.
+	fn enact_result(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		state: &DisputeState<T::BlockNumber>,
+		result: DisputeResult,
+	) {
+		let voters = |votes: &BitVec<BitOrderLsb0, u8>| votes.iter()
+			.enumerate()
+			.filter(|(_, voted)| **voted)
+			.map(|(i, _)| i as ValidatorIndex)
+			.collect::<Vec<_>>();
+
+		match result {
+			DisputeResult::Valid => T::SlashingHandler::punish_against_valid(
+				session,
+				candidate_hash,
+				voters(&state.validators_against),
+			),
+			DisputeResult::Invalid => {
+				T::SlashingHandler::punish_for_invalid(
+					session,
+					candidate_hash,
+					voters(&state.validators_for),
+				);
+
+				if let Some(included_in) = <Self as Store>::Included::get(session, candidate_hash) {
+					Self::revert_and_freeze(included_in);
+				}
+			}
+		}
+
+		Self::deposit_event(RawEvent::DisputeConcluded(candidate_hash, session, result));
+	}
+
+	/// Freezes the chain at the parent of the given block and deposits a digest requesting the
+	/// revert of the given block and its descendants.
+	fn revert_and_freeze(revert_from: T::BlockNumber) {
+		let last_valid_block = revert_from.saturating_sub(One::one());
+
+		// if the chain is already frozen at an earlier block, that revert supersedes this one.
+		if <Self as Store>::Frozen::get().map_or(false, |frozen| frozen <= last_valid_block) {
+			return;
+		}
+
+		<Self as Store>::Frozen::put(last_valid_block);
+
+		let revert_from_number = revert_from.saturated_into::<primitives::v1::BlockNumber>();
+		<fabric_system::Module<T>>::deposit_log(ConsensusLog::Revert(revert_from_number).into());
+
+		Self::deposit_event(RawEvent::Revert(revert_from));
+	}
+}
+
+impl<T: Config> DisputesHandler<T::BlockNumber> for Module<T> {
+	fn note_included(session: SessionIndex, candidate_hash: CandidateHash, included_in: T::BlockNumber) {
+		<Self as Store>::Included::insert(session, candidate_hash, included_in);
+
+		// a candidate may be disputed before it gets included.
+		let concluded_invalid = Self::concluded_invalid(session, candidate_hash);
+		if concluded_invalid {
+			Self::revert_and_freeze(included_in);
+		}
+	}
+
+	fn concluded_invalid(session: SessionIndex, candidate_hash: CandidateHash) -> bool {
+		<Self as Store>::Disputes::get(session, candidate_hash)
+			.map_or(false, |state| state.result == Some(DisputeResult::Invalid))
+	}
+
+	fn is_frozen() -> bool {
+		<Self as Store>::Frozen::get().is_some()
+	}
+}
+
+impl<T: Config> tp_runtime::traits::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		const PRIORITY: u64 = 100;
+
+		let statement_sets = match call {
+			Call::submit_dispute_statements(statement_sets) => statement_sets,
+			_ => return Err(InvalidTransaction::Call.into()),
+		};
+
+		match Self::check_statement_sets(statement_sets) {
+			Ok(_) => {}
+			Err(Error::<T>::FutureDisputeStatement) => return Err(InvalidTransaction::Future.into()),
+			Err(Error::<T>::AncientDisputeStatement)
+				| Err(Error::<T>::DuplicateStatement) => return Err(InvalidTransaction::Stale.into()),
+			Err(Error::<T>::DoubleVote) => return Err(InvalidTransaction::Call.into()),
+			Err(_) => return Err(InvalidTransaction::BadProof.into()),
+		}
+
+		let provides = statement_sets.iter()
+			.flat_map(|set| set.statements.iter().map(move |(valid, validator_index, _)| {
+				("disputes", set.session, set.candidate_hash, valid, validator_index).encode()
+			}))
+			.collect();
+
+		Ok(ValidTransaction {
+			priority: PRIORITY,
+			requires: vec![],
+			provides,
+			longevity: TransactionLongevity::max_value(),
+			propagate: true,
+		})
+	}
+}
+
+/// The weight of verifying the signature of a single dispute statement.
+const SIGNATURE_VERIFICATION_WEIGHT: Weight = 50_000_000;
+
+// the number of votes which forms a supermajority of `n_validators`, i.e. more than two thirds.
+const fn supermajority_threshold(n_validators: usize) -> usize {
+	n_validators - n_validators.saturating_sub(1) / 3
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use primitives::v1::BlockNumber;
+	use fabric_support::{assert_ok, assert_noop, traits::{OnFinalize, OnInitialize}};
+	use keyring::Sr25519Keyring;
+
+	use crate::mock::{
+		new_test_ext, Configuration, Disputes, SessionInfo, System, Test, Origin,
+		GenesisConfig as MockGenesisConfig, slashed_for_invalid, slashed_against_valid,
+	};
+	use crate::configuration::HostConfiguration;
+
+	const VALIDATORS: [Sr25519Keyring; 4] = [
+		Sr25519Keyring::Alice,
+		Sr25519Keyring::Bob,
+		Sr25519Keyring::Charlie,
+		Sr25519Keyring::Dave,
+	];
+
+	fn genesis_config() -> MockGenesisConfig {
+		MockGenesisConfig {
+			configuration: configuration::GenesisConfig {
+				config: HostConfiguration {
+					dispute_period: 2,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+
+	// runs to the given block, with a session change at every block.
+	fn run_to_block(to: BlockNumber) {
+		while System::block_number() < to {
+			let b = System::block_number();
+
+			Disputes::initializer_finalize();
+			SessionInfo::initializer_finalize();
+
+			let notification = SessionChangeNotification {
+				validators: VALIDATORS.iter().map(|v| v.public().into()).collect(),
+				new_config: Configuration::config(),
+				session_index: b + 1,
+				..Default::default()
+			};
+			SessionInfo::initializer_on_new_session(&notification);
+			Disputes::initializer_on_new_session(&notification);
+
+			System::on_finalize(b);
+
+			System::on_initialize(b + 1);
+			System::set_block_number(b + 1);
+
+			SessionInfo::initializer_initialize(b + 1);
+			Disputes::initializer_initialize(b + 1);
+		}
+	}
+
+	fn statement(
+		candidate_hash: CandidateHash,
+		session: SessionIndex,
+		valid: bool,
+		validator_index: ValidatorIndex,
+	) -> (bool, ValidatorIndex, ValidatorSignature) {
+		let payload = ExplicitDisputeStatement { valid, candidate_hash, session }.signing_payload();
+		let signature = VALIDATORS[validator_index as usize].sign(&payload).into();
+		(valid, validator_index, signature)
+	}
+
+	fn statement_set(
+		candidate_hash: CandidateHash,
+		session: SessionIndex,
+		votes: &[(bool, ValidatorIndex)],
+	) -> DisputeStatementSet {
+		DisputeStatementSet {
+			candidate_hash,
+			session,
+			statements: votes.iter()
+				.map(|(valid, i)| statement(candidate_hash, session, *valid, *i))
+				.collect(),
+		}
+	}
+
+	#[test]
+	fn dispute_concluding_valid_punishes_validators_against() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			run_to_block(3);
+
+			let candidate_hash = CandidateHash([1; 32].into());
+			let session = 3;
+
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![statement_set(candidate_hash, session, &[(true, 0), (true, 1)])],
+				),
+				Error::<Test>::SingleSidedDispute,
+			);
+
+			assert_ok!(Disputes::submit_dispute_statements(
+				Origin::none(),
+				vec![statement_set(candidate_hash, session, &[(true, 0), (false, 1)])],
+			));
+			assert!(Disputes::disputes(session, candidate_hash).unwrap().concluded_at.is_none());
+
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![statement_set(candidate_hash, session, &[(true, 0)])],
+				),
+				Error::<Test>::DuplicateStatement,
+			);
+
+			assert_ok!(Disputes::submit_dispute_statements(
+				Origin::none(),
+				vec![statement_set(candidate_hash, session, &[(true, 2), (true, 3)])],
+			));
+
+			let state = Disputes::disputes(session, candidate_hash).unwrap();
+			assert_eq!(state.concluded_at, Some(3));
+			assert_eq!(state.result, Some(DisputeResult::Valid));
+			assert_eq!(slashed_against_valid(), vec![(session, candidate_hash, vec![1])]);
+			assert!(slashed_for_invalid().is_empty());
+			assert!(!<Disputes as DisputesHandler<BlockNumber>>::concluded_invalid(session, candidate_hash));
+		});
+	}
+
+	#[test]
+	fn validators_cannot_vote_on_both_sides() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			run_to_block(3);
+
+			let candidate_hash = CandidateHash([1; 32].into());
+			let session = 3;
+
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![statement_set(candidate_hash, session, &[(true, 0), (false, 0)])],
+				),
+				Error::<Test>::DoubleVote,
+			);
+
+			assert_ok!(Disputes::submit_dispute_statements(
+				Origin::none(),
+				vec![statement_set(candidate_hash, session, &[(true, 0), (false, 1)])],
+			));
+
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![statement_set(candidate_hash, session, &[(true, 1)])],
+				),
+				Error::<Test>::DoubleVote,
+			);
+		});
+	}
+
+	#[test]
+	fn statement_sets_on_the_same_candidate_are_rejected() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			run_to_block(3);
+
+			let candidate_hash = CandidateHash([1; 32].into());
+			let session = 3;
+
+			// each set checks out on its own, but the second one would overwrite the first.
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![
+						statement_set(candidate_hash, session, &[(true, 0), (false, 1)]),
+						statement_set(candidate_hash, session, &[(true, 2), (false, 3)]),
+					],
+				),
+				Error::<Test>::DuplicateStatementSet,
+			);
+
+			// the same candidate in another session is another dispute.
+			assert_ok!(Disputes::submit_dispute_statements(
+				Origin::none(),
+				vec![
+					statement_set(candidate_hash, session, &[(true, 0), (false, 1)]),
+					statement_set(candidate_hash, session - 1, &[(true, 0), (false, 1)]),
+				],
+			));
+		});
+	}
+
+	#[test]
+	fn dispute_concluding_invalid_reverts_included_candidate() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			run_to_block(3);
+
+			let candidate_hash = CandidateHash([1; 32].into());
+			let session = 2;
+
+			<Disputes as DisputesHandler<BlockNumber>>::note_included(session, candidate_hash, 2);
+
+			assert_ok!(Disputes::submit_dispute_statements(
+				Origin::none(),
+				vec![statement_set(
+					candidate_hash,
+					session,
+					&[(true, 0), (false, 1), (false, 2), (false, 3)],
+				)],
+			));
+
+			assert!(<Disputes as DisputesHandler<BlockNumber>>::concluded_invalid(session, candidate_hash));
+			assert_eq!(slashed_for_invalid(), vec![(session, candidate_hash, vec![0])]);
+			assert!(<Disputes as DisputesHandler<BlockNumber>>::is_frozen());
+			assert_eq!(Disputes::last_valid_block(), Some(1));
+
+			let digest = System::digest();
+			assert!(digest.logs().iter().any(|log| {
+				matches!(ConsensusLog::from_digest_item(log), Ok(Some(ConsensusLog::Revert(2))))
+			}));
+
+			assert_ok!(Disputes::force_unfreeze(Origin::root()));
+			assert!(!<Disputes as DisputesHandler<BlockNumber>>::is_frozen());
+		});
+	}
+
+	#[test]
+	fn disputes_are_pruned_after_dispute_period() {
+		new_test_ext(genesis_config()).execute_with(|| {
+			run_to_block(3);
+
+			let candidate_hash = CandidateHash([1; 32].into());
+
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![statement_set(candidate_hash, 4, &[(true, 0), (false, 1)])],
+				),
+				Error::<Test>::FutureDisputeStatement,
+			);
+
+			assert_ok!(Disputes::submit_dispute_statements(
+				Origin::none(),
+				vec![statement_set(candidate_hash, 1, &[(true, 0), (false, 1)])],
+			));
+
+			assert!(Disputes::disputes(1, candidate_hash).is_some());
+
+			// session 1 is within the dispute period of session 3, but not of session 4.
+			run_to_block(4);
+			assert!(Disputes::disputes(1, candidate_hash).is_none());
+
+			assert_noop!(
+				Disputes::submit_dispute_statements(
+					Origin::none(),
+					vec![statement_set(candidate_hash, 1, &[(true, 2)])],
+				),
+				Error::<Test>::AncientDisputeStatement,
+			);
+		});
+	}
+}
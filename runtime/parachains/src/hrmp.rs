@@ -26,7 +26,7 @@ use fabric_support::{
 };
 use primitives::v1::{
 	Balance, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage,
-	SessionIndex,
+	OutboundHrmpBandwidth, OutboundHrmpChannelBandwidth, SessionIndex,
 };
 use tp_runtime::traits::{UniqueSaturatedInto, AccountIdConversion, BlakeTwo256, Hash as HashT};
 use tetcore_std::{
@@ -946,15 +946,17 @@ impl<T: Config> Module<T> {
 		);
 		<Self as Store>::HrmpOpenChannelRequestsList::append(channel_id);
 
+		// the notifications are wrapped into `VersionedXcm` so that the recipient can tell which
+		// version of XCM they are encoded with.
 		let notification_bytes = {
-			use xcm::v0::Xcm;
+			use xcm::{VersionedXcm, v0::Xcm};
 			use tetsy_scale_codec::Encode as _;
 
-			Xcm::HrmpNewChannelOpenRequest {
+			VersionedXcm::from(Xcm::HrmpNewChannelOpenRequest {
 				sender: u32::from(origin),
 				max_capacity: proposed_max_capacity,
 				max_message_size: proposed_max_message_size,
-			}
+			})
 			.encode()
 		};
 		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
@@ -1013,11 +1015,11 @@ impl<T: Config> Module<T> {
 
 		let notification_bytes = {
 			use tetsy_scale_codec::Encode as _;
-			use xcm::v0::Xcm;
+			use xcm::{VersionedXcm, v0::Xcm};
 
-			Xcm::HrmpChannelAccepted {
+			VersionedXcm::from(Xcm::HrmpChannelAccepted {
 				recipient: u32::from(origin),
-			}
+			})
 			.encode()
 		};
 		if let Err(dmp::QueueDownwardMessageError::ExceedsMaxMessageSize) =
@@ -1056,13 +1058,13 @@ impl<T: Config> Module<T> {
 		let config = <configuration::Module<T>>::config();
 		let notification_bytes = {
 			use tetsy_scale_codec::Encode as _;
-			use xcm::v0::Xcm;
+			use xcm::{VersionedXcm, v0::Xcm};
 
-			Xcm::HrmpChannelClosing {
+			VersionedXcm::from(Xcm::HrmpChannelClosing {
 				initiator: u32::from(origin),
 				sender: u32::from(channel_id.sender),
 				recipient: u32::from(channel_id.recipient),
-			}
+			})
 			.encode()
 		};
 		let opposite_party = if origin == channel_id.sender {
@@ -1117,6 +1119,88 @@ impl<T: Config> Module<T> {
 
 		inbound_hrmp_channels_contents
 	}
+
+	/// Returns the remaining bandwidth of all outbound channels of the given sender, i.e. how
+	/// many more messages and bytes can be put into each of them by the next candidate of the
+	/// sender before `check_outbound_hrmp` rejects it. The channels are sorted ascending by the
+	/// recipient para id.
+	pub(crate) fn outbound_hrmp_channels_bandwidth(sender: ParaId) -> OutboundHrmpBandwidth {
+		let config = <configuration::Module<T>>::config();
+		let recipient_set = <Self as Store>::HrmpEgressChannelsIndex::get(&sender);
+
+		// The egress channels vector is sorted, thus the result is sorted as well.
+		let channels = recipient_set
+			.into_iter()
+			.filter_map(|recipient| {
+				let channel = <Self as Store>::HrmpChannels::get(&HrmpChannelId { sender, recipient })?;
+				Some(OutboundHrmpChannelBandwidth {
+					recipient,
+					messages_remaining: channel.max_capacity.saturating_sub(channel.msg_count),
+					bytes_remaining: channel.max_total_size.saturating_sub(channel.total_size),
+					max_message_size: channel.max_message_size,
+				})
+			})
+			.collect();
+
+		OutboundHrmpBandwidth {
+			max_messages_per_candidate: config.hrmp_max_message_num_per_candidate,
+			channels,
+		}
+	}
+}
+
+/// An implementation of [`ManageHrmpChannels`](xcm_executor::traits::ManageHrmpChannels) which lets
+/// parachains manage their HRMP channels through the `Hrmp*` XCM instructions.
+///
+/// The origin of the message must be a child parachain. The deposits are reserved from the sovereign
+/// account of that parachain, in the same way as for the dispatchable counterparts.
+pub struct XcmChannelManager<T>(tetcore_std::marker::PhantomData<T>);
+
+impl<T: Config> XcmChannelManager<T> {
+	fn para_origin(origin: &xcm::v0::MultiLocation) -> Result<ParaId, xcm::v0::Error> {
+		use xcm::v0::{Junction, MultiLocation};
+
+		match origin {
+			MultiLocation::X1(Junction::Parachain { id }) => Ok(ParaId::from(*id)),
+			_ => Err(xcm::v0::Error::BadOrigin),
+		}
+	}
+
+	fn map_err(e: impl fmt::Debug) -> xcm::v0::Error {
+		fabric_support::debug::debug!(
+			target: "xcm",
+			"HRMP channel request from XCM failed: {:?}",
+			e,
+		);
+		xcm::v0::Error::HrmpChannelRequestFailed
+	}
+}
+
+impl<T: Config> xcm_executor::traits::ManageHrmpChannels for XcmChannelManager<T> {
+	fn init_open_channel(
+		origin: &xcm::v0::MultiLocation,
+		recipient: u32,
+		max_message_size: u32,
+		max_capacity: u32,
+	) -> xcm::v0::Result {
+		let origin = Self::para_origin(origin)?;
+		<Module<T>>::init_open_channel(origin, recipient.into(), max_capacity, max_message_size)
+			.map_err(Self::map_err)
+	}
+
+	fn accept_open_channel(origin: &xcm::v0::MultiLocation, sender: u32) -> xcm::v0::Result {
+		let origin = Self::para_origin(origin)?;
+		<Module<T>>::accept_open_channel(origin, sender.into()).map_err(Self::map_err)
+	}
+
+	fn close_channel(origin: &xcm::v0::MultiLocation, sender: u32, recipient: u32) -> xcm::v0::Result {
+		let origin = Self::para_origin(origin)?;
+		let channel_id = HrmpChannelId {
+			sender: sender.into(),
+			recipient: recipient.into(),
+		};
+		<Module<T>>::close_channel(origin, channel_id).map_err(Self::map_err)
+	}
 }
 
 #[cfg(test)]
@@ -1498,6 +1582,94 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn channels_can_be_managed_through_xcm() {
+		use tetcore_std::convert::TryFrom;
+		use xcm::{VersionedXcm, v0::{Junction, MultiLocation, Xcm}};
+		use xcm_executor::traits::ManageHrmpChannels;
+
+		let para_a = 1.into();
+		let para_b = 3.into();
+		let location = |id: u32| MultiLocation::X1(Junction::Parachain { id });
+		let last_notification = |para: ParaId| {
+			let msg = <crate::dmp::Module<Test>>::dmq_contents(para).pop().unwrap().msg;
+			Xcm::try_from(VersionedXcm::decode(&mut &msg[..]).unwrap()).unwrap()
+		};
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+			run_to_block(5, Some(vec![5]));
+
+			XcmChannelManager::<Test>::init_open_channel(&location(1), 3, 8, 2).unwrap();
+			assert_eq!(
+				<Test as Config>::Currency::reserved_balance(&para_a.into_account()),
+				100,
+			);
+			assert_eq!(
+				last_notification(para_b),
+				Xcm::HrmpNewChannelOpenRequest { sender: 1, max_message_size: 8, max_capacity: 2 },
+			);
+
+			XcmChannelManager::<Test>::accept_open_channel(&location(3), 1).unwrap();
+			assert_eq!(
+				<Test as Config>::Currency::reserved_balance(&para_b.into_account()),
+				100,
+			);
+			assert_eq!(last_notification(para_a), Xcm::HrmpChannelAccepted { recipient: 3 });
+			assert_storage_consistency_exhaustive();
+
+			run_to_block(6, Some(vec![6]));
+			assert!(channel_exists(para_a, para_b));
+
+			XcmChannelManager::<Test>::close_channel(&location(3), 1, 3).unwrap();
+			assert_eq!(
+				last_notification(para_a),
+				Xcm::HrmpChannelClosing { initiator: 3, sender: 1, recipient: 3 },
+			);
+
+			run_to_block(8, Some(vec![8]));
+			assert!(!channel_exists(para_a, para_b));
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
+	#[test]
+	fn xcm_channel_management_rejects_non_para_origins() {
+		use xcm::v0::{Error as XcmError, Junction, MultiLocation};
+		use xcm_executor::traits::ManageHrmpChannels;
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(1.into());
+			register_parachain(3.into());
+			run_to_block(5, Some(vec![5]));
+
+			assert_eq!(
+				XcmChannelManager::<Test>::init_open_channel(&MultiLocation::Null, 3, 8, 2),
+				Err(XcmError::BadOrigin),
+			);
+			assert_eq!(
+				XcmChannelManager::<Test>::init_open_channel(
+					&MultiLocation::X1(Junction::Parent),
+					3,
+					8,
+					2,
+				),
+				Err(XcmError::BadOrigin),
+			);
+
+			// A para origin gets the failure of the underlying request reported.
+			assert_eq!(
+				XcmChannelManager::<Test>::accept_open_channel(
+					&MultiLocation::X1(Junction::Parachain { id: 3 }),
+					1,
+				),
+				Err(XcmError::HrmpChannelRequestFailed),
+			);
+			assert_storage_consistency_exhaustive();
+		});
+	}
+
 	#[test]
 	fn send_recv_messages() {
 		let para_a = 32.into();
@@ -1626,6 +1798,62 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn outbound_bandwidth_accounts_for_queued_messages() {
+		let para_a = 32.into();
+		let para_b = 64.into();
+		let para_c = 97.into();
+
+		new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+			register_parachain(para_a);
+			register_parachain(para_b);
+			register_parachain(para_c);
+
+			run_to_block(5, Some(vec![5]));
+			Hrmp::init_open_channel(para_a, para_c, 1, 8).unwrap();
+			Hrmp::accept_open_channel(para_c, para_a).unwrap();
+			Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+			Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+			run_to_block(6, Some(vec![6]));
+			let config = Configuration::config();
+			let full_bandwidth = |recipient, messages_remaining| OutboundHrmpChannelBandwidth {
+				recipient,
+				messages_remaining,
+				bytes_remaining: config.hrmp_channel_max_total_size,
+				max_message_size: 8,
+			};
+			assert_eq!(
+				Hrmp::outbound_hrmp_channels_bandwidth(para_a),
+				OutboundHrmpBandwidth {
+					max_messages_per_candidate: config.hrmp_max_message_num_per_candidate,
+					channels: vec![full_bandwidth(para_b, 2), full_bandwidth(para_c, 1)],
+				},
+			);
+			assert!(Hrmp::outbound_hrmp_channels_bandwidth(para_b).channels.is_empty());
+
+			let msgs = vec![OutboundHrmpMessage {
+				recipient: para_b,
+				data: b"knock".to_vec(),
+			}];
+			assert!(Hrmp::check_outbound_hrmp(&config, para_a, &msgs).is_ok());
+			let _ = Hrmp::queue_outbound_hrmp(para_a, msgs);
+
+			assert_eq!(
+				Hrmp::outbound_hrmp_channels_bandwidth(para_a).channels,
+				vec![
+					OutboundHrmpChannelBandwidth {
+						recipient: para_b,
+						messages_remaining: 1,
+						bytes_remaining: config.hrmp_channel_max_total_size - 5,
+						max_message_size: 8,
+					},
+					full_bandwidth(para_c, 1),
+				],
+			);
+		});
+	}
+
 	#[test]
 	fn verify_externally_accessible() {
 		use primitives::v1::{well_known_keys, AbridgedHrmpChannel};
@@ -36,7 +36,7 @@ use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
 use tp_staking::SessionIndex;
 use tp_runtime::{DispatchError, traits::{One, Saturating}};
 
-use crate::{configuration, paras, dmp, ump, hrmp, scheduler::CoreAssignment};
+use crate::{configuration, paras, dmp, ump, hrmp, disputes::DisputesHandler, scheduler::CoreAssignment};
 
 /// A bitfield signed by a validator indicating that it is keeping its piece of the erasure-coding
 /// for any backed candidates referred to by a `1` bit available.
@@ -119,6 +119,16 @@ pub trait Config:
 {
 	type Event: From<Event<Self>> + Into<<Self as fabric_system::Config>::Event>;
 	type RewardValidators: RewardValidators;
+	type DisputesHandler: DisputesHandler<Self::BlockNumber>;
+}
+
+/// The index of the current session, as tracked by the inclusion module.
+pub struct CurrentSession<T>(tetcore_std::marker::PhantomData<T>);
+
+impl<T: Config> Get<SessionIndex> for CurrentSession<T> {
+	fn get() -> SessionIndex {
+		<Module<T>>::session_index()
+	}
 }
 
 decl_storage! {
@@ -241,6 +251,8 @@ impl<T: Config> Module<T> {
 
 	/// Process a set of incoming bitfields. Return a vec of cores freed by candidates
 	/// becoming available.
+	///
+	/// No candidate is included while the chain is frozen due to a dispute.
 	pub(crate) fn process_bitfields(
 		signed_bitfields: SignedAvailabilityBitfields,
 		core_lookup: impl Fn(CoreIndex) -> Option<ParaId>,
@@ -340,12 +352,16 @@ impl<T: Config> Module<T> {
 
 		let threshold = availability_threshold(validators.len());
 
+		// while the chain is frozen, candidates keep collecting availability votes but none of
+		// them is included.
+		let frozen = T::DisputesHandler::is_frozen();
+
 		let mut freed_cores = Vec::with_capacity(n_bits);
 		for (para_id, pending_availability) in assigned_paras_record.into_iter()
 			.filter_map(|x| x)
 			.filter_map(|(id, p)| p.map(|p| (id, p)))
 		{
-			if pending_availability.availability_votes.count_ones() >= threshold {
+			if !frozen && pending_availability.availability_votes.count_ones() >= threshold {
 				<PendingAvailability<T>>::remove(&para_id);
 				let commitments = match PendingAvailabilityCommitments::take(&para_id) {
 					Some(commitments) => commitments,
@@ -655,6 +671,9 @@ impl<T: Config> Module<T> {
 		let plain = receipt.to_plain();
 		let commitments = receipt.commitments;
 		let config = <configuration::Module<T>>::config();
+		let now = <fabric_system::Module<T>>::block_number();
+
+		T::DisputesHandler::note_included(CurrentSessionIndex::get(), plain.hash(), now);
 
 		T::RewardValidators::reward_backing(backers.iter().enumerate()
 			.filter(|(_, backed)| **backed)
@@ -899,20 +918,21 @@ mod tests {
 	use primitives::v1::{BlockNumber, Hash};
 	use primitives::v1::{
 		SignedAvailabilityBitfield, CompactStatement as Statement, ValidityAttestation, CollatorId,
-		CandidateCommitments, SignedStatement, CandidateDescriptor, ValidationCode,
+		CandidateCommitments, SignedStatement, CandidateDescriptor, ValidationCode, ParathreadClaim,
+		DisputeStatementSet, ExplicitDisputeStatement,
 	};
 	use tp_keystore::{SyncCryptoStorePtr, SyncCryptoStore};
 	use fabric_support::traits::{OnFinalize, OnInitialize};
 	use keyring::Sr25519Keyring;
 	use tc_keystore::LocalKeystore;
 	use crate::mock::{
-		new_test_ext, Configuration, Paras, System, Inclusion,
+		new_test_ext, Configuration, Paras, System, Inclusion, Scheduler, SessionInfo, Disputes,
 		GenesisConfig as MockGenesisConfig, Test,
 	};
 	use crate::initializer::SessionChangeNotification;
 	use crate::configuration::HostConfiguration;
 	use crate::paras::ParaGenesisArgs;
-	use crate::scheduler::AssignmentKind;
+	use crate::scheduler::{AssignmentKind, FreedReason};
 
 	fn default_config() -> HostConfiguration<BlockNumber> {
 		let mut config = HostConfiguration::default();
@@ -1548,6 +1568,183 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn available_parathread_candidate_fulfills_its_claim() {
+		let thread_a = ParaId::from(3);
+
+		let paras = vec![(thread_a, false)];
+		let validators = vec![
+			Sr25519Keyring::Alice,
+			Sr25519Keyring::Bob,
+			Sr25519Keyring::Charlie,
+			Sr25519Keyring::Dave,
+			Sr25519Keyring::Ferdie,
+		];
+		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+		for validator in validators.iter() {
+			SyncCryptoStore::sr25519_generate_new(&*keystore, PARACHAIN_KEY_TYPE_ID, Some(&validator.to_seed())).unwrap();
+		}
+		let validator_public = validator_pubkeys(&validators);
+
+		let mut genesis = genesis_config(paras);
+		genesis.configuration.config.scheduling_lookahead = 1;
+		let config = genesis.configuration.config.clone();
+
+		new_test_ext(genesis).execute_with(|| {
+			Validators::set(validator_public.clone());
+			CurrentSessionIndex::set(5);
+
+			// a single parathread core, assigned to a group of all the validators.
+			Scheduler::initializer_on_new_session(&SessionChangeNotification {
+				validators: validator_public.clone(),
+				prev_config: config.clone(),
+				new_config: config,
+				session_index: 5,
+				..Default::default()
+			});
+
+			// the session starts with the next block.
+			let now = System::block_number() + 1;
+			let collator = CollatorId::from(Sr25519Keyring::Two.public());
+			assert!(Scheduler::try_add_parathread_claim(ParathreadClaim(thread_a, collator)).is_ok());
+			Scheduler::schedule(Vec::new(), now);
+			assert_eq!(Scheduler::scheduled().len(), 1);
+
+			// a candidate of the parathread is backed on the core.
+			let candidate = TestCandidateBuilder {
+				para_id: thread_a,
+				head_data: vec![1, 2, 3, 4].into(),
+				..Default::default()
+			}.build();
+			Scheduler::occupied(&[CoreIndex::from(0)]);
+			<PendingAvailability<Test>>::insert(thread_a, CandidatePendingAvailability {
+				core: CoreIndex::from(0),
+				hash: candidate.hash(),
+				descriptor: candidate.descriptor,
+				availability_votes: default_availability_votes(),
+				relay_parent_number: 0,
+				backed_in_number: 0,
+				backers: backing_bitfield(&[0, 1, 2]),
+			});
+			PendingAvailabilityCommitments::insert(thread_a, candidate.commitments);
+
+			let signing_context = SigningContext {
+				parent_hash: System::parent_hash(),
+				session_index: 5,
+			};
+			let available = {
+				let mut bare_bitfield = default_bitfield();
+				*bare_bitfield.0.get_mut(0).unwrap() = true;
+
+				bare_bitfield
+			};
+			let signed_bitfields = validators.iter().enumerate().take(4).map(|(i, key)| {
+				block_on(sign_bitfield(&keystore, key, i as ValidatorIndex, available.clone(), &signing_context))
+			}).collect();
+
+			// the candidate becomes available, freeing the core exactly as the inclusion inherent does.
+			let freed = Inclusion::process_bitfields(signed_bitfields, Scheduler::core_para).unwrap();
+			assert_eq!(freed, vec![CoreIndex::from(0)]);
+			assert!(crate::mock::fulfilled_parathread_claims().is_empty());
+
+			Scheduler::schedule(freed.into_iter().map(|c| (c, FreedReason::Concluded)), now);
+
+			assert_eq!(Paras::para_head(&thread_a), Some(vec![1, 2, 3, 4].into()));
+			assert_eq!(crate::mock::fulfilled_parathread_claims(), vec![thread_a]);
+			assert!(crate::mock::dropped_parathread_claims().is_empty());
+		});
+	}
+
+	#[test]
+	fn available_candidate_is_not_included_while_frozen() {
+		let chain_a = ParaId::from(1);
+
+		let paras = vec![(chain_a, true)];
+		let validators = vec![
+			Sr25519Keyring::Alice,
+			Sr25519Keyring::Bob,
+			Sr25519Keyring::Charlie,
+			Sr25519Keyring::Dave,
+			Sr25519Keyring::Ferdie,
+		];
+		let keystore: SyncCryptoStorePtr = Arc::new(LocalKeystore::in_memory());
+		for validator in validators.iter() {
+			SyncCryptoStore::sr25519_generate_new(&*keystore, PARACHAIN_KEY_TYPE_ID, Some(&validator.to_seed())).unwrap();
+		}
+		let validator_public = validator_pubkeys(&validators);
+
+		new_test_ext(genesis_config(paras)).execute_with(|| {
+			Validators::set(validator_public.clone());
+			CurrentSessionIndex::set(5);
+
+			let notification = SessionChangeNotification {
+				validators: validator_public.clone(),
+				new_config: Configuration::config(),
+				session_index: 5,
+				..Default::default()
+			};
+			SessionInfo::initializer_on_new_session(&notification);
+			Disputes::initializer_on_new_session(&notification);
+
+			// a dispute concludes against a candidate included earlier, freezing the chain.
+			let disputed = CandidateHash(Hash::repeat_byte(1));
+			<Disputes as DisputesHandler<BlockNumber>>::note_included(5, disputed, 1);
+			let statements = validators.iter().enumerate().map(|(i, key)| {
+				let valid = i == 0;
+				let payload = ExplicitDisputeStatement { valid, candidate_hash: disputed, session: 5 }
+					.signing_payload();
+				(valid, i as ValidatorIndex, key.sign(&payload).into())
+			}).collect();
+			assert!(Disputes::submit_dispute_statements(
+				crate::mock::Origin::none(),
+				vec![DisputeStatementSet { candidate_hash: disputed, session: 5, statements }],
+			).is_ok());
+			assert!(<Disputes as DisputesHandler<BlockNumber>>::is_frozen());
+
+			let candidate = TestCandidateBuilder {
+				para_id: chain_a,
+				head_data: vec![1, 2, 3, 4].into(),
+				..Default::default()
+			}.build();
+			<PendingAvailability<Test>>::insert(chain_a, CandidatePendingAvailability {
+				core: CoreIndex::from(0),
+				hash: candidate.hash(),
+				descriptor: candidate.descriptor,
+				availability_votes: default_availability_votes(),
+				relay_parent_number: 0,
+				backed_in_number: 0,
+				backers: backing_bitfield(&[0, 1, 2]),
+			});
+			PendingAvailabilityCommitments::insert(chain_a, candidate.commitments);
+
+			let signing_context = SigningContext {
+				parent_hash: System::parent_hash(),
+				session_index: 5,
+			};
+			let available = {
+				let mut bare_bitfield = default_bitfield();
+				*bare_bitfield.0.get_mut(0).unwrap() = true;
+
+				bare_bitfield
+			};
+			let signed_bitfields = validators.iter().enumerate().map(|(i, key)| {
+				block_on(sign_bitfield(&keystore, key, i as ValidatorIndex, available.clone(), &signing_context))
+			}).collect();
+
+			let core_lookup = |core: CoreIndex| if core == CoreIndex::from(0) { Some(chain_a) } else { None };
+			let freed = Inclusion::process_bitfields(signed_bitfields, core_lookup).unwrap();
+
+			// the candidate is available, but stays pending.
+			assert!(freed.is_empty());
+			assert_eq!(
+				<PendingAvailability<Test>>::get(&chain_a).unwrap().availability_votes.count_ones(),
+				validators.len(),
+			);
+			assert!(PendingAvailabilityCommitments::get(&chain_a).is_some());
+			assert_eq!(Paras::para_head(&chain_a), Some(Vec::new().into()));
+		});
+	}
+
 	#[test]
 	fn candidate_checks() {
 		let chain_a = ParaId::from(1);
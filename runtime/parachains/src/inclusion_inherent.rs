@@ -33,6 +33,7 @@ use fabric_support::{
 };
 use fabric_system::ensure_none;
 use crate::{
+	disputes::DisputesHandler,
 	inclusion,
 	scheduler::{self, FreedReason},
 	ump,
@@ -86,7 +87,9 @@ decl_module! {
 
 		/// Include backed candidates and bitfields.
 		#[weight = (
-			MINIMAL_INCLUSION_INHERENT_WEIGHT + backed_candidates.len() as Weight * BACKED_CANDIDATE_WEIGHT,
+			MINIMAL_INCLUSION_INHERENT_WEIGHT
+				+ backed_candidates.len() as Weight * BACKED_CANDIDATE_WEIGHT
+				+ <ump::Module<T>>::max_upward_message_dispatch_weight(),
 			DispatchClass::Mandatory,
 		)]
 		pub fn inclusion(
@@ -131,6 +134,7 @@ decl_module! {
 			);
 
 			let backed_candidates = limit_backed_candidates::<T>(backed_candidates);
+			let backed_candidates = filter_disputed_candidates::<T>(backed_candidates);
 			let backed_candidates_len = backed_candidates.len() as Weight;
 
 			// Process backed candidates according to scheduled cores.
@@ -146,14 +150,15 @@ decl_module! {
 			<scheduler::Module<T>>::occupied(&occupied);
 
 			// Give some time slice to dispatch pending upward messages.
-			<ump::Module<T>>::process_pending_upward_messages();
+			let ump_weight = <ump::Module<T>>::process_pending_upward_messages();
 
 			// And track that we've finished processing the inherent for this block.
 			Included::set(Some(()));
 
 			Ok(Some(
 				MINIMAL_INCLUSION_INHERENT_WEIGHT +
-				(backed_candidates_len * BACKED_CANDIDATE_WEIGHT)
+				(backed_candidates_len * BACKED_CANDIDATE_WEIGHT) +
+				ump_weight
 			).into())
 		}
 	}
@@ -180,6 +185,25 @@ fn limit_backed_candidates<T: Config>(
 	}
 }
 
+/// Filter out the backed candidates which must not be processed due to disputes.
+///
+/// While the chain is frozen after a dispute concluded against an included candidate, no
+/// candidates are backed at all, just like no candidates pending availability are included.
+/// Otherwise, only the candidates which concluded invalid are skipped.
+fn filter_disputed_candidates<T: Config>(
+	mut backed_candidates: Vec<BackedCandidate<T::Hash>>,
+) -> Vec<BackedCandidate<T::Hash>> {
+	if <T as inclusion::Config>::DisputesHandler::is_frozen() {
+		return Vec::new();
+	}
+
+	let session_index = <inclusion::Module<T>>::session_index();
+	backed_candidates.retain(|backed| {
+		!<T as inclusion::Config>::DisputesHandler::concluded_invalid(session_index, backed.candidate.hash())
+	});
+	backed_candidates
+}
+
 impl<T: Config> ProvideInherent for Module<T> {
 	type Call = Call<T>;
 	type Error = MakeFatalError<()>;
@@ -281,7 +305,7 @@ mod tests {
 			new_test_ext, System, GenesisConfig as MockGenesisConfig, Test
 		};
 
-		use fabric_support::traits::UnfilteredDispatchable;
+		use fabric_support::{traits::UnfilteredDispatchable, weights::GetDispatchInfo};
 
 		fn default_header() -> Header {
 			Header {
@@ -370,5 +394,23 @@ mod tests {
 				);
 			});
 		}
+
+		/// We expect the weight of the inclusion inherent to account for dispatching upward messages,
+		/// up to the preferred weight of a dispatching stage.
+		#[test]
+		fn weight_accounts_for_upward_message_dispatch() {
+			let mut genesis = MockGenesisConfig::default();
+			genesis.configuration.config.preferred_dispatchable_upward_messages_step_weight = 1_000;
+
+			new_test_ext(genesis).execute_with(|| {
+				let backed_candidates = vec![BackedCandidate::default(); 10];
+				let call = Call::<Test>::inclusion(Vec::new(), backed_candidates, default_header());
+
+				assert_eq!(
+					call.get_dispatch_info().weight,
+					MINIMAL_INCLUSION_INHERENT_WEIGHT + 10 * BACKED_CANDIDATE_WEIGHT + 1_000,
+				);
+			});
+		}
 	}
 }
@@ -28,7 +28,7 @@ use fabric_support::{
 use tetsy_scale_codec::{Encode, Decode};
 use crate::{
 	configuration::{self, HostConfiguration},
-	paras, scheduler, inclusion, session_info, dmp, ump, hrmp,
+	paras, scheduler, inclusion, session_info, disputes, dmp, ump, hrmp,
 };
 
 /// Information about a session change that has just occurred.
@@ -75,6 +75,7 @@ pub trait Config:
 	+ scheduler::Config
 	+ inclusion::Config
 	+ session_info::Config
+	+ disputes::Config
 	+ dmp::Config
 	+ ump::Config
 	+ hrmp::Config
@@ -121,7 +122,7 @@ decl_module! {
 			// - Scheduler
 			// - Inclusion
 			// - SessionInfo
-			// - Validity
+			// - Disputes
 			// - DMP
 			// - UMP
 			// - HRMP
@@ -130,6 +131,7 @@ decl_module! {
 				scheduler::Module::<T>::initializer_initialize(now) +
 				inclusion::Module::<T>::initializer_initialize(now) +
 				session_info::Module::<T>::initializer_initialize(now) +
+				disputes::Module::<T>::initializer_initialize(now) +
 				dmp::Module::<T>::initializer_initialize(now) +
 				ump::Module::<T>::initializer_initialize(now) +
 				hrmp::Module::<T>::initializer_initialize(now);
@@ -144,6 +146,7 @@ decl_module! {
 			hrmp::Module::<T>::initializer_finalize();
 			ump::Module::<T>::initializer_finalize();
 			dmp::Module::<T>::initializer_finalize();
+			disputes::Module::<T>::initializer_finalize();
 			session_info::Module::<T>::initializer_finalize();
 			inclusion::Module::<T>::initializer_finalize();
 			scheduler::Module::<T>::initializer_finalize();
@@ -186,7 +189,11 @@ impl<T: Config> Module<T> {
 
 		// We can't pass the new config into the thing that determines the new config,
 		// so we don't pass the `SessionChangeNotification` into this module.
-		configuration::Module::<T>::initializer_on_new_session(&validators, &queued);
+		configuration::Module::<T>::initializer_on_new_session(
+			&validators,
+			&queued,
+			session_index,
+		);
 
 		let new_config = <configuration::Module<T>>::config();
 
@@ -203,6 +210,7 @@ impl<T: Config> Module<T> {
 		scheduler::Module::<T>::initializer_on_new_session(&notification);
 		inclusion::Module::<T>::initializer_on_new_session(&notification);
 		session_info::Module::<T>::initializer_on_new_session(&notification);
+		disputes::Module::<T>::initializer_on_new_session(&notification);
 		dmp::Module::<T>::initializer_on_new_session(&notification);
 		ump::Module::<T>::initializer_on_new_session(&notification);
 		hrmp::Module::<T>::initializer_on_new_session(&notification);
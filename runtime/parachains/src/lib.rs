@@ -29,6 +29,7 @@ pub mod initializer;
 pub mod paras;
 pub mod scheduler;
 pub mod session_info;
+pub mod disputes;
 pub mod origin;
 pub mod dmp;
 pub mod ump;
@@ -65,3 +66,17 @@ where
 	<ump::Module<T>>::schedule_para_cleanup(id);
 	<hrmp::Module<T>>::schedule_para_cleanup(id);
 }
+
+/// Schedule a parathread to be upgraded to a parachain at the start of the next session.
+pub fn schedule_parathread_upgrade<T: paras::Config>(
+	id: primitives::v1::Id,
+) -> fabric_support::dispatch::DispatchResult {
+	<paras::Module<T>>::schedule_parathread_upgrade(id)
+}
+
+/// Schedule a parachain to be downgraded to a parathread at the start of the next session.
+pub fn schedule_parachain_downgrade<T: paras::Config>(
+	id: primitives::v1::Id,
+) -> fabric_support::dispatch::DispatchResult {
+	<paras::Module<T>>::schedule_parachain_downgrade(id)
+}
@@ -21,14 +21,17 @@ use tet_core::H256;
 use tp_runtime::traits::{
 	BlakeTwo256, IdentityLookup,
 };
-use primitives::v1::{AuthorityDiscoveryId, Balance, BlockNumber, Header, ValidatorIndex};
+use primitives::v1::{
+	AuthorityDiscoveryId, Balance, BlockNumber, CandidateHash, Header, SessionIndex, ValidatorIndex,
+	Id as ParaId,
+};
 use fabric_support::{
 	impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types,
 	traits::Randomness as RandomnessT,
 };
 use std::cell::RefCell;
 use std::collections::HashMap;
-use crate::inclusion;
+use crate::{disputes, inclusion, paras, scheduler, ump};
 use crate as parachains;
 
 /// A test runtime struct.
@@ -51,7 +54,10 @@ impl_outer_event! {
 	pub enum TestEvent for Test {
 		fabric_system<T>,
 		noble_balances<T>,
+		paras,
 		inclusion<T>,
+		ump,
+		disputes<T>,
 	}
 }
 
@@ -112,15 +118,19 @@ impl crate::initializer::Config for Test {
 	type Randomness = TestRandomness;
 }
 
-impl crate::configuration::Config for Test { }
+impl crate::configuration::Config for Test {
+	type CurrentSessionIndex = crate::inclusion::CurrentSession<Test>;
+}
 
 impl crate::paras::Config for Test {
+	type Event = TestEvent;
 	type Origin = Origin;
 }
 
 impl crate::dmp::Config for Test { }
 
 impl crate::ump::Config for Test {
+	type Event = TestEvent;
 	type UmpSink = crate::ump::mock_sink::MockUmpSink;
 }
 
@@ -129,17 +139,25 @@ impl crate::hrmp::Config for Test {
 	type Currency = noble_balances::Module<Test>;
 }
 
-impl crate::scheduler::Config for Test { }
+impl crate::scheduler::Config for Test {
+	type OnParathreadClaimRemoved = TestParathreadClaimHandler;
+}
 
 impl crate::inclusion::Config for Test {
 	type Event = TestEvent;
 	type RewardValidators = TestRewardValidators;
+	type DisputesHandler = Disputes;
 }
 
 impl crate::inclusion_inherent::Config for Test { }
 
 impl crate::session_info::Config for Test { }
 
+impl crate::disputes::Config for Test {
+	type Event = TestEvent;
+	type SlashingHandler = TestSlashingHandler;
+}
+
 impl crate::session_info::AuthorityDiscoveryConfig for Test {
 	fn authorities() -> Vec<AuthorityDiscoveryId> {
 		Vec::new()
@@ -152,6 +170,16 @@ thread_local! {
 
 	pub static AVAILABILITY_REWARDS: RefCell<HashMap<ValidatorIndex, usize>>
 		= RefCell::new(HashMap::new());
+
+	pub static SLASHED_FOR_INVALID: RefCell<Vec<(SessionIndex, CandidateHash, Vec<ValidatorIndex>)>>
+		= RefCell::new(Vec::new());
+
+	pub static SLASHED_AGAINST_VALID: RefCell<Vec<(SessionIndex, CandidateHash, Vec<ValidatorIndex>)>>
+		= RefCell::new(Vec::new());
+
+	pub static FULFILLED_PARATHREAD_CLAIMS: RefCell<Vec<ParaId>> = RefCell::new(Vec::new());
+
+	pub static DROPPED_PARATHREAD_CLAIMS: RefCell<Vec<ParaId>> = RefCell::new(Vec::new());
 }
 
 pub fn backing_rewards() -> HashMap<ValidatorIndex, usize> {
@@ -162,6 +190,22 @@ pub fn availability_rewards() -> HashMap<ValidatorIndex, usize> {
 	AVAILABILITY_REWARDS.with(|r| r.borrow().clone())
 }
 
+pub fn slashed_for_invalid() -> Vec<(SessionIndex, CandidateHash, Vec<ValidatorIndex>)> {
+	SLASHED_FOR_INVALID.with(|r| r.borrow().clone())
+}
+
+pub fn slashed_against_valid() -> Vec<(SessionIndex, CandidateHash, Vec<ValidatorIndex>)> {
+	SLASHED_AGAINST_VALID.with(|r| r.borrow().clone())
+}
+
+pub fn fulfilled_parathread_claims() -> Vec<ParaId> {
+	FULFILLED_PARATHREAD_CLAIMS.with(|r| r.borrow().clone())
+}
+
+pub fn dropped_parathread_claims() -> Vec<ParaId> {
+	DROPPED_PARATHREAD_CLAIMS.with(|r| r.borrow().clone())
+}
+
 pub struct TestRewardValidators;
 
 impl inclusion::RewardValidators for TestRewardValidators {
@@ -183,6 +227,40 @@ impl inclusion::RewardValidators for TestRewardValidators {
 	}
 }
 
+pub struct TestSlashingHandler;
+
+impl disputes::SlashingHandler for TestSlashingHandler {
+	fn punish_for_invalid(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		losers: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		SLASHED_FOR_INVALID.with(|r| r.borrow_mut().push(
+			(session, candidate_hash, losers.into_iter().collect())
+		))
+	}
+	fn punish_against_valid(
+		session: SessionIndex,
+		candidate_hash: CandidateHash,
+		losers: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		SLASHED_AGAINST_VALID.with(|r| r.borrow_mut().push(
+			(session, candidate_hash, losers.into_iter().collect())
+		))
+	}
+}
+
+pub struct TestParathreadClaimHandler;
+
+impl scheduler::OnParathreadClaimRemoved for TestParathreadClaimHandler {
+	fn on_claim_fulfilled(para: ParaId) {
+		FULFILLED_PARATHREAD_CLAIMS.with(|r| r.borrow_mut().push(para))
+	}
+	fn on_claim_dropped(para: ParaId) {
+		DROPPED_PARATHREAD_CLAIMS.with(|r| r.borrow_mut().push(para))
+	}
+}
+
 pub type System = fabric_system::Module<Test>;
 
 /// Mocked initializer.
@@ -212,10 +290,15 @@ pub type Inclusion = crate::inclusion::Module<Test>;
 /// Mocked session info module.
 pub type SessionInfo = crate::session_info::Module<Test>;
 
+/// Mocked disputes module.
+pub type Disputes = crate::disputes::Module<Test>;
+
 /// Create a new set of test externalities.
 pub fn new_test_ext(state: GenesisConfig) -> TestExternalities {
 	BACKING_REWARDS.with(|r| r.borrow_mut().clear());
 	AVAILABILITY_REWARDS.with(|r| r.borrow_mut().clear());
+	SLASHED_FOR_INVALID.with(|r| r.borrow_mut().clear());
+	SLASHED_AGAINST_VALID.with(|r| r.borrow_mut().clear());
 
 	let mut t = state.system.build_storage::<Test>().unwrap();
 	state.configuration.assimilate_storage(&mut t).unwrap();
@@ -22,20 +22,34 @@
 //!
 //! A para is not considered live until it is registered and activated in this module. Activation can
 //! only occur at session boundaries.
+//!
+//! Every para known to this module has a [`ParaLifecycle`], which also records scheduled changes between
+//! parathread and parachain. Like activation, these are only enacted at session boundaries.
 
 use tetcore_std::prelude::*;
 use tetcore_std::result;
 #[cfg(feature = "std")]
 use tetcore_std::marker::PhantomData;
 use primitives::v1::{
-	Id as ParaId, ValidationCode, HeadData,
+	Id as ParaId, ValidationCode, HeadData, Hash, SessionIndex, ValidatorId, ValidatorSignature,
+	PvfCheckStatement, BlakeTwo256, HashT, ParaLifecycle,
+};
+use tp_runtime::{
+	traits::{One, AppVerify},
+	transaction_validity::{
+		TransactionValidity, ValidTransaction, InvalidTransaction, TransactionSource,
+		TransactionLongevity,
+	},
 };
-use tp_runtime::traits::One;
 use fabric_support::{
-	decl_storage, decl_module, decl_error,
+	decl_storage, decl_module, decl_error, decl_event, ensure,
+	dispatch::{DispatchResult, DispatchResultWithPostInfo},
 	traits::Get,
+	IterableStorageMap,
 	weights::Weight,
 };
+use fabric_system::{ensure_root, ensure_none};
+use bitvec::{bitvec, order::Lsb0 as BitOrderLsb0, vec::BitVec};
 use tetsy_scale_codec::{Encode, Decode};
 use crate::{configuration, initializer::SessionChangeNotification};
 use tet_core::RuntimeDebug;
@@ -45,7 +59,12 @@ use serde::{Serialize, Deserialize};
 
 pub use crate::Origin;
 
+const LOG_TARGET: &str = "parachains_runtime_paras";
+
 pub trait Config: fabric_system::Config + configuration::Config {
+	/// The outer event type.
+	type Event: From<Event> + Into<<Self as fabric_system::Config>::Event>;
+
 	/// The outer origin type.
 	type Origin: From<Origin>
 		+ From<<Self as fabric_system::Config>::Origin>
@@ -164,6 +183,93 @@ impl<N: Ord + Copy> ParaPastCodeMeta<N> {
 	}
 }
 
+/// The reason why a validation code blob is subject to pre-checking.
+#[derive(Encode, Decode, Clone)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum PvfCheckCause {
+	/// The code is the genesis code of a para being onboarded.
+	Onboarding(ParaId),
+	/// The code is the target of a code upgrade of a live para.
+	Upgrade(ParaId),
+}
+
+impl PvfCheckCause {
+	fn para_id(&self) -> ParaId {
+		match *self {
+			PvfCheckCause::Onboarding(id) | PvfCheckCause::Upgrade(id) => id,
+		}
+	}
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum PvfCheckOutcome {
+	Accepted,
+	Rejected,
+}
+
+/// The state of an ongoing pre-checking vote on a validation code blob.
+#[derive(Encode, Decode)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct PvfCheckActiveVoteState<N> {
+	/// The validators of the current session which voted to accept the code, by validator index.
+	votes_accept: BitVec<BitOrderLsb0, u8>,
+	/// The validators of the current session which voted to reject the code, by validator index.
+	votes_reject: BitVec<BitOrderLsb0, u8>,
+	/// The number of session changes this vote has lived through without concluding.
+	age: SessionIndex,
+	/// The relay-chain block number at which the vote was started.
+	created_at: N,
+	/// The reasons for which the code is checked. The same code may be wanted by several paras.
+	causes: Vec<PvfCheckCause>,
+}
+
+impl<N> PvfCheckActiveVoteState<N> {
+	fn new(now: N, n_validators: usize, cause: PvfCheckCause) -> Self {
+		let mut causes = Vec::with_capacity(1);
+		causes.push(cause);
+
+		PvfCheckActiveVoteState {
+			votes_accept: bitvec![BitOrderLsb0, u8; 0; n_validators],
+			votes_reject: bitvec![BitOrderLsb0, u8; 0; n_validators],
+			age: 0,
+			created_at: now,
+			causes,
+		}
+	}
+
+	// drops all the votes cast so far. Used when the validator set changes.
+	fn reinitialize_ballots(&mut self, n_validators: usize) {
+		self.votes_accept = bitvec![BitOrderLsb0, u8; 0; n_validators];
+		self.votes_reject = bitvec![BitOrderLsb0, u8; 0; n_validators];
+	}
+
+	// whether the validator with the given index has already voted, either way.
+	fn has_vote(&self, validator_index: usize) -> bool {
+		self.votes_accept.get(validator_index).map_or(false, |v| *v)
+			|| self.votes_reject.get(validator_index).map_or(false, |v| *v)
+	}
+
+	// the outcome of the vote, if it has concluded.
+	//
+	// The code is accepted once a supermajority voted for it, and rejected as soon as
+	// a supermajority of accepting votes can no longer be reached.
+	fn quorum(&self, n_validators: usize) -> Option<PvfCheckOutcome> {
+		let threshold = supermajority_threshold(n_validators);
+		if self.votes_accept.count_ones() >= threshold {
+			Some(PvfCheckOutcome::Accepted)
+		} else if self.votes_reject.count_ones() > n_validators - threshold {
+			Some(PvfCheckOutcome::Rejected)
+		} else {
+			None
+		}
+	}
+}
+
+// the number of votes which forms a supermajority of `n_validators`, i.e. more than two thirds.
+const fn supermajority_threshold(n_validators: usize) -> usize {
+	n_validators - n_validators.saturating_sub(1) / 3
+}
+
 /// Arguments for initializing a para.
 #[derive(PartialEq, Eq, Clone, Encode, Decode, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -214,7 +320,29 @@ decl_storage! {
 		UpcomingParasGenesis: map hasher(twox_64_concat) ParaId => Option<ParaGenesisArgs>;
 		/// Paras that are to be cleaned up at the end of the session.
 		OutgoingParas get(fn outgoing_paras): Vec<ParaId>;
-
+		/// The current lifecycle of every para known to this module.
+		ParaLifecycles get(fn para_lifecycle): map hasher(twox_64_concat) ParaId => Option<ParaLifecycle>;
+		/// Parathreads that are to be upgraded to parachains at the end of the session. Ordered ascending
+		/// by ParaId.
+		UpcomingUpgrades: Vec<ParaId>;
+		/// Parachains that are to be downgraded to parathreads at the end of the session. Ordered ascending
+		/// by ParaId.
+		UpcomingDowngrades: Vec<ParaId>;
+
+		/// The active pre-checking votes, keyed by the hash of the validation code under vote.
+		PvfActiveVoteMap get(fn pvf_active_vote_state):
+			map hasher(identity) Hash => Option<PvfCheckActiveVoteState<T::BlockNumber>>;
+		/// The hashes of all the validation code currently under vote. Auxiliary to `PvfActiveVoteMap`.
+		PvfActiveVoteList get(fn pvfs_require_precheck): Vec<Hash>;
+		/// The validation code under vote, keyed by its hash.
+		PvfCheckCode get(fn pvf_check_code): map hasher(identity) Hash => Option<ValidationCode>;
+		/// The hash of the validation code a para is waiting on to pass pre-checking, either for its
+		/// onboarding or for a code upgrade.
+		PendingPvfChecks: map hasher(twox_64_concat) ParaId => Option<Hash>;
+		/// The validators of the current session, which are the ones voting on pre-checking.
+		PvfCheckValidators: Vec<ValidatorId>;
+		/// The index of the current session. Pre-checking statements are only accepted for this session.
+		PvfCheckSession: SessionIndex;
 	}
 	add_extra_genesis {
 		config(paras): Vec<(ParaId, ParaGenesisArgs)>;
@@ -240,17 +368,195 @@ fn build<T: Config>(config: &GenesisConfig<T>) {
 	for (id, genesis_args) in &config.paras {
 		<Module<T> as Store>::CurrentCode::insert(&id, &genesis_args.validation_code);
 		<Module<T> as Store>::Heads::insert(&id, &genesis_args.genesis_head);
+
+		if genesis_args.parachain {
+			<Module<T> as Store>::ParaLifecycles::insert(&id, ParaLifecycle::Parachain);
+		} else {
+			<Module<T> as Store>::Parathreads::insert(&id, ());
+			<Module<T> as Store>::ParaLifecycles::insert(&id, ParaLifecycle::Parathread);
+		}
+	}
+}
+
+decl_event! {
+	pub enum Event {
+		/// Current code has been updated for a para. [para]
+		CurrentCodeUpdated(ParaId),
+		/// Current head has been updated for a para. [para]
+		CurrentHeadUpdated(ParaId),
+		/// A code upgrade has been scheduled for a para. [para]
+		CodeUpgradeScheduled(ParaId),
+		/// A new head has been noted for a para. [para]
+		NewHeadNoted(ParaId),
+		/// The given para either initiated or subscribed to a pre-checking vote on the given
+		/// validation code. [code_hash, para]
+		PvfCheckStarted(Hash, ParaId),
+		/// The given validation code was accepted by the pre-checking vote. [code_hash, para]
+		PvfCheckAccepted(Hash, ParaId),
+		/// The given validation code was rejected by the pre-checking vote. [code_hash, para]
+		PvfCheckRejected(Hash, ParaId),
+		/// The lifecycle of a para has changed. [para, lifecycle]
+		ParaLifecycleChanged(ParaId, ParaLifecycle),
+		/// A para has been offboarded and its state cleaned up. [para]
+		ParaOffboarded(ParaId),
 	}
 }
 
 decl_error! {
-	pub enum Error for Module<T: Config> { }
+	pub enum Error for Module<T: Config> {
+		/// Para is not registered in our system.
+		NotRegistered,
+		/// Para already has a code upgrade scheduled.
+		CodeUpgradeAlreadyScheduled,
+		/// The pre-checking statement is for a past session.
+		PvfCheckStatementStale,
+		/// The pre-checking statement is for a future session.
+		PvfCheckStatementFuture,
+		/// The validator index of the pre-checking statement is out of bounds.
+		PvfCheckValidatorIndexOutOfBounds,
+		/// The signature of the pre-checking statement is invalid.
+		PvfCheckInvalidSignature,
+		/// The validator has already voted on the validation code.
+		PvfCheckDoubleVote,
+		/// The validation code is not under vote.
+		PvfCheckSubjectInvalid,
+		/// Para is not a parathread which can be upgraded to a parachain.
+		CannotUpgrade,
+		/// Para is not a parachain which can be downgraded to a parathread.
+		CannotDowngrade,
+	}
 }
 
 decl_module! {
 	/// The parachains configuration module.
 	pub struct Module<T: Config> for enum Call where origin: <T as fabric_system::Config>::Origin {
 		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		fn on_runtime_upgrade() -> Weight {
+			Self::migrate_para_lifecycles()
+		}
+
+		/// Set the storage for the parachain validation code immediately.
+		///
+		/// The replaced code is kept around as past code, like with a regular upgrade.
+		#[weight = T::DbWeight::get().reads_writes(4, 4)]
+		pub fn force_set_current_code(origin, para: ParaId, new_code: ValidationCode) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Self::is_valid_para(para), Error::<T>::NotRegistered);
+
+			let prior_code = <Self as Store>::CurrentCode::get(&para).unwrap_or_default();
+			<Self as Store>::CurrentCode::insert(&para, new_code);
+
+			let now = <fabric_system::Module<T>>::block_number();
+			Self::note_past_code(para, now, now, prior_code);
+
+			Self::deposit_event(Event::CurrentCodeUpdated(para));
+			Ok(())
+		}
+
+		/// Set the storage for the current parachain head data immediately.
+		#[weight = T::DbWeight::get().reads_writes(2, 1)]
+		pub fn force_set_current_head(origin, para: ParaId, new_head: HeadData) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Self::is_valid_para(para), Error::<T>::NotRegistered);
+
+			<Self as Store>::Heads::insert(&para, new_head);
+
+			Self::deposit_event(Event::CurrentHeadUpdated(para));
+			Ok(())
+		}
+
+		/// Schedule a code upgrade for block `expected_at`.
+		///
+		/// The upgrade is applied with the first head of the para noted in the context of a
+		/// relay-chain block with number >= `expected_at`.
+		#[weight = T::DbWeight::get().reads_writes(3, 2)]
+		pub fn force_schedule_code_upgrade(
+			origin,
+			para: ParaId,
+			new_code: ValidationCode,
+			expected_at: T::BlockNumber,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Self::is_valid_para(para), Error::<T>::NotRegistered);
+			ensure!(
+				<Self as Store>::FutureCodeUpgrades::get(&para).is_none()
+					&& <Self as Store>::PendingPvfChecks::get(&para).is_none(),
+				Error::<T>::CodeUpgradeAlreadyScheduled,
+			);
+
+			Self::schedule_code_upgrade(para, new_code, expected_at);
+
+			Self::deposit_event(Event::CodeUpgradeScheduled(para));
+			Ok(())
+		}
+
+		/// Note a new block head for para within the context of the current block.
+		///
+		/// This applies a scheduled code upgrade which is due, just like the inclusion of a
+		/// candidate would.
+		#[weight = T::DbWeight::get().reads_writes(6, 5)]
+		pub fn force_note_new_head(origin, para: ParaId, new_head: HeadData) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Self::is_valid_para(para), Error::<T>::NotRegistered);
+
+			let now = <fabric_system::Module<T>>::block_number();
+			Self::note_new_head(para, new_head, now);
+
+			Self::deposit_event(Event::NewHeadNoted(para));
+			Ok(())
+		}
+
+		/// Include a statement of a validator on whether the given validation code passed
+		/// pre-checking.
+		///
+		/// Concludes the vote if this statement makes either side reach the required majority,
+		/// enacting the outcome for every para waiting on the code. The weight accounts for
+		/// enacting the outcome and is refunded if the vote doesn't conclude.
+		#[weight = Module::<T>::include_pvf_check_statement_weight(&stmt.subject)]
+		pub fn include_pvf_check_statement(
+			origin,
+			stmt: PvfCheckStatement,
+			signature: ValidatorSignature,
+		) -> DispatchResultWithPostInfo {
+			ensure_none(origin)?;
+
+			let mut active_vote = Self::check_pvf_check_statement(&stmt, &signature)?;
+			let n_causes = active_vote.causes.len();
+
+			let validator_index = stmt.validator_index as usize;
+			if stmt.accept {
+				active_vote.votes_accept.set(validator_index, true);
+			} else {
+				active_vote.votes_reject.set(validator_index, true);
+			}
+
+			let n_validators = <Self as Store>::PvfCheckValidators::decode_len().unwrap_or(0);
+			match active_vote.quorum(n_validators) {
+				None => {
+					<Self as Store>::PvfActiveVoteMap::insert(&stmt.subject, active_vote);
+					return Ok(Some(Self::pvf_check_statement_weight(0)).into());
+				}
+				Some(outcome) => {
+					<Self as Store>::PvfActiveVoteMap::remove(&stmt.subject);
+					<Self as Store>::PvfActiveVoteList::mutate(|l| l.retain(|h| h != &stmt.subject));
+
+					match outcome {
+						PvfCheckOutcome::Accepted => {
+							let now = <fabric_system::Module<T>>::block_number();
+							Self::enact_pvf_accepted(now, &stmt.subject, &active_vote.causes);
+						}
+						PvfCheckOutcome::Rejected => {
+							Self::enact_pvf_rejected(&stmt.subject, &active_vote.causes);
+						}
+					}
+				}
+			}
+
+			Ok(Some(Self::pvf_check_statement_weight(n_causes)).into())
+		}
 	}
 }
 
@@ -264,11 +570,41 @@ impl<T: Config> Module<T> {
 	pub(crate) fn initializer_finalize() { }
 
 	/// Called by the initializer to note that a new session has started.
-	pub(crate) fn initializer_on_new_session(_notification: &SessionChangeNotification<T::BlockNumber>) {
+	pub(crate) fn initializer_on_new_session(notification: &SessionChangeNotification<T::BlockNumber>) {
 		let now = <fabric_system::Module<T>>::block_number();
 		let mut parachains = Self::clean_up_outgoing(now);
 		Self::apply_incoming(&mut parachains);
+		Self::apply_kind_changes(&mut parachains);
 		<Self as Store>::Parachains::set(parachains);
+
+		<Self as Store>::PvfCheckValidators::set(notification.validators.clone());
+		<Self as Store>::PvfCheckSession::set(notification.session_index);
+		Self::age_pvf_votes(notification.validators.len(), &notification.new_config);
+	}
+
+	/// Ages all the active pre-checking votes by a session. Votes which reached the time-to-live
+	/// are rejected, the others start over with the validator set of the new session.
+	fn age_pvf_votes(n_validators: usize, config: &configuration::HostConfiguration<T::BlockNumber>) {
+		let mut active_votes = <Self as Store>::PvfActiveVoteList::get();
+		active_votes.retain(|code_hash| {
+			let mut active_vote = match <Self as Store>::PvfActiveVoteMap::get(code_hash) {
+				None => return false,
+				Some(v) => v,
+			};
+
+			active_vote.age += 1;
+			if active_vote.age < config.pvf_voting_ttl {
+				active_vote.reinitialize_ballots(n_validators);
+				<Self as Store>::PvfActiveVoteMap::insert(code_hash, active_vote);
+				true
+			} else {
+				<Self as Store>::PvfActiveVoteMap::remove(code_hash);
+				Self::enact_pvf_rejected(code_hash, &active_vote.causes);
+				false
+			}
+		});
+
+		<Self as Store>::PvfActiveVoteList::set(active_votes);
 	}
 
 	/// Cleans up all outgoing paras. Returns the new set of parachains
@@ -291,6 +627,9 @@ impl<T: Config> Module<T> {
 			if let Some(removed_code) = removed_code {
 				Self::note_past_code(outgoing_para, now, now, removed_code);
 			}
+
+			<Self as Store>::ParaLifecycles::remove(&outgoing_para);
+			Self::deposit_event(Event::ParaOffboarded(outgoing_para));
 		}
 
 		parachains
@@ -309,8 +648,10 @@ impl<T: Config> Module<T> {
 				if let Err(i) = parachains.binary_search(&upcoming_para) {
 					parachains.insert(i, upcoming_para);
 				}
+				Self::set_lifecycle(upcoming_para, ParaLifecycle::Parachain);
 			} else {
 				<Self as Store>::Parathreads::insert(&upcoming_para, ());
+				Self::set_lifecycle(upcoming_para, ParaLifecycle::Parathread);
 			}
 
 			<Self as Store>::Heads::insert(&upcoming_para, genesis_data.genesis_head);
@@ -318,6 +659,71 @@ impl<T: Config> Module<T> {
 		}
 	}
 
+	/// Applies all scheduled upgrades and downgrades, updating the parachains list accordingly.
+	fn apply_kind_changes(parachains: &mut Vec<ParaId>) {
+		let upgrades = <Self as Store>::UpcomingUpgrades::take();
+		for upgraded_para in upgrades {
+			if Self::para_lifecycle(&upgraded_para) != Some(ParaLifecycle::UpgradingToParachain) {
+				continue;
+			}
+
+			<Self as Store>::Parathreads::remove(&upgraded_para);
+			if let Err(i) = parachains.binary_search(&upgraded_para) {
+				parachains.insert(i, upgraded_para);
+			}
+			Self::set_lifecycle(upgraded_para, ParaLifecycle::Parachain);
+		}
+
+		let downgrades = <Self as Store>::UpcomingDowngrades::take();
+		for downgraded_para in downgrades {
+			if Self::para_lifecycle(&downgraded_para) != Some(ParaLifecycle::DowngradingToParathread) {
+				continue;
+			}
+
+			if let Ok(i) = parachains.binary_search(&downgraded_para) {
+				parachains.remove(i);
+			}
+			<Self as Store>::Parathreads::insert(&downgraded_para, ());
+			Self::set_lifecycle(downgraded_para, ParaLifecycle::Parathread);
+		}
+	}
+
+	/// Sets the lifecycle of a para, noting the change in an event.
+	/// Records the lifecycle of the paras known from before lifecycles were tracked, so that they
+	/// can change kind or be offboarded like any other. Paras which already have a lifecycle are
+	/// left untouched.
+	fn migrate_para_lifecycles() -> Weight {
+		let mut reads_writes = (3, 0);
+		let mut migrate = |id: ParaId, lifecycle: ParaLifecycle| {
+			reads_writes.0 += 1;
+			if !<Self as Store>::ParaLifecycles::contains_key(&id) {
+				<Self as Store>::ParaLifecycles::insert(&id, lifecycle);
+				reads_writes.1 += 1;
+			}
+		};
+
+		// Outgoing paras are still among the parachains or parathreads, so they go first.
+		for id in <Self as Store>::OutgoingParas::get() {
+			migrate(id, ParaLifecycle::Offboarding);
+		}
+		for id in <Self as Store>::UpcomingParas::get() {
+			migrate(id, ParaLifecycle::Onboarding);
+		}
+		for id in <Self as Store>::Parachains::get() {
+			migrate(id, ParaLifecycle::Parachain);
+		}
+		for (id, ()) in <Self as Store>::Parathreads::iter() {
+			migrate(id, ParaLifecycle::Parathread);
+		}
+
+		T::DbWeight::get().reads_writes(reads_writes.0, reads_writes.1)
+	}
+
+	fn set_lifecycle(id: ParaId, lifecycle: ParaLifecycle) {
+		<Self as Store>::ParaLifecycles::insert(&id, lifecycle);
+		Self::deposit_event(Event::ParaLifecycleChanged(id, lifecycle));
+	}
+
 	// note replacement of the code of para with given `id`, which occured in the
 	// context of the given relay-chain block number. provide the replaced code.
 	//
@@ -396,7 +802,24 @@ impl<T: Config> Module<T> {
 	}
 
 	/// Schedule a para to be initialized at the start of the next session.
+	///
+	/// If pre-checking is enabled, the para is only scheduled once its genesis validation code
+	/// is accepted by the validators.
 	pub(crate) fn schedule_para_initialize(id: ParaId, genesis: ParaGenesisArgs) -> Weight {
+		if UpcomingParasGenesis::contains_key(&id) {
+			return T::DbWeight::get().reads_writes(1, 0);
+		}
+
+		let config = configuration::Module::<T>::config();
+		if config.pvf_checking_enabled {
+			let code = genesis.validation_code.clone();
+			UpcomingParasGenesis::insert(&id, &genesis);
+			Self::set_lifecycle(id, ParaLifecycle::Onboarding);
+
+			let weight = Self::start_pvf_check(PvfCheckCause::Onboarding(id), code);
+			return weight + T::DbWeight::get().reads_writes(2, 2);
+		}
+
 		let dup = UpcomingParas::mutate(|v| {
 			match v.binary_search(&id) {
 				Ok(_) => true,
@@ -413,19 +836,29 @@ impl<T: Config> Module<T> {
 		}
 
 		UpcomingParasGenesis::insert(&id, &genesis);
+		Self::set_lifecycle(id, ParaLifecycle::Onboarding);
 
-		T::DbWeight::get().reads_writes(1, 2)
+		T::DbWeight::get().reads_writes(1, 3)
 	}
 
 	/// Schedule a para to be cleaned up at the start of the next session.
 	pub(crate) fn schedule_para_cleanup(id: ParaId) -> Weight {
+		// A para still waiting for its genesis code to pass pre-checking has never been live.
+		if PendingPvfChecks::take(&id).is_some() && UpcomingParasGenesis::take(&id).is_some() {
+			ParaLifecycles::remove(&id);
+			Self::deposit_event(Event::ParaOffboarded(id));
+			return T::DbWeight::get().reads_writes(2, 3);
+		}
+
 		let upcoming_weight = UpcomingParas::mutate(|v| {
 			match v.binary_search(&id) {
 				Ok(i) => {
 					v.remove(i);
 					UpcomingParasGenesis::remove(id);
+					ParaLifecycles::remove(id);
+					Self::deposit_event(Event::ParaOffboarded(id));
 					// If a para was only in the pending state it should not be moved to `Outgoing`
-					return T::DbWeight::get().reads_writes(2, 2);
+					return T::DbWeight::get().reads_writes(2, 3);
 				}
 				Err(_) => T::DbWeight::get().reads_writes(1, 0),
 			}
@@ -441,7 +874,55 @@ impl<T: Config> Module<T> {
 			}
 		});
 
-		outgoing_weight + upcoming_weight
+		// An offboarding para doesn't change kind anymore.
+		let lifecycle_weight = if ParaLifecycles::contains_key(&id) {
+			UpcomingUpgrades::mutate(|v| if let Ok(i) = v.binary_search(&id) { v.remove(i); });
+			UpcomingDowngrades::mutate(|v| if let Ok(i) = v.binary_search(&id) { v.remove(i); });
+			if Self::para_lifecycle(&id) != Some(ParaLifecycle::Offboarding) {
+				Self::set_lifecycle(id, ParaLifecycle::Offboarding);
+			}
+			T::DbWeight::get().reads_writes(4, 3)
+		} else {
+			T::DbWeight::get().reads_writes(1, 0)
+		};
+
+		outgoing_weight + upcoming_weight + lifecycle_weight
+	}
+
+	/// Schedule a parathread to be upgraded to a parachain at the start of the next session.
+	///
+	/// Fails if the para is not a live parathread, or if it is already changing kind or being
+	/// offboarded.
+	pub(crate) fn schedule_parathread_upgrade(id: ParaId) -> DispatchResult {
+		ensure!(
+			Self::para_lifecycle(&id) == Some(ParaLifecycle::Parathread),
+			Error::<T>::CannotUpgrade,
+		);
+
+		UpcomingUpgrades::mutate(|v| if let Err(i) = v.binary_search(&id) {
+			v.insert(i, id);
+		});
+		Self::set_lifecycle(id, ParaLifecycle::UpgradingToParachain);
+
+		Ok(())
+	}
+
+	/// Schedule a parachain to be downgraded to a parathread at the start of the next session.
+	///
+	/// Fails if the para is not a live parachain, or if it is already changing kind or being
+	/// offboarded.
+	pub(crate) fn schedule_parachain_downgrade(id: ParaId) -> DispatchResult {
+		ensure!(
+			Self::para_lifecycle(&id) == Some(ParaLifecycle::Parachain),
+			Error::<T>::CannotDowngrade,
+		);
+
+		UpcomingDowngrades::mutate(|v| if let Err(i) = v.binary_search(&id) {
+			v.insert(i, id);
+		});
+		Self::set_lifecycle(id, ParaLifecycle::DowngradingToParathread);
+
+		Ok(())
 	}
 
 	/// Schedule a future code upgrade of the given parachain, to be applied after inclusion
@@ -449,11 +930,28 @@ impl<T: Config> Module<T> {
 	/// with number >= `expected_at`
 	///
 	/// If there is already a scheduled code upgrade for the para, this is a no-op.
+	///
+	/// If pre-checking is enabled, the code is put up for a vote instead and the upgrade is only
+	/// scheduled once the code is accepted, `validation_upgrade_delay` blocks after the acceptance.
 	pub(crate) fn schedule_code_upgrade(
 		id: ParaId,
 		new_code: ValidationCode,
 		expected_at: T::BlockNumber,
 	) -> Weight {
+		if PendingPvfChecks::contains_key(&id) {
+			return T::DbWeight::get().reads_writes(1, 0);
+		}
+
+		let config = configuration::Module::<T>::config();
+		if config.pvf_checking_enabled {
+			if <Self as Store>::FutureCodeUpgrades::contains_key(&id) {
+				return T::DbWeight::get().reads_writes(3, 0);
+			}
+
+			let weight = Self::start_pvf_check(PvfCheckCause::Upgrade(id), new_code);
+			return weight + T::DbWeight::get().reads_writes(3, 0);
+		}
+
 		<Self as Store>::FutureCodeUpgrades::mutate(&id, |up| {
 			if up.is_some() {
 				T::DbWeight::get().reads_writes(1, 0)
@@ -465,6 +963,148 @@ impl<T: Config> Module<T> {
 		})
 	}
 
+	/// Puts the given validation code up for a pre-checking vote on behalf of the given cause,
+	/// joining the vote if the same code is already under vote.
+	fn start_pvf_check(cause: PvfCheckCause, code: ValidationCode) -> Weight {
+		let now = <fabric_system::Module<T>>::block_number();
+		let code_hash = BlakeTwo256::hash(&code.0);
+		let para = cause.para_id();
+
+		<Self as Store>::PendingPvfChecks::insert(&para, code_hash);
+		Self::deposit_event(Event::PvfCheckStarted(code_hash, para));
+
+		if let Some(mut active_vote) = <Self as Store>::PvfActiveVoteMap::get(&code_hash) {
+			active_vote.causes.push(cause);
+			<Self as Store>::PvfActiveVoteMap::insert(&code_hash, active_vote);
+			return T::DbWeight::get().reads_writes(1, 2);
+		}
+
+		let n_validators = <Self as Store>::PvfCheckValidators::decode_len().unwrap_or(0);
+		<Self as Store>::PvfCheckCode::insert(&code_hash, code);
+		<Self as Store>::PvfActiveVoteMap::insert(
+			&code_hash,
+			PvfCheckActiveVoteState::new(now, n_validators, cause),
+		);
+		<Self as Store>::PvfActiveVoteList::mutate(|l| l.push(code_hash));
+
+		T::DbWeight::get().reads_writes(3, 4)
+	}
+
+	/// Enacts the acceptance of the given validation code for every cause still waiting on it.
+	fn enact_pvf_accepted(now: T::BlockNumber, code_hash: &Hash, causes: &[PvfCheckCause]) {
+		let code = match <Self as Store>::PvfCheckCode::take(code_hash) {
+			None => return,
+			Some(c) => c,
+		};
+		let config = configuration::Module::<T>::config();
+
+		for cause in causes {
+			let para = cause.para_id();
+			// the para may have been cleaned up, or be waiting on different code by now.
+			if <Self as Store>::PendingPvfChecks::get(&para).as_ref() != Some(code_hash) {
+				continue;
+			}
+			<Self as Store>::PendingPvfChecks::remove(&para);
+
+			match cause {
+				PvfCheckCause::Onboarding(id) => {
+					if UpcomingParasGenesis::contains_key(id) {
+						UpcomingParas::mutate(|v| if let Err(i) = v.binary_search(id) {
+							v.insert(i, *id);
+						});
+					}
+				}
+				PvfCheckCause::Upgrade(id) => {
+					let expected_at = now + config.validation_upgrade_delay;
+					<Self as Store>::FutureCodeUpgrades::insert(id, expected_at);
+					FutureCode::insert(id, &code);
+				}
+			}
+
+			Self::deposit_event(Event::PvfCheckAccepted(*code_hash, para));
+		}
+	}
+
+	/// Enacts the rejection of the given validation code for every cause still waiting on it.
+	fn enact_pvf_rejected(code_hash: &Hash, causes: &[PvfCheckCause]) {
+		<Self as Store>::PvfCheckCode::remove(code_hash);
+
+		for cause in causes {
+			let para = cause.para_id();
+			if <Self as Store>::PendingPvfChecks::get(&para).as_ref() != Some(code_hash) {
+				continue;
+			}
+			<Self as Store>::PendingPvfChecks::remove(&para);
+
+			Self::deposit_event(Event::PvfCheckRejected(*code_hash, para));
+
+			// a para rejected while onboarding has never been live and is dropped right away.
+			if let PvfCheckCause::Onboarding(id) = cause {
+				UpcomingParasGenesis::remove(id);
+				ParaLifecycles::remove(id);
+				Self::deposit_event(Event::ParaOffboarded(*id));
+			}
+		}
+	}
+
+	/// The weight of `include_pvf_check_statement` for a statement on the given code, assuming
+	/// it concludes the vote and the outcome is enacted for every cause waiting on the code.
+	fn include_pvf_check_statement_weight(subject: &Hash) -> Weight {
+		let n_causes = <Self as Store>::PvfActiveVoteMap::get(subject)
+			.map_or(0, |active_vote| active_vote.causes.len());
+		Self::pvf_check_statement_weight(n_causes)
+	}
+
+	// the weight of including a pre-checking statement and enacting the outcome for `n_causes`.
+	//
+	// Besides checking the statement and updating the vote, enacting the outcome reads and
+	// writes the pending check of every cause and writes up to two more items for each.
+	fn pvf_check_statement_weight(n_causes: usize) -> Weight {
+		let n_causes = n_causes as u64;
+		T::DbWeight::get().reads_writes(6 + 2 * n_causes, 6 + 3 * n_causes)
+	}
+
+	/// Checks a pre-checking statement against the current session and the active votes,
+	/// returning the state of the vote it is for.
+	fn check_pvf_check_statement(
+		stmt: &PvfCheckStatement,
+		signature: &ValidatorSignature,
+	) -> Result<PvfCheckActiveVoteState<T::BlockNumber>, Error<T>> {
+		let current_session = <Self as Store>::PvfCheckSession::get();
+		ensure!(stmt.session_index >= current_session, Error::<T>::PvfCheckStatementStale);
+		ensure!(stmt.session_index <= current_session, Error::<T>::PvfCheckStatementFuture);
+
+		let validators = <Self as Store>::PvfCheckValidators::get();
+		let validator_public = validators.get(stmt.validator_index as usize)
+			.ok_or(Error::<T>::PvfCheckValidatorIndexOutOfBounds)?;
+		ensure!(
+			signature.verify(&stmt.signing_payload()[..], validator_public),
+			Error::<T>::PvfCheckInvalidSignature,
+		);
+
+		let active_vote = <Self as Store>::PvfActiveVoteMap::get(&stmt.subject)
+			.ok_or(Error::<T>::PvfCheckSubjectInvalid)?;
+		ensure!(!active_vote.has_vote(stmt.validator_index as usize), Error::<T>::PvfCheckDoubleVote);
+
+		Ok(active_vote)
+	}
+
+	/// Submits a pre-checking statement signed by a local validator to the transaction pool.
+	pub(crate) fn submit_pvf_check_statement(stmt: PvfCheckStatement, signature: ValidatorSignature)
+		where T: fabric_system::offchain::SendTransactionTypes<Call<T>>
+	{
+		use fabric_system::offchain::SubmitTransaction;
+
+		let call = Call::include_pvf_check_statement(stmt, signature);
+		if let Err(()) = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()) {
+			fabric_support::debug::RuntimeLogger::init();
+			log::error!(
+				target: LOG_TARGET,
+				"Error submitting a pre-checking statement to the transaction pool",
+			);
+		}
+	}
+
 	/// Note that a para has progressed to a new head, where the new head was executed in the context
 	/// of a relay-chain block with given number. This will apply pending code upgrades based
 	/// on the block number provided.
@@ -552,6 +1192,11 @@ impl<T: Config> Module<T> {
 		Parathreads::get(&id).is_some()
 	}
 
+	/// Returns the validation code under pre-checking with the given hash.
+	pub(crate) fn validation_code_by_hash(code_hash: Hash) -> Option<ValidationCode> {
+		Self::pvf_check_code(&code_hash)
+	}
+
 	/// The block number of the last scheduled upgrade of the requested para. Includes future upgrades
 	/// if the flag is set. This is the `expected_at` number, not the `activated_at` number.
 	pub(crate) fn last_code_upgrade(id: ParaId, include_future: bool) -> Option<T::BlockNumber> {
@@ -565,13 +1210,50 @@ impl<T: Config> Module<T> {
 	}
 }
 
+impl<T: Config> tp_runtime::traits::ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		const PRIORITY: u64 = 100;
+
+		let (stmt, signature) = match call {
+			Call::include_pvf_check_statement(stmt, signature) => (stmt, signature),
+			_ => return Err(InvalidTransaction::Call.into()),
+		};
+
+		match Self::check_pvf_check_statement(stmt, signature) {
+			Ok(_) => {}
+			Err(Error::<T>::PvfCheckStatementFuture) => return Err(InvalidTransaction::Future.into()),
+			Err(Error::<T>::PvfCheckStatementStale)
+				| Err(Error::<T>::PvfCheckDoubleVote)
+				| Err(Error::<T>::PvfCheckSubjectInvalid) => return Err(InvalidTransaction::Stale.into()),
+			Err(_) => return Err(InvalidTransaction::BadProof.into()),
+		}
+
+		Ok(ValidTransaction {
+			priority: PRIORITY,
+			requires: vec![],
+			provides: vec![("pvf_check", stmt.session_index, stmt.validator_index, stmt.subject).encode()],
+			longevity: TransactionLongevity::max_value(),
+			propagate: true,
+		})
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use primitives::v1::BlockNumber;
-	use fabric_support::traits::{OnFinalize, OnInitialize};
+	use primitives::v1::{BlockNumber, ValidatorIndex};
+	use fabric_support::traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade};
 
-	use crate::mock::{new_test_ext, Paras, System, GenesisConfig as MockGenesisConfig};
+	use fabric_support::{assert_ok, assert_noop};
+	use tp_runtime::DispatchError;
+	use keyring::Sr25519Keyring;
+
+	use crate::mock::{
+		new_test_ext, Origin, Paras, System, Test, TestEvent, Configuration,
+		GenesisConfig as MockGenesisConfig,
+	};
 	use crate::configuration::HostConfiguration;
 
 	fn run_to_block(to: BlockNumber, new_session: Option<Vec<BlockNumber>>) {
@@ -594,6 +1276,57 @@ mod tests {
 		ReplacementTimes { expected_at, activated_at }
 	}
 
+	const PVF_CHECK_VALIDATORS: [Sr25519Keyring; 4] = [
+		Sr25519Keyring::Alice,
+		Sr25519Keyring::Bob,
+		Sr25519Keyring::Charlie,
+		Sr25519Keyring::Dave,
+	];
+
+	fn pvf_checking_genesis(paras: Vec<(ParaId, ParaGenesisArgs)>) -> MockGenesisConfig {
+		MockGenesisConfig {
+			paras: GenesisConfig { paras, ..Default::default() },
+			configuration: crate::configuration::GenesisConfig {
+				config: HostConfiguration {
+					acceptance_period: 10,
+					validation_upgrade_delay: 5,
+					pvf_checking_enabled: true,
+					pvf_voting_ttl: 2,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		}
+	}
+
+	fn new_pvf_check_session(session_index: SessionIndex) {
+		Paras::initializer_on_new_session(&SessionChangeNotification {
+			validators: PVF_CHECK_VALIDATORS.iter().map(|v| v.public().into()).collect(),
+			new_config: Configuration::config(),
+			session_index,
+			..Default::default()
+		});
+	}
+
+	fn include_pvf_check_statement(
+		validator_index: ValidatorIndex,
+		subject: Hash,
+		accept: bool,
+		session_index: SessionIndex,
+	) -> DispatchResultWithPostInfo {
+		let stmt = PvfCheckStatement { accept, subject, session_index, validator_index };
+		let signature = PVF_CHECK_VALIDATORS[validator_index as usize]
+			.sign(&stmt.signing_payload())
+			.into();
+
+		Paras::include_pvf_check_statement(Origin::none(), stmt, signature)
+	}
+
+	fn paras_event_deposited(event: Event) -> bool {
+		System::events().iter().any(|r| r.event == TestEvent::paras(event.clone()))
+	}
+
 	#[test]
 	fn para_past_code_meta_gives_right_code() {
 		let mut past_code = ParaPastCodeMeta::default();
@@ -754,6 +1487,124 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn force_calls_require_root_and_registered_para() {
+		let paras = vec![
+			(0u32.into(), ParaGenesisArgs {
+				parachain: true,
+				genesis_head: Default::default(),
+				validation_code: Default::default(),
+			}),
+		];
+
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig { paras, ..Default::default() },
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			let id_a = ParaId::from(0u32);
+			let id_b = ParaId::from(1u32);
+
+			assert_noop!(
+				Paras::force_set_current_head(Origin::signed(1), id_a, vec![1].into()),
+				DispatchError::BadOrigin,
+			);
+			assert_noop!(
+				Paras::force_set_current_head(Origin::root(), id_b, vec![1].into()),
+				Error::<Test>::NotRegistered,
+			);
+			assert_noop!(
+				Paras::force_set_current_code(Origin::root(), id_b, vec![1].into()),
+				Error::<Test>::NotRegistered,
+			);
+
+			assert_ok!(Paras::force_set_current_head(Origin::root(), id_a, vec![1].into()));
+			assert_eq!(Paras::para_head(&id_a), Some(vec![1].into()));
+		});
+	}
+
+	#[test]
+	fn force_set_current_code_keeps_past_code() {
+		let acceptance_period = 10;
+		let paras = vec![
+			(0u32.into(), ParaGenesisArgs {
+				parachain: true,
+				genesis_head: Default::default(),
+				validation_code: vec![1, 2, 3].into(),
+			}),
+		];
+
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig { paras, ..Default::default() },
+			configuration: crate::configuration::GenesisConfig {
+				config: HostConfiguration {
+					acceptance_period,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			let id_a = ParaId::from(0u32);
+
+			run_to_block(2, None);
+
+			assert_ok!(Paras::force_set_current_code(Origin::root(), id_a, vec![4, 5, 6].into()));
+
+			assert_eq!(Paras::current_code(&id_a), Some(vec![4, 5, 6].into()));
+			assert_eq!(<Paras as Store>::PastCode::get(&(id_a, 2)), Some(vec![1, 2, 3].into()));
+			assert_eq!(<Paras as Store>::PastCodePruning::get(), vec![(id_a, 2)]);
+		});
+	}
+
+	#[test]
+	fn force_schedule_code_upgrade_applied_by_force_note_new_head() {
+		let paras = vec![
+			(0u32.into(), ParaGenesisArgs {
+				parachain: true,
+				genesis_head: Default::default(),
+				validation_code: vec![1, 2, 3].into(),
+			}),
+		];
+
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig { paras, ..Default::default() },
+			configuration: crate::configuration::GenesisConfig {
+				config: HostConfiguration {
+					acceptance_period: 10,
+					..Default::default()
+				},
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			let id_a = ParaId::from(0u32);
+
+			assert_ok!(Paras::force_schedule_code_upgrade(Origin::root(), id_a, vec![4, 5, 6].into(), 3));
+			assert_noop!(
+				Paras::force_schedule_code_upgrade(Origin::root(), id_a, vec![7].into(), 3),
+				Error::<Test>::CodeUpgradeAlreadyScheduled,
+			);
+
+			// not yet due.
+			run_to_block(2, None);
+			assert_ok!(Paras::force_note_new_head(Origin::root(), id_a, vec![1].into()));
+			assert_eq!(Paras::current_code(&id_a), Some(vec![1, 2, 3].into()));
+			assert_eq!(Paras::future_code_upgrade_at(&id_a), Some(3));
+
+			run_to_block(3, None);
+			assert_ok!(Paras::force_note_new_head(Origin::root(), id_a, vec![2].into()));
+			assert_eq!(Paras::para_head(&id_a), Some(vec![2].into()));
+			assert_eq!(Paras::current_code(&id_a), Some(vec![4, 5, 6].into()));
+			assert_eq!(Paras::future_code_upgrade_at(&id_a), None);
+		});
+	}
+
 	#[test]
 	fn note_past_code_sets_up_pruning_correctly() {
 		let acceptance_period = 10;
@@ -1123,6 +1974,7 @@ mod tests {
 
 			assert_eq!(<Paras as Store>::UpcomingParas::get(), vec![c, b, a]);
 			assert!(<Paras as Store>::Parathreads::get(&a).is_none());
+			assert_eq!(Paras::para_lifecycle(&a), Some(ParaLifecycle::Onboarding));
 
 
 			// run to block without session change.
@@ -1139,6 +1991,9 @@ mod tests {
 			assert_eq!(<Paras as Store>::UpcomingParas::get(), Vec::new());
 
 			assert!(<Paras as Store>::Parathreads::get(&a).is_some());
+			assert_eq!(Paras::para_lifecycle(&a), Some(ParaLifecycle::Parathread));
+			assert_eq!(Paras::para_lifecycle(&b), Some(ParaLifecycle::Parachain));
+			assert!(paras_event_deposited(Event::ParaLifecycleChanged(a, ParaLifecycle::Parathread)));
 
 			assert_eq!(Paras::current_code(&a), Some(vec![2].into()));
 			assert_eq!(Paras::current_code(&b), Some(vec![1].into()));
@@ -1210,6 +2065,183 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn genesis_paras_have_lifecycles() {
+		let paras = vec![
+			(0u32.into(), ParaGenesisArgs {
+				parachain: true,
+				genesis_head: Default::default(),
+				validation_code: Default::default(),
+			}),
+			(1u32.into(), ParaGenesisArgs {
+				parachain: false,
+				genesis_head: Default::default(),
+				validation_code: Default::default(),
+			}),
+		];
+
+		let genesis_config = MockGenesisConfig {
+			paras: GenesisConfig { paras, ..Default::default() },
+			..Default::default()
+		};
+
+		new_test_ext(genesis_config).execute_with(|| {
+			assert_eq!(Paras::para_lifecycle(ParaId::from(0)), Some(ParaLifecycle::Parachain));
+			assert_eq!(Paras::para_lifecycle(ParaId::from(1)), Some(ParaLifecycle::Parathread));
+			assert!(Paras::is_parathread(ParaId::from(1)));
+			assert!(Paras::para_lifecycle(ParaId::from(2)).is_none());
+		});
+	}
+
+	#[test]
+	fn para_upgrade_and_downgrade_at_session() {
+		new_test_ext(Default::default()).execute_with(|| {
+			run_to_block(1, None);
+
+			let chain = ParaId::from(100);
+			let thread = ParaId::from(200);
+
+			for &(id, parachain) in &[(chain, true), (thread, false)] {
+				Paras::schedule_para_initialize(id, ParaGenesisArgs {
+					parachain,
+					genesis_head: vec![1].into(),
+					validation_code: vec![1].into(),
+				});
+			}
+
+			// kind changes are only possible for live paras.
+			assert_noop!(Paras::schedule_parathread_upgrade(thread), Error::<Test>::CannotUpgrade);
+			assert_noop!(Paras::schedule_parachain_downgrade(chain), Error::<Test>::CannotDowngrade);
+
+			run_to_block(2, Some(vec![2]));
+
+			assert_eq!(Paras::parachains(), vec![chain]);
+			assert!(Paras::is_parathread(thread));
+
+			// a para can only be changed into the other kind.
+			assert_noop!(Paras::schedule_parathread_upgrade(chain), Error::<Test>::CannotUpgrade);
+			assert_noop!(Paras::schedule_parachain_downgrade(thread), Error::<Test>::CannotDowngrade);
+
+			assert_ok!(Paras::schedule_parathread_upgrade(thread));
+			assert_ok!(Paras::schedule_parachain_downgrade(chain));
+
+			assert_eq!(Paras::para_lifecycle(&thread), Some(ParaLifecycle::UpgradingToParachain));
+			assert_eq!(Paras::para_lifecycle(&chain), Some(ParaLifecycle::DowngradingToParathread));
+			assert!(paras_event_deposited(
+				Event::ParaLifecycleChanged(thread, ParaLifecycle::UpgradingToParachain),
+			));
+
+			// and only once per session.
+			assert_noop!(Paras::schedule_parathread_upgrade(thread), Error::<Test>::CannotUpgrade);
+			assert_noop!(Paras::schedule_parachain_downgrade(chain), Error::<Test>::CannotDowngrade);
+
+			// nothing changes until the session boundary.
+			run_to_block(3, None);
+
+			assert_eq!(Paras::parachains(), vec![chain]);
+			assert!(Paras::is_parathread(thread));
+
+			run_to_block(4, Some(vec![4]));
+
+			assert_eq!(Paras::parachains(), vec![thread]);
+			assert!(Paras::is_parathread(chain));
+			assert!(!Paras::is_parathread(thread));
+			assert!(Paras::is_valid_para(chain));
+			assert!(Paras::is_valid_para(thread));
+
+			assert_eq!(Paras::para_lifecycle(&thread), Some(ParaLifecycle::Parachain));
+			assert_eq!(Paras::para_lifecycle(&chain), Some(ParaLifecycle::Parathread));
+			assert!(<Paras as Store>::UpcomingUpgrades::get().is_empty());
+			assert!(<Paras as Store>::UpcomingDowngrades::get().is_empty());
+
+			// the head data and code are kept across the change.
+			assert_eq!(Paras::para_head(&thread), Some(vec![1].into()));
+			assert_eq!(Paras::current_code(&thread), Some(vec![1].into()));
+		});
+	}
+
+	#[test]
+	fn para_lifecycles_are_migrated() {
+		new_test_ext(Default::default()).execute_with(|| {
+			let (chain, thread, upcoming, outgoing) =
+				(ParaId::from(1), ParaId::from(2), ParaId::from(3), ParaId::from(4));
+
+			<Paras as Store>::Parachains::put(vec![chain, outgoing]);
+			<Paras as Store>::Parathreads::insert(&thread, ());
+			<Paras as Store>::UpcomingParas::put(vec![upcoming]);
+			<Paras as Store>::OutgoingParas::put(vec![outgoing]);
+
+			Paras::on_runtime_upgrade();
+
+			assert_eq!(Paras::para_lifecycle(&chain), Some(ParaLifecycle::Parachain));
+			assert_eq!(Paras::para_lifecycle(&thread), Some(ParaLifecycle::Parathread));
+			assert_eq!(Paras::para_lifecycle(&upcoming), Some(ParaLifecycle::Onboarding));
+			assert_eq!(Paras::para_lifecycle(&outgoing), Some(ParaLifecycle::Offboarding));
+
+			// A migrated parathread can be upgraded, and migrating again changes nothing.
+			assert_ok!(Paras::schedule_parathread_upgrade(thread));
+			Paras::on_runtime_upgrade();
+			assert_eq!(Paras::para_lifecycle(&thread), Some(ParaLifecycle::UpgradingToParachain));
+		});
+	}
+
+	#[test]
+	fn para_cleanup_cancels_kind_change() {
+		new_test_ext(Default::default()).execute_with(|| {
+			run_to_block(1, None);
+
+			let thread = ParaId::from(200);
+
+			Paras::schedule_para_initialize(thread, ParaGenesisArgs {
+				parachain: false,
+				genesis_head: vec![1].into(),
+				validation_code: vec![1].into(),
+			});
+
+			run_to_block(2, Some(vec![2]));
+
+			assert_ok!(Paras::schedule_parathread_upgrade(thread));
+			assert_eq!(<Paras as Store>::UpcomingUpgrades::get(), vec![thread]);
+
+			Paras::schedule_para_cleanup(thread);
+
+			assert_eq!(Paras::para_lifecycle(&thread), Some(ParaLifecycle::Offboarding));
+			assert!(<Paras as Store>::UpcomingUpgrades::get().is_empty());
+			assert_noop!(Paras::schedule_parathread_upgrade(thread), Error::<Test>::CannotUpgrade);
+
+			run_to_block(3, Some(vec![3]));
+
+			assert!(Paras::parachains().is_empty());
+			assert!(!Paras::is_valid_para(thread));
+			assert!(Paras::para_lifecycle(&thread).is_none());
+			assert!(paras_event_deposited(Event::ParaOffboarded(thread)));
+		});
+	}
+
+	#[test]
+	fn para_cleanup_of_onboarding_para_removes_lifecycle() {
+		new_test_ext(Default::default()).execute_with(|| {
+			run_to_block(1, None);
+
+			let thread = ParaId::from(200);
+
+			Paras::schedule_para_initialize(thread, ParaGenesisArgs {
+				parachain: false,
+				genesis_head: vec![1].into(),
+				validation_code: vec![1].into(),
+			});
+			Paras::schedule_para_cleanup(thread);
+
+			assert!(Paras::para_lifecycle(&thread).is_none());
+			assert!(paras_event_deposited(Event::ParaOffboarded(thread)));
+
+			run_to_block(2, Some(vec![2]));
+
+			assert!(!Paras::is_valid_para(thread));
+			assert!(Paras::para_lifecycle(&thread).is_none());
+		});
+	}
+
 	#[test]
 	fn code_at_with_intermediate() {
 		let acceptance_period = 10;
@@ -1324,4 +2356,182 @@ mod tests {
 			assert_eq!(Paras::validation_code_at(para_id, 3, None), Some(new_code.clone()));
 		});
 	}
+
+	#[test]
+	fn pvf_check_upgrade_accepted_by_supermajority() {
+		let paras = vec![
+			(0u32.into(), ParaGenesisArgs {
+				parachain: true,
+				genesis_head: Default::default(),
+				validation_code: vec![1, 2, 3].into(),
+			}),
+		];
+
+		new_test_ext(pvf_checking_genesis(paras)).execute_with(|| {
+			let para_id = ParaId::from(0);
+			let new_code = ValidationCode(vec![4, 5, 6]);
+			let code_hash = BlakeTwo256::hash(&new_code.0);
+
+			run_to_block(1, None);
+			new_pvf_check_session(1);
+
+			Paras::schedule_code_upgrade(para_id, new_code.clone(), 6);
+
+			// the upgrade is not scheduled until the code is accepted.
+			assert!(<Paras as Store>::FutureCodeUpgrades::get(&para_id).is_none());
+			assert_eq!(Paras::pvfs_require_precheck(), vec![code_hash]);
+			assert_eq!(Paras::validation_code_by_hash(code_hash), Some(new_code.clone()));
+			assert!(paras_event_deposited(Event::PvfCheckStarted(code_hash, para_id)));
+
+			// another upgrade can't be scheduled while the vote is ongoing.
+			Paras::schedule_code_upgrade(para_id, vec![7].into(), 6);
+			assert_eq!(Paras::pvfs_require_precheck(), vec![code_hash]);
+
+			run_to_block(2, None);
+
+			assert_ok!(include_pvf_check_statement(0, code_hash, true, 1));
+			assert_ok!(include_pvf_check_statement(1, code_hash, true, 1));
+			assert_noop!(
+				include_pvf_check_statement(1, code_hash, false, 1),
+				Error::<Test>::PvfCheckDoubleVote,
+			);
+			assert_noop!(
+				include_pvf_check_statement(2, code_hash, true, 2),
+				Error::<Test>::PvfCheckStatementFuture,
+			);
+			assert!(<Paras as Store>::FutureCodeUpgrades::get(&para_id).is_none());
+
+			// 3 out of 4 is a supermajority.
+			assert_ok!(include_pvf_check_statement(2, code_hash, true, 1));
+
+			assert!(Paras::pvfs_require_precheck().is_empty());
+			assert!(Paras::validation_code_by_hash(code_hash).is_none());
+			assert_eq!(<Paras as Store>::FutureCodeUpgrades::get(&para_id), Some(2 + 5));
+			assert_eq!(<Paras as Store>::FutureCode::get(&para_id), Some(new_code));
+			assert!(paras_event_deposited(Event::PvfCheckAccepted(code_hash, para_id)));
+
+			assert_noop!(
+				include_pvf_check_statement(3, code_hash, true, 1),
+				Error::<Test>::PvfCheckSubjectInvalid,
+			);
+		});
+	}
+
+	#[test]
+	fn pvf_check_onboarding_rejected() {
+		new_test_ext(pvf_checking_genesis(Vec::new())).execute_with(|| {
+			let a = ParaId::from(111);
+			let b = ParaId::from(222);
+			let code_a = ValidationCode(vec![1]);
+			let code_b = ValidationCode(vec![2]);
+			let hash_a = BlakeTwo256::hash(&code_a.0);
+			let hash_b = BlakeTwo256::hash(&code_b.0);
+
+			run_to_block(1, None);
+			new_pvf_check_session(1);
+
+			Paras::schedule_para_initialize(a, ParaGenesisArgs {
+				parachain: true,
+				genesis_head: vec![1].into(),
+				validation_code: code_a,
+			});
+			Paras::schedule_para_initialize(b, ParaGenesisArgs {
+				parachain: true,
+				genesis_head: vec![2].into(),
+				validation_code: code_b,
+			});
+
+			assert!(Paras::upcoming_paras().is_empty());
+			assert_eq!(Paras::pvfs_require_precheck(), vec![hash_a, hash_b]);
+
+			// 2 out of 4 rejecting means a supermajority can't be reached anymore.
+			assert_ok!(include_pvf_check_statement(0, hash_a, false, 1));
+			assert_ok!(include_pvf_check_statement(1, hash_a, false, 1));
+			assert!(paras_event_deposited(Event::PvfCheckRejected(hash_a, a)));
+			assert!(paras_event_deposited(Event::ParaOffboarded(a)));
+			assert!(<Paras as Store>::UpcomingParasGenesis::get(&a).is_none());
+			assert_eq!(Paras::para_lifecycle(&a), None);
+
+			assert_ok!(include_pvf_check_statement(0, hash_b, true, 1));
+			assert_ok!(include_pvf_check_statement(1, hash_b, false, 1));
+			assert_ok!(include_pvf_check_statement(2, hash_b, true, 1));
+			assert_ok!(include_pvf_check_statement(3, hash_b, true, 1));
+			assert!(paras_event_deposited(Event::PvfCheckAccepted(hash_b, b)));
+			assert_eq!(Paras::upcoming_paras(), vec![b]);
+
+			run_to_block(2, None);
+			new_pvf_check_session(2);
+
+			assert_eq!(Paras::parachains(), vec![b]);
+			assert!(Paras::current_code(&a).is_none());
+			assert_eq!(Paras::current_code(&b), Some(vec![2].into()));
+		});
+	}
+
+	#[test]
+	fn pvf_check_statement_weight_accounts_for_causes() {
+		new_test_ext(pvf_checking_genesis(Vec::new())).execute_with(|| {
+			let code = ValidationCode(vec![1]);
+			let code_hash = BlakeTwo256::hash(&code.0);
+			let genesis = ParaGenesisArgs {
+				parachain: true,
+				genesis_head: vec![1].into(),
+				validation_code: code,
+			};
+
+			run_to_block(1, None);
+			new_pvf_check_session(1);
+
+			Paras::schedule_para_initialize(ParaId::from(111), genesis.clone());
+			let one_cause = Paras::include_pvf_check_statement_weight(&code_hash);
+
+			Paras::schedule_para_initialize(ParaId::from(222), genesis);
+			let two_causes = Paras::include_pvf_check_statement_weight(&code_hash);
+			assert!(two_causes > one_cause);
+
+			// nothing is enacted by a statement which doesn't conclude the vote.
+			let post_info = include_pvf_check_statement(0, code_hash, true, 1).unwrap();
+			assert_eq!(post_info.actual_weight, Some(Paras::pvf_check_statement_weight(0)));
+		});
+	}
+
+	#[test]
+	fn pvf_check_vote_times_out() {
+		new_test_ext(pvf_checking_genesis(Vec::new())).execute_with(|| {
+			let a = ParaId::from(111);
+			let code = ValidationCode(vec![1]);
+			let code_hash = BlakeTwo256::hash(&code.0);
+
+			run_to_block(1, None);
+			new_pvf_check_session(1);
+
+			Paras::schedule_para_initialize(a, ParaGenesisArgs {
+				parachain: false,
+				genesis_head: vec![1].into(),
+				validation_code: code,
+			});
+			assert_ok!(include_pvf_check_statement(0, code_hash, true, 1));
+
+			// the vote survives the first session change, but the ballots are reset.
+			run_to_block(2, None);
+			new_pvf_check_session(2);
+
+			assert_eq!(Paras::pvfs_require_precheck(), vec![code_hash]);
+			assert_noop!(
+				include_pvf_check_statement(1, code_hash, true, 1),
+				Error::<Test>::PvfCheckStatementStale,
+			);
+			assert_ok!(include_pvf_check_statement(0, code_hash, true, 2));
+
+			// and times out at the second one.
+			run_to_block(3, None);
+			new_pvf_check_session(3);
+
+			assert!(Paras::pvfs_require_precheck().is_empty());
+			assert!(<Paras as Store>::PvfActiveVoteMap::get(&code_hash).is_none());
+			assert!(<Paras as Store>::UpcomingParasGenesis::get(&a).is_none());
+			assert!(paras_event_deposited(Event::PvfCheckRejected(code_hash, a)));
+			assert!(<Paras as Store>::Parathreads::get(&a).is_none());
+		});
+	}
 }
@@ -25,7 +25,8 @@ use primitives::v1::{
 	Id as ParaId, OccupiedCoreAssumption, SessionIndex, ValidationCode,
 	CommittedCandidateReceipt, ScheduledCore, OccupiedCore, CoreOccupied, CoreIndex,
 	GroupIndex, CandidateEvent, PersistedValidationData, SessionInfo,
-	InboundDownwardMessage, InboundHrmpMessage, Hash,
+	InboundDownwardMessage, InboundHrmpMessage, Hash, PvfCheckStatement, ValidatorSignature,
+	OutboundHrmpBandwidth, ParaLifecycle, PvfExecutionLimits,
 };
 use fabric_support::debug;
 use crate::{initializer, inclusion, scheduler, configuration, paras, session_info, dmp, hrmp};
@@ -278,11 +279,21 @@ where
 		.collect()
 }
 
+/// Implementation for the `para_lifecycle` function of the runtime API.
+pub fn para_lifecycle<T: paras::Config>(para_id: ParaId) -> Option<ParaLifecycle> {
+	<paras::Module<T>>::para_lifecycle(para_id)
+}
+
 /// Get the session info for the given session, if stored.
 pub fn session_info<T: session_info::Config>(index: SessionIndex) -> Option<SessionInfo> {
 	<session_info::Module<T>>::session_info(index)
 }
 
+/// Implementation for the `earliest_stored_session` function of the runtime API.
+pub fn earliest_stored_session<T: session_info::Config>() -> SessionIndex {
+	<session_info::Module<T>>::earliest_stored_session()
+}
+
 /// Implementation for the `dmq_contents` function of the runtime API.
 pub fn dmq_contents<T: dmp::Config>(
 	recipient: ParaId,
@@ -296,3 +307,32 @@ pub fn inbound_hrmp_channels_contents<T: hrmp::Config>(
 ) -> BTreeMap<ParaId, Vec<InboundHrmpMessage<T::BlockNumber>>> {
 	<hrmp::Module<T>>::inbound_hrmp_channels_contents(recipient)
 }
+
+/// Implementation for the `outbound_hrmp_channels_bandwidth` function of the runtime API.
+pub fn outbound_hrmp_channels_bandwidth<T: hrmp::Config>(
+	sender: ParaId,
+) -> OutboundHrmpBandwidth {
+	<hrmp::Module<T>>::outbound_hrmp_channels_bandwidth(sender)
+}
+
+/// Implementation for the `pvfs_require_precheck` function of the runtime API.
+pub fn pvfs_require_precheck<T: paras::Config>() -> Vec<Hash> {
+	<paras::Module<T>>::pvfs_require_precheck()
+}
+
+/// Implementation for the `validation_code_by_hash` function of the runtime API.
+pub fn validation_code_by_hash<T: paras::Config>(hash: Hash) -> Option<ValidationCode> {
+	<paras::Module<T>>::validation_code_by_hash(hash)
+}
+
+/// Implementation for the `pvf_execution_limits` function of the runtime API.
+pub fn pvf_execution_limits<T: configuration::Config>() -> PvfExecutionLimits {
+	<configuration::Module<T>>::config().pvf_execution_limits()
+}
+
+/// Implementation for the `submit_pvf_check_statement` function of the runtime API.
+pub fn submit_pvf_check_statement<T>(stmt: PvfCheckStatement, signature: ValidatorSignature)
+	where T: paras::Config + fabric_system::offchain::SendTransactionTypes<paras::Call<T>>
+{
+	<paras::Module<T>>::submit_pvf_check_statement(stmt, signature)
+}
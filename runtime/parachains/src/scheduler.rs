@@ -42,7 +42,8 @@ use primitives::v1::{
 	GroupIndex, ParathreadClaim, ParathreadEntry, GroupRotationInfo, ScheduledCore,
 };
 use fabric_support::{
-	decl_storage, decl_module, decl_error,
+	decl_storage, decl_module, decl_error, ensure,
+	dispatch::DispatchResult,
 	weights::Weight,
 };
 use tetsy_scale_codec::{Encode, Decode};
@@ -153,7 +154,25 @@ impl CoreAssignment {
 	}
 }
 
-pub trait Config: fabric_system::Config + configuration::Config + paras::Config { }
+/// Something which is told about parathread claims leaving the scheduler.
+pub trait OnParathreadClaimRemoved {
+	/// A candidate of the parathread was included under its claim.
+	fn on_claim_fulfilled(para: ParaId);
+
+	/// The claim of the parathread was dropped without any candidate being included under it,
+	/// either because it ran out of retries or because the para is no longer a parathread.
+	fn on_claim_dropped(para: ParaId);
+}
+
+impl OnParathreadClaimRemoved for () {
+	fn on_claim_fulfilled(_: ParaId) { }
+	fn on_claim_dropped(_: ParaId) { }
+}
+
+pub trait Config: fabric_system::Config + configuration::Config + paras::Config {
+	/// Handler for the removal of parathread claims.
+	type OnParathreadClaimRemoved: OnParathreadClaimRemoved;
+}
 
 decl_storage! {
 	trait Store for Module<T: Config> as ParaScheduler {
@@ -200,7 +219,14 @@ decl_storage! {
 }
 
 decl_error! {
-	pub enum Error for Module<T: Config> { }
+	pub enum Error for Module<T: Config> {
+		/// The claimed para is not a parathread.
+		NotParathread,
+		/// The parathread claim queue is full.
+		ClaimQueueFull,
+		/// There is already a claim for the parathread in the queue or on a core.
+		ClaimAlreadyExists,
+	}
 }
 
 decl_module! {
@@ -289,6 +315,9 @@ impl<T: Config> Module<T> {
 		ParathreadClaimIndex::mutate(|claim_index| {
 			// wipe all parathread metadata if no parathread cores are configured.
 			if config.parathread_cores == 0 {
+				for queued in &thread_queue.queue {
+					T::OnParathreadClaimRemoved::on_claim_dropped(queued.claim.claim.0);
+				}
 				thread_queue = ParathreadClaimQueue {
 					queue: Vec::new(),
 					next_core_offset: 0,
@@ -309,6 +338,8 @@ impl<T: Config> Module<T> {
 					if let Ok(i) = claim_index.binary_search(&claim_para) {
 						claim_index.remove(i);
 					}
+
+					T::OnParathreadClaimRemoved::on_claim_dropped(claim_para);
 				}
 
 				will_keep
@@ -336,13 +367,20 @@ impl<T: Config> Module<T> {
 	/// Fails if the claim does not correspond to any live parathread.
 	#[allow(unused)]
 	pub fn add_parathread_claim(claim: ParathreadClaim) {
-		if !<paras::Module<T>>::is_parathread(claim.0) { return }
+		let _ = Self::try_add_parathread_claim(claim);
+	}
+
+	/// Add a parathread claim to the queue, returning an error if it could not be added.
+	///
+	/// See [`add_parathread_claim`](Self::add_parathread_claim) for the conditions.
+	pub fn try_add_parathread_claim(claim: ParathreadClaim) -> DispatchResult {
+		ensure!(<paras::Module<T>>::is_parathread(claim.0), Error::<T>::NotParathread);
 
 		let config = <configuration::Module<T>>::config();
 		let queue_max_size = config.parathread_cores * config.scheduling_lookahead;
 
-		ParathreadQueue::mutate(|queue| {
-			if queue.queue.len() >= queue_max_size as usize { return }
+		ParathreadQueue::try_mutate(|queue| {
+			ensure!(queue.queue.len() < queue_max_size as usize, Error::<T>::ClaimQueueFull);
 
 			let para_id = claim.0;
 
@@ -356,13 +394,20 @@ impl<T: Config> Module<T> {
 				}
 			});
 
-			if competes_with_another { return }
+			ensure!(!competes_with_another, Error::<T>::ClaimAlreadyExists);
 
 			let entry = ParathreadEntry { claim, retries: 0 };
 			queue.enqueue_entry(entry, config.parathread_cores);
+
+			Ok(())
 		})
 	}
 
+	/// The number of parathread claims waiting in the queue.
+	pub fn parathread_queue_len() -> u32 {
+		ParathreadQueue::get().queue.len() as u32
+	}
+
 	/// Schedule all unassigned cores, where possible. Provide a list of cores that should be considered
 	/// newly-freed along with the reason for them being freed. The list is assumed to be sorted in
 	/// ascending order by core index.
@@ -387,7 +432,9 @@ impl<T: Config> Module<T> {
 									if let Ok(i) = index.binary_search(&entry.claim.0) {
 										index.remove(i);
 									}
-								})
+								});
+
+								T::OnParathreadClaimRemoved::on_claim_fulfilled(entry.claim.0);
 							}
 							FreedReason::TimedOut => {
 								// If a parathread candidate times out, it's not the collator's fault,
@@ -715,15 +762,24 @@ impl<T: Config> Module<T> {
 		ParathreadQueue::mutate(|queue| {
 			for core_assignment in Scheduled::take() {
 				if let AssignmentKind::Parathread(collator, retries) = core_assignment.kind {
-					if !<paras::Module<T>>::is_parathread(core_assignment.para_id) { continue }
-
 					let entry = ParathreadEntry {
 						claim: ParathreadClaim(core_assignment.para_id, collator),
 						retries: retries + 1,
 					};
 
-					if entry.retries <= config.parathread_retries {
+					if entry.retries <= config.parathread_retries
+						&& <paras::Module<T>>::is_parathread(core_assignment.para_id)
+					{
 						queue.enqueue_entry(entry, config.parathread_cores);
+					} else {
+						// the claim is dropped, so open the parathread up for further claims.
+						ParathreadClaimIndex::mutate(|index| {
+							if let Ok(i) = index.binary_search(&core_assignment.para_id) {
+								index.remove(i);
+							}
+						});
+
+						T::OnParathreadClaimRemoved::on_claim_dropped(core_assignment.para_id);
 					}
 				}
 			}
@@ -1666,6 +1722,10 @@ mod tests {
 
 			run_to_block(2 + max_retries + 1, |_| None);
 			assert_eq!(Scheduler::scheduled().len(), 0);
+
+			// the dropped claims no longer block new ones.
+			assert!(ParathreadClaimIndex::get().is_empty());
+			assert!(Scheduler::try_add_parathread_claim(ParathreadClaim(thread_a, collator.clone())).is_ok());
 		});
 	}
 
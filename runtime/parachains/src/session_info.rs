@@ -184,7 +184,11 @@ mod tests {
 			Configuration::initializer_finalize();
 
 			if let Some(notification) = new_session(b + 1) {
-				Configuration::initializer_on_new_session(&notification.validators, &notification.queued);
+				Configuration::initializer_on_new_session(
+					&notification.validators,
+					&notification.queued,
+					notification.session_index,
+				);
 				SessionInfo::initializer_on_new_session(&notification);
 			}
 
@@ -20,8 +20,13 @@ use crate::{
 };
 use tetcore_std::{fmt, prelude::*};
 use tetcore_std::collections::{btree_map::BTreeMap, vec_deque::VecDeque};
-use fabric_support::{decl_module, decl_storage, StorageMap, StorageValue, weights::Weight, traits::Get};
+use fabric_support::{
+	decl_module, decl_storage, decl_event, decl_error, StorageMap, StorageValue, weights::Weight, traits::Get,
+	dispatch::DispatchResultWithPostInfo,
+};
+use fabric_system::ensure_signed;
 use primitives::v1::{Id as ParaId, UpwardMessage};
+use xcm::v0::Outcome;
 
 /// All upward messages coming from parachains will be funneled into an implementation of this trait.
 ///
@@ -32,6 +37,10 @@ use primitives::v1::{Id as ParaId, UpwardMessage};
 /// returns the amount of weight consumed in the process of handling. Ignoring a message is a valid
 /// strategy.
 ///
+/// Each message is given a maximum weight which it may consume. A sink which cannot handle a message
+/// within that weight must leave it untouched and report the weight it would require, so that the
+/// message may be retried later with a greater allowance.
+///
 /// There are no guarantees on how much time it takes for the message sent by a candidate to end up
 /// in the sink after the candidate was enacted. That typically depends on the UMP traffic, the sizes
 /// of upward messages and the configuration of UMP.
@@ -39,52 +48,84 @@ use primitives::v1::{Id as ParaId, UpwardMessage};
 /// It is possible that by the time the message is sank the origin parachain was offboarded. It is
 /// up to the implementer to check that if it cares.
 pub trait UmpSink {
-	/// Process an incoming upward message and return the amount of weight it consumed.
+	/// Process an incoming upward message and return the amount of weight it consumed, or, if it
+	/// could not be processed within `max_weight`, the message id along with the weight it requires.
 	///
 	/// See the trait docs for more details.
-	fn process_upward_message(origin: ParaId, msg: Vec<u8>) -> Weight;
+	fn process_upward_message(
+		origin: ParaId,
+		msg: &[u8],
+		max_weight: Weight,
+	) -> Result<Weight, (MessageId, Weight)>;
 }
 
 /// An implementation of a sink that just swallows the message without consuming any weight.
 impl UmpSink for () {
-	fn process_upward_message(_: ParaId, _: Vec<u8>) -> Weight {
-		0
+	fn process_upward_message(_: ParaId, _: &[u8], _: Weight) -> Result<Weight, (MessageId, Weight)> {
+		Ok(0)
 	}
 }
 
+/// The identifier of an upward message, as reported in events. This is the blake2-256 hash of the message.
+pub type MessageId = [u8; 32];
+
+/// The index of a message in the overweight queue.
+pub type OverweightIndex = u64;
+
 /// A specific implementation of a UmpSink where messages are in the XCM format
 /// and will be forwarded to the XCM Executor.
-pub struct XcmSink<Config>(tetcore_std::marker::PhantomData<Config>);
-
-impl<Config: xcm_executor::Config> UmpSink for XcmSink<Config> {
-	fn process_upward_message(origin: ParaId, msg: Vec<u8>) -> Weight {
+///
+/// The outcome of every message is reported through an event of the UMP module of `T`.
+pub struct XcmSink<XcmConfig, T>(tetcore_std::marker::PhantomData<(XcmConfig, T)>);
+
+impl<XcmConfig: xcm_executor::Config, T: Config> UmpSink for XcmSink<XcmConfig, T> {
+	fn process_upward_message(
+		origin: ParaId,
+		msg: &[u8],
+		max_weight: Weight,
+	) -> Result<Weight, (MessageId, Weight)> {
 		use tetsy_scale_codec::Decode;
 		use xcm::VersionedXcm;
-		use xcm::v0::{Junction, MultiLocation, ExecuteXcm};
+		use tetcore_std::convert::TryFrom;
+		use xcm::v0::{Junction, MultiLocation, ExecuteXcm, Xcm, Error as XcmError};
 		use xcm_executor::XcmExecutor;
 
-		let weight: Weight = 0;
+		let id = tet_io::hashing::blake2_256(msg);
 
-		if let Ok(versioned_xcm_message) = VersionedXcm::decode(&mut &msg[..]) {
-			match versioned_xcm_message {
-				VersionedXcm::V0(xcm_message) => {
-					let xcm_junction: Junction = Junction::Parachain { id: origin.into() };
-					let xcm_location: MultiLocation = xcm_junction.into();
-					// TODO: Do something with result.
-					let _result = XcmExecutor::<Config>::execute_xcm(xcm_location, xcm_message);
-				}
+		let versioned_xcm_message = match VersionedXcm::decode(&mut &msg[..]) {
+			Ok(versioned_xcm_message) => versioned_xcm_message,
+			Err(_) => {
+				fabric_support::debug::error!(
+					target: "xcm",
+					"Failed to decode versioned XCM from upward message.",
+				);
+				<Module<T>>::deposit_event(Event::InvalidFormat(id));
+				return Ok(0)
 			}
-		} else {
-			fabric_support::debug::error!(
-				target: "xcm",
-				"Failed to decode versioned XCM from upward message.",
-			);
-		}
+		};
 
-		// TODO: to be sound, this implementation must ensure that returned (and thus consumed)
-		// weight is limited to some small portion of the total block weight (as a ballpark, 1/4, 1/8
-		// or lower).
-		weight
+		// Messages of any later version are interpreted in the version understood by the executor.
+		let outcome = match Xcm::try_from(versioned_xcm_message) {
+			Ok(xcm_message) => {
+				let xcm_junction: Junction = Junction::Parachain { id: origin.into() };
+				let xcm_location: MultiLocation = xcm_junction.into();
+				XcmExecutor::<XcmConfig>::execute_xcm(xcm_location, xcm_message, max_weight)
+			}
+			Err(()) => Outcome::Error(XcmError::UnhandledXcmVersion),
+		};
+
+		match outcome {
+			Outcome::Error(XcmError::WeightLimitReached(required)) => {
+				// Nothing was executed; the message stays with the caller.
+				<Module<T>>::deposit_event(Event::WeightExhausted(id, max_weight, required));
+				Err((id, required))
+			}
+			outcome => {
+				let weight_used = outcome.weight_used();
+				<Module<T>>::deposit_event(Event::ExecutedUpward(id, outcome));
+				Ok(weight_used)
+			}
+		}
 	}
 }
 
@@ -142,6 +183,9 @@ impl fmt::Debug for AcceptanceCheckErr {
 }
 
 pub trait Config: fabric_system::Config + configuration::Config {
+	/// The outer event type.
+	type Event: From<Event> + Into<<Self as fabric_system::Config>::Event>;
+
 	/// A place where all received upward messages are funneled.
 	type UmpSink: UmpSink;
 }
@@ -185,15 +229,77 @@ decl_storage! {
 		/// Invariant:
 		/// - If `Some(para)`, then `para` must be present in `NeedsDispatch`.
 		NextDispatchRoundStartWith: Option<ParaId>;
+
+		/// Upward messages which could never be executed within the weight of a dispatch round, along
+		/// with the para which sent them. They may be executed by `service_overweight`.
+		Overweight: map hasher(twox_64_concat) OverweightIndex => Option<(ParaId, UpwardMessage)>;
+		/// The number of messages ever placed in `Overweight`, and therefore the index of the next.
+		OverweightCount: OverweightIndex;
+	}
+}
+
+decl_event! {
+	pub enum Event {
+		/// Upward message is invalid XCM. [id]
+		InvalidFormat(MessageId),
+		/// Upward message executed with the given outcome. [id, outcome]
+		ExecutedUpward(MessageId, Outcome),
+		/// Upward message could not be executed within the remaining weight. [id, remaining, required]
+		WeightExhausted(MessageId, Weight, Weight),
+		/// Upward message requires more weight than a dispatch round provides and was placed in the
+		/// overweight queue. [para, id, overweight_index, required]
+		OverweightEnqueued(ParaId, MessageId, OverweightIndex, Weight),
+		/// Upward message from the overweight queue was executed with the given weight.
+		/// [overweight_index, used]
+		OverweightServiced(OverweightIndex, Weight),
+	}
+}
+
+decl_error! {
+	pub enum Error for Module<T: Config> {
+		/// The message index given is unknown.
+		UnknownMessageIndex,
+		/// The weight limit given is not enough to execute the message.
+		WeightOverLimit,
 	}
 }
 
 decl_module! {
 	/// The UMP module.
 	pub struct Module<T: Config> for enum Call where origin: <T as fabric_system::Config>::Origin {
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// Execute an overweight message.
+		///
+		/// The caller pays for the execution up front through `weight_limit`, which must be enough
+		/// for the message to be executed in full. Unused weight is refunded.
+		///
+		/// - `index`: The index of the message in the overweight queue, as given by the
+		///   `OverweightEnqueued` event.
+		/// - `weight_limit`: The maximum amount of weight the message may consume.
+		#[weight = weight_limit.saturating_add(SERVICE_OVERWEIGHT_BASE_WEIGHT)]
+		pub fn service_overweight(
+			origin,
+			index: OverweightIndex,
+			weight_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			ensure_signed(origin)?;
+
+			let (sender, data) = Overweight::get(index).ok_or(Error::<T>::UnknownMessageIndex)?;
+			let used = T::UmpSink::process_upward_message(sender, &data[..], weight_limit)
+				.map_err(|_| Error::<T>::WeightOverLimit)?;
+			Overweight::remove(index);
+			Self::deposit_event(Event::OverweightServiced(index, used));
+			Ok(Some(used.saturating_add(SERVICE_OVERWEIGHT_BASE_WEIGHT)).into())
+		}
 	}
 }
 
+/// The weight of `service_overweight` besides that of executing the message.
+const SERVICE_OVERWEIGHT_BASE_WEIGHT: Weight = 1_000_000;
+
 /// Routines related to the upward message passing.
 impl<T: Config> Module<T> {
 	/// Block initialization logic, called by initializer.
@@ -330,28 +436,56 @@ impl<T: Config> Module<T> {
 	}
 
 	/// Devote some time into dispatching pending upward messages.
-	pub(crate) fn process_pending_upward_messages() {
+	///
+	/// Each message is given whatever remains of the preferred weight of the dispatching stage. A
+	/// message which does not fit is left at the front of its queue for a later stage, unless it
+	/// would not fit even in a whole stage, in which case it is moved to the overweight queue.
+	///
+	/// Returns the weight consumed by dispatching the messages, which is at most
+	/// `max_upward_message_dispatch_weight`.
+	pub(crate) fn process_pending_upward_messages() -> Weight {
 		let mut used_weight_so_far = 0;
 
 		let config = <configuration::Module<T>>::config();
+		let step_weight = config.preferred_dispatchable_upward_messages_step_weight;
 		let mut cursor = NeedsDispatchCursor::new::<T>();
 		let mut queue_cache = QueueCache::new();
 
 		while let Some(dispatchee) = cursor.peek() {
-			if used_weight_so_far >= config.preferred_dispatchable_upward_messages_step_weight {
+			if used_weight_so_far >= step_weight {
 				// Then check whether we've reached or overshoot the
 				// preferred weight for the dispatching stage.
 				//
 				// if so - bail.
 				break;
 			}
-
-			// dequeue the next message from the queue of the dispatchee
-			let (upward_message, became_empty) = queue_cache.dequeue::<T>(dispatchee);
-			if let Some(upward_message) = upward_message {
-				used_weight_so_far +=
-					T::UmpSink::process_upward_message(dispatchee, upward_message);
-			}
+			let max_weight = step_weight - used_weight_so_far;
+
+			// peek the next message from the queue of the dispatchee, dequeuing it only once it
+			// has been handled.
+			let became_empty = match queue_cache.peek_front::<T>(dispatchee) {
+				Some(upward_message) => {
+					match T::UmpSink::process_upward_message(dispatchee, &upward_message[..], max_weight) {
+						Ok(used) => {
+							used_weight_so_far += used;
+							queue_cache.consume_front::<T>(dispatchee)
+						}
+						Err((id, required)) if required > step_weight => {
+							// This message will never fit into a dispatching stage. Stash it away so
+							// that it doesn't block the queue.
+							let upward_message = upward_message.clone();
+							let index = Self::stash_overweight(dispatchee, upward_message);
+							Self::deposit_event(Event::OverweightEnqueued(dispatchee, id, index, required));
+							queue_cache.consume_front::<T>(dispatchee)
+						}
+						Err(_) => {
+							// The message fits into a stage, but not into what remains of this one.
+							break;
+						}
+					}
+				}
+				None => true,
+			};
 
 			if became_empty {
 				// the queue is empty now - this para doesn't need attention anymore.
@@ -363,6 +497,24 @@ impl<T: Config> Module<T> {
 
 		cursor.flush::<T>();
 		queue_cache.flush::<T>();
+
+		used_weight_so_far
+	}
+
+	/// The most weight that `process_pending_upward_messages` may consume in a block.
+	pub(crate) fn max_upward_message_dispatch_weight() -> Weight {
+		<configuration::Module<T>>::config().preferred_dispatchable_upward_messages_step_weight
+	}
+
+	/// Place a message into the overweight queue, returning its index there.
+	fn stash_overweight(sender: ParaId, upward_message: UpwardMessage) -> OverweightIndex {
+		let index = OverweightCount::mutate(|count| {
+			let index = *count;
+			*count += 1;
+			index
+		});
+		Overweight::insert(index, (sender, upward_message));
+		index
 	}
 }
 
@@ -397,14 +549,27 @@ impl QueueCache {
 		Self(BTreeMap::new())
 	}
 
-	/// Dequeues one item from the upward message queue of the given para.
-	///
-	/// Returns `(upward_message, became_empty)`, where
+	/// Returns the item at the front of the upward message queue of the given para, or `None` if
+	/// the queue is empty.
+	fn peek_front<T: Config>(&mut self, para: ParaId) -> Option<&UpwardMessage> {
+		self.ensure_cached::<T>(para).queue.front()
+	}
+
+	/// Removes the item at the front of the upward message queue of the given para.
 	///
-	/// - `upward_message` a dequeued message or `None` if the queue _was_ empty.
-	/// - `became_empty` is true if the queue _became_ empty.
-	fn dequeue<T: Config>(&mut self, para: ParaId) -> (Option<UpwardMessage>, bool) {
-		let cache_entry = self.0.entry(para).or_insert_with(|| {
+	/// Returns `true` if the queue _became_ empty.
+	fn consume_front<T: Config>(&mut self, para: ParaId) -> bool {
+		let cache_entry = self.ensure_cached::<T>(para);
+		if let Some(msg) = cache_entry.queue.pop_front() {
+			cache_entry.count -= 1;
+			cache_entry.total_size -= msg.len() as u32;
+		}
+
+		cache_entry.queue.is_empty()
+	}
+
+	fn ensure_cached<T: Config>(&mut self, para: ParaId) -> &mut QueueCacheEntry {
+		self.0.entry(para).or_insert_with(|| {
 			let queue = <Module<T> as Store>::RelayDispatchQueues::get(&para);
 			let (count, total_size) = <Module<T> as Store>::RelayDispatchQueueSize::get(&para);
 			QueueCacheEntry {
@@ -412,15 +577,7 @@ impl QueueCache {
 				count,
 				total_size,
 			}
-		});
-		let upward_message = cache_entry.queue.pop_front();
-		if let Some(ref msg) = upward_message {
-			cache_entry.count -= 1;
-			cache_entry.total_size -= msg.len() as u32;
-		}
-
-		let became_empty = cache_entry.queue.is_empty();
-		(upward_message, became_empty)
+		})
 	}
 
 	/// Flushes the updated queues into the storage.
@@ -535,6 +692,8 @@ pub(crate) mod mock_sink {
 	//!
 	//! A probe can be attached to the mock UMP sink. When attached, the mock sink would consult the
 	//! probe to check whether the received message was expected and what weight it should return.
+	//! The probe may also make the sink reject a message as overweight. Otherwise, the sink doesn't
+	//! check the weight it returns against the limit it was given.
 	//!
 	//! There are two rules on how to use a probe:
 	//!
@@ -544,7 +703,7 @@ pub(crate) mod mock_sink {
 	//! 2. All messages expected by the probe must be received by the time of dropping it. Unreceived
 	//!    messages will lead to a panic while dropping a probe.
 
-	use super::{UmpSink, UpwardMessage, ParaId};
+	use super::{UmpSink, UpwardMessage, ParaId, MessageId};
 	use std::cell::RefCell;
 	use std::collections::vec_deque::VecDeque;
 	use fabric_support::weights::Weight;
@@ -554,6 +713,7 @@ pub(crate) mod mock_sink {
 		expected_origin: ParaId,
 		expected_msg: UpwardMessage,
 		mock_weight: Weight,
+		overweight: bool,
 	}
 
 	std::thread_local! {
@@ -563,13 +723,18 @@ pub(crate) mod mock_sink {
 
 	pub struct MockUmpSink;
 	impl UmpSink for MockUmpSink {
-		fn process_upward_message(actual_origin: ParaId, actual_msg: Vec<u8>) -> Weight {
+		fn process_upward_message(
+			actual_origin: ParaId,
+			actual_msg: &[u8],
+			_max_weight: Weight,
+		) -> Result<Weight, (MessageId, Weight)> {
 			HOOK.with(|opt_hook| match &mut *opt_hook.borrow_mut() {
 				Some(hook) => {
 					let UmpExpectation {
 						expected_origin,
 						expected_msg,
 						mock_weight,
+						overweight,
 					} = match hook.pop_front() {
 						Some(expectation) => expectation,
 						None => {
@@ -580,10 +745,14 @@ pub(crate) mod mock_sink {
 						}
 					};
 					assert_eq!(expected_origin, actual_origin);
-					assert_eq!(expected_msg, actual_msg);
-					mock_weight
+					assert_eq!(&expected_msg[..], actual_msg);
+					if overweight {
+						Err((tet_io::hashing::blake2_256(actual_msg), mock_weight))
+					} else {
+						Ok(mock_weight)
+					}
 				}
-				None => 0,
+				None => Ok(0),
 			})
 		}
 	}
@@ -614,6 +783,28 @@ pub(crate) mod mock_sink {
 			expected_origin: ParaId,
 			expected_msg: UpwardMessage,
 			mock_weight: Weight,
+		) {
+			Self::push_expectation(expected_origin, expected_msg, mock_weight, false);
+		}
+
+		/// Add an expected message which the sink will refuse to process, reporting that it requires
+		/// `required_weight`.
+		///
+		/// The enqueued messages are processed in FIFO order.
+		pub fn assert_overweight_msg(
+			&mut self,
+			expected_origin: ParaId,
+			expected_msg: UpwardMessage,
+			required_weight: Weight,
+		) {
+			Self::push_expectation(expected_origin, expected_msg, required_weight, true);
+		}
+
+		fn push_expectation(
+			expected_origin: ParaId,
+			expected_msg: UpwardMessage,
+			mock_weight: Weight,
+			overweight: bool,
 		) {
 			HOOK.with(|opt_hook| {
 				opt_hook
@@ -624,6 +815,7 @@ pub(crate) mod mock_sink {
 						expected_origin,
 						expected_msg,
 						mock_weight,
+						overweight,
 					})
 			});
 		}
@@ -661,7 +853,7 @@ pub(crate) mod mock_sink {
 mod tests {
 	use super::*;
 	use super::mock_sink::Probe;
-	use crate::mock::{Configuration, Ump, new_test_ext, GenesisConfig as MockGenesisConfig};
+	use crate::mock::{Configuration, Ump, Test, new_test_ext, GenesisConfig as MockGenesisConfig};
 	use fabric_support::IterableStorageMap;
 	use std::collections::HashSet;
 
@@ -825,7 +1017,7 @@ mod tests {
 
 				probe.assert_msg(a, a_msg_1.clone(), 300);
 				probe.assert_msg(c, c_msg_1.clone(), 300);
-				Ump::process_pending_upward_messages();
+				assert_eq!(Ump::process_pending_upward_messages(), 600);
 				assert_storage_consistency_exhaustive();
 
 				drop(probe);
@@ -839,7 +1031,7 @@ mod tests {
 				let mut probe = Probe::new();
 
 				probe.assert_msg(q, q_msg.clone(), 500);
-				Ump::process_pending_upward_messages();
+				assert_eq!(Ump::process_pending_upward_messages(), 500);
 				assert_storage_consistency_exhaustive();
 
 				drop(probe);
@@ -851,7 +1043,7 @@ mod tests {
 
 				probe.assert_msg(a, a_msg_2.clone(), 100);
 				probe.assert_msg(c, c_msg_2.clone(), 100);
-				Ump::process_pending_upward_messages();
+				assert_eq!(Ump::process_pending_upward_messages(), 200);
 				assert_storage_consistency_exhaustive();
 
 				drop(probe);
@@ -861,7 +1053,7 @@ mod tests {
 			{
 				let probe = Probe::new();
 
-				Ump::process_pending_upward_messages();
+				assert_eq!(Ump::process_pending_upward_messages(), 0);
 				assert_storage_consistency_exhaustive();
 
 				drop(probe);
@@ -910,6 +1102,124 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn dispatch_keeps_message_exceeding_remaining_weight() {
+		let a = ParaId::from(128);
+		let c = ParaId::from(228);
+
+		let a_msg_1 = vec![1, 2, 3];
+		let c_msg_1 = vec![4, 5, 6];
+
+		new_test_ext(
+			GenesisConfigBuilder {
+				preferred_dispatchable_upward_messages_step_weight: 500,
+				..Default::default()
+			}
+			.build(),
+		)
+		.execute_with(|| {
+			queue_upward_msg(a, a_msg_1.clone());
+			queue_upward_msg(c, c_msg_1.clone());
+
+			// the message of `c` doesn't fit into what remains of the stage, but it would fit into
+			// a whole one, so it should stay at the front of the queue.
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_msg(a, a_msg_1.clone(), 300);
+				probe.assert_overweight_msg(c, c_msg_1.clone(), 400);
+				Ump::process_pending_upward_messages();
+				assert_storage_consistency_exhaustive();
+
+				drop(probe);
+			}
+
+			assert_eq!(<Ump as Store>::RelayDispatchQueues::get(&c), vec![c_msg_1.clone()]);
+			assert_eq!(<Ump as Store>::OverweightCount::get(), 0);
+
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_msg(c, c_msg_1.clone(), 400);
+				Ump::process_pending_upward_messages();
+				assert_storage_consistency_exhaustive();
+
+				drop(probe);
+			}
+
+			assert!(<Ump as Store>::NeedsDispatch::get().is_empty());
+		});
+	}
+
+	#[test]
+	fn overweight_message_is_stashed_and_serviced() {
+		use crate::mock::Origin;
+		use fabric_support::{assert_noop, assert_ok};
+
+		let a = ParaId::from(128);
+
+		let a_msg_1 = vec![1, 2, 3];
+		let a_msg_2 = vec![3, 2, 1];
+
+		new_test_ext(
+			GenesisConfigBuilder {
+				preferred_dispatchable_upward_messages_step_weight: 500,
+				..Default::default()
+			}
+			.build(),
+		)
+		.execute_with(|| {
+			queue_upward_msg(a, a_msg_1.clone());
+			queue_upward_msg(a, a_msg_2.clone());
+
+			// the first message can never fit into a stage so it should be moved out of the way,
+			// letting the second message through.
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_overweight_msg(a, a_msg_1.clone(), 600);
+				probe.assert_msg(a, a_msg_2.clone(), 100);
+				Ump::process_pending_upward_messages();
+				assert_storage_consistency_exhaustive();
+
+				drop(probe);
+			}
+
+			assert!(<Ump as Store>::NeedsDispatch::get().is_empty());
+			assert_eq!(<Ump as Store>::OverweightCount::get(), 1);
+			assert_eq!(<Ump as Store>::Overweight::get(0), Some((a, a_msg_1.clone())));
+
+			assert_noop!(
+				Ump::service_overweight(Origin::signed(1), 1, 1000),
+				Error::<Test>::UnknownMessageIndex,
+			);
+
+			// too little weight given leaves the message in place.
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_overweight_msg(a, a_msg_1.clone(), 600);
+				assert_noop!(
+					Ump::service_overweight(Origin::signed(1), 0, 500),
+					Error::<Test>::WeightOverLimit,
+				);
+
+				drop(probe);
+			}
+
+			{
+				let mut probe = Probe::new();
+
+				probe.assert_msg(a, a_msg_1.clone(), 600);
+				assert_ok!(Ump::service_overweight(Origin::signed(1), 0, 1000));
+
+				drop(probe);
+			}
+
+			assert_eq!(<Ump as Store>::Overweight::get(0), None);
+		});
+	}
+
 	#[test]
 	fn verify_relay_dispatch_queue_size_is_externally_accessible() {
 		// Make sure that the relay dispatch queue size storage entry is accessible via well known
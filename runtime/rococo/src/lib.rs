@@ -29,7 +29,8 @@ use primitives::v1::{
 	GroupRotationInfo, CoreState, Id, ValidationCode, CandidateEvent,
 	ValidatorId, ValidatorIndex, CommittedCandidateReceipt, OccupiedCoreAssumption,
 	PersistedValidationData, InboundDownwardMessage, InboundHrmpMessage,
-	SessionInfo as SessionInfoData,
+	SessionInfo as SessionInfoData, PvfCheckStatement, ValidatorSignature, OutboundHrmpBandwidth,
+	ParaLifecycle, PvfExecutionLimits,
 };
 use runtime_common::{
 	SlowAdjustingFeeUpdate, impls::ToAuthor, BlockHashCount, BlockWeights, BlockLength, RocksDbWeight,
@@ -61,7 +62,7 @@ use tet_core::OpaqueMetadata;
 use tp_staking::SessionIndex;
 use noble_session::historical as session_historical;
 use fabric_system::{EnsureRoot, EnsureOneOf, EnsureSigned};
-use runtime_common::{paras_sudo_wrapper, paras_registrar};
+use runtime_common::{paras_sudo_wrapper, paras_registrar, xcm_queries};
 
 use runtime_parachains::origin as parachains_origin;
 use runtime_parachains::configuration as parachains_configuration;
@@ -69,6 +70,7 @@ use runtime_parachains::inclusion as parachains_inclusion;
 use runtime_parachains::inclusion_inherent as parachains_inclusion_inherent;
 use runtime_parachains::initializer as parachains_initializer;
 use runtime_parachains::session_info as parachains_session_info;
+use runtime_parachains::disputes as parachains_disputes;
 use runtime_parachains::paras as parachains_paras;
 use runtime_parachains::dmp as parachains_dmp;
 use runtime_parachains::ump as parachains_ump;
@@ -83,7 +85,9 @@ use xcm_executor::traits::IsConcrete;
 use xcm_builder::{
 	AccountId32Aliases, ChildParachainConvertsVia, SovereignSignedViaLocation,
 	CurrencyAdapter as XcmCurrencyAdapter, ChildParachainAsNative,
-	SignedAccountId32AsNative, ChildSystemParachainAsSuperuser, LocationInverter,
+	SignedAccountId32AsNative, ChildSystemParachainAsSuperuser, LocationInverter, FixedWeightBounds,
+	UsingComponents, TakeWeightCredit, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom,
+	IsChildSystemParachain, AllowHrmpChannelManagementFrom, IsChildParachain,
 };
 use constants::{time::*, currency::*, fee::*};
 
@@ -190,15 +194,17 @@ construct_runtime! {
 		Inclusion: parachains_inclusion::{Module, Call, Storage, Event<T>},
 		InclusionInherent: parachains_inclusion_inherent::{Module, Call, Storage, Inherent},
 		Scheduler: parachains_scheduler::{Module, Call, Storage},
-		Paras: parachains_paras::{Module, Call, Storage},
+		Paras: parachains_paras::{Module, Call, Storage, Event, ValidateUnsigned},
 		Initializer: parachains_initializer::{Module, Call, Storage},
 		Dmp: parachains_dmp::{Module, Call, Storage},
-		Ump: parachains_ump::{Module, Call, Storage},
+		Ump: parachains_ump::{Module, Call, Storage, Event},
 		Hrmp: parachains_hrmp::{Module, Call, Storage},
 		SessionInfo: parachains_session_info::{Module, Call, Storage},
+		ParasDisputes: parachains_disputes::{Module, Call, Storage, Event<T>, ValidateUnsigned},
 
-		Registrar: paras_registrar::{Module, Call, Storage},
+		Registrar: paras_registrar::{Module, Call, Storage, Event<T>},
 		ParasSudoWrapper: paras_sudo_wrapper::{Module, Call},
+		XcmQueries: xcm_queries::{Module, Call, Storage, Event},
 
 		// Sudo
 		Sudo: noble_sudo::{Module, Call, Storage, Event<T>, Config<T>},
@@ -356,6 +362,7 @@ impl<C> fabric_system::offchain::SendTransactionTypes<C> for Runtime where
 
 parameter_types! {
 	pub const ParathreadDeposit: Balance = 5 * DOLLARS;
+	pub const ParathreadClaimFee: Balance = 10 * CENTS;
 	pub const QueueSize: usize = 2;
 	pub const MaxRetries: u32 = 3;
 }
@@ -495,7 +502,9 @@ impl noble_authorship::Config for Runtime {
 
 impl parachains_origin::Config for Runtime {}
 
-impl parachains_configuration::Config for Runtime {}
+impl parachains_configuration::Config for Runtime {
+	type CurrentSessionIndex = parachains_inclusion::CurrentSession<Runtime>;
+}
 
 /// Special `RewardValidators` that does nothing ;)
 pub struct RewardValidators;
@@ -507,9 +516,11 @@ impl runtime_parachains::inclusion::RewardValidators for RewardValidators {
 impl parachains_inclusion::Config for Runtime {
 	type Event = Event;
 	type RewardValidators = RewardValidators;
+	type DisputesHandler = ParasDisputes;
 }
 
 impl parachains_paras::Config for Runtime {
+	type Event = Event;
 	type Origin = Origin;
 }
 
@@ -543,21 +554,59 @@ type LocalOriginConverter = (
 	ChildSystemParachainAsSuperuser<ParaId, Origin>,
 );
 
+parameter_types! {
+	pub const BaseXcmWeight: Weight = 100_000_000;
+	pub const MaxXcmRelayDepth: u32 = 4;
+}
+
+pub type Barrier = (
+	// Weight that is paid for may be consumed.
+	TakeWeightCredit,
+	// Any location may execute a message if it pays for it up front (the unit filter allows everything).
+	AllowTopLevelPaidExecutionFrom<()>,
+	// System parachains are trusted and need not pay.
+	AllowUnpaidExecutionFrom<IsChildSystemParachain<ParaId>>,
+	// Parachains may manage their HRMP channels, the deposits being taken from their sovereign accounts.
+	AllowHrmpChannelManagementFrom<IsChildParachain>,
+);
+
 pub struct XcmConfig;
 impl xcm_executor::Config for XcmConfig {
 	type Call = Call;
 	type XcmSender = ();
 	type AssetTransactor = LocalAssetTransactor;
+	type AssetExchanger = ();
 	type OriginConverter = LocalOriginConverter;
 	type IsReserve = ();
 	type IsTeleporter = ();
 	type LocationInverter = LocationInverter<Ancestry>;
+	type MaxRelayDepth = MaxXcmRelayDepth;
+	type Barrier = Barrier;
+	type Weigher = FixedWeightBounds<BaseXcmWeight>;
+	type Trader = UsingComponents<WeightToFee, RocLocation, AccountId, Balances, ToAuthor<Runtime>>;
+	type ResponseHandler = XcmQueries;
+	type HrmpChannelManager = parachains_hrmp::XcmChannelManager<Runtime>;
+}
+
+parameter_types! {
+	pub const XcmQueryTimeout: BlockNumber = 10 * MINUTES;
+}
+
+impl xcm_queries::Config for Runtime {
+	type Event = Event;
+	type QueryTimeout = XcmQueryTimeout;
 }
 
 impl parachains_session_info::Config for Runtime {}
 
+impl parachains_disputes::Config for Runtime {
+	type Event = Event;
+	type SlashingHandler = ();
+}
+
 impl parachains_ump::Config for Runtime {
-	type UmpSink = crate::parachains_ump::XcmSink<XcmConfig>;
+	type Event = Event;
+	type UmpSink = crate::parachains_ump::XcmSink<XcmConfig, Runtime>;
 }
 
 impl parachains_dmp::Config for Runtime {}
@@ -569,7 +618,9 @@ impl parachains_hrmp::Config for Runtime {
 
 impl parachains_inclusion_inherent::Config for Runtime {}
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type OnParathreadClaimRemoved = Registrar;
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = Babe;
@@ -578,8 +629,10 @@ impl parachains_initializer::Config for Runtime {
 impl paras_sudo_wrapper::Config for Runtime {}
 
 impl paras_registrar::Config for Runtime {
+	type Event = Event;
 	type Currency = Balances;
 	type ParathreadDeposit = ParathreadDeposit;
+	type ParathreadClaimFee = ParathreadClaimFee;
 	type Origin = Origin;
 }
 
@@ -740,10 +793,18 @@ tp_api::impl_runtime_apis! {
 			})
 		}
 
+		fn para_lifecycle(para_id: Id) -> Option<ParaLifecycle> {
+			runtime_api_impl::para_lifecycle::<Runtime>(para_id)
+		}
+
 		fn session_info(index: SessionIndex) -> Option<SessionInfoData> {
 			runtime_api_impl::session_info::<Runtime>(index)
 		}
 
+		fn earliest_stored_session() -> SessionIndex {
+			runtime_api_impl::earliest_stored_session::<Runtime>()
+		}
+
 		fn dmq_contents(recipient: Id) -> Vec<InboundDownwardMessage<BlockNumber>> {
 			runtime_api_impl::dmq_contents::<Runtime>(recipient)
 		}
@@ -753,6 +814,36 @@ tp_api::impl_runtime_apis! {
 		) -> BTreeMap<Id, Vec<InboundHrmpMessage<BlockNumber>>> {
 			runtime_api_impl::inbound_hrmp_channels_contents::<Runtime>(recipient)
 		}
+
+		fn outbound_hrmp_channels_bandwidth(sender: Id) -> OutboundHrmpBandwidth {
+			runtime_api_impl::outbound_hrmp_channels_bandwidth::<Runtime>(sender)
+		}
+
+		fn pvfs_require_precheck() -> Vec<Hash> {
+			runtime_api_impl::pvfs_require_precheck::<Runtime>()
+		}
+
+		fn validation_code_by_hash(hash: Hash) -> Option<ValidationCode> {
+			runtime_api_impl::validation_code_by_hash::<Runtime>(hash)
+		}
+
+		fn pvf_execution_limits() -> PvfExecutionLimits {
+			runtime_api_impl::pvf_execution_limits::<Runtime>()
+		}
+
+		fn submit_pvf_check_statement(stmt: PvfCheckStatement, signature: ValidatorSignature) {
+			runtime_api_impl::submit_pvf_check_statement::<Runtime>(stmt, signature)
+		}
+	}
+
+	impl primitives::v1::ConfigurationApi<Block, parachains_configuration::HostConfiguration<BlockNumber>>
+		for Runtime
+	{
+		fn pending_host_configurations()
+			-> Vec<(SessionIndex, parachains_configuration::HostConfiguration<BlockNumber>)>
+		{
+			parachains_configuration::Module::<Runtime>::pending_configs()
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
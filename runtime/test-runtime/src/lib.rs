@@ -30,6 +30,7 @@ use tetcoin_runtime_parachains::inclusion as parachains_inclusion;
 use tetcoin_runtime_parachains::inclusion_inherent as parachains_inclusion_inherent;
 use tetcoin_runtime_parachains::initializer as parachains_initializer;
 use tetcoin_runtime_parachains::session_info as parachains_session_info;
+use tetcoin_runtime_parachains::disputes as parachains_disputes;
 use tetcoin_runtime_parachains::paras as parachains_paras;
 use tetcoin_runtime_parachains::dmp as parachains_dmp;
 use tetcoin_runtime_parachains::ump as parachains_ump;
@@ -41,7 +42,8 @@ use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
 	CoreState, GroupRotationInfo, Hash as HashT, Id as ParaId, Moment, Nonce, OccupiedCoreAssumption,
 	PersistedValidationData, Signature, ValidationCode, ValidatorId, ValidatorIndex,
-	InboundDownwardMessage, InboundHrmpMessage, SessionInfo as SessionInfoData,
+	InboundDownwardMessage, InboundHrmpMessage, SessionInfo as SessionInfoData, PvfCheckStatement,
+	OutboundHrmpBandwidth, ParaLifecycle, PvfExecutionLimits, ValidatorSignature,
 };
 use runtime_common::{
 	claims, SlowAdjustingFeeUpdate, paras_sudo_wrapper,
@@ -446,11 +448,14 @@ impl noble_sudo::Config for Runtime {
 	type Call = Call;
 }
 
-impl parachains_configuration::Config for Runtime {}
+impl parachains_configuration::Config for Runtime {
+	type CurrentSessionIndex = parachains_inclusion::CurrentSession<Runtime>;
+}
 
 impl parachains_inclusion::Config for Runtime {
 	type Event = Event;
 	type RewardValidators = RewardValidatorsWithEraPoints<Runtime>;
+	type DisputesHandler = ParasDisputes;
 }
 
 impl parachains_inclusion_inherent::Config for Runtime {}
@@ -461,13 +466,20 @@ impl parachains_initializer::Config for Runtime {
 
 impl parachains_session_info::Config for Runtime {}
 
+impl parachains_disputes::Config for Runtime {
+	type Event = Event;
+	type SlashingHandler = ();
+}
+
 impl parachains_paras::Config for Runtime {
+	type Event = Event;
 	type Origin = Origin;
 }
 
 impl parachains_dmp::Config for Runtime {}
 
 impl parachains_ump::Config for Runtime {
+	type Event = Event;
 	type UmpSink = ();
 }
 
@@ -476,7 +488,9 @@ impl parachains_hrmp::Config for Runtime {
 	type Currency = Balances;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type OnParathreadClaimRemoved = ();
+}
 
 impl paras_sudo_wrapper::Config for Runtime {}
 
@@ -518,10 +532,12 @@ construct_runtime! {
 		Inclusion: parachains_inclusion::{Module, Call, Storage, Event<T>},
 		InclusionInherent: parachains_inclusion_inherent::{Module, Call, Storage, Inherent},
 		Initializer: parachains_initializer::{Module, Call, Storage},
-		Paras: parachains_paras::{Module, Call, Storage, Origin},
+		Paras: parachains_paras::{Module, Call, Storage, Origin, Event, ValidateUnsigned},
 		Scheduler: parachains_scheduler::{Module, Call, Storage},
 		ParasSudoWrapper: paras_sudo_wrapper::{Module, Call},
 		SessionInfo: parachains_session_info::{Module, Call, Storage},
+		ParasDisputes: parachains_disputes::{Module, Call, Storage, Event<T>, ValidateUnsigned},
+		Ump: parachains_ump::{Module, Call, Storage, Event},
 
 		Sudo: noble_sudo::{Module, Call, Storage, Config<T>, Event<T>},
 	}
@@ -678,10 +694,18 @@ tp_api::impl_runtime_apis! {
 			runtime_impl::candidate_events::<Runtime, _>(|trait_event| trait_event.try_into().ok())
 		}
 
+		fn para_lifecycle(para_id: ParaId) -> Option<ParaLifecycle> {
+			runtime_impl::para_lifecycle::<Runtime>(para_id)
+		}
+
 		fn session_info(index: SessionIndex) -> Option<SessionInfoData> {
 			runtime_impl::session_info::<Runtime>(index)
 		}
 
+		fn earliest_stored_session() -> SessionIndex {
+			runtime_impl::earliest_stored_session::<Runtime>()
+		}
+
 		fn dmq_contents(
 			recipient: ParaId,
 		) -> Vec<InboundDownwardMessage<BlockNumber>> {
@@ -693,6 +717,36 @@ tp_api::impl_runtime_apis! {
 		) -> BTreeMap<ParaId, Vec<InboundHrmpMessage<BlockNumber>>> {
 			runtime_impl::inbound_hrmp_channels_contents::<Runtime>(recipient)
 		}
+
+		fn outbound_hrmp_channels_bandwidth(sender: ParaId) -> OutboundHrmpBandwidth {
+			runtime_impl::outbound_hrmp_channels_bandwidth::<Runtime>(sender)
+		}
+
+		fn pvfs_require_precheck() -> Vec<HashT> {
+			runtime_impl::pvfs_require_precheck::<Runtime>()
+		}
+
+		fn validation_code_by_hash(hash: HashT) -> Option<ValidationCode> {
+			runtime_impl::validation_code_by_hash::<Runtime>(hash)
+		}
+
+		fn pvf_execution_limits() -> PvfExecutionLimits {
+			runtime_impl::pvf_execution_limits::<Runtime>()
+		}
+
+		fn submit_pvf_check_statement(stmt: PvfCheckStatement, signature: ValidatorSignature) {
+			runtime_impl::submit_pvf_check_statement::<Runtime>(stmt, signature)
+		}
+	}
+
+	impl primitives::v1::ConfigurationApi<Block, parachains_configuration::HostConfiguration<BlockNumber>>
+		for Runtime
+	{
+		fn pending_host_configurations()
+			-> Vec<(SessionIndex, parachains_configuration::HostConfiguration<BlockNumber>)>
+		{
+			parachains_configuration::Module::<Runtime>::pending_configs()
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
@@ -28,7 +28,8 @@ use primitives::v1::{
 	AccountId, AccountIndex, Balance, BlockNumber, CandidateEvent, CommittedCandidateReceipt,
 	CoreState, GroupRotationInfo, Hash, Id, Moment, Nonce, OccupiedCoreAssumption,
 	PersistedValidationData, Signature, ValidationCode, ValidatorId, ValidatorIndex,
-	InboundDownwardMessage, InboundHrmpMessage, SessionInfo, AssignmentId,
+	InboundDownwardMessage, InboundHrmpMessage, SessionInfo, AssignmentId, PvfCheckStatement,
+	OutboundHrmpBandwidth, ParaLifecycle, PvfExecutionLimits, ValidatorSignature,
 };
 use runtime_common::{
 	SlowAdjustingFeeUpdate, CurrencyToVote,
@@ -876,6 +877,10 @@ tp_api::impl_runtime_apis! {
 			None
 		}
 
+		fn earliest_stored_session() -> SessionIndex {
+			0
+		}
+
 		fn validation_code(_: Id, _: OccupiedCoreAssumption) -> Option<ValidationCode> {
 			None
 		}
@@ -888,6 +893,10 @@ tp_api::impl_runtime_apis! {
 			Vec::new()
 		}
 
+		fn para_lifecycle(_: Id) -> Option<ParaLifecycle> {
+			None
+		}
+
 		fn dmq_contents(
 			_recipient: Id,
 		) -> Vec<InboundDownwardMessage<BlockNumber>> {
@@ -899,6 +908,24 @@ tp_api::impl_runtime_apis! {
 		) -> BTreeMap<Id, Vec<InboundHrmpMessage<BlockNumber>>> {
 			BTreeMap::new()
 		}
+
+		fn outbound_hrmp_channels_bandwidth(_sender: Id) -> OutboundHrmpBandwidth {
+			Default::default()
+		}
+
+		fn pvfs_require_precheck() -> Vec<Hash> {
+			Vec::new()
+		}
+
+		fn validation_code_by_hash(_hash: Hash) -> Option<ValidationCode> {
+			None
+		}
+
+		fn pvf_execution_limits() -> PvfExecutionLimits {
+			PvfExecutionLimits::default()
+		}
+
+		fn submit_pvf_check_statement(_stmt: PvfCheckStatement, _signature: ValidatorSignature) {}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
@@ -23,24 +23,106 @@
 #![no_std]
 extern crate alloc;
 
+use core::{result, convert::TryInto};
 use tetsy_scale_codec::{Encode, Decode};
 
 pub mod v0;
+pub mod v1;
+
+/// A version of the XCM format, equal to the index of its variant in `VersionedXcm`.
+pub type Version = u32;
+
+/// The most recent version of the XCM format.
+pub const LATEST_VERSION: Version = 1;
 
 /// A single XCM message, together with its version code.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
 pub enum VersionedXcm {
 	V0(v0::Xcm),
+	V1(v1::Xcm),
+}
+
+impl VersionedXcm {
+	/// The version of the format in which this message is expressed.
+	pub fn version(&self) -> Version {
+		match self {
+			VersionedXcm::V0(_) => 0,
+			VersionedXcm::V1(_) => 1,
+		}
+	}
+
+	/// Convert the message into the given version, or `Err` if the version is unknown or the message has no
+	/// representation in it.
+	pub fn into_version(self, version: Version) -> result::Result<Self, ()> {
+		Ok(match version {
+			0 => VersionedXcm::V0(self.try_into()?),
+			1 => VersionedXcm::V1(self.try_into()?),
+			_ => return Err(()),
+		})
+	}
 }
 
 /// A versioned multi-location, a relative location of a cross-consensus system identifier.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
 pub enum VersionedMultiLocation {
 	V0(v0::MultiLocation),
+	V1(v1::MultiLocation),
 }
 
 /// A versioned multi-asset, an identifier for an asset within a consensus system.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
 pub enum VersionedMultiAsset {
 	V0(v0::MultiAsset),
+	V1(v1::MultiAsset),
+}
+
+/// The most recent version of the XCM format which a destination is configured to understand.
+///
+/// This is configuration of the sending chain: nothing is learnt from the destinations themselves, so it must be
+/// kept in step with their upgrades.
+pub trait ConfiguredVersion {
+	/// The version configured for `dest`, or `None` if there is none.
+	fn configured_version_for(dest: &v1::MultiLocation) -> Option<Version>;
+}
+
+impl ConfiguredVersion for () {
+	fn configured_version_for(_dest: &v1::MultiLocation) -> Option<Version> {
+		None
+	}
+}
+
+/// Every destination speaks version 0.
+pub struct AlwaysV0;
+impl ConfiguredVersion for AlwaysV0 {
+	fn configured_version_for(_dest: &v1::MultiLocation) -> Option<Version> {
+		Some(0)
+	}
+}
+
+/// Every destination speaks version 1.
+pub struct AlwaysV1;
+impl ConfiguredVersion for AlwaysV1 {
+	fn configured_version_for(_dest: &v1::MultiLocation) -> Option<Version> {
+		Some(1)
+	}
+}
+
+/// Every destination speaks the latest version.
+pub struct AlwaysLatest;
+impl ConfiguredVersion for AlwaysLatest {
+	fn configured_version_for(_dest: &v1::MultiLocation) -> Option<Version> {
+		Some(LATEST_VERSION)
+	}
+}
+
+/// A transport for messages whose version has already been chosen for their destination.
+pub trait SendVersionedXcm {
+	/// Send the message `msg` to the destination `dest`.
+	fn send_versioned_xcm(dest: v1::MultiLocation, msg: VersionedXcm) -> v0::Result;
+}
+
+impl SendVersionedXcm for () {
+	fn send_versioned_xcm(_dest: v1::MultiLocation, _msg: VersionedXcm) -> v0::Result {
+		Err(v0::Error::Unimplemented)
+	}
 }
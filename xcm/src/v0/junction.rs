@@ -98,4 +98,21 @@ impl Junction {
 			Junction::OnlyChild => true,
 		}
 	}
+
+	/// Whether this names something within its context, such as an account or a noble, rather than a consensus
+	/// system which may be sent messages in its own right.
+	pub fn is_interior(&self) -> bool {
+		match self {
+			Junction::Parent |
+			Junction::Parachain { .. } |
+			Junction::OnlyChild => false,
+
+			Junction::AccountId32 { .. } |
+			Junction::AccountIndex64 { .. } |
+			Junction::AccountKey20 { .. } |
+			Junction::NobleInstance { .. } |
+			Junction::GeneralIndex { .. } |
+			Junction::GeneralKey(..) => true,
+		}
+	}
 }
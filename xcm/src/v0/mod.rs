@@ -31,7 +31,16 @@ pub use junction::{Junction, NetworkId};
 pub use multi_asset::{MultiAsset, AssetInstance};
 pub use multi_location::MultiLocation;
 pub use order::Order;
-pub use traits::{Error, Result, SendXcm, ExecuteXcm};
+pub use traits::{Error, Result, SendXcm, ExecuteXcm, Outcome, Weight};
+
+/// Response data to a query.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
+pub enum Response {
+	/// Some assets.
+	Assets(Vec<MultiAsset>),
+	/// The outcome of executing an XCM message.
+	ExecutionResult(Outcome),
+}
 
 // TODO: Efficient encodings for Vec<MultiAsset>, Vec<Order>, using initial byte values 128+ to encode the number of
 //   items in the vector.
@@ -122,6 +131,8 @@ pub enum Xcm {
 	/// of origin `origin_type`.
 	///
 	/// - `origin_type`: The means of expressing the message origin as a dispatch origin.
+	/// - `require_weight_at_most`: The weight of `call`; this should be at least the chain's calculated weight
+	///   and will be used in the weight determination arithmetic.
 	/// - `call`: The encoded transaction to be applied.
 	///
 	/// Safety: No concerns.
@@ -129,15 +140,20 @@ pub enum Xcm {
 	/// Kind: *Instruction*.
 	///
 	/// Errors:
-	Transact { origin_type: OriginKind, call: Vec<u8> },
+	Transact { origin_type: OriginKind, require_weight_at_most: u64, call: Vec<u8> },
 
 	/// Relay an inner message (`inner`) to a locally reachable destination ID `dest`.
 	///
 	/// The message sent to the destination will be wrapped into a `RelayedFrom` message, with the
 	/// `superorigin` being this location.
 	///
+	/// If `dest` has more than one junction, the message is forwarded hop by hop: the first junction of `dest`
+	/// is sent a `RelayedFrom` message wrapping a `RelayTo` of the remaining junctions. Junctions at the end of
+	/// `dest` which name something within a consensus system, such as an account, are forwarded along with the
+	/// message to that consensus system, which then sends the `RelayedFrom` message to them as its target.
+	///
 	/// - `dest: MultiLocation`: The location of the to be relayed into. This may never contain `Parent`, and
-	///   it must be immediately reachable from the interpreting context.
+	///   its first junction must be immediately reachable from the interpreting context.
 	/// - `inner: VersionedXcm`: The message to be wrapped and relayed.
 	///
 	/// Safety: No concerns.
@@ -203,6 +219,87 @@ pub enum Xcm {
 		#[codec(compact)] sender: u32,
 		#[codec(compact)] recipient: u32,
 	},
+
+	/// Some information relating to the query `query_id`, sent in response to an earlier message.
+	///
+	/// - `query_id`: The identifier of the query that resulted in this message being sent.
+	/// - `response`: The message content.
+	///
+	/// Safety: No concerns.
+	///
+	/// Kind: *Information*.
+	///
+	/// Errors:
+	QueryResponse { #[codec(compact)] query_id: u64, response: Response },
+
+	/// Execute the inner `message` and then send the `Outcome` of it, whatever it is, to `dest` in a
+	/// `QueryResponse` message with the given `query_id`.
+	///
+	/// - `query_id`: An identifier that will be replicated into the returned XCM message.
+	/// - `dest`: The destination for the returned XCM message. This must be the origin of this message, so that
+	///   it cannot be used to have responses sent elsewhere on its behalf.
+	/// - `message`: The message to be executed, with the same origin as this message.
+	///
+	/// Safety: No concerns.
+	///
+	/// Kind: *Instruction*.
+	///
+	/// Errors:
+	/// - `EscalationOfPrivilege`: `dest` is not the origin.
+	ReportOutcome { #[codec(compact)] query_id: u64, dest: MultiLocation, message: Box<Xcm> },
+
+	/// Request the opening of an HRMP channel from the `origin` para to the `recipient` para. This message is
+	/// meant to be sent by a para to the relay-chain.
+	///
+	/// - `recipient`: The recipient in the to-be opened channel.
+	/// - `max_message_size`: The maximum size of a message proposed by the sender.
+	/// - `max_capacity`: The maximum number of messages that can be queued in the channel.
+	///
+	/// The sender deposit is reserved from the sovereign account of `origin` and the recipient is notified with
+	/// `HrmpNewChannelOpenRequest`.
+	///
+	/// Safety: `origin` must be a para.
+	///
+	/// Kind: *Instruction*.
+	///
+	/// Errors:
+	HrmpInitOpenChannel {
+		#[codec(compact)] recipient: u32,
+		#[codec(compact)] max_message_size: u32,
+		#[codec(compact)] max_capacity: u32,
+	},
+
+	/// Accept a pending request to open an HRMP channel from the `sender` para to the `origin` para. This message
+	/// is meant to be sent by a para to the relay-chain.
+	///
+	/// - `sender`: The sender in the to-be opened channel.
+	///
+	/// The recipient deposit is reserved from the sovereign account of `origin` and the sender is notified with
+	/// `HrmpChannelAccepted`.
+	///
+	/// Safety: `origin` must be a para.
+	///
+	/// Kind: *Instruction*.
+	///
+	/// Errors:
+	HrmpAcceptOpenChannel {
+		#[codec(compact)] sender: u32,
+	},
+
+	/// Request the closing of the HRMP channel from `sender` to `recipient`, one of which must be the `origin` para.
+	/// This message is meant to be sent by a para to the relay-chain.
+	///
+	/// The other party of the channel is notified with `HrmpChannelClosing`.
+	///
+	/// Safety: `origin` must be a para.
+	///
+	/// Kind: *Instruction*.
+	///
+	/// Errors:
+	HrmpCloseChannel {
+		#[codec(compact)] sender: u32,
+		#[codec(compact)] recipient: u32,
+	},
 }
 
 impl From<Xcm> for VersionedXcm {
@@ -216,6 +313,7 @@ impl TryFrom<VersionedXcm> for Xcm {
 	fn try_from(x: VersionedXcm) -> result::Result<Self, ()> {
 		match x {
 			VersionedXcm::V0(x) => Ok(x),
+			VersionedXcm::V1(x) => Xcm::try_from(x),
 		}
 	}
 }
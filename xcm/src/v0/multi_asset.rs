@@ -146,6 +146,27 @@ pub enum MultiAsset {
 	ConcreteNonFungible { class: MultiLocation, instance: AssetInstance },
 }
 
+impl MultiAsset {
+	/// Returns `true` if `self` is a wildcard, standing for any number of assets rather than a definite asset.
+	pub fn is_wildcard(&self) -> bool {
+		match self {
+			MultiAsset::All |
+			MultiAsset::AllFungible |
+			MultiAsset::AllNonFungible |
+			MultiAsset::AllAbstractFungible { .. } |
+			MultiAsset::AllAbstractNonFungible { .. } |
+			MultiAsset::AllConcreteFungible { .. } |
+			MultiAsset::AllConcreteNonFungible { .. } => true,
+
+			MultiAsset::None |
+			MultiAsset::AbstractFungible { .. } |
+			MultiAsset::AbstractNonFungible { .. } |
+			MultiAsset::ConcreteFungible { .. } |
+			MultiAsset::ConcreteNonFungible { .. } => false,
+		}
+	}
+}
+
 impl From<MultiAsset> for VersionedMultiAsset {
 	fn from(x: MultiAsset) -> Self {
 		VersionedMultiAsset::V0(x)
@@ -157,6 +178,7 @@ impl TryFrom<VersionedMultiAsset> for MultiAsset {
 	fn try_from(x: VersionedMultiAsset) -> result::Result<Self, ()> {
 		match x {
 			VersionedMultiAsset::V0(x) => Ok(x),
+			VersionedMultiAsset::V1(x) => MultiAsset::try_from(x),
 		}
 	}
 }
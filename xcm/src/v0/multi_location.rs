@@ -388,6 +388,7 @@ impl TryFrom<VersionedMultiLocation> for MultiLocation {
 	fn try_from(x: VersionedMultiLocation) -> result::Result<Self, ()> {
 		match x {
 			VersionedMultiLocation::V0(x) => Ok(x),
+			VersionedMultiLocation::V1(x) => MultiLocation::try_from(x),
 		}
 	}
 }
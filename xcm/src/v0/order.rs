@@ -18,7 +18,7 @@
 
 use alloc::vec::Vec;
 use tetsy_scale_codec::{self, Encode, Decode};
-use super::{MultiAsset, MultiLocation};
+use super::{MultiAsset, MultiLocation, Xcm};
 
 /// An instruction to be executed on some or all of the assets in holding, used by asset-related XCM messages.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
@@ -53,10 +53,11 @@ pub enum Order {
 	/// The minimum amount of assets to be received into holding for the order not to fail may be stated.
 	///
 	/// - `give`: The asset(s) to remove from holding.
-	/// - `receive`: The minimum amount of assets(s) which `give` should be exchanged for. The meaning of wildcards
-	///   is undefined and they should be not be used.
+	/// - `receive`: The minimum amount of assets(s) which `give` should be exchanged for. A wildcard names assets
+	///   which are wanted without stating a minimum amount of them.
 	///
 	/// Errors:
+	/// - `TooMuchSlippage`: `give` could not be exchanged for at least `receive`. Holding is left unchanged.
 	ExchangeAsset { give: Vec<MultiAsset>, receive: Vec<MultiAsset> },
 
 	/// Remove the asset(s) (`assets`) from holding and send a `WithdrawAsset` XCM message to a reserve location.
@@ -89,4 +90,19 @@ pub enum Order {
 	///
 	/// Errors:
 	QueryHolding { #[codec(compact)] query_id: u64, dest: MultiLocation, assets: Vec<MultiAsset> },
+
+	/// Pay for the execution of some XCM with up to `weight` picoseconds of execution time, paying for this with
+	/// up to `fees` from the holding account.
+	///
+	/// - `fees`: The asset(s) to remove from holding to pay for fees.
+	/// - `weight`: The amount of weight to purchase; this should be at least the shallow weight of `xcm`.
+	/// - `debt`: The amount of weight-debt already incurred to be paid off; this should be equal to the unpaid
+	///   weight of any surrounding operations/orders.
+	/// - `halt_on_error`: If `true`, the execution of the `xcm` will halt on the first failure. If `false`, then
+	///   execution will continue regardless.
+	/// - `xcm`: XCM instructions to be executed outside of the context of the current holding account, with the
+	///   purchased weight.
+	///
+	/// Errors:
+	BuyExecution { fees: MultiAsset, weight: u64, debt: u64, halt_on_error: bool, xcm: Vec<Xcm> },
 }
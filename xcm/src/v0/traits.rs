@@ -21,6 +21,10 @@ use tetsy_scale_codec::{Encode, Decode};
 
 use super::{MultiLocation, Xcm};
 
+/// A measure of computational resources consumed by the execution of a message. This is expected to be
+/// compatible with the `Weight` type of the interpreting FRAME-based chain, if any.
+pub type Weight = u64;
+
 #[derive(Copy, Clone, Encode, Decode, Eq, PartialEq, Ord, PartialOrd, Debug)]
 pub enum Error {
 	Undefined,
@@ -36,6 +40,32 @@ pub enum Error {
 	MultiLocationFull,
 	FailedToDecode,
 	BadOrigin,
+	/// An arithmetic overflow happened.
+	Overflow,
+	/// The weight of the message could not be determined.
+	UnweighableMessage,
+	/// The message would need more weight than the given limit; the amount required is included.
+	WeightLimitReached(Weight),
+	/// The `Transact` call declared a maximum weight lower than that of the dispatchable it contains.
+	TooMuchWeightRequired,
+	/// The holding register does not contain the assets named for paying fees.
+	NotHoldingFees,
+	/// The fees offered are not enough to buy the weight requested.
+	TooExpensive,
+	/// The assets given could not be exchanged for at least the minimum amounts requested.
+	TooMuchSlippage,
+	/// The call of a `Transact` was dispatched but returned an error.
+	DispatchFailed,
+	/// A response was received for a query which is not expected from the origin.
+	UnexpectedResponse,
+	/// The message was rejected by the barrier before it could be executed.
+	Barrier,
+	/// The message is nested in more `RelayedFrom` layers than allowed.
+	RelayDepthExceeded,
+	/// The HRMP channel request could not be actioned by the relay-chain.
+	HrmpChannelRequestFailed,
+	/// The asset is not one which the asset transactor handles.
+	AssetNotFound,
 }
 
 impl From<()> for Error {
@@ -46,13 +76,81 @@ impl From<()> for Error {
 
 pub type Result = result::Result<(), Error>;
 
+/// Outcome of an XCM execution.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, Debug)]
+pub enum Outcome {
+	/// Execution completed successfully; given weight was used.
+	Complete(Weight),
+	/// Execution started, but did not complete successfully due to the given error; given weight was used.
+	///
+	/// The index is that of the order within the message's `effects` which failed, or `None` if it was the
+	/// instruction itself which failed after having taken effect (e.g. a `Transact` whose call returned an error).
+	Incomplete(Weight, Option<u32>, Error),
+	/// Execution did not start due to the given error.
+	Error(Error),
+}
+
+impl Outcome {
+	/// Convert into `Ok(())` if the execution completed, or the error otherwise.
+	pub fn ensure_complete(self) -> Result {
+		match self {
+			Outcome::Complete(_) => Ok(()),
+			Outcome::Incomplete(_, _, e) => Err(e),
+			Outcome::Error(e) => Err(e),
+		}
+	}
+
+	/// Convert into the weight used if the execution began, or the error otherwise.
+	pub fn ensure_execution(self) -> result::Result<Weight, Error> {
+		match self {
+			Outcome::Complete(w) => Ok(w),
+			Outcome::Incomplete(w, _, _) => Ok(w),
+			Outcome::Error(e) => Err(e),
+		}
+	}
+
+	/// How much weight was used by the XCM execution attempt.
+	pub fn weight_used(&self) -> Weight {
+		match self {
+			Outcome::Complete(w) => *w,
+			Outcome::Incomplete(w, _, _) => *w,
+			Outcome::Error(_) => 0,
+		}
+	}
+}
+
+/// Type of XCM message executor.
 pub trait ExecuteXcm {
-	fn execute_xcm(origin: MultiLocation, msg: Xcm) -> Result;
+	/// Execute the given message `msg` from `origin`, consuming no more than `weight_limit`.
+	///
+	/// The weight reported in the outcome will never be greater than `weight_limit`. If the maximum weight of
+	/// the message exceeds `weight_limit`, then nothing is executed and `Outcome::Error` is returned with
+	/// `Error::WeightLimitReached`.
+	fn execute_xcm(origin: MultiLocation, msg: Xcm, weight_limit: Weight) -> Outcome {
+		Self::execute_xcm_in_credit(origin, msg, weight_limit, 0)
+	}
+
+	/// Execute the given message `msg` from `origin`, consuming no more than `weight_limit`, where
+	/// `weight_credit` is an amount of weight which has already been paid for by other means (e.g. the
+	/// transaction fee of the extrinsic which requested the execution).
+	///
+	/// Whether any of `weight_credit` is taken into account is up to the executor's barrier.
+	fn execute_xcm_in_credit(
+		origin: MultiLocation,
+		msg: Xcm,
+		weight_limit: Weight,
+		weight_credit: Weight,
+	) -> Outcome;
 }
 
 impl ExecuteXcm for () {
-	fn execute_xcm(_origin: MultiLocation, _msg: Xcm) -> Result {
-		Err(Error::Unimplemented)
+	fn execute_xcm_in_credit(
+		_origin: MultiLocation,
+		_msg: Xcm,
+		_weight_limit: Weight,
+		_weight_credit: Weight,
+	) -> Outcome {
+		Outcome::Error(Error::Unimplemented)
 	}
 }
 
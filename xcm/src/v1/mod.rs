@@ -0,0 +1,447 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Version 1 of the Cross-Consensus Message format data structures.
+//!
+//! Compared to version 0, locations are normalised into a count of parents followed by interior junctions, and
+//! assets are split into definite assets and wildcard filters. Messages may be converted to and from version 0
+//! through `TryFrom`, which fails only where a value has no representation in the other version.
+
+use core::{result, convert::{TryFrom, TryInto}};
+use alloc::{boxed::Box, vec::Vec};
+
+use tetsy_scale_codec::{self, Encode, Decode};
+use super::{v0, VersionedXcm};
+
+mod multi_asset;
+mod multi_location;
+mod order;
+mod traits;
+pub use super::v0::{Junction, NetworkId, AssetInstance, OriginKind};
+pub use multi_asset::{AssetId, Fungibility, MultiAsset, WildFungibility, WildMultiAsset, MultiAssetFilter};
+pub use multi_location::{MultiLocation, Junctions};
+pub use order::Order;
+pub use traits::{Error, Result, SendXcm, Outcome, Weight};
+
+/// Response data to a query.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
+pub enum Response {
+	/// Some assets.
+	Assets(Vec<MultiAsset>),
+	/// The outcome of executing an XCM message.
+	ExecutionResult(Outcome),
+}
+
+/// Cross-Consensus Message: A message from one consensus system to another.
+///
+/// The instructions are those of version 0, except that `Balances` is subsumed by `QueryResponse` with
+/// `Response::Assets`, and that relay destinations and super-origins are expressed as interior `Junctions`, so they
+/// can never contain a `Parent`.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
+pub enum Xcm {
+	/// Withdraw asset(s) (`assets`) from the ownership of `origin` and place them into `holding`. Execute the
+	/// orders (`effects`).
+	///
+	/// Kind: *Instruction*.
+	WithdrawAsset { assets: Vec<MultiAsset>, effects: Vec<Order> },
+
+	/// Asset(s) (`assets`) have been received into the ownership of this system on the `origin` system. Execute
+	/// the orders (`effects`) once the corresponding derivative assets have been placed into `holding`.
+	///
+	/// Safety: `origin` must be trusted to have received and be storing `assets` such that they may later be
+	/// withdrawn should this system send a corresponding message.
+	///
+	/// Kind: *Trusted Indication*.
+	ReserveAssetDeposit { assets: Vec<MultiAsset>, effects: Vec<Order> },
+
+	/// Asset(s) (`assets`) have been destroyed on the `origin` system and equivalent assets should be created on
+	/// this system. Execute the orders (`effects`) once they have been placed into `holding`.
+	///
+	/// Safety: `origin` must be trusted to have irrevocably destroyed the `assets` prior as a consequence of
+	/// sending this message.
+	///
+	/// Kind: *Trusted Indication*.
+	TeleportAsset { assets: Vec<MultiAsset>, effects: Vec<Order> },
+
+	/// Some information relating to the query `query_id`, sent in response to an earlier message.
+	///
+	/// Kind: *Information*.
+	QueryResponse { #[codec(compact)] query_id: u64, response: Response },
+
+	/// Apply the encoded transaction `call`, whose dispatch-origin should be `origin` as expressed by the kind
+	/// of origin `origin_type`.
+	///
+	/// Kind: *Instruction*.
+	Transact { origin_type: OriginKind, require_weight_at_most: u64, call: Vec<u8> },
+
+	/// Relay an inner message (`inner`) to a locally reachable destination `dest`, hop by hop.
+	///
+	/// Kind: *Instruction*.
+	RelayTo { dest: Junctions, inner: Box<VersionedXcm> },
+
+	/// A message (`inner`) was sent to `origin` from `superorigin` with the intention of being relayed.
+	///
+	/// Safety: `superorigin` is relative to `origin` and, being `Junctions`, can only express a sub-consensus.
+	///
+	/// Kind: *Trusted Indication*.
+	RelayedFrom { superorigin: Junctions, inner: Box<VersionedXcm> },
+
+	/// A notification about a new incoming HRMP channel, sent by the relay-chain to a para.
+	///
+	/// Kind: *System Notification*
+	HrmpNewChannelOpenRequest {
+		#[codec(compact)] sender: u32,
+		#[codec(compact)] max_message_size: u32,
+		#[codec(compact)] max_capacity: u32,
+	},
+
+	/// A notification that a previously sent open channel request has been accepted by the recipient, sent by the
+	/// relay-chain to a para.
+	///
+	/// Kind: *System Notification*
+	HrmpChannelAccepted {
+		#[codec(compact)] recipient: u32,
+	},
+
+	/// A notification that the `initiator` is going to close the channel opened from `sender` to `recipient`, sent
+	/// by the relay-chain to a para.
+	///
+	/// Kind: *System Notification*
+	HrmpChannelClosing {
+		#[codec(compact)] initiator: u32,
+		#[codec(compact)] sender: u32,
+		#[codec(compact)] recipient: u32,
+	},
+
+	/// Execute the inner `message` and then send the `Outcome` of it to `dest`, which must be the origin of this
+	/// message, in a `QueryResponse` message with the given `query_id`.
+	///
+	/// Kind: *Instruction*.
+	ReportOutcome { #[codec(compact)] query_id: u64, dest: MultiLocation, message: Box<Xcm> },
+
+	/// Request the opening of an HRMP channel from the `origin` para to the `recipient` para, sent by a para to the
+	/// relay-chain.
+	///
+	/// Kind: *Instruction*.
+	HrmpInitOpenChannel {
+		#[codec(compact)] recipient: u32,
+		#[codec(compact)] max_message_size: u32,
+		#[codec(compact)] max_capacity: u32,
+	},
+
+	/// Accept a pending request to open an HRMP channel from the `sender` para to the `origin` para, sent by a para
+	/// to the relay-chain.
+	///
+	/// Kind: *Instruction*.
+	HrmpAcceptOpenChannel {
+		#[codec(compact)] sender: u32,
+	},
+
+	/// Request the closing of the HRMP channel from `sender` to `recipient`, one of which must be the `origin` para,
+	/// sent by a para to the relay-chain.
+	///
+	/// Kind: *Instruction*.
+	HrmpCloseChannel {
+		#[codec(compact)] sender: u32,
+		#[codec(compact)] recipient: u32,
+	},
+}
+
+/// Convert every item of `x`, failing if any one of them cannot be converted.
+fn convert_all<A, B: TryFrom<A, Error = ()>>(x: Vec<A>) -> result::Result<Vec<B>, ()> {
+	x.into_iter().map(B::try_from).collect()
+}
+
+/// Convert a version 0 location into interior junctions, failing if it has any parents.
+fn into_junctions(old: v0::MultiLocation) -> result::Result<Junctions, ()> {
+	match MultiLocation::try_from(old)? {
+		MultiLocation { parents: 0, interior } => Ok(interior),
+		_ => Err(()),
+	}
+}
+
+impl TryFrom<v0::Response> for Response {
+	type Error = ();
+	fn try_from(old: v0::Response) -> result::Result<Self, ()> {
+		Ok(match old {
+			v0::Response::Assets(assets) => Response::Assets(convert_all(assets)?),
+			v0::Response::ExecutionResult(outcome) => Response::ExecutionResult(outcome),
+		})
+	}
+}
+
+impl TryFrom<Response> for v0::Response {
+	type Error = ();
+	fn try_from(new: Response) -> result::Result<Self, ()> {
+		Ok(match new {
+			Response::Assets(assets) => v0::Response::Assets(convert_all(assets)?),
+			Response::ExecutionResult(outcome) => v0::Response::ExecutionResult(outcome),
+		})
+	}
+}
+
+impl TryFrom<v0::Xcm> for Xcm {
+	type Error = ();
+	fn try_from(old: v0::Xcm) -> result::Result<Self, ()> {
+		use Xcm::*;
+		Ok(match old {
+			v0::Xcm::WithdrawAsset { assets, effects } =>
+				WithdrawAsset { assets: convert_all(assets)?, effects: convert_all(effects)? },
+			v0::Xcm::ReserveAssetDeposit { assets, effects } =>
+				ReserveAssetDeposit { assets: convert_all(assets)?, effects: convert_all(effects)? },
+			v0::Xcm::TeleportAsset { assets, effects } =>
+				TeleportAsset { assets: convert_all(assets)?, effects: convert_all(effects)? },
+			v0::Xcm::Balances { query_id, assets } =>
+				QueryResponse { query_id, response: Response::Assets(convert_all(assets)?) },
+			v0::Xcm::Transact { origin_type, require_weight_at_most, call } =>
+				Transact { origin_type, require_weight_at_most, call },
+			v0::Xcm::RelayTo { dest, inner } => RelayTo {
+				dest: into_junctions(dest)?,
+				inner: Box::new(VersionedXcm::V1((*inner).try_into()?)),
+			},
+			v0::Xcm::RelayedFrom { superorigin, inner } => RelayedFrom {
+				superorigin: into_junctions(superorigin)?,
+				inner: Box::new(VersionedXcm::V1((*inner).try_into()?)),
+			},
+			v0::Xcm::HrmpNewChannelOpenRequest { sender, max_message_size, max_capacity } =>
+				HrmpNewChannelOpenRequest { sender, max_message_size, max_capacity },
+			v0::Xcm::HrmpChannelAccepted { recipient } => HrmpChannelAccepted { recipient },
+			v0::Xcm::HrmpChannelClosing { initiator, sender, recipient } =>
+				HrmpChannelClosing { initiator, sender, recipient },
+			v0::Xcm::QueryResponse { query_id, response } =>
+				QueryResponse { query_id, response: response.try_into()? },
+			v0::Xcm::ReportOutcome { query_id, dest, message } => ReportOutcome {
+				query_id,
+				dest: dest.try_into()?,
+				message: Box::new((*message).try_into()?),
+			},
+			v0::Xcm::HrmpInitOpenChannel { recipient, max_message_size, max_capacity } =>
+				HrmpInitOpenChannel { recipient, max_message_size, max_capacity },
+			v0::Xcm::HrmpAcceptOpenChannel { sender } => HrmpAcceptOpenChannel { sender },
+			v0::Xcm::HrmpCloseChannel { sender, recipient } => HrmpCloseChannel { sender, recipient },
+		})
+	}
+}
+
+impl TryFrom<Xcm> for v0::Xcm {
+	type Error = ();
+	fn try_from(new: Xcm) -> result::Result<Self, ()> {
+		use v0::Xcm::*;
+		Ok(match new {
+			Xcm::WithdrawAsset { assets, effects } =>
+				WithdrawAsset { assets: convert_all(assets)?, effects: convert_all(effects)? },
+			Xcm::ReserveAssetDeposit { assets, effects } =>
+				ReserveAssetDeposit { assets: convert_all(assets)?, effects: convert_all(effects)? },
+			Xcm::TeleportAsset { assets, effects } =>
+				TeleportAsset { assets: convert_all(assets)?, effects: convert_all(effects)? },
+			Xcm::QueryResponse { query_id, response } =>
+				QueryResponse { query_id, response: response.try_into()? },
+			Xcm::Transact { origin_type, require_weight_at_most, call } =>
+				Transact { origin_type, require_weight_at_most, call },
+			Xcm::RelayTo { dest, inner } => RelayTo {
+				dest: MultiLocation::from(dest).try_into()?,
+				inner: Box::new(VersionedXcm::V0((*inner).try_into()?)),
+			},
+			Xcm::RelayedFrom { superorigin, inner } => RelayedFrom {
+				superorigin: MultiLocation::from(superorigin).try_into()?,
+				inner: Box::new(VersionedXcm::V0((*inner).try_into()?)),
+			},
+			Xcm::HrmpNewChannelOpenRequest { sender, max_message_size, max_capacity } =>
+				HrmpNewChannelOpenRequest { sender, max_message_size, max_capacity },
+			Xcm::HrmpChannelAccepted { recipient } => HrmpChannelAccepted { recipient },
+			Xcm::HrmpChannelClosing { initiator, sender, recipient } =>
+				HrmpChannelClosing { initiator, sender, recipient },
+			Xcm::ReportOutcome { query_id, dest, message } => ReportOutcome {
+				query_id,
+				dest: dest.try_into()?,
+				message: Box::new((*message).try_into()?),
+			},
+			Xcm::HrmpInitOpenChannel { recipient, max_message_size, max_capacity } =>
+				HrmpInitOpenChannel { recipient, max_message_size, max_capacity },
+			Xcm::HrmpAcceptOpenChannel { sender } => HrmpAcceptOpenChannel { sender },
+			Xcm::HrmpCloseChannel { sender, recipient } => HrmpCloseChannel { sender, recipient },
+		})
+	}
+}
+
+impl From<Xcm> for VersionedXcm {
+	fn from(x: Xcm) -> Self {
+		VersionedXcm::V1(x)
+	}
+}
+
+impl TryFrom<VersionedXcm> for Xcm {
+	type Error = ();
+	fn try_from(x: VersionedXcm) -> result::Result<Self, ()> {
+		match x {
+			VersionedXcm::V0(x) => Xcm::try_from(x),
+			VersionedXcm::V1(x) => Ok(x),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use alloc::vec;
+	use v0::{Junction::*, MultiAsset as OldAsset, MultiLocation as OldLocation, Order as OldOrder};
+
+	fn relay_tokens(amount: u128) -> OldAsset {
+		OldAsset::ConcreteFungible { id: OldLocation::X1(Parent), amount }
+	}
+
+	fn round_trip(old: v0::Xcm) {
+		let new = Xcm::try_from(old.clone()).expect("every v0 message here has a v1 representation; qed");
+		assert_eq!(v0::Xcm::try_from(new), Ok(old));
+	}
+
+	#[test]
+	fn asset_messages_round_trip() {
+		let para = OldLocation::X1(Parachain { id: 1 });
+		let transact = v0::Xcm::Transact {
+			origin_type: OriginKind::SovereignAccount,
+			require_weight_at_most: 1_000,
+			call: vec![1, 2, 3],
+		};
+		round_trip(v0::Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(100), OldAsset::AbstractFungible { id: b"other".to_vec(), amount: 5 }],
+			effects: vec![
+				OldOrder::BuyExecution {
+					fees: relay_tokens(10),
+					weight: 1_000,
+					debt: 30,
+					halt_on_error: true,
+					xcm: vec![transact],
+				},
+				OldOrder::ExchangeAsset {
+					give: vec![OldAsset::AllAbstractFungible { id: b"other".to_vec() }],
+					receive: vec![relay_tokens(1)],
+				},
+				OldOrder::QueryHolding {
+					query_id: 3,
+					dest: OldLocation::X1(Parent),
+					assets: vec![OldAsset::ConcreteNonFungible {
+						class: OldLocation::X2(Parent, Parachain { id: 2 }),
+						instance: AssetInstance::Index { id: 7 },
+					}],
+				},
+				OldOrder::DepositAsset { assets: vec![OldAsset::All], dest: para.clone() },
+			],
+		});
+		round_trip(v0::Xcm::TeleportAsset {
+			assets: vec![relay_tokens(100)],
+			effects: vec![
+				OldOrder::InitiateReserveWithdraw {
+					assets: vec![OldAsset::AllConcreteFungible { id: OldLocation::X1(Parent) }],
+					reserve: OldLocation::X1(Parent),
+					effects: vec![OldOrder::Null],
+				},
+				OldOrder::InitiateTeleport {
+					assets: vec![relay_tokens(50)],
+					dest: OldLocation::X2(Parent, Parachain { id: 2 }),
+					effects: vec![OldOrder::DepositAsset { assets: vec![OldAsset::All], dest: para.clone() }],
+				},
+				OldOrder::DepositReserveAsset {
+					assets: vec![OldAsset::AllAbstractNonFungible { class: b"nft".to_vec() }],
+					dest: para,
+					effects: vec![],
+				},
+			],
+		});
+		round_trip(v0::Xcm::ReserveAssetDeposit { assets: vec![], effects: vec![] });
+	}
+
+	#[test]
+	fn other_messages_round_trip() {
+		let inner = v0::Xcm::WithdrawAsset { assets: vec![relay_tokens(1)], effects: vec![] };
+		round_trip(v0::Xcm::RelayTo {
+			dest: OldLocation::X2(Parachain { id: 1 }, Parachain { id: 2 }),
+			inner: Box::new(inner.clone().into()),
+		});
+		round_trip(v0::Xcm::RelayedFrom {
+			superorigin: OldLocation::X1(OnlyChild),
+			inner: Box::new(inner.clone().into()),
+		});
+		round_trip(v0::Xcm::ReportOutcome {
+			query_id: 1,
+			dest: OldLocation::X2(Parent, Parachain { id: 2 }),
+			message: Box::new(inner),
+		});
+		round_trip(v0::Xcm::QueryResponse {
+			query_id: 2,
+			response: v0::Response::Assets(vec![relay_tokens(1)]),
+		});
+		round_trip(v0::Xcm::QueryResponse {
+			query_id: 3,
+			response: v0::Response::ExecutionResult(Outcome::Complete(10)),
+		});
+		round_trip(v0::Xcm::HrmpNewChannelOpenRequest { sender: 1, max_message_size: 2, max_capacity: 3 });
+		round_trip(v0::Xcm::HrmpChannelAccepted { recipient: 1 });
+		round_trip(v0::Xcm::HrmpChannelClosing { initiator: 1, sender: 1, recipient: 2 });
+		round_trip(v0::Xcm::HrmpInitOpenChannel { recipient: 1, max_message_size: 2, max_capacity: 3 });
+		round_trip(v0::Xcm::HrmpAcceptOpenChannel { sender: 1 });
+		round_trip(v0::Xcm::HrmpCloseChannel { sender: 1, recipient: 2 });
+	}
+
+	#[test]
+	fn versioned_messages_convert_both_ways() {
+		let old = v0::Xcm::WithdrawAsset { assets: vec![relay_tokens(1)], effects: vec![] };
+		let new = Xcm::try_from(old.clone()).unwrap();
+		assert_eq!(VersionedXcm::from(old.clone()).into_version(1), Ok(VersionedXcm::V1(new.clone())));
+		assert_eq!(VersionedXcm::from(new).into_version(0), Ok(VersionedXcm::V0(old)));
+	}
+
+	#[test]
+	fn balances_become_query_response() {
+		let old = v0::Xcm::Balances { query_id: 1, assets: vec![relay_tokens(1)] };
+		let new = Xcm::try_from(old).unwrap();
+		assert_eq!(v0::Xcm::try_from(new), Ok(v0::Xcm::QueryResponse {
+			query_id: 1,
+			response: v0::Response::Assets(vec![relay_tokens(1)]),
+		}));
+	}
+
+	#[test]
+	fn unrepresentable_messages_do_not_convert() {
+		let deposit = |assets, dest| v0::Xcm::WithdrawAsset {
+			assets: vec![],
+			effects: vec![OldOrder::DepositAsset { assets, dest }],
+		};
+		let para = OldLocation::X1(Parachain { id: 1 });
+		// Wildcards may only be used alone.
+		assert_eq!(Xcm::try_from(deposit(vec![OldAsset::All, relay_tokens(1)], para.clone())), Err(()));
+		// Not normalised.
+		assert_eq!(Xcm::try_from(deposit(vec![], OldLocation::X2(Parachain { id: 1 }, Parent))), Err(()));
+		// Fees must be definite.
+		assert_eq!(
+			Xcm::try_from(v0::Xcm::WithdrawAsset {
+				assets: vec![],
+				effects: vec![OldOrder::BuyExecution {
+					fees: OldAsset::All, weight: 0, debt: 0, halt_on_error: true, xcm: vec![],
+				}],
+			}),
+			Err(()),
+		);
+		// Relays may never go up.
+		assert_eq!(
+			Xcm::try_from(v0::Xcm::RelayTo {
+				dest: OldLocation::X1(Parent),
+				inner: Box::new(deposit(vec![], para).into()),
+			}),
+			Err(()),
+		);
+	}
+}
@@ -0,0 +1,198 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cross-Consensus Message format data structures.
+
+use core::{result, convert::{TryFrom, TryInto}};
+use alloc::vec::Vec;
+
+use tetsy_scale_codec::{self, Encode, Decode};
+use super::{MultiLocation, AssetInstance};
+use crate::{v0, VersionedMultiAsset};
+
+/// Classification of an asset being concrete or abstract.
+///
+/// See the version 0 `MultiAsset` for the meaning of concrete and abstract identifiers.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub enum AssetId {
+	/// An asset identified by its location relative to the interpreting context.
+	Concrete(MultiLocation),
+	/// An asset identified by an absolute, universal name.
+	Abstract(Vec<u8>),
+}
+
+/// Classification of whether an asset is fungible or not, along with a mandatory amount or instance.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub enum Fungibility {
+	/// Some amount of a fungible asset.
+	Fungible(#[codec(compact)] u128),
+	/// A single instance of a non-fungible asset.
+	NonFungible(AssetInstance),
+}
+
+/// A single, definite asset: an asset class together with an amount or instance of it.
+///
+/// Unlike version 0, wildcards are not representable as a `MultiAsset`; see `WildMultiAsset`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub struct MultiAsset {
+	/// The asset class.
+	pub id: AssetId,
+	/// The amount or instance of the asset class.
+	pub fun: Fungibility,
+}
+
+/// Classification of whether an asset is fungible or not.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub enum WildFungibility {
+	Fungible,
+	NonFungible,
+}
+
+/// A wildcard representing a set of assets.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub enum WildMultiAsset {
+	/// All assets in the holding register.
+	All,
+	/// All assets in the holding register of a given fungibility and asset class `id`.
+	AllOf { id: AssetId, fun: WildFungibility },
+}
+
+/// A filter of assets, either definite or wild, used by orders to select assets from the holding register.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub enum MultiAssetFilter {
+	/// Exactly the given assets, or as much of them as is held.
+	Definite(Vec<MultiAsset>),
+	/// All held assets matching the wildcard.
+	Wild(WildMultiAsset),
+}
+
+impl From<Vec<MultiAsset>> for MultiAssetFilter {
+	fn from(x: Vec<MultiAsset>) -> Self {
+		MultiAssetFilter::Definite(x)
+	}
+}
+
+impl From<WildMultiAsset> for MultiAssetFilter {
+	fn from(x: WildMultiAsset) -> Self {
+		MultiAssetFilter::Wild(x)
+	}
+}
+
+impl TryFrom<v0::MultiAsset> for MultiAsset {
+	type Error = ();
+	fn try_from(old: v0::MultiAsset) -> result::Result<Self, ()> {
+		use v0::MultiAsset::*;
+		let (id, fun) = match old {
+			ConcreteFungible { id, amount } => (AssetId::Concrete(id.try_into()?), Fungibility::Fungible(amount)),
+			AbstractFungible { id, amount } => (AssetId::Abstract(id), Fungibility::Fungible(amount)),
+			ConcreteNonFungible { class, instance } =>
+				(AssetId::Concrete(class.try_into()?), Fungibility::NonFungible(instance)),
+			AbstractNonFungible { class, instance } => (AssetId::Abstract(class), Fungibility::NonFungible(instance)),
+			_ => return Err(()),
+		};
+		Ok(MultiAsset { id, fun })
+	}
+}
+
+impl TryFrom<MultiAsset> for v0::MultiAsset {
+	type Error = ();
+	fn try_from(new: MultiAsset) -> result::Result<Self, ()> {
+		use v0::MultiAsset::*;
+		Ok(match (new.id, new.fun) {
+			(AssetId::Concrete(id), Fungibility::Fungible(amount)) => ConcreteFungible { id: id.try_into()?, amount },
+			(AssetId::Abstract(id), Fungibility::Fungible(amount)) => AbstractFungible { id, amount },
+			(AssetId::Concrete(class), Fungibility::NonFungible(instance)) =>
+				ConcreteNonFungible { class: class.try_into()?, instance },
+			(AssetId::Abstract(class), Fungibility::NonFungible(instance)) => AbstractNonFungible { class, instance },
+		})
+	}
+}
+
+impl TryFrom<v0::MultiAsset> for WildMultiAsset {
+	type Error = ();
+	fn try_from(old: v0::MultiAsset) -> result::Result<Self, ()> {
+		use v0::MultiAsset::*;
+		let (id, fun) = match old {
+			All => return Ok(WildMultiAsset::All),
+			AllConcreteFungible { id } => (AssetId::Concrete(id.try_into()?), WildFungibility::Fungible),
+			AllAbstractFungible { id } => (AssetId::Abstract(id), WildFungibility::Fungible),
+			AllConcreteNonFungible { class } => (AssetId::Concrete(class.try_into()?), WildFungibility::NonFungible),
+			AllAbstractNonFungible { class } => (AssetId::Abstract(class), WildFungibility::NonFungible),
+			_ => return Err(()),
+		};
+		Ok(WildMultiAsset::AllOf { id, fun })
+	}
+}
+
+impl TryFrom<WildMultiAsset> for v0::MultiAsset {
+	type Error = ();
+	fn try_from(new: WildMultiAsset) -> result::Result<Self, ()> {
+		use v0::MultiAsset::*;
+		Ok(match new {
+			WildMultiAsset::All => All,
+			WildMultiAsset::AllOf { id: AssetId::Concrete(id), fun: WildFungibility::Fungible } =>
+				AllConcreteFungible { id: id.try_into()? },
+			WildMultiAsset::AllOf { id: AssetId::Abstract(id), fun: WildFungibility::Fungible } =>
+				AllAbstractFungible { id },
+			WildMultiAsset::AllOf { id: AssetId::Concrete(class), fun: WildFungibility::NonFungible } =>
+				AllConcreteNonFungible { class: class.try_into()? },
+			WildMultiAsset::AllOf { id: AssetId::Abstract(class), fun: WildFungibility::NonFungible } =>
+				AllAbstractNonFungible { class },
+		})
+	}
+}
+
+impl TryFrom<Vec<v0::MultiAsset>> for MultiAssetFilter {
+	type Error = ();
+	fn try_from(old: Vec<v0::MultiAsset>) -> result::Result<Self, ()> {
+		if old.len() == 1 && old[0].is_wildcard() {
+			let wild = old.into_iter().next().expect("length checked above; qed");
+			return Ok(MultiAssetFilter::Wild(wild.try_into()?))
+		}
+		// Wildcards may only be used alone; `None` is simply dropped.
+		old.into_iter()
+			.filter(|a| a != &v0::MultiAsset::None)
+			.map(MultiAsset::try_from)
+			.collect::<result::Result<_, _>>()
+			.map(MultiAssetFilter::Definite)
+	}
+}
+
+impl TryFrom<MultiAssetFilter> for Vec<v0::MultiAsset> {
+	type Error = ();
+	fn try_from(new: MultiAssetFilter) -> result::Result<Self, ()> {
+		match new {
+			MultiAssetFilter::Definite(assets) => assets.into_iter().map(v0::MultiAsset::try_from).collect(),
+			MultiAssetFilter::Wild(wild) => Ok(alloc::vec![wild.try_into()?]),
+		}
+	}
+}
+
+impl From<MultiAsset> for VersionedMultiAsset {
+	fn from(x: MultiAsset) -> Self {
+		VersionedMultiAsset::V1(x)
+	}
+}
+
+impl TryFrom<VersionedMultiAsset> for MultiAsset {
+	type Error = ();
+	fn try_from(x: VersionedMultiAsset) -> result::Result<Self, ()> {
+		match x {
+			VersionedMultiAsset::V0(x) => MultiAsset::try_from(x),
+			VersionedMultiAsset::V1(x) => Ok(x),
+		}
+	}
+}
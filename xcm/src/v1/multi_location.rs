@@ -0,0 +1,252 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cross-Consensus Message format data structures.
+
+use core::{result, mem, convert::TryFrom};
+
+use tetsy_scale_codec::{self, Encode, Decode};
+use super::Junction;
+use crate::{v0, VersionedMultiLocation};
+
+/// A relative path between state-bearing consensus systems.
+///
+/// This is the same notion of location as in version 0 of the format, but its representation is normalised: any
+/// `Parent` junctions are given only as a count of `parents`, ascended before the `interior` path is descended.
+/// The `interior` may never contain a `Parent` junction.
+///
+/// The `MultiLocation` with zero `parents` and an `interior` of `Here` simply refers to the interpreting consensus
+/// system.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub struct MultiLocation {
+	/// The number of parent junctions at the beginning of this `MultiLocation`.
+	pub parents: u8,
+	/// The interior (i.e. non-parent) junctions that this `MultiLocation` contains.
+	pub interior: Junctions,
+}
+
+impl MultiLocation {
+	/// Creates a new `MultiLocation` with the given number of parents and interior junctions.
+	pub fn new(parents: u8, interior: Junctions) -> MultiLocation {
+		MultiLocation { parents, interior }
+	}
+
+	/// The location of the interpreting consensus system.
+	pub fn here() -> MultiLocation {
+		MultiLocation { parents: 0, interior: Junctions::Here }
+	}
+
+	/// The location of the immediate parent of the interpreting consensus system.
+	pub fn parent() -> MultiLocation {
+		MultiLocation { parents: 1, interior: Junctions::Here }
+	}
+
+	/// Returns `true` if this location refers to the interpreting consensus system.
+	pub fn is_here(&self) -> bool {
+		self.parents == 0 && self.interior == Junctions::Here
+	}
+
+	/// Returns the number of `Parent` junctions at the beginning of `self`.
+	pub fn parent_count(&self) -> u8 {
+		self.parents
+	}
+
+	/// Returns a reference to the interior junctions.
+	pub fn interior(&self) -> &Junctions {
+		&self.interior
+	}
+
+	/// Returns the total number of junctions in `self`, counting each parent as one.
+	pub fn len(&self) -> usize {
+		self.parents as usize + self.interior.len()
+	}
+}
+
+impl From<Junctions> for MultiLocation {
+	fn from(interior: Junctions) -> Self {
+		MultiLocation { parents: 0, interior }
+	}
+}
+
+impl From<Junction> for MultiLocation {
+	fn from(x: Junction) -> Self {
+		MultiLocation { parents: 0, interior: Junctions::X1(x) }
+	}
+}
+
+/// Non-parent junctions that can be constructed, up to the length of 4.
+///
+/// None of the junctions may be `Parent`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode, Debug)]
+pub enum Junctions {
+	/// The interpreting consensus system.
+	Here,
+	/// A relative path comprising one junction.
+	X1(Junction),
+	/// A relative path comprising two junctions.
+	X2(Junction, Junction),
+	/// A relative path comprising three junctions.
+	X3(Junction, Junction, Junction),
+	/// A relative path comprising four junctions.
+	X4(Junction, Junction, Junction, Junction),
+}
+
+pub struct JunctionsIterator(Junctions);
+impl Iterator for JunctionsIterator {
+	type Item = Junction;
+	fn next(&mut self) -> Option<Junction> {
+		self.0.take_first()
+	}
+}
+
+pub struct JunctionsRefIterator<'a>(&'a Junctions, usize);
+impl<'a> Iterator for JunctionsRefIterator<'a> {
+	type Item = &'a Junction;
+	fn next(&mut self) -> Option<&'a Junction> {
+		let result = self.0.at(self.1);
+		self.1 += 1;
+		result
+	}
+}
+
+impl Junctions {
+	/// Returns first junction, or `None` if the location is empty.
+	pub fn first(&self) -> Option<&Junction> {
+		self.at(0)
+	}
+
+	/// Returns last junction, or `None` if the location is empty.
+	pub fn last(&self) -> Option<&Junction> {
+		self.at(self.len().checked_sub(1)?)
+	}
+
+	/// Splits off the first junction, returning the remaining suffix (first item in tuple) and the first element
+	/// (second item in tuple) or `None` if it was empty.
+	pub fn split_first(self) -> (Junctions, Option<Junction>) {
+		match self {
+			Junctions::Here => (Junctions::Here, None),
+			Junctions::X1(a) => (Junctions::Here, Some(a)),
+			Junctions::X2(a, b) => (Junctions::X1(b), Some(a)),
+			Junctions::X3(a, b, c) => (Junctions::X2(b, c), Some(a)),
+			Junctions::X4(a, b, c, d) => (Junctions::X3(b, c, d), Some(a)),
+		}
+	}
+
+	/// Removes the first element from `self`, returning it (or `None` if it was empty).
+	pub fn take_first(&mut self) -> Option<Junction> {
+		let mut d = Junctions::Here;
+		mem::swap(&mut *self, &mut d);
+		let (tail, head) = d.split_first();
+		*self = tail;
+		head
+	}
+
+	/// Consumes `self` and returns a `Junctions` suffixed with `new`, or an `Err` with the original value of
+	/// `self` in case of overflow.
+	pub fn pushed_with(self, new: Junction) -> result::Result<Self, Self> {
+		Ok(match self {
+			Junctions::Here => Junctions::X1(new),
+			Junctions::X1(a) => Junctions::X2(a, new),
+			Junctions::X2(a, b) => Junctions::X3(a, b, new),
+			Junctions::X3(a, b, c) => Junctions::X4(a, b, c, new),
+			s => Err(s)?,
+		})
+	}
+
+	/// Returns the number of junctions in `self`.
+	pub fn len(&self) -> usize {
+		match &self {
+			Junctions::Here => 0,
+			Junctions::X1(..) => 1,
+			Junctions::X2(..) => 2,
+			Junctions::X3(..) => 3,
+			Junctions::X4(..) => 4,
+		}
+	}
+
+	/// Returns the junction at index `i`, or `None` if the location doesn't contain that many elements.
+	pub fn at(&self, i: usize) -> Option<&Junction> {
+		Some(match (i, &self) {
+			(0, Junctions::X1(ref a)) => a,
+			(0, Junctions::X2(ref a, ..)) => a,
+			(0, Junctions::X3(ref a, ..)) => a,
+			(0, Junctions::X4(ref a, ..)) => a,
+			(1, Junctions::X2(_, ref a)) => a,
+			(1, Junctions::X3(_, ref a, ..)) => a,
+			(1, Junctions::X4(_, ref a, ..)) => a,
+			(2, Junctions::X3(_, _, ref a)) => a,
+			(2, Junctions::X4(_, _, ref a, ..)) => a,
+			(3, Junctions::X4(_, _, _, ref a)) => a,
+			_ => return None,
+		})
+	}
+
+	/// Returns a reference iterator over the junctions.
+	pub fn iter(&self) -> JunctionsRefIterator {
+		JunctionsRefIterator(&self, 0)
+	}
+
+	/// Consumes `self` and returns an iterator over the junctions.
+	pub fn into_iter(self) -> JunctionsIterator {
+		JunctionsIterator(self)
+	}
+}
+
+impl TryFrom<v0::MultiLocation> for MultiLocation {
+	type Error = ();
+	fn try_from(old: v0::MultiLocation) -> result::Result<Self, ()> {
+		let parents = old.parent_count();
+		let mut interior = Junctions::Here;
+		for j in old.into_iter().skip(parents) {
+			// Version 0 allows un-normalised locations with a `Parent` after an interior junction.
+			if j == Junction::Parent {
+				return Err(())
+			}
+			interior = interior.pushed_with(j).map_err(|_| ())?;
+		}
+		Ok(MultiLocation { parents: parents as u8, interior })
+	}
+}
+
+impl TryFrom<MultiLocation> for v0::MultiLocation {
+	type Error = ();
+	fn try_from(new: MultiLocation) -> result::Result<Self, ()> {
+		let mut result = v0::MultiLocation::Null;
+		for _ in 0..new.parents {
+			result.push(Junction::Parent)?;
+		}
+		for j in new.interior.into_iter() {
+			result.push(j)?;
+		}
+		Ok(result)
+	}
+}
+
+impl From<MultiLocation> for VersionedMultiLocation {
+	fn from(x: MultiLocation) -> Self {
+		VersionedMultiLocation::V1(x)
+	}
+}
+
+impl TryFrom<VersionedMultiLocation> for MultiLocation {
+	type Error = ();
+	fn try_from(x: VersionedMultiLocation) -> result::Result<Self, ()> {
+		match x {
+			VersionedMultiLocation::V0(x) => MultiLocation::try_from(x),
+			VersionedMultiLocation::V1(x) => Ok(x),
+		}
+	}
+}
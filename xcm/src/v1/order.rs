@@ -0,0 +1,177 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Version 1 of the Cross-Consensus Message format data structures.
+
+use core::{result, convert::{TryFrom, TryInto}};
+use alloc::vec::Vec;
+use tetsy_scale_codec::{self, Encode, Decode};
+use super::{MultiAsset, MultiAssetFilter, MultiLocation, Xcm, convert_all};
+use crate::v0;
+
+/// An instruction to be executed on some or all of the assets in holding, used by asset-related XCM messages.
+///
+/// The orders are those of version 0, with asset selections expressed as a `MultiAssetFilter`.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Debug)]
+pub enum Order {
+	/// Do nothing. Not generally used.
+	Null,
+
+	/// Remove the asset(s) (`assets`) from holding and place equivalent assets under the ownership of `dest` within
+	/// this consensus system.
+	///
+	/// - `assets`: The asset(s) to remove from holding.
+	/// - `dest`: The new owner for the assets.
+	///
+	/// Errors:
+	DepositAsset { assets: MultiAssetFilter, dest: MultiLocation },
+
+	/// Remove the asset(s) (`assets`) from holding and place equivalent assets under the ownership of `dest` within
+	/// this consensus system, then send an onward `ReserveAssetDeposit` XCM message to `dest`.
+	///
+	/// - `assets`: The asset(s) to remove from holding.
+	/// - `dest`: The new owner for the assets.
+	/// - `effects`: The orders that should be contained in the `ReserveAssetDeposit` which is sent onwards to
+	///   `dest`.
+	///
+	/// Errors:
+	DepositReserveAsset { assets: MultiAssetFilter, dest: MultiLocation, effects: Vec<Order> },
+
+	/// Remove the asset(s) (`give`) from holding and replace them with alternative assets.
+	///
+	/// - `give`: The asset(s) to remove from holding.
+	/// - `receive`: The minimum amount of assets(s) which `give` should be exchanged for.
+	///
+	/// Errors:
+	/// - `TooMuchSlippage`: `give` could not be exchanged for at least `receive`. Holding is left unchanged.
+	ExchangeAsset { give: MultiAssetFilter, receive: Vec<MultiAsset> },
+
+	/// Remove the asset(s) (`assets`) from holding and send a `WithdrawAsset` XCM message to a reserve location.
+	///
+	/// - `assets`: The asset(s) to remove from holding.
+	/// - `reserve`: A valid location that acts as a reserve for all asset(s) in `assets`.
+	/// - `effects`: The orders to execute on the assets once withdrawn *on the reserve location*.
+	///
+	/// Errors:
+	InitiateReserveWithdraw { assets: MultiAssetFilter, reserve: MultiLocation, effects: Vec<Order> },
+
+	/// Remove the asset(s) (`assets`) from holding and send a `TeleportAsset` XCM message to a destination location.
+	///
+	/// - `assets`: The asset(s) to remove from holding.
+	/// - `dest`: A valid location that has a bi-lateral teleportation arrangement.
+	/// - `effects`: The orders to execute on the assets once arrived *on the destination location*.
+	///
+	/// Errors:
+	InitiateTeleport { assets: MultiAssetFilter, dest: MultiLocation, effects: Vec<Order> },
+
+	/// Send a `QueryResponse` XCM message with the holding contents, or a portion thereof, as `Response::Assets`.
+	///
+	/// - `query_id`: An identifier that will be replicated into the returned XCM message.
+	/// - `dest`: A valid destination for the returned XCM message. This may be limited to the current origin.
+	/// - `assets`: A filter for the assets that should be reported back.
+	///
+	/// Errors:
+	QueryHolding { #[codec(compact)] query_id: u64, dest: MultiLocation, assets: MultiAssetFilter },
+
+	/// Pay for the execution of some XCM with up to `weight` picoseconds of execution time, paying for this with
+	/// up to `fees` from the holding account.
+	///
+	/// - `fees`: The asset to remove from holding to pay for fees.
+	/// - `weight`: The amount of weight to purchase; this should be at least the shallow weight of `xcm`.
+	/// - `debt`: The amount of weight-debt already incurred to be paid off.
+	/// - `halt_on_error`: If `true`, the execution of the `xcm` will halt on the first failure.
+	/// - `xcm`: XCM instructions to be executed outside of the context of the current holding account, with the
+	///   purchased weight.
+	///
+	/// Errors:
+	BuyExecution { fees: MultiAsset, weight: u64, debt: u64, halt_on_error: bool, xcm: Vec<Xcm> },
+}
+
+impl TryFrom<v0::Order> for Order {
+	type Error = ();
+	fn try_from(old: v0::Order) -> result::Result<Self, ()> {
+		use Order::*;
+		Ok(match old {
+			v0::Order::Null => Null,
+			v0::Order::DepositAsset { assets, dest } =>
+				DepositAsset { assets: assets.try_into()?, dest: dest.try_into()? },
+			v0::Order::DepositReserveAsset { assets, dest, effects } => DepositReserveAsset {
+				assets: assets.try_into()?,
+				dest: dest.try_into()?,
+				effects: convert_all(effects)?,
+			},
+			v0::Order::ExchangeAsset { give, receive } =>
+				ExchangeAsset { give: give.try_into()?, receive: convert_all(receive)? },
+			v0::Order::InitiateReserveWithdraw { assets, reserve, effects } => InitiateReserveWithdraw {
+				assets: assets.try_into()?,
+				reserve: reserve.try_into()?,
+				effects: convert_all(effects)?,
+			},
+			v0::Order::InitiateTeleport { assets, dest, effects } => InitiateTeleport {
+				assets: assets.try_into()?,
+				dest: dest.try_into()?,
+				effects: convert_all(effects)?,
+			},
+			v0::Order::QueryHolding { query_id, dest, assets } =>
+				QueryHolding { query_id, dest: dest.try_into()?, assets: assets.try_into()? },
+			v0::Order::BuyExecution { fees, weight, debt, halt_on_error, xcm } => BuyExecution {
+				fees: fees.try_into()?,
+				weight,
+				debt,
+				halt_on_error,
+				xcm: convert_all(xcm)?,
+			},
+		})
+	}
+}
+
+impl TryFrom<Order> for v0::Order {
+	type Error = ();
+	fn try_from(new: Order) -> result::Result<Self, ()> {
+		use v0::Order::*;
+		Ok(match new {
+			Order::Null => Null,
+			Order::DepositAsset { assets, dest } =>
+				DepositAsset { assets: assets.try_into()?, dest: dest.try_into()? },
+			Order::DepositReserveAsset { assets, dest, effects } => DepositReserveAsset {
+				assets: assets.try_into()?,
+				dest: dest.try_into()?,
+				effects: convert_all(effects)?,
+			},
+			Order::ExchangeAsset { give, receive } =>
+				ExchangeAsset { give: give.try_into()?, receive: convert_all(receive)? },
+			Order::InitiateReserveWithdraw { assets, reserve, effects } => InitiateReserveWithdraw {
+				assets: assets.try_into()?,
+				reserve: reserve.try_into()?,
+				effects: convert_all(effects)?,
+			},
+			Order::InitiateTeleport { assets, dest, effects } => InitiateTeleport {
+				assets: assets.try_into()?,
+				dest: dest.try_into()?,
+				effects: convert_all(effects)?,
+			},
+			Order::QueryHolding { query_id, dest, assets } =>
+				QueryHolding { query_id, dest: dest.try_into()?, assets: assets.try_into()? },
+			Order::BuyExecution { fees, weight, debt, halt_on_error, xcm } => BuyExecution {
+				fees: fees.try_into()?,
+				weight,
+				debt,
+				halt_on_error,
+				xcm: convert_all(xcm)?,
+			},
+		})
+	}
+}
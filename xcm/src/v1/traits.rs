@@ -0,0 +1,30 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{MultiLocation, Xcm};
+pub use crate::v0::{Error, Result, Outcome, Weight};
+
+/// Type of XCM message sender, generally a transport to some other consensus system.
+pub trait SendXcm {
+	/// Send the message `msg` to the destination `dest`.
+	fn send_xcm(dest: MultiLocation, msg: Xcm) -> Result;
+}
+
+impl SendXcm for () {
+	fn send_xcm(_dest: MultiLocation, _msg: Xcm) -> Result {
+		Err(Error::Unimplemented)
+	}
+}
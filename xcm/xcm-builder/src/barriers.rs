@@ -0,0 +1,137 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use tetcore_std::{result::Result, marker::PhantomData, vec::Vec};
+use xcm::v0::{Xcm, Order, MultiLocation, Junction};
+use fabric_support::{ensure, traits::{Get, Filter}, weights::Weight};
+use xcm_executor::traits::ShouldExecute;
+use tetcoin_parachain::primitives::IsSystem;
+
+/// Execution barrier that just takes `shallow_weight` from `weight_credit`.
+pub struct TakeWeightCredit;
+impl ShouldExecute for TakeWeightCredit {
+	fn should_execute(
+		_origin: &MultiLocation,
+		_top_level: bool,
+		_message: &Xcm,
+		shallow_weight: Weight,
+		weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		*weight_credit = weight_credit.checked_sub(shallow_weight).ok_or(())?;
+		Ok(())
+	}
+}
+
+/// Allows execution from `origin` if it is contained in `T` (i.e. `T::filter(origin)`) for any top-level
+/// message which begins by paying for at least its shallow weight through `Order::BuyExecution`.
+pub struct AllowTopLevelPaidExecutionFrom<T>(PhantomData<T>);
+impl<T: Filter<MultiLocation>> ShouldExecute for AllowTopLevelPaidExecutionFrom<T> {
+	fn should_execute(
+		origin: &MultiLocation,
+		top_level: bool,
+		message: &Xcm,
+		shallow_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		ensure!(T::filter(origin), ());
+		ensure!(top_level, ());
+		match message {
+			Xcm::TeleportAsset { effects, .. }
+			| Xcm::WithdrawAsset { effects, .. }
+			| Xcm::ReserveAssetDeposit { effects, .. }
+				if matches!(
+					effects.first(),
+					Some(Order::BuyExecution { debt, .. }) if *debt >= shallow_weight
+				)
+			=> Ok(()),
+			_ => Err(()),
+		}
+	}
+}
+
+/// Allows execution of any message, paid or not, from any `origin` contained in `T`
+/// (i.e. `T::filter(origin)`).
+///
+/// Use only for origins which are trusted not to abuse the chain, such as the Relay-chain or system parachains.
+pub struct AllowUnpaidExecutionFrom<T>(PhantomData<T>);
+impl<T: Filter<MultiLocation>> ShouldExecute for AllowUnpaidExecutionFrom<T> {
+	fn should_execute(
+		origin: &MultiLocation,
+		_top_level: bool,
+		_message: &Xcm,
+		_shallow_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		ensure!(T::filter(origin), ());
+		Ok(())
+	}
+}
+
+/// Allows the top-level HRMP channel management instructions (`HrmpInitOpenChannel`, `HrmpAcceptOpenChannel` and
+/// `HrmpCloseChannel`) from any `origin` contained in `T` (i.e. `T::filter(origin)`), without payment.
+///
+/// These instructions are paid for through the deposits held in the sovereign account of the origin.
+pub struct AllowHrmpChannelManagementFrom<T>(PhantomData<T>);
+impl<T: Filter<MultiLocation>> ShouldExecute for AllowHrmpChannelManagementFrom<T> {
+	fn should_execute(
+		origin: &MultiLocation,
+		top_level: bool,
+		message: &Xcm,
+		_shallow_weight: Weight,
+		_weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		ensure!(T::filter(origin), ());
+		ensure!(top_level, ());
+		match message {
+			Xcm::HrmpInitOpenChannel { .. }
+			| Xcm::HrmpAcceptOpenChannel { .. }
+			| Xcm::HrmpCloseChannel { .. } => Ok(()),
+			_ => Err(()),
+		}
+	}
+}
+
+/// Filter for the location of this system's parent.
+pub struct IsParent;
+impl Filter<MultiLocation> for IsParent {
+	fn filter(location: &MultiLocation) -> bool {
+		matches!(location, MultiLocation::X1(Junction::Parent))
+	}
+}
+
+/// Filter for the locations of child parachains which are system parachains.
+pub struct IsChildSystemParachain<ParaId>(PhantomData<ParaId>);
+impl<ParaId: IsSystem + From<u32>> Filter<MultiLocation> for IsChildSystemParachain<ParaId> {
+	fn filter(location: &MultiLocation) -> bool {
+		matches!(location, MultiLocation::X1(Junction::Parachain { id }) if ParaId::from(*id).is_system())
+	}
+}
+
+/// Filter for the locations of any child parachains.
+pub struct IsChildParachain;
+impl Filter<MultiLocation> for IsChildParachain {
+	fn filter(location: &MultiLocation) -> bool {
+		matches!(location, MultiLocation::X1(Junction::Parachain { .. }))
+	}
+}
+
+/// Filter for an allow-list of locations, given by `T`.
+pub struct IsInVec<T>(PhantomData<T>);
+impl<T: Get<Vec<MultiLocation>>> Filter<MultiLocation> for IsInVec<T> {
+	fn filter(location: &MultiLocation) -> bool {
+		T::get().contains(location)
+	}
+}
@@ -34,6 +34,11 @@ impl<
 	AccountId,	// can't get away without it since Currency is generic over it.
 > TransactAsset for CurrencyAdapter<Currency, Matcher, AccountIdConverter, AccountId> {
 
+	fn can_check_in(_origin: &MultiLocation, what: &MultiAsset) -> Result {
+		// Check we handle this asset. No teleport accounting is done for the currency.
+		Matcher::matches_fungible(what).map(|_| ()).ok_or(Error::AssetNotFound)
+	}
+
 	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
 		// Check we handle this asset.
 		let amount: u128 = Matcher::matches_fungible(&what).ok_or(())?.saturated_into();
@@ -0,0 +1,170 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use tetcore_std::{prelude::*, result, convert::TryFrom, marker::PhantomData};
+use xcm::v0::{Error, Result, MultiAsset, MultiLocation, Junction};
+use tp_runtime::DispatchResult;
+use fabric_support::traits::{Get, Filter};
+use xcm_executor::traits::{MatchesFungibles, AssetIdConversion, LocationConversion, TransactAsset};
+
+/// A registry of many fungible assets, each identified by an `AssetId` and held by accounts of type
+/// `AccountId`.
+pub trait Fungibles<AccountId> {
+	/// The identifier of an asset class.
+	type AssetId: Clone;
+	/// The balance type of all asset classes.
+	type Balance: Copy + PartialOrd;
+
+	/// The free balance of `who` in the asset class `id`.
+	fn balance(id: Self::AssetId, who: &AccountId) -> Self::Balance;
+
+	/// Mint `amount` of asset `id` into the account of `who`.
+	fn deposit(id: Self::AssetId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+	/// Burn `amount` of asset `id` from the account of `who`, failing if the account cannot afford it.
+	fn withdraw(id: Self::AssetId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+}
+
+/// Matches `ConcreteFungible` assets whose id can be converted into a local `AssetId` by `ConvertAssetId`.
+pub struct ConvertedConcreteAssetId<AssetId, Balance, ConvertAssetId>(
+	PhantomData<(AssetId, Balance, ConvertAssetId)>
+);
+impl<
+	AssetId: Clone,
+	Balance: TryFrom<u128>,
+	ConvertAssetId: AssetIdConversion<MultiLocation, AssetId>,
+> MatchesFungibles<AssetId, Balance> for ConvertedConcreteAssetId<AssetId, Balance, ConvertAssetId> {
+	fn matches_fungibles(a: &MultiAsset) -> Option<(AssetId, Balance)> {
+		match a {
+			MultiAsset::ConcreteFungible { id, amount } =>
+				Some((ConvertAssetId::from_asset_id(id)?, Balance::try_from(*amount).ok()?)),
+			_ => None,
+		}
+	}
+}
+
+/// Matches `AbstractFungible` assets whose id can be converted into a local `AssetId` by `ConvertAssetId`.
+pub struct ConvertedAbstractAssetId<AssetId, Balance, ConvertAssetId>(
+	PhantomData<(AssetId, Balance, ConvertAssetId)>
+);
+impl<
+	AssetId: Clone,
+	Balance: TryFrom<u128>,
+	ConvertAssetId: AssetIdConversion<Vec<u8>, AssetId>,
+> MatchesFungibles<AssetId, Balance> for ConvertedAbstractAssetId<AssetId, Balance, ConvertAssetId> {
+	fn matches_fungibles(a: &MultiAsset) -> Option<(AssetId, Balance)> {
+		match a {
+			MultiAsset::AbstractFungible { id, amount } =>
+				Some((ConvertAssetId::from_asset_id(id)?, Balance::try_from(*amount).ok()?)),
+			_ => None,
+		}
+	}
+}
+
+/// Converts between a local `AssetId` and the location `Prefix` followed by a `GeneralIndex` of that id.
+pub struct AsPrefixedGeneralIndex<Prefix, AssetId>(PhantomData<(Prefix, AssetId)>);
+impl<
+	Prefix: Get<MultiLocation>,
+	AssetId: TryFrom<u128> + Into<u128> + Clone,
+> AssetIdConversion<MultiLocation, AssetId> for AsPrefixedGeneralIndex<Prefix, AssetId> {
+	fn from_asset_id(id: &MultiLocation) -> Option<AssetId> {
+		let prefix = Prefix::get();
+		if prefix.len() + 1 != id.len() || prefix.iter().zip(id.iter()).any(|(a, b)| a != b) {
+			return None
+		}
+		match id.last() {
+			Some(Junction::GeneralIndex { id }) => AssetId::try_from(*id).ok(),
+			_ => None,
+		}
+	}
+
+	fn try_into_asset_id(what: AssetId) -> result::Result<MultiLocation, AssetId> {
+		Prefix::get().pushed_with(Junction::GeneralIndex { id: what.clone().into() }).map_err(|_| what)
+	}
+}
+
+/// Filter for asset ids which should not have their teleports accounted for.
+pub struct NoChecking;
+impl<AssetId> Filter<AssetId> for NoChecking {
+	fn filter(_: &AssetId) -> bool { false }
+}
+
+/// Transacts in many fungible assets held in the `Assets` registry.
+///
+/// Assets are identified through `Matcher` and accounts through `AccountIdConverter`. Teleports of any asset
+/// whose id passes `CheckAsset` are accounted for in `CheckingAccount`: assets teleported out are deposited into
+/// it, and assets teleported in must be withdrawable from it, so that no more can be teleported in than was
+/// ever sent out. Use `NoChecking` to disable this.
+pub struct FungiblesAdapter<Assets, Matcher, AccountIdConverter, AccountId, CheckAsset, CheckingAccount>(
+	PhantomData<(Assets, Matcher, AccountIdConverter, AccountId, CheckAsset, CheckingAccount)>
+);
+
+impl<
+	Assets: Fungibles<AccountId>,
+	Matcher: MatchesFungibles<Assets::AssetId, Assets::Balance>,
+	AccountIdConverter: LocationConversion<AccountId>,
+	AccountId,	// can't get away without it since Assets is generic over it.
+	CheckAsset: Filter<Assets::AssetId>,
+	CheckingAccount: Get<AccountId>,
+> TransactAsset for FungiblesAdapter<Assets, Matcher, AccountIdConverter, AccountId, CheckAsset, CheckingAccount> {
+	fn can_check_in(_origin: &MultiLocation, what: &MultiAsset) -> Result {
+		// Check we handle this asset.
+		let (asset_id, amount) = Matcher::matches_fungibles(what).ok_or(Error::AssetNotFound)?;
+		if CheckAsset::filter(&asset_id) {
+			// This is an asset whose teleports we track; make sure we have sent out at least this much.
+			let checking_account = CheckingAccount::get();
+			if Assets::balance(asset_id, &checking_account) < amount {
+				return Err(Error::UntrustedTeleportLocation)
+			}
+		}
+		Ok(())
+	}
+
+	fn check_in(_origin: &MultiLocation, what: &MultiAsset) {
+		if let Some((asset_id, amount)) = Matcher::matches_fungibles(what) {
+			if CheckAsset::filter(&asset_id) {
+				let checking_account = CheckingAccount::get();
+				let ok = Assets::withdraw(asset_id, &checking_account, amount).is_ok();
+				debug_assert!(ok, "`can_check_in` must have returned `true` immediately prior; qed");
+			}
+		}
+	}
+
+	fn check_out(_dest: &MultiLocation, what: &MultiAsset) {
+		if let Some((asset_id, amount)) = Matcher::matches_fungibles(what) {
+			if CheckAsset::filter(&asset_id) {
+				let checking_account = CheckingAccount::get();
+				let _ = Assets::deposit(asset_id, &checking_account, amount);
+			}
+		}
+	}
+
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		// Check we handle this asset.
+		let (asset_id, amount) = Matcher::matches_fungibles(what).ok_or(())?;
+		let who = AccountIdConverter::from_location(who).ok_or(())?;
+		Assets::deposit(asset_id, &who, amount).map_err(|_| ())?;
+		Ok(())
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> result::Result<MultiAsset, Error> {
+		// Check we handle this asset.
+		let (asset_id, amount) = Matcher::matches_fungibles(what).ok_or(())?;
+		let who = AccountIdConverter::from_location(who).ok_or(())?;
+		Assets::withdraw(asset_id, &who, amount).map_err(|_| ())?;
+		Ok(what.clone())
+	}
+}
@@ -16,6 +16,11 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 mod location_conversion;
 pub use location_conversion::{
 	Account32Hash, ParentIsDefault, ChildParachainConvertsVia, SiblingParachainConvertsVia, AccountId32Aliases
@@ -30,6 +35,29 @@ pub use origin_conversion::{
 mod currency_adapter;
 pub use currency_adapter::CurrencyAdapter;
 
+mod fungibles_adapter;
+pub use fungibles_adapter::{
+	Fungibles, FungiblesAdapter, ConvertedConcreteAssetId, ConvertedAbstractAssetId, AsPrefixedGeneralIndex,
+	NoChecking,
+};
+
+mod nonfungibles_adapter;
+pub use nonfungibles_adapter::{
+	UniqueItems, NonFungiblesAdapter, ConvertedConcreteClassId, ConvertedAbstractClassId, AsIndexInstance,
+};
+
+mod versioned_sender;
+pub use versioned_sender::VersionedSender;
+
+mod barriers;
+pub use barriers::{
+	TakeWeightCredit, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, AllowHrmpChannelManagementFrom,
+	IsParent, IsChildParachain, IsChildSystemParachain, IsInVec,
+};
+
+mod weight;
+pub use weight::{MAX_WEIGHING_DEPTH, FixedWeightBounds, FixedRateOfConcreteFungible, UsingComponents};
+
 use tetcore_std::marker::PhantomData;
 use xcm_executor::traits::InvertLocation;
 use xcm::v0::{MultiLocation, Junction};
@@ -0,0 +1,384 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A configuration of the XCM executor built from the components of this crate and some mocks. The mocks keep
+//! their state in thread-locals, or in storage where it must be transactional, so that every test starts afresh.
+
+pub use std::{cell::RefCell, collections::BTreeMap};
+pub use tet_core::H256;
+pub use tp_runtime::{testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+pub use fabric_support::{
+	impl_outer_origin, impl_outer_dispatch, parameter_types,
+	storage::unhashed,
+	traits::{Get, Filter},
+	weights::{Weight, constants::WEIGHT_PER_SECOND},
+};
+pub use xcm::v0::{
+	Xcm, Order, MultiAsset, MultiLocation, Junction, OriginKind, Outcome, Response, ExecuteXcm, SendXcm,
+	Error as XcmError, Result as XcmResult,
+};
+pub use xcm_executor::{
+	XcmExecutor, Assets, Config,
+	traits::{
+		TransactAsset, ConvertOrigin, NativeAsset, AssetExchange, LocationConversion, ManageHrmpChannels, IsConcrete,
+	},
+};
+pub use crate::{
+	TakeWeightCredit, AllowTopLevelPaidExecutionFrom, AllowUnpaidExecutionFrom, AllowHrmpChannelManagementFrom,
+	IsChildParachain, IsInVec, FixedWeightBounds,
+	FixedRateOfConcreteFungible, LocationInverter, Fungibles, FungiblesAdapter, ConvertedConcreteAssetId,
+	AsPrefixedGeneralIndex, CurrencyAdapter,
+};
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+
+impl_outer_origin! {
+	pub enum Origin for Test {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Test where origin: Origin {
+		fabric_system::System,
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub BlockWeights: fabric_system::limits::BlockWeights =
+		fabric_system::limits::BlockWeights::simple_max(4 * 1024 * 1024 * 1024);
+}
+
+impl fabric_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = BlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = u64;
+	type Lookup = IdentityLookup<u64>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type NobleInfo = ();
+	type AccountData = noble_balances::AccountData<u128>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+pub type System = fabric_system::Module<Test>;
+
+parameter_types! {
+	pub const ExistentialDeposit: u128 = 1;
+}
+
+impl noble_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u128;
+	type Event = ();
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+pub type Balances = noble_balances::Module<Test>;
+
+thread_local! {
+	/// The assets held by each location.
+	static ASSETS: RefCell<BTreeMap<MultiLocation, Assets>> = RefCell::new(BTreeMap::new());
+	/// Every message sent, together with its destination, in the order it was sent.
+	static SENT_XCM: RefCell<Vec<(MultiLocation, Xcm)>> = RefCell::new(Vec::new());
+	/// The origins which may execute messages without paying for them.
+	static ALLOW_UNPAID_FROM: RefCell<Vec<MultiLocation>> = RefCell::new(Vec::new());
+	/// The origins which may execute messages which pay for themselves.
+	static ALLOW_PAID_FROM: RefCell<Vec<MultiLocation>> = RefCell::new(Vec::new());
+	/// The balance of each account in each class of `TestFungibles`.
+	static FUNGIBLES: RefCell<BTreeMap<(u32, u64), u128>> = RefCell::new(BTreeMap::new());
+	/// Every HRMP channel operation requested, in the order it was requested.
+	static HRMP_OPERATIONS: RefCell<Vec<HrmpOperation>> = RefCell::new(Vec::new());
+}
+
+/// Give `what` to `who`.
+pub fn add_asset(who: MultiLocation, what: MultiAsset) {
+	ASSETS.with(|a| a.borrow_mut().entry(who).or_default().saturating_subsume(what));
+}
+
+/// The assets held by `who`.
+pub fn assets(who: &MultiLocation) -> Vec<MultiAsset> {
+	ASSETS.with(|a| a.borrow().get(who).map_or_else(Vec::new, |a| a.assets_iter().collect()))
+}
+
+/// Every message sent so far, together with its destination.
+pub fn sent_xcm() -> Vec<(MultiLocation, Xcm)> {
+	SENT_XCM.with(|q| q.borrow().clone())
+}
+
+/// Allow `origin` to execute messages without paying for them.
+pub fn allow_unpaid_from(origin: MultiLocation) {
+	ALLOW_UNPAID_FROM.with(|l| l.borrow_mut().push(origin));
+}
+
+/// Allow `origin` to execute messages which pay for themselves.
+pub fn allow_paid_from(origin: MultiLocation) {
+	ALLOW_PAID_FROM.with(|l| l.borrow_mut().push(origin));
+}
+
+pub struct AllowUnpaidFrom;
+impl Get<Vec<MultiLocation>> for AllowUnpaidFrom {
+	fn get() -> Vec<MultiLocation> {
+		ALLOW_UNPAID_FROM.with(|l| l.borrow().clone())
+	}
+}
+
+pub struct AllowPaidFrom;
+impl Get<Vec<MultiLocation>> for AllowPaidFrom {
+	fn get() -> Vec<MultiLocation> {
+		ALLOW_PAID_FROM.with(|l| l.borrow().clone())
+	}
+}
+
+/// Records every message sent, and can reach anywhere.
+pub struct TestSendXcm;
+impl SendXcm for TestSendXcm {
+	fn send_xcm(dest: MultiLocation, msg: Xcm) -> XcmResult {
+		SENT_XCM.with(|q| q.borrow_mut().push((dest, msg)));
+		Ok(())
+	}
+}
+
+/// Keeps any asset in the `ASSETS` of any location.
+pub struct TestAssetTransactor;
+impl TransactAsset for TestAssetTransactor {
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> XcmResult {
+		add_asset(who.clone(), what.clone());
+		Ok(())
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> Result<MultiAsset, XcmError> {
+		ASSETS.with(|a| {
+			let mut a = a.borrow_mut();
+			let held = a.get_mut(who).ok_or(XcmError::Undefined)?;
+			*held = held.clone().checked_sub(what.clone()).map_err(|_| XcmError::Undefined)?;
+			Ok(what.clone())
+		})
+	}
+}
+
+/// An HRMP channel operation requested of `TestHrmpChannelManager`, by the para with the given id.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HrmpOperation {
+	InitOpenChannel { origin: u32, recipient: u32, max_message_size: u32, max_capacity: u32 },
+	AcceptOpenChannel { origin: u32, sender: u32 },
+	CloseChannel { origin: u32, sender: u32, recipient: u32 },
+}
+
+/// Every HRMP channel operation requested so far.
+pub fn hrmp_operations() -> Vec<HrmpOperation> {
+	HRMP_OPERATIONS.with(|o| o.borrow().clone())
+}
+
+/// Records every operation requested by a child parachain, and refuses those of any other origin.
+pub struct TestHrmpChannelManager;
+impl TestHrmpChannelManager {
+	fn record(origin: &MultiLocation, operation: impl FnOnce(u32) -> HrmpOperation) -> XcmResult {
+		match origin {
+			MultiLocation::X1(Junction::Parachain { id }) => {
+				HRMP_OPERATIONS.with(|o| o.borrow_mut().push(operation(*id)));
+				Ok(())
+			}
+			_ => Err(XcmError::BadOrigin),
+		}
+	}
+}
+impl ManageHrmpChannels for TestHrmpChannelManager {
+	fn init_open_channel(
+		origin: &MultiLocation,
+		recipient: u32,
+		max_message_size: u32,
+		max_capacity: u32,
+	) -> XcmResult {
+		Self::record(origin, |origin| HrmpOperation::InitOpenChannel {
+			origin, recipient, max_message_size, max_capacity,
+		})
+	}
+
+	fn accept_open_channel(origin: &MultiLocation, sender: u32) -> XcmResult {
+		Self::record(origin, |origin| HrmpOperation::AcceptOpenChannel { origin, sender })
+	}
+
+	fn close_channel(origin: &MultiLocation, sender: u32, recipient: u32) -> XcmResult {
+		Self::record(origin, |origin| HrmpOperation::CloseChannel { origin, sender, recipient })
+	}
+}
+
+const EXCHANGE_STOCK: &[u8] = b":test_exchange_stock:";
+
+/// Stock the exchange with `assets`.
+pub fn set_exchange_stock(assets: Vec<MultiAsset>) {
+	unhashed::put(EXCHANGE_STOCK, &assets);
+}
+
+/// The assets the exchange is stocked with.
+pub fn exchange_stock() -> Vec<MultiAsset> {
+	unhashed::get_or_default(EXCHANGE_STOCK)
+}
+
+/// Hands over its whole stock in return for whatever it is given, whether or not that covers what is wanted.
+///
+/// The stock is kept in storage, so that the exchange is undone along with any storage transaction it is part of.
+pub struct TestAssetExchanger;
+impl AssetExchange for TestAssetExchanger {
+	fn exchange_holding(_origin: &MultiLocation, give: &Assets, _want: &[MultiAsset]) -> Result<Assets, XcmError> {
+		let stock = exchange_stock();
+		set_exchange_stock(give.assets_iter().collect());
+		Ok(stock.into())
+	}
+}
+
+/// Dispatches calls as the root origin when asked to by the relay-chain, and as the signed origin of account
+/// `id` when asked to by parachain `id`.
+pub struct TestOriginConverter;
+impl ConvertOrigin<Origin> for TestOriginConverter {
+	fn convert_origin(origin: MultiLocation, kind: OriginKind) -> Result<Origin, MultiLocation> {
+		match (kind, &origin) {
+			(OriginKind::Superuser, MultiLocation::X1(Junction::Parent)) =>
+				Ok(fabric_system::RawOrigin::Root.into()),
+			(OriginKind::SovereignAccount, MultiLocation::X1(Junction::Parachain { id })) =>
+				Ok(fabric_system::RawOrigin::Signed(*id as u64).into()),
+			_ => Err(origin),
+		}
+	}
+}
+
+/// A registry of fungible assets, kept in `FUNGIBLES`.
+pub struct TestFungibles;
+impl Fungibles<u64> for TestFungibles {
+	type AssetId = u32;
+	type Balance = u128;
+
+	fn balance(id: u32, who: &u64) -> u128 {
+		FUNGIBLES.with(|f| f.borrow().get(&(id, *who)).copied().unwrap_or_default())
+	}
+
+	fn deposit(id: u32, who: &u64, amount: u128) -> tp_runtime::DispatchResult {
+		FUNGIBLES.with(|f| *f.borrow_mut().entry((id, *who)).or_default() += amount);
+		Ok(())
+	}
+
+	fn withdraw(id: u32, who: &u64, amount: u128) -> tp_runtime::DispatchResult {
+		FUNGIBLES.with(|f| {
+			let mut f = f.borrow_mut();
+			let balance = f.entry((id, *who)).or_default();
+			*balance = balance.checked_sub(amount).ok_or("InsufficientBalance")?;
+			Ok(())
+		})
+	}
+}
+
+/// The account of parachain `id` is `id`.
+pub struct TestAccountIdConverter;
+impl LocationConversion<u64> for TestAccountIdConverter {
+	fn from_location(location: &MultiLocation) -> Option<u64> {
+		match location {
+			MultiLocation::X1(Junction::Parachain { id }) => Some(*id as u64),
+			_ => None,
+		}
+	}
+
+	fn try_into_location(who: u64) -> Result<MultiLocation, u64> {
+		Ok(Junction::Parachain { id: who as u32 }.into())
+	}
+}
+
+/// Checks the teleports of every asset.
+pub struct CheckAll;
+impl<T> Filter<T> for CheckAll {
+	fn filter(_: &T) -> bool { true }
+}
+
+parameter_types! {
+	pub const UnitWeightCost: Weight = 10;
+	pub const MaxRelayDepth: u32 = 2;
+	pub TestAncestry: MultiLocation = Junction::Parachain { id: 42 }.into();
+	// One unit of the relay-chain token buys one unit of weight.
+	pub WeightPrice: (MultiLocation, u128) = (MultiLocation::X1(Junction::Parent), WEIGHT_PER_SECOND as u128);
+	pub RelayLocation: MultiLocation = Junction::Parent.into();
+	pub FungiblesPrefix: MultiLocation = Junction::NobleInstance { id: 50 }.into();
+	pub const CheckingAccount: u64 = 1_000;
+}
+
+pub type TestFungiblesAdapter = FungiblesAdapter<
+	TestFungibles,
+	ConvertedConcreteAssetId<u32, u128, AsPrefixedGeneralIndex<FungiblesPrefix, u32>>,
+	TestAccountIdConverter,
+	u64,
+	CheckAll,
+	CheckingAccount,
+>;
+
+pub type TestCurrencyAdapter = CurrencyAdapter<Balances, IsConcrete<RelayLocation>, TestAccountIdConverter, u64>;
+
+pub type TestBarrier = (
+	TakeWeightCredit,
+	AllowTopLevelPaidExecutionFrom<IsInVec<AllowPaidFrom>>,
+	AllowUnpaidExecutionFrom<IsInVec<AllowUnpaidFrom>>,
+	AllowHrmpChannelManagementFrom<IsChildParachain>,
+);
+
+pub struct TestConfig;
+impl Config for TestConfig {
+	type Call = Call;
+	type XcmSender = TestSendXcm;
+	type AssetTransactor = TestAssetTransactor;
+	type AssetExchanger = TestAssetExchanger;
+	type OriginConverter = TestOriginConverter;
+	type IsReserve = NativeAsset;
+	type IsTeleporter = NativeAsset;
+	type LocationInverter = LocationInverter<TestAncestry>;
+	type MaxRelayDepth = MaxRelayDepth;
+	type Barrier = TestBarrier;
+	type Weigher = FixedWeightBounds<UnitWeightCost>;
+	type Trader = FixedRateOfConcreteFungible<WeightPrice>;
+	type ResponseHandler = ();
+	type HrmpChannelManager = TestHrmpChannelManager;
+}
+
+pub type TestXcmExecutor = XcmExecutor<TestConfig>;
+
+/// The relay-chain token, as seen from this parachain.
+pub fn relay_tokens(amount: u128) -> MultiAsset {
+	MultiAsset::ConcreteFungible { id: MultiLocation::X1(Junction::Parent), amount }
+}
+
+/// `amount` of the asset of `TestFungibles` with the given `id`.
+pub fn fungibles_tokens(id: u32, amount: u128) -> MultiAsset {
+	let id = MultiLocation::X2(Junction::NobleInstance { id: 50 }, Junction::GeneralIndex { id: id as u128 });
+	MultiAsset::ConcreteFungible { id, amount }
+}
+
+/// Execute `test` with empty storage.
+pub fn new_test_ext<R>(test: impl FnOnce() -> R) -> R {
+	tet_io::TestExternalities::default().execute_with(test)
+}
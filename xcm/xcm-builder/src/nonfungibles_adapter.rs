@@ -0,0 +1,164 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use tetcore_std::{prelude::*, result, convert::TryFrom, marker::PhantomData};
+use xcm::v0::{Error, Result, MultiAsset, MultiLocation, AssetInstance};
+use tp_runtime::DispatchResult;
+use fabric_support::traits::{Get, Filter};
+use xcm_executor::traits::{MatchesNonFungibles, AssetIdConversion, LocationConversion, TransactAsset};
+
+/// A registry of unique items, each identified by the class it belongs to and its instance within that class.
+pub trait UniqueItems<AccountId> {
+	/// The identifier of a class of items.
+	type ClassId: Clone;
+	/// The identifier of an item within its class.
+	type InstanceId: Clone;
+
+	/// The owner of the given item, if it exists.
+	fn owner(class: &Self::ClassId, instance: &Self::InstanceId) -> Option<AccountId>;
+
+	/// Create the given item, owned by `who`. Fails if it already exists.
+	fn mint_into(class: &Self::ClassId, instance: &Self::InstanceId, who: &AccountId) -> DispatchResult;
+
+	/// Destroy the given item. Fails if it does not exist.
+	fn burn(class: &Self::ClassId, instance: &Self::InstanceId) -> DispatchResult;
+}
+
+/// Matches `ConcreteNonFungible` assets whose class and instance can both be converted into local identifiers.
+pub struct ConvertedConcreteClassId<ClassId, InstanceId, ConvertClassId, ConvertInstanceId>(
+	PhantomData<(ClassId, InstanceId, ConvertClassId, ConvertInstanceId)>
+);
+impl<
+	ClassId: Clone,
+	InstanceId: Clone,
+	ConvertClassId: AssetIdConversion<MultiLocation, ClassId>,
+	ConvertInstanceId: AssetIdConversion<AssetInstance, InstanceId>,
+> MatchesNonFungibles<ClassId, InstanceId>
+	for ConvertedConcreteClassId<ClassId, InstanceId, ConvertClassId, ConvertInstanceId>
+{
+	fn matches_nonfungibles(a: &MultiAsset) -> Option<(ClassId, InstanceId)> {
+		match a {
+			MultiAsset::ConcreteNonFungible { class, instance } =>
+				Some((ConvertClassId::from_asset_id(class)?, ConvertInstanceId::from_asset_id(instance)?)),
+			_ => None,
+		}
+	}
+}
+
+/// Matches `AbstractNonFungible` assets whose class and instance can both be converted into local identifiers.
+pub struct ConvertedAbstractClassId<ClassId, InstanceId, ConvertClassId, ConvertInstanceId>(
+	PhantomData<(ClassId, InstanceId, ConvertClassId, ConvertInstanceId)>
+);
+impl<
+	ClassId: Clone,
+	InstanceId: Clone,
+	ConvertClassId: AssetIdConversion<Vec<u8>, ClassId>,
+	ConvertInstanceId: AssetIdConversion<AssetInstance, InstanceId>,
+> MatchesNonFungibles<ClassId, InstanceId>
+	for ConvertedAbstractClassId<ClassId, InstanceId, ConvertClassId, ConvertInstanceId>
+{
+	fn matches_nonfungibles(a: &MultiAsset) -> Option<(ClassId, InstanceId)> {
+		match a {
+			MultiAsset::AbstractNonFungible { class, instance } =>
+				Some((ConvertClassId::from_asset_id(class)?, ConvertInstanceId::from_asset_id(instance)?)),
+			_ => None,
+		}
+	}
+}
+
+/// Converts between a local `InstanceId` and an `AssetInstance::Index` of the same value.
+pub struct AsIndexInstance<InstanceId>(PhantomData<InstanceId>);
+impl<
+	InstanceId: TryFrom<u128> + Into<u128> + Clone,
+> AssetIdConversion<AssetInstance, InstanceId> for AsIndexInstance<InstanceId> {
+	fn from_asset_id(id: &AssetInstance) -> Option<InstanceId> {
+		match id {
+			AssetInstance::Index { id } => InstanceId::try_from(*id).ok(),
+			_ => None,
+		}
+	}
+
+	fn try_into_asset_id(what: InstanceId) -> result::Result<AssetInstance, InstanceId> {
+		Ok(AssetInstance::Index { id: what.into() })
+	}
+}
+
+/// Transacts in unique items held in the `Items` registry.
+///
+/// Items are identified through `Matcher` and accounts through `AccountIdConverter`. Withdrawing an item burns it
+/// and depositing one mints it, so an item exists on at most one chain at a time. Teleports of items of any class
+/// passing `CheckClass` are accounted for in `CheckingAccount`, which holds every such item teleported out; an
+/// item may only be teleported in if it is held there. Use `NoChecking` to disable this.
+pub struct NonFungiblesAdapter<Items, Matcher, AccountIdConverter, AccountId, CheckClass, CheckingAccount>(
+	PhantomData<(Items, Matcher, AccountIdConverter, AccountId, CheckClass, CheckingAccount)>
+);
+
+impl<
+	Items: UniqueItems<AccountId>,
+	Matcher: MatchesNonFungibles<Items::ClassId, Items::InstanceId>,
+	AccountIdConverter: LocationConversion<AccountId>,
+	AccountId: PartialEq,	// can't get away without it since Items is generic over it.
+	CheckClass: Filter<Items::ClassId>,
+	CheckingAccount: Get<AccountId>,
+> TransactAsset for NonFungiblesAdapter<Items, Matcher, AccountIdConverter, AccountId, CheckClass, CheckingAccount> {
+	fn can_check_in(_origin: &MultiLocation, what: &MultiAsset) -> Result {
+		// Check we handle this asset.
+		let (class, instance) = Matcher::matches_nonfungibles(what).ok_or(Error::AssetNotFound)?;
+		if CheckClass::filter(&class) {
+			// This is a class whose teleports we track; make sure this very item was sent out.
+			if Items::owner(&class, &instance) != Some(CheckingAccount::get()) {
+				return Err(Error::UntrustedTeleportLocation)
+			}
+		}
+		Ok(())
+	}
+
+	fn check_in(_origin: &MultiLocation, what: &MultiAsset) {
+		if let Some((class, instance)) = Matcher::matches_nonfungibles(what) {
+			if CheckClass::filter(&class) {
+				let ok = Items::burn(&class, &instance).is_ok();
+				debug_assert!(ok, "`can_check_in` must have returned `true` immediately prior; qed");
+			}
+		}
+	}
+
+	fn check_out(_dest: &MultiLocation, what: &MultiAsset) {
+		if let Some((class, instance)) = Matcher::matches_nonfungibles(what) {
+			if CheckClass::filter(&class) {
+				let _ = Items::mint_into(&class, &instance, &CheckingAccount::get());
+			}
+		}
+	}
+
+	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> Result {
+		// Check we handle this asset.
+		let (class, instance) = Matcher::matches_nonfungibles(what).ok_or(())?;
+		let who = AccountIdConverter::from_location(who).ok_or(())?;
+		Items::mint_into(&class, &instance, &who).map_err(|_| ())?;
+		Ok(())
+	}
+
+	fn withdraw_asset(what: &MultiAsset, who: &MultiLocation) -> result::Result<MultiAsset, Error> {
+		// Check we handle this asset.
+		let (class, instance) = Matcher::matches_nonfungibles(what).ok_or(())?;
+		let who = AccountIdConverter::from_location(who).ok_or(())?;
+		if Items::owner(&class, &instance) != Some(who) {
+			return Err(Error::BadOrigin)
+		}
+		Items::burn(&class, &instance).map_err(|_| ())?;
+		Ok(what.clone())
+	}
+}
@@ -0,0 +1,570 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::{mock::*, MAX_WEIGHING_DEPTH};
+use xcm_executor::traits::WeightBounds;
+use tetsy_scale_codec::Encode;
+use fabric_support::weights::GetDispatchInfo;
+
+fn relayed(times: u32, mut msg: Xcm) -> Xcm {
+	for _ in 0..times {
+		msg = Xcm::RelayedFrom { superorigin: Junction::OnlyChild.into(), inner: Box::new(msg.into()) };
+	}
+	msg
+}
+
+#[test]
+fn weighing_is_bounded_in_depth() {
+	let report = |mut msg: Xcm, times: u32| {
+		for query_id in 0..times as u64 {
+			msg = Xcm::ReportOutcome { query_id, dest: Junction::Parent.into(), message: Box::new(msg) };
+		}
+		msg
+	};
+	let base = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	let deepest = report(base.clone(), MAX_WEIGHING_DEPTH);
+	assert_eq!(
+		<FixedWeightBounds<UnitWeightCost>>::weight(&deepest),
+		Ok(10 * (MAX_WEIGHING_DEPTH as u64 + 1)),
+	);
+
+	let too_deep = report(base, MAX_WEIGHING_DEPTH + 1);
+	assert_eq!(<FixedWeightBounds<UnitWeightCost>>::weight(&too_deep), Err(()));
+}
+
+#[test]
+fn relay_depth_is_checked_before_weighing() {
+	allow_unpaid_from(MultiLocation::X3(Junction::Parent, Junction::OnlyChild, Junction::OnlyChild));
+	let msg = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	let within_limit = relayed(MaxRelayDepth::get(), msg.clone());
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(Junction::Parent.into(), within_limit, 1_000),
+		Outcome::Complete(30),
+	);
+
+	// Too deep to be weighed at all, yet it is the depth which is reported.
+	let too_deep = relayed(MAX_WEIGHING_DEPTH + 1, msg);
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(Junction::Parent.into(), too_deep, 1_000),
+		Outcome::Error(XcmError::RelayDepthExceeded),
+	);
+}
+
+fn exchange_message(receive: Vec<MultiAsset>) -> Xcm {
+	Xcm::WithdrawAsset {
+		assets: vec![relay_tokens(100)],
+		effects: vec![
+			Order::ExchangeAsset { give: vec![relay_tokens(100)], receive },
+			Order::DepositAsset { assets: vec![MultiAsset::All], dest: Junction::Parachain { id: 1 }.into() },
+		],
+	}
+}
+
+fn other_tokens(amount: u128) -> MultiAsset {
+	MultiAsset::AbstractFungible { id: b"other".to_vec(), amount }
+}
+
+#[test]
+fn exchange_asset_works() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+		set_exchange_stock(vec![other_tokens(50)]);
+
+		let outcome = TestXcmExecutor::execute_xcm(para.clone(), exchange_message(vec![other_tokens(40)]), 1_000);
+		assert_eq!(outcome, Outcome::Complete(30));
+		assert_eq!(assets(&para), vec![other_tokens(50)]);
+		assert_eq!(exchange_stock(), vec![relay_tokens(100)]);
+	});
+}
+
+#[test]
+fn exchange_asset_falling_short_is_undone() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+		set_exchange_stock(vec![other_tokens(50)]);
+
+		let outcome = TestXcmExecutor::execute_xcm(para.clone(), exchange_message(vec![other_tokens(60)]), 1_000);
+		assert_eq!(outcome, Outcome::Incomplete(30, Some(0), XcmError::TooMuchSlippage));
+		assert_eq!(exchange_stock(), vec![other_tokens(50)]);
+	});
+}
+
+#[test]
+fn exchange_asset_wildcard_sets_no_minimum() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+		set_exchange_stock(vec![other_tokens(50)]);
+
+		let receive = vec![MultiAsset::AllAbstractFungible { id: b"other".to_vec() }];
+		let outcome = TestXcmExecutor::execute_xcm(para.clone(), exchange_message(receive), 1_000);
+		assert_eq!(outcome, Outcome::Complete(30));
+		assert_eq!(assets(&para), vec![other_tokens(50)]);
+	});
+}
+
+fn call_weight(call: &Call) -> Weight {
+	call.get_dispatch_info().weight
+}
+
+#[test]
+fn outcome_accounts_for_weight_used() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+
+		let msg = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(100)],
+			effects: vec![Order::DepositAsset { assets: vec![MultiAsset::All], dest: para.clone() }],
+		};
+		assert_eq!(TestXcmExecutor::execute_xcm(para.clone(), msg, 1_000), Outcome::Complete(20));
+
+		// Only the weight which the call actually needed is reported, not all of that which it was allowed.
+		let call = Call::System(fabric_system::Call::remark(vec![]));
+		let msg = Xcm::Transact {
+			origin_type: OriginKind::SovereignAccount,
+			require_weight_at_most: 1_000_000_000,
+			call: call.encode(),
+		};
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para, msg, 2_000_000_000),
+			Outcome::Complete(10 + call_weight(&call)),
+		);
+	});
+}
+
+#[test]
+fn outcome_reports_failed_order() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+
+		// Everything is deposited before fees are bought, so there is nothing left to buy them with.
+		let msg = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(100)],
+			effects: vec![
+				Order::DepositAsset { assets: vec![MultiAsset::All], dest: para.clone() },
+				Order::BuyExecution { fees: MultiAsset::All, weight: 0, debt: 0, halt_on_error: true, xcm: vec![] },
+			],
+		};
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), msg, 1_000),
+			Outcome::Incomplete(30, Some(1), XcmError::NotHoldingFees),
+		);
+		assert_eq!(assets(&para), vec![relay_tokens(100)]);
+	});
+}
+
+#[test]
+fn outcome_reports_failed_dispatch() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+
+		// A parachain's sovereign account is not root.
+		let call = Call::System(fabric_system::Call::set_heap_pages(64));
+		let msg = Xcm::Transact {
+			origin_type: OriginKind::SovereignAccount,
+			require_weight_at_most: 1_000_000_000,
+			call: call.encode(),
+		};
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para, msg, 2_000_000_000),
+			Outcome::Incomplete(10 + call_weight(&call), None, XcmError::DispatchFailed),
+		);
+	});
+}
+
+#[test]
+fn outcome_reports_error_before_execution() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		let msg = Xcm::WithdrawAsset { assets: vec![relay_tokens(100)], effects: vec![] };
+
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), msg.clone(), 1_000),
+			Outcome::Error(XcmError::Barrier),
+		);
+
+		allow_unpaid_from(para.clone());
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), msg.clone(), 5),
+			Outcome::Error(XcmError::WeightLimitReached(10)),
+		);
+		// Nothing is held, so there is nothing to withdraw.
+		assert_eq!(TestXcmExecutor::execute_xcm(para, msg, 1_000), Outcome::Error(XcmError::Undefined));
+	});
+}
+
+#[test]
+fn report_outcome_sends_outcome_of_message() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+
+		let message = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(100)],
+			effects: vec![
+				Order::DepositAsset { assets: vec![MultiAsset::All], dest: para.clone() },
+				Order::BuyExecution { fees: MultiAsset::All, weight: 0, debt: 0, halt_on_error: true, xcm: vec![] },
+			],
+		};
+		let msg = Xcm::ReportOutcome { query_id: 7, dest: para.clone(), message: Box::new(message) };
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), msg, 1_000),
+			Outcome::Incomplete(40, Some(1), XcmError::NotHoldingFees),
+		);
+
+		// The report leaves out the weight of the `ReportOutcome` itself.
+		let outcome = Outcome::Incomplete(30, Some(1), XcmError::NotHoldingFees);
+		assert_eq!(
+			sent_xcm(),
+			vec![(para, Xcm::QueryResponse { query_id: 7, response: Response::ExecutionResult(outcome) })],
+		);
+	});
+}
+
+#[test]
+fn weight_credit_pays_for_execution() {
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+	let msg = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	// The origin is allowed nothing, so only the credit lets the message through.
+	assert_eq!(
+		TestXcmExecutor::execute_xcm_in_credit(para.clone(), msg.clone(), 1_000, 9),
+		Outcome::Error(XcmError::Barrier),
+	);
+	assert_eq!(TestXcmExecutor::execute_xcm_in_credit(para, msg, 1_000, 10), Outcome::Complete(10));
+}
+
+fn paid_message(para: &MultiLocation, debt: Weight, weight: Weight, xcm: Vec<Xcm>) -> Xcm {
+	Xcm::WithdrawAsset {
+		assets: vec![relay_tokens(1_000)],
+		effects: vec![
+			Order::BuyExecution { fees: MultiAsset::All, weight, debt, halt_on_error: true, xcm },
+			Order::DepositAsset { assets: vec![MultiAsset::All], dest: para.clone() },
+		],
+	}
+}
+
+#[test]
+fn paid_execution_is_allowed_when_paid_for() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_paid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(1_000));
+
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), paid_message(&para, 30, 0, vec![]), 1_000),
+			Outcome::Complete(30),
+		);
+		assert_eq!(assets(&para), vec![relay_tokens(970)]);
+	});
+}
+
+#[test]
+fn paid_execution_is_not_allowed_unless_paid_for() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		add_asset(para.clone(), relay_tokens(1_000));
+
+		// Not an origin which is allowed to pay.
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), paid_message(&para, 30, 0, vec![]), 1_000),
+			Outcome::Error(XcmError::Barrier),
+		);
+
+		allow_paid_from(para.clone());
+		// Paying for less than the shallow weight of the message.
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), paid_message(&para, 20, 0, vec![]), 1_000),
+			Outcome::Error(XcmError::Barrier),
+		);
+		// Paying, but not before anything else is done.
+		let msg = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(1_000)],
+			effects: vec![
+				Order::DepositAsset { assets: vec![], dest: para.clone() },
+				Order::BuyExecution { fees: MultiAsset::All, weight: 0, debt: 30, halt_on_error: true, xcm: vec![] },
+			],
+		};
+		assert_eq!(TestXcmExecutor::execute_xcm(para.clone(), msg, 1_000), Outcome::Error(XcmError::Barrier));
+		assert_eq!(assets(&para), vec![relay_tokens(1_000)]);
+	});
+}
+
+#[test]
+fn bought_weight_pays_for_nested_messages() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		let other: MultiLocation = Junction::Parachain { id: 2 }.into();
+		allow_paid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(1_010));
+
+		// The nested message is not top-level, so it is only let through by the weight bought for it.
+		let nested = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(10)],
+			effects: vec![Order::DepositAsset { assets: vec![MultiAsset::All], dest: other.clone() }],
+		};
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), paid_message(&para, 30, 20, vec![nested.clone()]), 1_000),
+			Outcome::Complete(50),
+		);
+		assert_eq!(assets(&para), vec![relay_tokens(950)]);
+		assert_eq!(assets(&other), vec![relay_tokens(10)]);
+
+		// Not buying enough weight for the nested message stops execution.
+		add_asset(para.clone(), relay_tokens(50));
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), paid_message(&para, 30, 10, vec![nested]), 1_000),
+			Outcome::Incomplete(50, Some(0), XcmError::TooExpensive),
+		);
+		assert_eq!(assets(&other), vec![relay_tokens(10)]);
+	});
+}
+
+fn relay_to(dest: MultiLocation, inner: Xcm) -> Xcm {
+	Xcm::RelayTo { dest, inner: Box::new(inner.into()) }
+}
+
+fn relayed_from(superorigin: MultiLocation, inner: Xcm) -> Xcm {
+	Xcm::RelayedFrom { superorigin, inner: Box::new(inner.into()) }
+}
+
+fn alice() -> Junction {
+	Junction::AccountId32 { network: xcm::v0::NetworkId::Any, id: [1; 32] }
+}
+
+#[test]
+fn relay_to_is_routed_hop_by_hop() {
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+	allow_unpaid_from(para.clone());
+	let inner = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	let dest = MultiLocation::X2(Junction::Parachain { id: 2 }, Junction::Parachain { id: 3 });
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(para.clone(), relay_to(dest, inner.clone()), 1_000),
+		Outcome::Complete(10),
+	);
+	// The first hop is asked to relay the message on to the rest of the way.
+	assert_eq!(sent_xcm(), vec![(
+		Junction::Parachain { id: 2 }.into(),
+		relayed_from(para, relay_to(Junction::Parachain { id: 3 }.into(), inner)),
+	)]);
+}
+
+#[test]
+fn relay_to_an_account_is_delivered_to_its_chain() {
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+	allow_unpaid_from(para.clone());
+	let inner = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	let dest = MultiLocation::X2(Junction::Parachain { id: 2 }, alice());
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(para.clone(), relay_to(dest, inner.clone()), 1_000),
+		Outcome::Complete(10),
+	);
+	// The account is forwarded along with the message to its chain.
+	let forwarded = relayed_from(para.clone(), relay_to(alice().into(), inner.clone()));
+	assert_eq!(sent_xcm(), vec![(Junction::Parachain { id: 2 }.into(), forwarded.clone())]);
+
+	// Which hands the message to the account.
+	let relayed_origin = MultiLocation::X2(Junction::Parent, Junction::Parachain { id: 1 });
+	allow_unpaid_from(relayed_origin.clone());
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(Junction::Parent.into(), forwarded, 1_000),
+		Outcome::Complete(20),
+	);
+	assert_eq!(sent_xcm()[1], (alice().into(), relayed_from(relayed_origin, inner)));
+}
+
+#[test]
+fn relay_to_unreachable_destination_fails() {
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+	allow_unpaid_from(para.clone());
+	let inner = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	for dest in vec![
+		MultiLocation::Null,
+		Junction::Parent.into(),
+		MultiLocation::X2(Junction::Parachain { id: 2 }, Junction::Parent),
+		// Nothing within this chain contains another consensus system.
+		MultiLocation::X2(alice(), Junction::Parachain { id: 2 }),
+	] {
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), relay_to(dest, inner.clone()), 1_000),
+			Outcome::Error(XcmError::CannotReachDestination),
+		);
+	}
+	assert!(sent_xcm().is_empty());
+}
+
+#[test]
+fn relayed_message_is_executed_with_its_superorigin() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		let other: MultiLocation = Junction::Parachain { id: 2 }.into();
+		let account = MultiLocation::X2(Junction::Parachain { id: 1 }, alice());
+		allow_unpaid_from(account.clone());
+		add_asset(account.clone(), relay_tokens(100));
+
+		let inner = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(100)],
+			effects: vec![Order::DepositAsset { assets: vec![MultiAsset::All], dest: other.clone() }],
+		};
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), relayed_from(alice().into(), inner.clone()), 1_000),
+			Outcome::Complete(30),
+		);
+		assert_eq!(assets(&other), vec![relay_tokens(100)]);
+
+		// A superorigin may never claim to be outside of the origin relaying it.
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para, relayed_from(Junction::Parent.into(), inner), 1_000),
+			Outcome::Error(XcmError::EscalationOfPrivilege),
+		);
+	});
+}
+
+#[test]
+fn relay_depth_is_limited() {
+	allow_unpaid_from(MultiLocation::X4(
+		Junction::Parent, Junction::OnlyChild, Junction::OnlyChild, Junction::OnlyChild,
+	));
+	let msg = Xcm::WithdrawAsset { assets: vec![], effects: vec![] };
+
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(Junction::Parent.into(), relayed(MaxRelayDepth::get() + 1, msg), 1_000),
+		Outcome::Error(XcmError::RelayDepthExceeded),
+	);
+}
+
+#[test]
+fn report_outcome_only_reports_to_origin() {
+	new_test_ext(|| {
+		let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+		allow_unpaid_from(para.clone());
+		add_asset(para.clone(), relay_tokens(100));
+
+		let message = Xcm::WithdrawAsset {
+			assets: vec![relay_tokens(100)],
+			effects: vec![Order::DepositAsset { assets: vec![MultiAsset::All], dest: para.clone() }],
+		};
+		let msg = Xcm::ReportOutcome {
+			query_id: 7,
+			dest: Junction::Parachain { id: 2 }.into(),
+			message: Box::new(message),
+		};
+		assert_eq!(
+			TestXcmExecutor::execute_xcm(para.clone(), msg, 1_000),
+			Outcome::Error(XcmError::EscalationOfPrivilege),
+		);
+		// Neither is the message executed nor is anything reported.
+		assert_eq!(assets(&para), vec![relay_tokens(100)]);
+		assert!(sent_xcm().is_empty());
+	});
+}
+
+#[test]
+fn can_check_in_passes_over_adapters_not_handling_the_asset() {
+	type Transactor = (TestFungiblesAdapter, TestAssetTransactor);
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+
+	// Not handled by the fungibles adapter, but the other one does no teleport accounting.
+	assert_eq!(TestFungiblesAdapter::can_check_in(&para, &relay_tokens(100)), Err(XcmError::AssetNotFound));
+	assert_eq!(Transactor::can_check_in(&para, &relay_tokens(100)), Ok(()));
+
+	// Handled, and refused, by the fungibles adapter since none of it was ever teleported out.
+	assert_eq!(
+		Transactor::can_check_in(&para, &fungibles_tokens(1, 100)),
+		Err(XcmError::UntrustedTeleportLocation),
+	);
+
+	Transactor::check_out(&para, &fungibles_tokens(1, 100));
+	assert_eq!(Transactor::can_check_in(&para, &fungibles_tokens(1, 100)), Ok(()));
+	Transactor::check_in(&para, &fungibles_tokens(1, 100));
+	assert_eq!(TestFungibles::balance(1, &CheckingAccount::get()), 0);
+
+	// Nothing handles it at all.
+	assert_eq!(<()>::can_check_in(&para, &relay_tokens(100)), Err(XcmError::AssetNotFound));
+}
+
+#[test]
+fn can_check_in_reaches_adapters_after_a_currency_adapter() {
+	type Transactor = (TestCurrencyAdapter, TestFungiblesAdapter);
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+
+	// The currency is checked in by the currency adapter, which does no teleport accounting.
+	assert_eq!(TestCurrencyAdapter::can_check_in(&para, &relay_tokens(100)), Ok(()));
+	assert_eq!(Transactor::can_check_in(&para, &relay_tokens(100)), Ok(()));
+
+	// Anything else is passed on to the fungibles adapter, which refuses it since none was teleported out.
+	assert_eq!(
+		TestCurrencyAdapter::can_check_in(&para, &fungibles_tokens(1, 100)),
+		Err(XcmError::AssetNotFound),
+	);
+	assert_eq!(
+		Transactor::can_check_in(&para, &fungibles_tokens(1, 100)),
+		Err(XcmError::UntrustedTeleportLocation),
+	);
+}
+
+#[test]
+fn hrmp_channel_management_passes_the_barrier_from_child_parachains() {
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+
+	let open = Xcm::HrmpInitOpenChannel { recipient: 2, max_message_size: 8, max_capacity: 4 };
+	assert_eq!(TestXcmExecutor::execute_xcm(para.clone(), open, 50), Outcome::Complete(10));
+	let accept = Xcm::HrmpAcceptOpenChannel { sender: 3 };
+	assert_eq!(TestXcmExecutor::execute_xcm(para.clone(), accept, 50), Outcome::Complete(10));
+	let close = Xcm::HrmpCloseChannel { sender: 1, recipient: 2 };
+	assert_eq!(TestXcmExecutor::execute_xcm(para, close, 50), Outcome::Complete(10));
+
+	assert_eq!(hrmp_operations(), vec![
+		HrmpOperation::InitOpenChannel { origin: 1, recipient: 2, max_message_size: 8, max_capacity: 4 },
+		HrmpOperation::AcceptOpenChannel { origin: 1, sender: 3 },
+		HrmpOperation::CloseChannel { origin: 1, sender: 1, recipient: 2 },
+	]);
+}
+
+#[test]
+fn hrmp_channel_management_is_stopped_by_the_barrier_otherwise() {
+	let para: MultiLocation = Junction::Parachain { id: 1 }.into();
+	let close = Xcm::HrmpCloseChannel { sender: 1, recipient: 2 };
+
+	// Not from a child parachain.
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(Junction::Parent.into(), close.clone(), 50),
+		Outcome::Error(XcmError::Barrier),
+	);
+	// Relayed from within the parachain, so no longer from the parachain itself.
+	assert_eq!(
+		TestXcmExecutor::execute_xcm(para, relayed(1, close), 50),
+		Outcome::Error(XcmError::Barrier),
+	);
+
+	assert!(hrmp_operations().is_empty());
+}
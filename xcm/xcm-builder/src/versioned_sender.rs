@@ -0,0 +1,45 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use tetcore_std::{convert::TryInto, marker::PhantomData};
+use xcm::{v0, v1, VersionedXcm, ConfiguredVersion, SendVersionedXcm, LATEST_VERSION};
+
+/// Sends messages through `Transport` in the version which `Versions` is configured with for the destination,
+/// converting them up or down as needed.
+///
+/// Destinations with no configured version are sent version 0, which every destination understands.
+pub struct VersionedSender<Versions, Transport>(PhantomData<(Versions, Transport)>);
+
+impl<Versions: ConfiguredVersion, Transport: SendVersionedXcm> VersionedSender<Versions, Transport> {
+	fn send(dest: v1::MultiLocation, msg: VersionedXcm) -> v0::Result {
+		let version = Versions::configured_version_for(&dest).unwrap_or(0).min(LATEST_VERSION);
+		let msg = msg.into_version(version).map_err(|_| v0::Error::UnhandledXcmVersion)?;
+		Transport::send_versioned_xcm(dest, msg)
+	}
+}
+
+impl<Versions: ConfiguredVersion, Transport: SendVersionedXcm> v0::SendXcm for VersionedSender<Versions, Transport> {
+	fn send_xcm(dest: v0::MultiLocation, msg: v0::Xcm) -> v0::Result {
+		let dest = dest.try_into().map_err(|_| v0::Error::CannotReachDestination)?;
+		Self::send(dest, msg.into())
+	}
+}
+
+impl<Versions: ConfiguredVersion, Transport: SendVersionedXcm> v1::SendXcm for VersionedSender<Versions, Transport> {
+	fn send_xcm(dest: v1::MultiLocation, msg: v1::Xcm) -> v1::Result {
+		Self::send(dest, msg.into())
+	}
+}
@@ -0,0 +1,177 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use tetcore_std::{result::Result, marker::PhantomData, convert::TryInto};
+use xcm::v0::{Xcm, Order, MultiAsset, MultiLocation, Error, Weight};
+use tp_runtime::traits::{Zero, Saturating, SaturatedConversion};
+use fabric_support::{ensure, traits::{Get, OnUnbalanced as OnUnbalancedT, Currency as CurrencyT}};
+use fabric_support::weights::{WeightToFeePolynomial, constants::WEIGHT_PER_SECOND};
+use xcm_executor::{Assets, traits::{WeightBounds, WeightTrader}};
+
+/// The deepest that `FixedWeightBounds` will look into messages nested within other messages, such as those of
+/// `RelayedFrom`, `ReportOutcome` and `Order::BuyExecution`. Any message nested more deeply is unweighable.
+pub const MAX_WEIGHING_DEPTH: u32 = 8;
+
+/// Weigher which assigns a fixed weight, `T`, to every instruction and order, plus the declared weight of any
+/// `Transact` call.
+pub struct FixedWeightBounds<T>(PhantomData<T>);
+impl<T: Get<Weight>> FixedWeightBounds<T> {
+	fn shallow_at(message: &Xcm, depth: u32) -> Result<Weight, ()> {
+		ensure!(depth <= MAX_WEIGHING_DEPTH, ());
+		Ok(match message {
+			Xcm::Transact { require_weight_at_most, .. } =>
+				T::get().checked_add(*require_weight_at_most).ok_or(())?,
+			Xcm::RelayedFrom { inner, .. } => {
+				let inner: Xcm = (**inner).clone().try_into()?;
+				T::get().checked_add(Self::shallow_at(&inner, depth + 1)?).ok_or(())?
+			}
+			Xcm::ReportOutcome { message, .. } =>
+				T::get().checked_add(Self::shallow_at(message, depth + 1)?).ok_or(())?,
+			Xcm::WithdrawAsset { effects, .. }
+			| Xcm::ReserveAssetDeposit { effects, .. }
+			| Xcm::TeleportAsset { effects, .. } =>
+				T::get().checked_mul(effects.len() as Weight + 1).ok_or(())?,
+			_ => T::get(),
+		})
+	}
+
+	fn deep_at(message: &Xcm, depth: u32) -> Result<Weight, ()> {
+		ensure!(depth <= MAX_WEIGHING_DEPTH, ());
+		Ok(match message {
+			Xcm::RelayedFrom { inner, .. } => {
+				let inner: Xcm = (**inner).clone().try_into()?;
+				Self::deep_at(&inner, depth + 1)?
+			}
+			Xcm::ReportOutcome { message, .. } => Self::deep_at(message, depth + 1)?,
+			Xcm::WithdrawAsset { effects, .. }
+			| Xcm::ReserveAssetDeposit { effects, .. }
+			| Xcm::TeleportAsset { effects, .. } => Self::effects_weight(effects, depth)?,
+			_ => 0,
+		})
+	}
+
+	fn effects_weight(effects: &[Order], depth: u32) -> Result<Weight, ()> {
+		let mut extra: Weight = 0;
+		for effect in effects.iter() {
+			if let Order::BuyExecution { xcm, .. } = effect {
+				for message in xcm.iter() {
+					extra = extra.checked_add(Self::shallow_at(message, depth + 1)?).ok_or(())?;
+					extra = extra.checked_add(Self::deep_at(message, depth + 1)?).ok_or(())?;
+				}
+			}
+		}
+		Ok(extra)
+	}
+}
+impl<T: Get<Weight>> WeightBounds for FixedWeightBounds<T> {
+	fn shallow(message: &Xcm) -> Result<Weight, ()> {
+		Self::shallow_at(message, 0)
+	}
+	fn deep(message: &Xcm) -> Result<Weight, ()> {
+		Self::deep_at(message, 0)
+	}
+}
+
+/// Simple fee calculator that requires payment in a single concrete fungible at a fixed rate.
+///
+/// The constant `Get` type parameter should be the concrete fungible ID and the amount of it required for
+/// one second of weight.
+pub struct FixedRateOfConcreteFungible<T>(Weight, u128, PhantomData<T>);
+impl<T: Get<(MultiLocation, u128)>> FixedRateOfConcreteFungible<T> {
+	fn amount_for(weight: Weight) -> u128 {
+		let (_, units_per_second) = T::get();
+		units_per_second.saturating_mul(weight as u128) / (WEIGHT_PER_SECOND as u128)
+	}
+}
+impl<T: Get<(MultiLocation, u128)>> WeightTrader for FixedRateOfConcreteFungible<T> {
+	fn new() -> Self { Self(0, 0, PhantomData) }
+
+	fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, Error> {
+		let (id, _) = T::get();
+		let amount = Self::amount_for(weight);
+		let required = MultiAsset::ConcreteFungible { amount, id };
+		let unused = payment.checked_sub(required).map_err(|_| Error::TooExpensive)?;
+		self.0 = self.0.saturating_add(weight);
+		self.1 = self.1.saturating_add(amount);
+		Ok(unused)
+	}
+
+	fn refund_weight(&mut self, weight: Weight) -> MultiAsset {
+		let weight = weight.min(self.0);
+		let amount = Self::amount_for(weight).min(self.1);
+		self.0 -= weight;
+		self.1 -= amount;
+		if amount > 0 {
+			let (id, _) = T::get();
+			MultiAsset::ConcreteFungible { amount, id }
+		} else {
+			MultiAsset::None
+		}
+	}
+}
+
+/// Weight trader which uses the chain's `WeightToFee` to price weight in its native `Currency` (identified by
+/// `AssetId`). The fees collected are handed to `OnUnbalanced` once the XCM has been executed.
+pub struct UsingComponents<
+	WeightToFee: WeightToFeePolynomial<Balance = Currency::Balance>,
+	AssetId: Get<MultiLocation>,
+	AccountId,
+	Currency: CurrencyT<AccountId>,
+	OnUnbalanced: OnUnbalancedT<Currency::NegativeImbalance>,
+>(Weight, Currency::Balance, PhantomData<(WeightToFee, AssetId, AccountId, Currency, OnUnbalanced)>);
+impl<
+	WeightToFee: WeightToFeePolynomial<Balance = Currency::Balance>,
+	AssetId: Get<MultiLocation>,
+	AccountId,
+	Currency: CurrencyT<AccountId>,
+	OnUnbalanced: OnUnbalancedT<Currency::NegativeImbalance>,
+> WeightTrader for UsingComponents<WeightToFee, AssetId, AccountId, Currency, OnUnbalanced> {
+	fn new() -> Self { Self(0, Zero::zero(), PhantomData) }
+
+	fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, Error> {
+		let amount = WeightToFee::calc(&weight);
+		let u128_amount: u128 = amount.try_into().map_err(|_| Error::Overflow)?;
+		let required = MultiAsset::ConcreteFungible { amount: u128_amount, id: AssetId::get() };
+		let unused = payment.checked_sub(required).map_err(|_| Error::TooExpensive)?;
+		self.0 = self.0.saturating_add(weight);
+		self.1 = self.1.saturating_add(amount);
+		Ok(unused)
+	}
+
+	fn refund_weight(&mut self, weight: Weight) -> MultiAsset {
+		let weight = weight.min(self.0);
+		let amount = WeightToFee::calc(&weight).min(self.1);
+		self.0 -= weight;
+		self.1 = self.1.saturating_sub(amount);
+		let amount: u128 = amount.saturated_into();
+		if amount > 0 {
+			MultiAsset::ConcreteFungible { amount, id: AssetId::get() }
+		} else {
+			MultiAsset::None
+		}
+	}
+}
+impl<
+	WeightToFee: WeightToFeePolynomial<Balance = Currency::Balance>,
+	AssetId: Get<MultiLocation>,
+	AccountId,
+	Currency: CurrencyT<AccountId>,
+	OnUnbalanced: OnUnbalancedT<Currency::NegativeImbalance>,
+> Drop for UsingComponents<WeightToFee, AssetId, AccountId, Currency, OnUnbalanced> {
+	fn drop(&mut self) {
+		OnUnbalanced::on_unbalanced(Currency::issue(self.1));
+	}
+}
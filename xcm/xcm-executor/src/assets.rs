@@ -119,6 +119,58 @@ impl Assets {
 		}
 	}
 
+	/// Modify `self` to include all the assets of `assets`, saturating if necessary.
+	pub fn subsume_assets(&mut self, assets: Assets) {
+		for (id, amount) in assets.fungible.into_iter() {
+			self.saturating_subsume_fungible(id, amount);
+		}
+		self.non_fungible.extend(assets.non_fungible.into_iter());
+	}
+
+	/// Returns `true` if `self` contains no assets.
+	pub fn is_empty(&self) -> bool {
+		self.fungible.is_empty() && self.non_fungible.is_empty()
+	}
+
+	/// Return `self` less the single fungible or non-fungible `asset`, or `Err` containing `self` unchanged
+	/// if it does not contain at least `asset`. Wildcards are not supported.
+	pub fn checked_sub(mut self, asset: MultiAsset) -> Result<Assets, Assets> {
+		match asset {
+			MultiAsset::ConcreteFungible { id, amount } => self.checked_sub_fungible(AssetId::Concrete(id), amount),
+			MultiAsset::AbstractFungible { id, amount } => self.checked_sub_fungible(AssetId::Abstract(id), amount),
+			MultiAsset::ConcreteNonFungible { class, instance } => {
+				if self.non_fungible.remove(&(AssetId::Concrete(class), instance)) {
+					Ok(self)
+				} else {
+					Err(self)
+				}
+			}
+			MultiAsset::AbstractNonFungible { class, instance } => {
+				if self.non_fungible.remove(&(AssetId::Abstract(class), instance)) {
+					Ok(self)
+				} else {
+					Err(self)
+				}
+			}
+			_ => Err(self),
+		}
+	}
+
+	fn checked_sub_fungible(mut self, id: AssetId, amount: u128) -> Result<Assets, Assets> {
+		match self.fungible.get(&id).copied() {
+			Some(have) if have > amount => {
+				self.fungible.insert(id, have - amount);
+				Ok(self)
+			}
+			Some(have) if have == amount => {
+				self.fungible.remove(&id);
+				Ok(self)
+			}
+			None if amount == 0 => Ok(self),
+			_ => Err(self),
+		}
+	}
+
 	/// Modify `self` to include a new fungible asset by `id` and `amount`,
 	/// saturating if necessary.
 	pub fn saturating_subsume_fungible(&mut self, id: AssetId, amount: u128) {
@@ -252,8 +304,8 @@ impl Assets {
 				},
 				x @ MultiAsset::ConcreteNonFungible { .. } | x @ MultiAsset::AbstractNonFungible { .. } => {
 					let (class, instance) = match x {
-						MultiAsset::ConcreteNonFungible { class, instance } => (AssetId::Concrete(class.clone()), instance.clone()),
-						MultiAsset::AbstractNonFungible { class, instance } => (AssetId::Abstract(class.clone()), instance.clone()),
+						MultiAsset::ConcreteNonFungible { class, instance } => (AssetId::Concrete(class), instance),
+						MultiAsset::AbstractNonFungible { class, instance } => (AssetId::Abstract(class), instance),
 						_ => unreachable!(),
 					};
 					let item = (class, instance);
@@ -619,4 +671,27 @@ mod tests {
 		let assets = assets1.into_assets_iter().collect::<Vec<_>>();
 		assert_eq!(assets, vec![AF(1, 50), ANF(2, 200)]);
 	}
+
+	#[test]
+	fn subsume_assets_works() {
+		let mut assets = test_assets();
+		let other: Assets = vec![AF(1, 50), CF(100), CNF(500)].into();
+		assets.subsume_assets(other);
+		let assets = assets.into_assets_iter().collect::<Vec<_>>();
+		assert_eq!(assets, vec![CF(400), AF(1, 150), CNF(400), CNF(500), ANF(2, 200)]);
+	}
+
+	#[test]
+	fn checked_sub_works() {
+		let assets = test_assets();
+		let assets = assets.checked_sub(CF(100)).unwrap();
+		let assets = assets.checked_sub(ANF(2, 200)).unwrap();
+		assert_eq!(assets.assets_iter().collect::<Vec<_>>(), vec![CF(200), AF(1, 100), CNF(400)]);
+		let assets = assets.checked_sub(AF(1, 100)).unwrap();
+		assert_eq!(assets.assets_iter().collect::<Vec<_>>(), vec![CF(200), CNF(400)]);
+		// Not enough, or not present at all; left unchanged.
+		let assets = assets.checked_sub(CF(201)).unwrap_err();
+		let assets = assets.checked_sub(CNF(401)).unwrap_err();
+		assert_eq!(assets.into_assets_iter().collect::<Vec<_>>(), vec![CF(200), CNF(400)]);
+	}
 }
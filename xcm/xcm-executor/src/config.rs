@@ -15,13 +15,16 @@
 // along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
 
 use xcm::v0::SendXcm;
-use fabric_support::dispatch::{Dispatchable, Parameter};
-use crate::traits::{TransactAsset, ConvertOrigin, FilterAssetLocation, InvertLocation};
+use fabric_support::{traits::Get, dispatch::{Dispatchable, Parameter}, weights::{PostDispatchInfo, GetDispatchInfo}};
+use crate::traits::{
+	TransactAsset, ConvertOrigin, FilterAssetLocation, InvertLocation, WeightBounds, WeightTrader,
+	AssetExchange, OnResponse, ShouldExecute, ManageHrmpChannels,
+};
 
 /// The trait to parametrize the `XcmExecutor`.
 pub trait Config {
 	/// The outer call dispatch type.
-	type Call: Parameter + Dispatchable;
+	type Call: Parameter + Dispatchable<PostInfo = PostDispatchInfo> + GetDispatchInfo;
 
 	/// How to send an onward XCM message.
 	type XcmSender: SendXcm;
@@ -29,6 +32,9 @@ pub trait Config {
 	/// How to withdraw and deposit an asset.
 	type AssetTransactor: TransactAsset;
 
+	/// How to exchange assets in holding for others, as requested by `Order::ExchangeAsset`.
+	type AssetExchanger: AssetExchange;
+
 	/// How to get a call origin from a `OriginKind` value.
 	type OriginConverter: ConvertOrigin<<Self::Call as Dispatchable>::Origin>;
 
@@ -40,4 +46,22 @@ pub trait Config {
 
 	/// Means of inverting a location.
 	type LocationInverter: InvertLocation;
+
+	/// The maximum number of `RelayedFrom` layers which a message may be wrapped in.
+	type MaxRelayDepth: Get<u32>;
+
+	/// Whether we should execute the given XCM at all.
+	type Barrier: ShouldExecute;
+
+	/// The means of determining an XCM message's weight.
+	type Weigher: WeightBounds;
+
+	/// The means of purchasing weight credit for XCM execution.
+	type Trader: WeightTrader;
+
+	/// What to do when a response to a query is received.
+	type ResponseHandler: OnResponse;
+
+	/// How to act upon the HRMP channel management instructions sent by a para.
+	type HrmpChannelManager: ManageHrmpChannels;
 }
@@ -17,38 +17,145 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use tetcore_std::{prelude::*, marker::PhantomData, convert::TryInto};
-use fabric_support::{ensure, dispatch::Dispatchable};
+use fabric_support::{ensure, dispatch::Dispatchable, weights::GetDispatchInfo, storage::with_transaction};
+use tp_runtime::TransactionOutcome;
 use tetsy_scale_codec::Decode;
 use xcm::v0::{
-	Xcm, Order, ExecuteXcm, SendXcm, Error as XcmError, Result as XcmResult,
-	MultiLocation, MultiAsset, Junction,
+	Xcm, Order, ExecuteXcm, SendXcm, Error as XcmError, MultiLocation, MultiAsset, Junction, Weight, Outcome,
+	Response,
 };
 
 pub mod traits;
 mod assets;
 mod config;
 
-use traits::{TransactAsset, ConvertOrigin, FilterAssetLocation, InvertLocation};
+use traits::{
+	TransactAsset, ConvertOrigin, FilterAssetLocation, InvertLocation, WeightBounds, WeightTrader,
+	AssetExchange, OnResponse, ShouldExecute, ManageHrmpChannels,
+};
 pub use assets::{Assets, AssetId};
 pub use config::Config;
 
 pub struct XcmExecutor<Config>(PhantomData<Config>);
 
+/// The reason for which the execution of an XCM message stopped early.
+#[derive(Clone)]
+enum ExecutionError {
+	/// The message failed before having any effect.
+	Failed(XcmError),
+	/// The message failed after having taken some effect. `index` and `error` are as in `Outcome::Incomplete`
+	/// and `surplus` is the weight which is known not to have been used.
+	Incomplete { index: Option<u32>, error: XcmError, surplus: Weight },
+}
+
+impl ExecutionError {
+	fn into_error(self) -> XcmError {
+		match self {
+			ExecutionError::Failed(error) => error,
+			ExecutionError::Incomplete { error, .. } => error,
+		}
+	}
+}
+
+impl From<XcmError> for ExecutionError {
+	fn from(error: XcmError) -> Self {
+		ExecutionError::Failed(error)
+	}
+}
+
 impl<Config: config::Config> ExecuteXcm for XcmExecutor<Config> {
-	fn execute_xcm(origin: MultiLocation, msg: Xcm) -> XcmResult {
-		let (mut holding, effects) = match (origin.clone(), msg) {
-			(origin, Xcm::RelayedFrom { superorigin, inner }) => {
-				// We ensure that it doesn't contain any `Parent` Junctions which would imply a privilege escalation.
-				let mut new_origin = origin;
-				for j in superorigin.into_iter() {
-					ensure!(j.is_sub_consensus(), XcmError::EscalationOfPrivilege);
-					new_origin.push(j).map_err(|_| XcmError::MultiLocationFull)?;
-				}
-				return Self::execute_xcm(
-					new_origin,
-					(*inner).try_into().map_err(|_| XcmError::UnhandledXcmVersion)?
-				)
+	fn execute_xcm_in_credit(
+		origin: MultiLocation,
+		msg: Xcm,
+		weight_limit: Weight,
+		mut weight_credit: Weight,
+	) -> Outcome {
+		// The weigher has to unwrap every `RelayedFrom` layer, so make sure there are not too many first.
+		if let Err(error) = Self::ensure_relay_depth(&msg) {
+			return Outcome::Error(error);
+		}
+		let maximum_weight = match Config::Weigher::weight(&msg) {
+			Ok(weight) => weight,
+			Err(()) => return Outcome::Error(XcmError::UnweighableMessage),
+		};
+		if maximum_weight > weight_limit {
+			return Outcome::Error(XcmError::WeightLimitReached(maximum_weight));
+		}
+		let mut trader = Config::Trader::new();
+		let result = Self::do_execute_xcm(origin, true, msg, &mut weight_credit, &mut trader);
+		// The trader is dropped here, allowing it to settle any fees it has collected.
+		drop(trader);
+		Self::outcome(maximum_weight, result)
+	}
+}
+
+impl<Config: config::Config> XcmExecutor<Config> {
+	fn reanchored(mut assets: Assets, dest: &MultiLocation) -> Vec<MultiAsset> {
+		let inv_dest = Config::LocationInverter::invert_location(&dest);
+		assets.reanchor(&inv_dest);
+		assets.into_assets_iter().collect::<Vec<_>>()
+	}
+
+	/// Ensure that `msg` is wrapped in no more than `Config::MaxRelayDepth` layers of `RelayedFrom`.
+	fn ensure_relay_depth(msg: &Xcm) -> Result<(), XcmError> {
+		let mut depth = 0;
+		let mut next = match msg {
+			Xcm::RelayedFrom { inner, .. } => Some((**inner).clone()),
+			_ => None,
+		};
+		while let Some(versioned) = next {
+			depth += 1;
+			ensure!(depth <= Config::MaxRelayDepth::get(), XcmError::RelayDepthExceeded);
+			let inner: Xcm = versioned.try_into().map_err(|_| XcmError::UnhandledXcmVersion)?;
+			next = match inner {
+				Xcm::RelayedFrom { inner, .. } => Some(*inner),
+				_ => None,
+			};
+		}
+		Ok(())
+	}
+
+	/// Express the `result` of executing a message whose estimated weight was `maximum_weight` as an `Outcome`.
+	fn outcome(maximum_weight: Weight, result: Result<Weight, ExecutionError>) -> Outcome {
+		match result {
+			Ok(surplus) => Outcome::Complete(maximum_weight.saturating_sub(surplus)),
+			Err(ExecutionError::Failed(error)) => Outcome::Error(error),
+			Err(ExecutionError::Incomplete { index, error, surplus }) =>
+				Outcome::Incomplete(maximum_weight.saturating_sub(surplus), index, error),
+		}
+	}
+
+	/// Execute the message `msg` from `origin`, returning the amount of weight which was accounted for by
+	/// `Config::Weigher` but turned out not to be needed.
+	///
+	/// `top_level` and `weight_credit` are passed on to `Config::Barrier`, which must allow the message.
+	fn do_execute_xcm(
+		mut origin: MultiLocation,
+		top_level: bool,
+		mut msg: Xcm,
+		weight_credit: &mut Weight,
+		trader: &mut Config::Trader,
+	) -> Result<Weight, ExecutionError> {
+		// Relayed messages are unwrapped first, so that the barrier judges the inner message by its actual origin.
+		let mut depth = 0;
+		while let Xcm::RelayedFrom { superorigin, inner } = msg {
+			depth += 1;
+			ensure!(depth <= Config::MaxRelayDepth::get(), XcmError::RelayDepthExceeded);
+			// We ensure that it doesn't contain any `Parent` Junctions which would imply a privilege escalation.
+			for j in superorigin.into_iter() {
+				ensure!(j.is_sub_consensus(), XcmError::EscalationOfPrivilege);
+				origin.push(j).map_err(|_| XcmError::MultiLocationFull)?;
 			}
+			msg = (*inner).try_into().map_err(|_| XcmError::UnhandledXcmVersion)?;
+		}
+
+		let shallow_weight = Config::Weigher::shallow(&msg).map_err(|()| XcmError::UnweighableMessage)?;
+		Config::Barrier::should_execute(&origin, top_level, &msg, shallow_weight, weight_credit)
+			.map_err(|()| XcmError::Barrier)?;
+
+		// This is the weight that was estimated for the message but which has not been used.
+		let mut total_surplus: Weight = 0;
+		let (mut holding, effects) = match (origin.clone(), msg) {
 			(origin, Xcm::WithdrawAsset { assets, effects }) => {
 				// Take `assets` from the origin account (on-chain) and place in holding.
 				let mut holding = Assets::default();
@@ -75,61 +182,134 @@ impl<Config: config::Config> ExecuteXcm for XcmExecutor<Config> {
 				if assets.iter().all(|asset| Config::IsTeleporter::filter_asset_location(asset, &origin)) {
 					// We only trust the origin to send us assets that they identify as their
 					// sovereign assets.
+					for asset in assets.iter() {
+						Config::AssetTransactor::can_check_in(&origin, asset)?;
+					}
+					for asset in assets.iter() {
+						Config::AssetTransactor::check_in(&origin, asset);
+					}
 					(Assets::from(assets), effects)
 				} else {
 					Err(XcmError::UntrustedTeleportLocation)?
 				}
 			}
-			(origin, Xcm::Transact { origin_type, call }) => {
+			(origin, Xcm::Transact { origin_type, require_weight_at_most, call }) => {
 				// We assume that the Relay-chain is allowed to use transact on this parachain.
 
-				// TODO: Weight fees should be paid.
-
 				// TODO: allow this to be configurable in the trait.
 				// TODO: allow the trait to issue filters for the relay-chain
 				let message_call = Config::Call::decode(&mut &call[..]).map_err(|_| XcmError::FailedToDecode)?;
 				let dispatch_origin = Config::OriginConverter::convert_origin(origin, origin_type)
 					.map_err(|_| XcmError::BadOrigin)?;
-				let _ok = message_call.dispatch(dispatch_origin).is_ok();
-				// Not much to do with the result as it is. It's up to the parachain to ensure that the
-				// message makes sense.
-				return Ok(());
+				let weight = message_call.get_dispatch_info().weight;
+				ensure!(weight <= require_weight_at_most, XcmError::TooMuchWeightRequired);
+				return match message_call.dispatch(dispatch_origin) {
+					Ok(post_info) => {
+						let actual_weight = post_info.actual_weight.unwrap_or(weight);
+						Ok(require_weight_at_most.saturating_sub(actual_weight))
+					}
+					Err(error_and_info) => {
+						let actual_weight = error_and_info.post_info.actual_weight.unwrap_or(weight);
+						Err(ExecutionError::Incomplete {
+							index: None,
+							error: XcmError::DispatchFailed,
+							surplus: require_weight_at_most.saturating_sub(actual_weight),
+						})
+					}
+				};
+			}
+			(origin, Xcm::QueryResponse { query_id, response }) => {
+				ensure!(
+					Config::ResponseHandler::expecting_response(&origin, query_id),
+					XcmError::UnexpectedResponse
+				);
+				Config::ResponseHandler::on_response(origin, query_id, response);
+				return Ok(0);
 			}
-			(origin, Xcm::RelayTo { dest: MultiLocation::X1(Junction::Parachain { id }), inner }) => {
+			(origin, Xcm::Balances { query_id, assets }) => {
+				ensure!(
+					Config::ResponseHandler::expecting_response(&origin, query_id),
+					XcmError::UnexpectedResponse
+				);
+				Config::ResponseHandler::on_response(origin, query_id, Response::Assets(assets));
+				return Ok(0);
+			}
+			(origin, Xcm::ReportOutcome { query_id, dest, message }) => {
+				// Otherwise the origin could have this chain send responses anywhere, to queries it never made.
+				ensure!(dest == origin, XcmError::EscalationOfPrivilege);
+				let message_weight = Config::Weigher::weight(&message).map_err(|()| XcmError::UnweighableMessage)?;
+				let result = Self::do_execute_xcm(origin, top_level, *message, weight_credit, trader);
+				let response = Response::ExecutionResult(Self::outcome(message_weight, result.clone()));
+				Config::XcmSender::send_xcm(dest, Xcm::QueryResponse { query_id, response })
+					.map_err(|error| ExecutionError::Incomplete { index: None, error, surplus: 0 })?;
+				return result;
+			}
+			(origin, Xcm::RelayTo { dest, inner }) => {
+				ensure!(dest.iter().all(Junction::is_sub_consensus), XcmError::CannotReachDestination);
+				let (rest, first) = dest.clone().split_first();
+				let first = first.ok_or(XcmError::CannotReachDestination)?;
+				let (dest, inner) = if first.is_interior() {
+					// Something within this consensus system, such as an account or a noble, is the target itself
+					// and is handed the message by the router.
+					ensure!(dest.iter().all(Junction::is_interior), XcmError::CannotReachDestination);
+					(dest, inner)
+				} else {
+					// Anything beyond the first hop is reached by having the first hop relay the message onwards.
+					let inner = match rest {
+						MultiLocation::Null => inner,
+						rest => Box::new(Xcm::RelayTo { dest: rest, inner }.into()),
+					};
+					(first.into(), inner)
+				};
 				let msg = Xcm::RelayedFrom { superorigin: origin, inner }.into();
-				return Config::XcmSender::send_xcm(Junction::Parachain { id }.into(), msg)
+				Config::XcmSender::send_xcm(dest, msg)?;
+				return Ok(0);
 			},
+			(origin, Xcm::HrmpInitOpenChannel { recipient, max_message_size, max_capacity }) => {
+				Config::HrmpChannelManager::init_open_channel(&origin, recipient, max_message_size, max_capacity)?;
+				return Ok(0);
+			}
+			(origin, Xcm::HrmpAcceptOpenChannel { sender }) => {
+				Config::HrmpChannelManager::accept_open_channel(&origin, sender)?;
+				return Ok(0);
+			}
+			(origin, Xcm::HrmpCloseChannel { sender, recipient }) => {
+				Config::HrmpChannelManager::close_channel(&origin, sender, recipient)?;
+				return Ok(0);
+			}
 			_ => Err(XcmError::UnhandledXcmMessage)?,	// Unhandled XCM message.
 		};
 
-		// TODO: stuff that should happen after holding is populated but before effects,
-		//   including depositing fees for effects from holding account.
-
-		for effect in effects.into_iter() {
-			let _ = Self::execute_effects(&origin, &mut holding, effect)?;
+		for (index, effect) in effects.into_iter().enumerate() {
+			match Self::execute_effects(&origin, &mut holding, effect, trader) {
+				Ok(surplus) => total_surplus = total_surplus.saturating_add(surplus),
+				Err(error) => return Err(ExecutionError::Incomplete {
+					index: Some(index as u32),
+					error,
+					// The weight of the failed order and any which follow it is not refunded.
+					surplus: total_surplus,
+				}),
+			}
 		}
 
-		// TODO: stuff that should happen after effects including refunding unused fees.
-
-		Ok(())
+		Ok(total_surplus)
 	}
-}
 
-impl<Config: config::Config> XcmExecutor<Config> {
-	fn reanchored(mut assets: Assets, dest: &MultiLocation) -> Vec<MultiAsset> {
-		let inv_dest = Config::LocationInverter::invert_location(&dest);
-		assets.reanchor(&inv_dest);
-		assets.into_assets_iter().collect::<Vec<_>>()
-	}
-
-	fn execute_effects(_origin: &MultiLocation, holding: &mut Assets, effect: Order) -> XcmResult {
+	/// Execute a single `effect` on `holding`, returning the amount of weight accounted for by
+	/// `Config::Weigher` which turned out not to be needed.
+	fn execute_effects(
+		origin: &MultiLocation,
+		holding: &mut Assets,
+		effect: Order,
+		trader: &mut Config::Trader,
+	) -> Result<Weight, XcmError> {
+		let mut total_surplus: Weight = 0;
 		match effect {
 			Order::DepositAsset { assets, dest } => {
 				let deposited = holding.saturating_take(assets);
 				for asset in deposited.into_assets_iter() {
 					Config::AssetTransactor::deposit_asset(&asset, &dest)?;
 				}
-				Ok(())
 			},
 			Order::DepositReserveAsset { assets, dest, effects } => {
 				let deposited = holding.saturating_take(assets);
@@ -137,21 +317,79 @@ impl<Config: config::Config> XcmExecutor<Config> {
 					Config::AssetTransactor::deposit_asset(&asset, &dest)?;
 				}
 				let assets = Self::reanchored(deposited, &dest);
-				Config::XcmSender::send_xcm(dest, Xcm::ReserveAssetDeposit { assets, effects })
+				Config::XcmSender::send_xcm(dest, Xcm::ReserveAssetDeposit { assets, effects })?;
 			},
 			Order::InitiateReserveWithdraw { assets, reserve, effects} => {
 				let assets = Self::reanchored(holding.saturating_take(assets), &reserve);
-				Config::XcmSender::send_xcm(reserve, Xcm::WithdrawAsset { assets, effects })
+				Config::XcmSender::send_xcm(reserve, Xcm::WithdrawAsset { assets, effects })?;
 			}
 			Order::InitiateTeleport { assets, dest, effects} => {
-				let assets = Self::reanchored(holding.saturating_take(assets), &dest);
-				Config::XcmSender::send_xcm(dest, Xcm::TeleportAsset { assets, effects })
+				let teleported = holding.saturating_take(assets);
+				for asset in teleported.assets_iter() {
+					Config::AssetTransactor::check_out(&dest, &asset);
+				}
+				let assets = Self::reanchored(teleported, &dest);
+				Config::XcmSender::send_xcm(dest, Xcm::TeleportAsset { assets, effects })?;
+			}
+			Order::ExchangeAsset { give, receive } => {
+				let give = holding.min(give.iter());
+				// Wildcards name the assets wanted without stating how much of them, so they set no minimum.
+				let minimum = receive.iter()
+					.filter(|asset| !asset.is_wildcard() && **asset != MultiAsset::None)
+					.cloned()
+					.collect::<Vec<_>>();
+				// The exchange is trusted to honour `receive`, but we check anyway since falling short of it
+				// would mean the origin's minimum was silently ignored. A short exchange is undone, and holding
+				// is only touched once the exchange is known to be good.
+				let received = with_transaction(|| {
+					match Config::AssetExchanger::exchange_holding(origin, &give, &receive) {
+						Ok(received) => if minimum.iter()
+							.try_fold(received.clone(), |remaining, asset| remaining.checked_sub(asset.clone()))
+							.is_ok()
+						{
+							TransactionOutcome::Commit(Ok(received))
+						} else {
+							TransactionOutcome::Rollback(Err(XcmError::TooMuchSlippage))
+						},
+						Err(error) => TransactionOutcome::Rollback(Err(error)),
+					}
+				})?;
+				holding.saturating_take(give.into_assets_iter());
+				holding.subsume_assets(received);
 			}
 			Order::QueryHolding { query_id, dest, assets } => {
 				let assets = Self::reanchored(holding.min(assets.iter()), &dest);
-				Config::XcmSender::send_xcm(dest, Xcm::Balances { query_id, assets })
+				Config::XcmSender::send_xcm(dest, Xcm::Balances { query_id, assets })?;
+			}
+			Order::BuyExecution { fees, weight, debt, halt_on_error, xcm } => {
+				// pay for `weight` using up to `fees` of the holding account.
+				let purchasing_weight = weight.checked_add(debt).ok_or(XcmError::Overflow)?;
+				let max_fee = holding.saturating_take(vec![fees]);
+				ensure!(!max_fee.is_empty(), XcmError::NotHoldingFees);
+				let unspent = trader.buy_weight(purchasing_weight, max_fee)?;
+				holding.subsume_assets(unspent);
+
+				let mut remaining_weight = weight;
+				for message in xcm.into_iter() {
+					let mut message_weight = Config::Weigher::weight(&message)
+						.map_err(|()| XcmError::UnweighableMessage)?;
+					remaining_weight = remaining_weight.checked_sub(message_weight)
+						.ok_or(XcmError::TooExpensive)?;
+					// The message has been paid for, so its weight is credited to it.
+					match Self::do_execute_xcm(origin.clone(), false, message, &mut message_weight, trader) {
+						Err(e) if halt_on_error => return Err(e.into_error()),
+						Err(_) => {}
+						Ok(surplus) => {
+							total_surplus = total_surplus.saturating_add(surplus);
+							remaining_weight = remaining_weight.saturating_add(surplus);
+						}
+					}
+				}
+				// Any weight which was bought but not used is refunded into holding.
+				holding.saturating_subsume(trader.refund_weight(remaining_weight));
 			}
 			_ => Err(XcmError::UnhandledEffect)?,
 		}
+		Ok(total_surplus)
 	}
 }
@@ -16,8 +16,11 @@
 
 use tetcore_std::{result::Result, marker::PhantomData, convert::TryFrom};
 use tp_runtime::traits::CheckedConversion;
-use xcm::v0::{Error as XcmError, Result as XcmResult, MultiAsset, MultiLocation, OriginKind};
+use xcm::v0::{
+	Error as XcmError, Result as XcmResult, MultiAsset, MultiLocation, OriginKind, Xcm, Weight, Response,
+};
 use fabric_support::traits::Get;
+use crate::Assets;
 
 pub trait FilterAssetLocation {
 	/// A filter to distinguish between asset/location pairs.
@@ -56,6 +59,33 @@ impl<T: Get<(MultiAsset, MultiLocation)>> FilterAssetLocation for Case<T> {
 /// account locations such as a `MultiLocation::X1(Junction::Parachain)`. Different chains may handle them in
 /// different ways.
 pub trait TransactAsset {
+	/// Ensure that `check_in` will do as expected.
+	///
+	/// This is used before any assets teleported in from `origin` are placed into holding. The default
+	/// implementation does no teleport accounting and always succeeds.
+	///
+	/// Implementations should return `AssetNotFound` for any asset which they do not handle.
+	///
+	/// When composed as a tuple, type-items are called in order until one results in `Ok`. Those resulting in
+	/// `AssetNotFound` are passed over, but any other error is returned immediately.
+	fn can_check_in(_origin: &MultiLocation, _what: &MultiAsset) -> XcmResult {
+		Ok(())
+	}
+
+	/// An asset has been teleported in from the given origin. This should do whatever housekeeping is needed.
+	///
+	/// NOTE: This will make only a best-effort at bookkeeping. The caller should ensure that `can_check_in`
+	/// returned with `Ok` in order to guarantee that this operation proceeds properly.
+	///
+	/// When composed as a tuple, all type-items are called.
+	fn check_in(_origin: &MultiLocation, _what: &MultiAsset) {}
+
+	/// An asset has been teleported out to the given destination. This should do whatever housekeeping is
+	/// needed.
+	///
+	/// When composed as a tuple, all type-items are called.
+	fn check_out(_dest: &MultiLocation, _what: &MultiAsset) {}
+
 	/// Deposit the `what` asset into the account of `who`.
 	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> XcmResult;
 
@@ -75,6 +105,25 @@ pub trait TransactAsset {
 
 #[impl_trait_for_tuples::impl_for_tuples(30)]
 impl TransactAsset for Tuple {
+	fn can_check_in(origin: &MultiLocation, what: &MultiAsset) -> XcmResult {
+		for_tuples!( #(
+			match Tuple::can_check_in(origin, what) {
+				Err(XcmError::AssetNotFound) => (),
+				r => return r,
+			}
+		)* );
+		Err(XcmError::AssetNotFound)
+	}
+	fn check_in(origin: &MultiLocation, what: &MultiAsset) {
+		for_tuples!( #(
+			Tuple::check_in(origin, what);
+		)* );
+	}
+	fn check_out(dest: &MultiLocation, what: &MultiAsset) {
+		for_tuples!( #(
+			Tuple::check_out(dest, what);
+		)* );
+	}
 	fn deposit_asset(what: &MultiAsset, who: &MultiLocation) -> XcmResult {
 		for_tuples!( #(
 			match Tuple::deposit_asset(what, who) { o @ Ok(_) => return o, _ => () }
@@ -119,6 +168,55 @@ impl<B: From<u128>, X: MatchesFungible<B>, Y: MatchesFungible<B>> MatchesFungibl
 	}
 }
 
+pub trait MatchesFungibles<AssetId, Balance> {
+	fn matches_fungibles(a: &MultiAsset) -> Option<(AssetId, Balance)>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AssetId, Balance> MatchesFungibles<AssetId, Balance> for Tuple {
+	fn matches_fungibles(a: &MultiAsset) -> Option<(AssetId, Balance)> {
+		for_tuples!( #(
+			if let Some(result) = Tuple::matches_fungibles(a) { return Some(result) }
+		)* );
+		None
+	}
+}
+
+pub trait MatchesNonFungibles<ClassId, InstanceId> {
+	fn matches_nonfungibles(a: &MultiAsset) -> Option<(ClassId, InstanceId)>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<ClassId, InstanceId> MatchesNonFungibles<ClassId, InstanceId> for Tuple {
+	fn matches_nonfungibles(a: &MultiAsset) -> Option<(ClassId, InstanceId)> {
+		for_tuples!( #(
+			if let Some(result) = Tuple::matches_nonfungibles(a) { return Some(result) }
+		)* );
+		None
+	}
+}
+
+pub trait AssetIdConversion<Id, AssetId> {
+	fn from_asset_id(id: &Id) -> Option<AssetId>;
+	fn try_into_asset_id(what: AssetId) -> Result<Id, AssetId>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<Id, AssetId> AssetIdConversion<Id, AssetId> for Tuple {
+	fn from_asset_id(id: &Id) -> Option<AssetId> {
+		for_tuples!( #(
+			if let Some(result) = Tuple::from_asset_id(id) { return Some(result) }
+		)* );
+		None
+	}
+	fn try_into_asset_id(what: AssetId) -> Result<Id, AssetId> {
+		for_tuples!( #(
+			let what = match Tuple::try_into_asset_id(what) { Err(w) => w, r => return r };
+		)* );
+		Err(what)
+	}
+}
+
 pub trait LocationConversion<AccountId> {
 	fn from_location(location: &MultiLocation) -> Option<AccountId>;
 	fn try_into_location(who: AccountId) -> Result<MultiLocation, AccountId>;
@@ -157,3 +255,178 @@ impl<O> ConvertOrigin<O> for Tuple {
 pub trait InvertLocation {
 	fn invert_location(l: &MultiLocation) -> MultiLocation;
 }
+
+/// A service for exchanging assets, typically against some local market.
+pub trait AssetExchange {
+	/// Exchange all of `give` for at least `want`, on behalf of `origin`, returning the assets obtained.
+	///
+	/// `give` is only consumed if `Ok` is returned. If the exchange cannot provide at least every asset of `want`
+	/// then `XcmError::TooMuchSlippage` should be returned and nothing exchanged. Any wildcards in `want` name
+	/// assets which are wanted without a minimum amount.
+	fn exchange_holding(origin: &MultiLocation, give: &Assets, want: &[MultiAsset]) -> Result<Assets, XcmError>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl AssetExchange for Tuple {
+	fn exchange_holding(origin: &MultiLocation, give: &Assets, want: &[MultiAsset]) -> Result<Assets, XcmError> {
+		let mut error = XcmError::Unimplemented;
+		for_tuples!( #(
+			match Tuple::exchange_holding(origin, give, want) {
+				o @ Ok(_) => return o,
+				Err(XcmError::Unimplemented) => (),
+				Err(e) => error = e,
+			}
+		)* );
+		Err(error)
+	}
+}
+
+/// Determine the weight of an XCM message.
+pub trait WeightBounds {
+	/// Return the minimum amount of weight that an attempted execution of this message would definitely
+	/// consume.
+	///
+	/// This is useful to gauge how many fees should be paid up front to begin execution of the message.
+	/// It is not useful for determining whether execution should begin lest it result in surpassing weight
+	/// limits - in that case `deep` is the function to use.
+	fn shallow(message: &Xcm) -> Result<Weight, ()>;
+
+	/// Return the deep amount of weight, over `shallow` that complete, successful and worst-case execution of
+	/// `message` would incur.
+	///
+	/// This is perhaps overly pessimistic for determining how many fees should be paid for up-front since
+	/// fee payment (or any other way of offsetting the execution costs such as an voucher-style NFT) may
+	/// happen in stages throughout execution of the XCM.
+	///
+	/// A reminder: if it is possible that `message` may have alternative means of successful completion
+	/// (perhaps a conditional path), then the *worst case* weight must be reported.
+	///
+	/// This is guaranteed equal to the eventual sum of all `shallow` XCM messages that get executed through
+	/// any internal effects. Inner XCM messages may be executed by:
+	/// - `Order::BuyExecution`
+	fn deep(message: &Xcm) -> Result<Weight, ()>;
+
+	/// Return the total weight for executing `message`.
+	fn weight(message: &Xcm) -> Result<Weight, ()> {
+		Self::shallow(message)?.checked_add(Self::deep(message)?).ok_or(())
+	}
+}
+
+/// Charge for weight in order to execute XCM.
+///
+/// A `WeightTrader` is created for each XCM message executed and dropped once execution is complete; an
+/// implementation may use its `Drop` to finalise any accounting of the fees it collected.
+pub trait WeightTrader: Sized {
+	/// Create a new trader instance.
+	fn new() -> Self;
+
+	/// Purchase execution weight credit in return for up to a given `fee`. If less of the fee is required
+	/// then the surplus is returned. If the `fee` cannot be used to pay for the `weight`, then an error is
+	/// returned.
+	fn buy_weight(&mut self, weight: Weight, payment: Assets) -> Result<Assets, XcmError>;
+
+	/// Attempt a refund of `weight` into some asset. The caller does not guarantee that the weight was
+	/// purchased using `buy_weight`.
+	///
+	/// Default implementation refunds nothing.
+	fn refund_weight(&mut self, _weight: Weight) -> MultiAsset { MultiAsset::None }
+}
+
+/// A trader which refuses to sell any weight.
+impl WeightTrader for () {
+	fn new() -> Self { () }
+	fn buy_weight(&mut self, _: Weight, _: Assets) -> Result<Assets, XcmError> {
+		Err(XcmError::Unimplemented)
+	}
+}
+
+/// Handler for responses to queries which this system has made of others.
+pub trait OnResponse {
+	/// Returns `true` if we are expecting a response from `origin` for query `query_id`.
+	fn expecting_response(origin: &MultiLocation, query_id: u64) -> bool;
+
+	/// Handle a `response` from `origin` relating to `query_id`.
+	fn on_response(origin: MultiLocation, query_id: u64, response: Response);
+}
+
+/// A handler which expects no responses at all.
+impl OnResponse for () {
+	fn expecting_response(_origin: &MultiLocation, _query_id: u64) -> bool { false }
+	fn on_response(_origin: MultiLocation, _query_id: u64, _response: Response) {}
+}
+
+/// Facility for managing HRMP channels on behalf of a para, as requested by the `Hrmp*` XCM instructions.
+///
+/// Any deposits are expected to be taken from the sovereign account of the para identified by `origin`.
+pub trait ManageHrmpChannels {
+	/// Request the opening of a channel from `origin` to `recipient`.
+	fn init_open_channel(
+		origin: &MultiLocation,
+		recipient: u32,
+		max_message_size: u32,
+		max_capacity: u32,
+	) -> XcmResult;
+
+	/// Accept a pending request to open a channel from `sender` to `origin`.
+	fn accept_open_channel(origin: &MultiLocation, sender: u32) -> XcmResult;
+
+	/// Request the closing of the channel from `sender` to `recipient`, where `origin` is either party.
+	fn close_channel(origin: &MultiLocation, sender: u32, recipient: u32) -> XcmResult;
+}
+
+/// A manager which supports no HRMP channel operations at all.
+impl ManageHrmpChannels for () {
+	fn init_open_channel(_: &MultiLocation, _: u32, _: u32, _: u32) -> XcmResult {
+		Err(XcmError::Unimplemented)
+	}
+	fn accept_open_channel(_: &MultiLocation, _: u32) -> XcmResult {
+		Err(XcmError::Unimplemented)
+	}
+	fn close_channel(_: &MultiLocation, _: u32, _: u32) -> XcmResult {
+		Err(XcmError::Unimplemented)
+	}
+}
+
+/// Trait to determine whether the execution engine should actually execute a given XCM.
+///
+/// Can be amalgamated into a tuple to have multiple trials. If any of the tuple elements returns `Ok()`, the
+/// execution stops. Else, `Err(_)` is returned if all elements reject the message.
+pub trait ShouldExecute {
+	/// Returns `Ok(())` if the given `message` may be executed.
+	///
+	/// - `origin`: The origin (sender) of the message.
+	/// - `top_level`: `true` indicates the initial XCM coming from the `origin`, `false` indicates an embedded
+	///   XCM executed internally as part of another message or an `Order`.
+	/// - `message`: The message itself.
+	/// - `shallow_weight`: The weight of the non-negotiable execution of the message. This does not include any
+	///   embedded XCMs sat behind mechanisms like `BuyExecution` which would need to answer for their own weight.
+	/// - `weight_credit`: The pre-established amount of weight that the system has determined this message may
+	///   utilise in its execution. Typically non-zero only because of prior fee payment, but could in principle
+	///   be due to other factors.
+	fn should_execute(
+		origin: &MultiLocation,
+		top_level: bool,
+		message: &Xcm,
+		shallow_weight: Weight,
+		weight_credit: &mut Weight,
+	) -> Result<(), ()>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl ShouldExecute for Tuple {
+	fn should_execute(
+		origin: &MultiLocation,
+		top_level: bool,
+		message: &Xcm,
+		shallow_weight: Weight,
+		weight_credit: &mut Weight,
+	) -> Result<(), ()> {
+		for_tuples!( #(
+			match Tuple::should_execute(origin, top_level, message, shallow_weight, weight_credit) {
+				o @ Ok(()) => return o,
+				_ => (),
+			}
+		)* );
+		Err(())
+	}
+}
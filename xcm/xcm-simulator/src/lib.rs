@@ -0,0 +1,214 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A simulator of a relay chain and some parachains, all in one process, which exchange XCM messages through
+//! in-memory queues standing in for DMP, UMP and HRMP.
+//!
+//! Each chain lives in its own `TestExternalities`. Messages sent by a chain are queued and, once the closure given
+//! to `execute_with` returns, delivered in order until no more are in flight, so a whole multi-hop exchange
+//! completes deterministically within a single call.
+
+pub mod relay;
+pub mod parachain;
+
+#[cfg(test)]
+mod tests;
+
+use std::{cell::RefCell, collections::VecDeque, marker::PhantomData};
+use tetsy_scale_codec::Encode;
+use tet_io::TestExternalities;
+use tp_runtime::traits::AccountIdConversion;
+use fabric_support::{traits::Get, weights::Weight};
+use runtime_parachains::ump::UmpSink;
+use tetcoin_parachain::primitives::Id as ParaId;
+use xcm::{VersionedXcm, v0::{Xcm, Junction, MultiLocation, SendXcm, Result as XcmResult, Error as XcmError}};
+
+/// The id of the first simulated parachain.
+pub const PARA_A_ID: u32 = 1;
+/// The id of the second simulated parachain.
+pub const PARA_B_ID: u32 = 2;
+
+/// The raw account id of an account endowed on every chain.
+pub const ALICE: [u8; 32] = [1u8; 32];
+/// The balance of `ALICE` on every chain at genesis.
+pub const INITIAL_BALANCE: u128 = 1_000_000_000;
+
+thread_local! {
+	/// Messages from the relay chain to a parachain: `(recipient, message)`.
+	static DOWNWARD_MESSAGES: RefCell<VecDeque<(u32, Vec<u8>)>> = RefCell::new(VecDeque::new());
+	/// Messages from a parachain to the relay chain: `(sender, message)`.
+	static UPWARD_MESSAGES: RefCell<VecDeque<(u32, Vec<u8>)>> = RefCell::new(VecDeque::new());
+	/// Messages between parachains: `(sender, recipient, message)`.
+	static HORIZONTAL_MESSAGES: RefCell<VecDeque<(u32, u32, Vec<u8>)>> = RefCell::new(VecDeque::new());
+}
+
+/// Routes messages sent by the relay chain to the downward queue of a child parachain.
+pub struct RelayChainXcmRouter;
+impl SendXcm for RelayChainXcmRouter {
+	fn send_xcm(dest: MultiLocation, msg: Xcm) -> XcmResult {
+		match dest {
+			MultiLocation::X1(Junction::Parachain { id }) => {
+				let msg = VersionedXcm::from(msg).encode();
+				DOWNWARD_MESSAGES.with(|q| q.borrow_mut().push_back((id, msg)));
+				Ok(())
+			}
+			_ => Err(XcmError::CannotReachDestination),
+		}
+	}
+}
+
+/// Routes messages sent by the parachain with id `ParachainId` upward to the relay chain or horizontally to a
+/// sibling.
+pub struct ParachainXcmRouter<ParachainId>(PhantomData<ParachainId>);
+impl<ParachainId: Get<u32>> SendXcm for ParachainXcmRouter<ParachainId> {
+	fn send_xcm(dest: MultiLocation, msg: Xcm) -> XcmResult {
+		match dest {
+			MultiLocation::X1(Junction::Parent) => {
+				let msg = VersionedXcm::from(msg).encode();
+				UPWARD_MESSAGES.with(|q| q.borrow_mut().push_back((ParachainId::get(), msg)));
+				Ok(())
+			}
+			MultiLocation::X2(Junction::Parent, Junction::Parachain { id }) => {
+				let msg = VersionedXcm::from(msg).encode();
+				HORIZONTAL_MESSAGES.with(|q| q.borrow_mut().push_back((ParachainId::get(), id, msg)));
+				Ok(())
+			}
+			_ => Err(XcmError::CannotReachDestination),
+		}
+	}
+}
+
+/// A simulated chain.
+pub trait TestExt {
+	/// Build the genesis state of the chain.
+	fn new_ext() -> TestExternalities;
+
+	/// Reset the chain to its genesis state.
+	fn reset_ext();
+
+	/// Execute `execute` in the context of the chain, leaving any messages it sends in flight.
+	fn execute_without_dispatch<R>(execute: impl FnOnce() -> R) -> R;
+
+	/// Execute `execute` in the context of the chain and then deliver all messages in flight.
+	fn execute_with<R>(execute: impl FnOnce() -> R) -> R {
+		let result = Self::execute_without_dispatch(execute);
+		dispatch_xcm_messages();
+		result
+	}
+}
+
+macro_rules! decl_test_chain {
+	($name:ident, $ext:ident, $new_ext:expr) => {
+		thread_local! {
+			static $ext: RefCell<TestExternalities> = RefCell::new($new_ext);
+		}
+
+		pub struct $name;
+		impl TestExt for $name {
+			fn new_ext() -> TestExternalities {
+				$new_ext
+			}
+
+			fn reset_ext() {
+				$ext.with(|ext| *ext.borrow_mut() = $new_ext);
+			}
+
+			fn execute_without_dispatch<R>(execute: impl FnOnce() -> R) -> R {
+				$ext.with(|ext| ext.borrow_mut().execute_with(execute))
+			}
+		}
+	}
+}
+
+decl_test_chain!(Relay, RELAY_EXT, relay_ext());
+decl_test_chain!(ParaA, PARA_A_EXT, para_ext(PARA_A_ID));
+decl_test_chain!(ParaB, PARA_B_EXT, para_ext(PARA_B_ID));
+
+/// The sovereign account of the parachain `id` on the relay chain.
+pub fn para_account_id(id: u32) -> relay::AccountId {
+	ParaId::from(id).into_account()
+}
+
+/// Build the genesis state of the relay chain, where `ALICE` holds `INITIAL_BALANCE`.
+pub fn relay_ext() -> TestExternalities {
+	use relay::{Runtime, System};
+
+	let mut t = fabric_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	noble_balances::GenesisConfig::<Runtime> {
+		balances: vec![(ALICE.into(), INITIAL_BALANCE)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext = TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Build the genesis state of the parachain `id`, where `ALICE` holds `INITIAL_BALANCE`.
+pub fn para_ext(id: u32) -> TestExternalities {
+	use parachain::{Runtime, System, MsgQueue};
+
+	let mut t = fabric_system::GenesisConfig::default().build_storage::<Runtime>().unwrap();
+	noble_balances::GenesisConfig::<Runtime> {
+		balances: vec![(ALICE.into(), INITIAL_BALANCE)],
+	}.assimilate_storage(&mut t).unwrap();
+
+	let mut ext = TestExternalities::new(t);
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		MsgQueue::set_parachain_id(id);
+	});
+	ext
+}
+
+/// Reset every chain to its genesis state and drop all messages in flight.
+pub fn reset() {
+	DOWNWARD_MESSAGES.with(|q| q.borrow_mut().clear());
+	UPWARD_MESSAGES.with(|q| q.borrow_mut().clear());
+	HORIZONTAL_MESSAGES.with(|q| q.borrow_mut().clear());
+	Relay::reset_ext();
+	ParaA::reset_ext();
+	ParaB::reset_ext();
+}
+
+fn execute_on_para(id: u32, execute: impl FnOnce()) {
+	match id {
+		PARA_A_ID => ParaA::execute_without_dispatch(execute),
+		PARA_B_ID => ParaB::execute_without_dispatch(execute),
+		// There is no such parachain; the message is lost.
+		_ => (),
+	}
+}
+
+/// Deliver all messages in flight, including any sent as a consequence of delivering others, until none remain.
+pub fn dispatch_xcm_messages() {
+	loop {
+		if let Some((sender, msg)) = UPWARD_MESSAGES.with(|q| q.borrow_mut().pop_front()) {
+			Relay::execute_without_dispatch(|| {
+				let _ = <relay::Runtime as runtime_parachains::ump::Config>::UmpSink::process_upward_message(
+					ParaId::from(sender),
+					&msg[..],
+					Weight::max_value(),
+				);
+			});
+		} else if let Some((recipient, msg)) = DOWNWARD_MESSAGES.with(|q| q.borrow_mut().pop_front()) {
+			execute_on_para(recipient, || parachain::MsgQueue::handle_downward_message(msg));
+		} else if let Some((sender, recipient, msg)) = HORIZONTAL_MESSAGES.with(|q| q.borrow_mut().pop_front()) {
+			execute_on_para(recipient, || parachain::MsgQueue::handle_horizontal_message(sender, msg));
+		} else {
+			break
+		}
+	}
+}
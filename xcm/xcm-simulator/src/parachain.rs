@@ -0,0 +1,232 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A mock parachain, which receives downward and horizontal messages through its `MsgQueue` module.
+
+use tet_core::H256;
+use tp_runtime::{AccountId32, testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+use fabric_support::{impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types, weights::Weight};
+use tetcoin_parachain::primitives::Sibling;
+use xcm::v0::{MultiLocation, NetworkId, Junction};
+use xcm_executor::{XcmExecutor, traits::{IsConcrete, NativeAsset}};
+use xcm_builder::{
+	AccountId32Aliases, ParentIsDefault, SiblingParachainConvertsVia, SovereignSignedViaLocation,
+	SignedAccountId32AsNative, CurrencyAdapter as XcmCurrencyAdapter, LocationInverter, AllowUnpaidExecutionFrom,
+	FixedWeightBounds, FixedRateOfConcreteFungible,
+};
+
+use crate::ParachainXcmRouter;
+
+pub type AccountId = AccountId32;
+pub type Balance = u128;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Runtime;
+
+impl_outer_origin! {
+	pub enum Origin for Runtime {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Runtime where origin: Origin {
+		fabric_system::System,
+		noble_balances::Balances,
+	}
+}
+
+impl_outer_event! {
+	pub enum Event for Runtime {
+		fabric_system<T>,
+		noble_balances<T>,
+		mock_msg_queue,
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub BlockWeights: fabric_system::limits::BlockWeights =
+		fabric_system::limits::BlockWeights::simple_max(4 * 1024 * 1024 * 1024);
+}
+
+impl fabric_system::Config for Runtime {
+	type BaseCallFilter = ();
+	type BlockWeights = BlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type NobleInfo = ();
+	type AccountData = noble_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+
+impl noble_balances::Config for Runtime {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub ParachainId: u32 = MsgQueue::parachain_id();
+	pub Ancestry: MultiLocation = Junction::Parachain { id: ParachainId::get() }.into();
+	pub const RelayLocation: MultiLocation = MultiLocation::X1(Junction::Parent);
+	pub const RelayNetwork: NetworkId = NetworkId::Metrocoin;
+	pub const BaseXcmWeight: Weight = 1_000;
+	pub const MaxXcmRelayDepth: u32 = 4;
+	pub RelayTokenPerSecond: (MultiLocation, u128) = (RelayLocation::get(), 1);
+}
+
+pub type LocationConverter = (
+	ParentIsDefault<AccountId>,
+	SiblingParachainConvertsVia<Sibling, AccountId>,
+	AccountId32Aliases<RelayNetwork, AccountId>,
+);
+
+/// The relay chain token is this chain's native currency.
+pub type LocalAssetTransactor = XcmCurrencyAdapter<Balances, IsConcrete<RelayLocation>, LocationConverter, AccountId>;
+
+type LocalOriginConverter = (
+	SovereignSignedViaLocation<LocationConverter, Origin>,
+	SignedAccountId32AsNative<RelayNetwork, Origin>,
+);
+
+pub type XcmRouter = ParachainXcmRouter<ParachainId>;
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = XcmRouter;
+	type AssetTransactor = LocalAssetTransactor;
+	type AssetExchanger = ();
+	type OriginConverter = LocalOriginConverter;
+	type IsReserve = NativeAsset;
+	type IsTeleporter = NativeAsset;
+	type LocationInverter = LocationInverter<Ancestry>;
+	type MaxRelayDepth = MaxXcmRelayDepth;
+	// Everything may execute for free in the simulator.
+	type Barrier = AllowUnpaidExecutionFrom<()>;
+	type Weigher = FixedWeightBounds<BaseXcmWeight>;
+	type Trader = FixedRateOfConcreteFungible<RelayTokenPerSecond>;
+	type ResponseHandler = ();
+	type HrmpChannelManager = ();
+}
+
+/// Stand-in for the message handling of a real parachain: executes every message it is handed.
+pub mod mock_msg_queue {
+	use tetsy_scale_codec::Decode;
+	use tetcore_std::{prelude::*, convert::TryFrom};
+	use fabric_support::{decl_module, decl_storage, decl_event, weights::Weight, StorageValue};
+	use xcm::{VersionedXcm, v0::{Xcm, Junction, MultiLocation, Outcome, ExecuteXcm, Error as XcmError}};
+
+	pub trait Config: fabric_system::Config {
+		/// The outer event type.
+		type Event: From<Event> + Into<<Self as fabric_system::Config>::Event>;
+
+		/// The executor of incoming messages.
+		type XcmExecutor: ExecuteXcm;
+	}
+
+	decl_storage! {
+		trait Store for Module<T: Config> as MockMsgQueue {
+			/// The parachain id of this chain.
+			pub ParachainId get(fn parachain_id): u32;
+		}
+	}
+
+	decl_event! {
+		pub enum Event {
+			/// A downward message was executed with the given outcome. [outcome]
+			ExecutedDownward(Outcome),
+			/// A horizontal message from the given sibling was executed with the given outcome. [sender, outcome]
+			ExecutedHorizontal(u32, Outcome),
+			/// A message could not be decoded.
+			InvalidFormat,
+		}
+	}
+
+	decl_module! {
+		pub struct Module<T: Config> for enum Call where origin: T::Origin {
+			fn deposit_event() = default;
+		}
+	}
+
+	impl<T: Config> Module<T> {
+		/// Set the parachain id of this chain.
+		pub fn set_parachain_id(id: u32) {
+			ParachainId::put(id);
+		}
+
+		/// Execute a message sent down from the relay chain.
+		pub fn handle_downward_message(msg: Vec<u8>) {
+			let origin = MultiLocation::X1(Junction::Parent);
+			if let Some(outcome) = Self::execute(origin, msg) {
+				Self::deposit_event(Event::ExecutedDownward(outcome));
+			}
+		}
+
+		/// Execute a message sent across from the sibling `sender`.
+		pub fn handle_horizontal_message(sender: u32, msg: Vec<u8>) {
+			let origin = MultiLocation::X2(Junction::Parent, Junction::Parachain { id: sender });
+			if let Some(outcome) = Self::execute(origin, msg) {
+				Self::deposit_event(Event::ExecutedHorizontal(sender, outcome));
+			}
+		}
+
+		fn execute(origin: MultiLocation, msg: Vec<u8>) -> Option<Outcome> {
+			let versioned = match VersionedXcm::decode(&mut &msg[..]) {
+				Ok(versioned) => versioned,
+				Err(_) => {
+					Self::deposit_event(Event::InvalidFormat);
+					return None
+				}
+			};
+			Some(match Xcm::try_from(versioned) {
+				Ok(xcm) => T::XcmExecutor::execute_xcm(origin, xcm, Weight::max_value()),
+				Err(()) => Outcome::Error(XcmError::UnhandledXcmVersion),
+			})
+		}
+	}
+}
+
+impl mock_msg_queue::Config for Runtime {
+	type Event = Event;
+	type XcmExecutor = XcmExecutor<XcmConfig>;
+}
+
+pub type System = fabric_system::Module<Runtime>;
+pub type Balances = noble_balances::Module<Runtime>;
+pub type MsgQueue = mock_msg_queue::Module<Runtime>;
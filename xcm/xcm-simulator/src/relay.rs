@@ -0,0 +1,156 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A mock relay chain, built from the UMP module of `runtime-parachains`.
+
+use tet_core::H256;
+use tp_runtime::{AccountId32, testing::Header, traits::{BlakeTwo256, IdentityLookup}};
+use fabric_support::{impl_outer_origin, impl_outer_dispatch, impl_outer_event, parameter_types, weights::Weight};
+use runtime_parachains::{configuration, ump};
+use tetcoin_parachain::primitives::Id as ParaId;
+use xcm::v0::{MultiLocation, NetworkId};
+use xcm_executor::{XcmExecutor, traits::IsConcrete};
+use xcm_builder::{
+	AccountId32Aliases, ChildParachainConvertsVia, SovereignSignedViaLocation, SignedAccountId32AsNative,
+	CurrencyAdapter as XcmCurrencyAdapter, LocationInverter, AllowUnpaidExecutionFrom, FixedWeightBounds,
+	FixedRateOfConcreteFungible,
+};
+
+use crate::RelayChainXcmRouter;
+
+pub type AccountId = AccountId32;
+pub type Balance = u128;
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Runtime;
+
+impl_outer_origin! {
+	pub enum Origin for Runtime {}
+}
+
+impl_outer_dispatch! {
+	pub enum Call for Runtime where origin: Origin {
+		fabric_system::System,
+		noble_balances::Balances,
+	}
+}
+
+impl_outer_event! {
+	pub enum Event for Runtime {
+		fabric_system<T>,
+		noble_balances<T>,
+		ump,
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub BlockWeights: fabric_system::limits::BlockWeights =
+		fabric_system::limits::BlockWeights::simple_max(4 * 1024 * 1024 * 1024);
+}
+
+impl fabric_system::Config for Runtime {
+	type BaseCallFilter = ();
+	type BlockWeights = BlockWeights;
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type NobleInfo = ();
+	type AccountData = noble_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 1;
+}
+
+impl noble_balances::Config for Runtime {
+	type MaxLocks = ();
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl configuration::Config for Runtime {}
+
+parameter_types! {
+	pub const RelayLocation: MultiLocation = MultiLocation::Null;
+	pub const RelayNetwork: NetworkId = NetworkId::Metrocoin;
+	pub const Ancestry: MultiLocation = MultiLocation::Null;
+	pub const BaseXcmWeight: Weight = 1_000;
+	pub const MaxXcmRelayDepth: u32 = 4;
+	pub RelayTokenPerSecond: (MultiLocation, u128) = (RelayLocation::get(), 1);
+}
+
+pub type LocationConverter = (
+	ChildParachainConvertsVia<ParaId, AccountId>,
+	AccountId32Aliases<RelayNetwork, AccountId>,
+);
+
+pub type LocalAssetTransactor = XcmCurrencyAdapter<Balances, IsConcrete<RelayLocation>, LocationConverter, AccountId>;
+
+type LocalOriginConverter = (
+	SovereignSignedViaLocation<LocationConverter, Origin>,
+	SignedAccountId32AsNative<RelayNetwork, Origin>,
+);
+
+pub type XcmRouter = RelayChainXcmRouter;
+
+pub struct XcmConfig;
+impl xcm_executor::Config for XcmConfig {
+	type Call = Call;
+	type XcmSender = XcmRouter;
+	type AssetTransactor = LocalAssetTransactor;
+	type AssetExchanger = ();
+	type OriginConverter = LocalOriginConverter;
+	type IsReserve = ();
+	type IsTeleporter = ();
+	type LocationInverter = LocationInverter<Ancestry>;
+	type MaxRelayDepth = MaxXcmRelayDepth;
+	// Everything may execute for free in the simulator.
+	type Barrier = AllowUnpaidExecutionFrom<()>;
+	type Weigher = FixedWeightBounds<BaseXcmWeight>;
+	type Trader = FixedRateOfConcreteFungible<RelayTokenPerSecond>;
+	type ResponseHandler = ();
+	type HrmpChannelManager = ();
+}
+
+impl ump::Config for Runtime {
+	type Event = Event;
+	type UmpSink = ump::XcmSink<XcmConfig, Runtime>;
+}
+
+pub type System = fabric_system::Module<Runtime>;
+pub type Balances = noble_balances::Module<Runtime>;
+pub type Ump = ump::Module<Runtime>;
+pub type RelayXcmExecutor = XcmExecutor<XcmConfig>;
@@ -0,0 +1,156 @@
+// Copyright 2020 Parity Technologies (UK) Ltd.
+// This file is part of Tetcoin.
+
+// Tetcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Tetcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Tetcoin.  If not, see <http://www.gnu.org/licenses/>.
+
+use tetsy_scale_codec::Encode;
+use fabric_support::{assert_ok, weights::Weight};
+use runtime_parachains::ump;
+use xcm::v0::{
+	Xcm, Order, Junction, MultiAsset, MultiLocation, NetworkId, OriginKind, Outcome, ExecuteXcm, SendXcm,
+};
+use super::*;
+
+fn alice_location() -> MultiLocation {
+	Junction::AccountId32 { network: NetworkId::Any, id: ALICE }.into()
+}
+
+fn relay_tokens(amount: u128) -> Vec<MultiAsset> {
+	vec![MultiAsset::ConcreteFungible { id: MultiLocation::Null, amount }]
+}
+
+#[test]
+fn reserve_transfer_from_relay_to_para() {
+	reset();
+
+	Relay::execute_with(|| {
+		let outcome = relay::RelayXcmExecutor::execute_xcm(
+			alice_location(),
+			Xcm::WithdrawAsset {
+				assets: relay_tokens(123),
+				effects: vec![Order::DepositReserveAsset {
+					assets: vec![MultiAsset::All],
+					dest: Junction::Parachain { id: PARA_A_ID }.into(),
+					effects: vec![Order::DepositAsset { assets: vec![MultiAsset::All], dest: alice_location() }],
+				}],
+			},
+			Weight::max_value(),
+		);
+		assert!(matches!(outcome, Outcome::Complete(_)));
+		assert_eq!(relay::Balances::free_balance(&ALICE.into()), INITIAL_BALANCE - 123);
+		assert_eq!(relay::Balances::free_balance(&para_account_id(PARA_A_ID)), 123);
+	});
+
+	ParaA::execute_with(|| {
+		assert_eq!(parachain::Balances::free_balance(&ALICE.into()), INITIAL_BALANCE + 123);
+	});
+	ParaB::execute_with(|| {
+		assert_eq!(parachain::Balances::free_balance(&ALICE.into()), INITIAL_BALANCE);
+	});
+}
+
+#[test]
+fn teleport_from_relay_to_para() {
+	reset();
+
+	Relay::execute_with(|| {
+		let outcome = relay::RelayXcmExecutor::execute_xcm(
+			alice_location(),
+			Xcm::WithdrawAsset {
+				assets: relay_tokens(100),
+				effects: vec![Order::InitiateTeleport {
+					assets: vec![MultiAsset::All],
+					dest: Junction::Parachain { id: PARA_B_ID }.into(),
+					effects: vec![Order::DepositAsset { assets: vec![MultiAsset::All], dest: alice_location() }],
+				}],
+			},
+			Weight::max_value(),
+		);
+		assert!(matches!(outcome, Outcome::Complete(_)));
+		assert_eq!(relay::Balances::free_balance(&ALICE.into()), INITIAL_BALANCE - 100);
+		// Teleported assets are destroyed rather than held in reserve.
+		assert_eq!(relay::Balances::free_balance(&para_account_id(PARA_B_ID)), 0);
+	});
+
+	ParaB::execute_with(|| {
+		assert_eq!(parachain::Balances::free_balance(&ALICE.into()), INITIAL_BALANCE + 100);
+		assert!(parachain::System::events().iter().any(|r| matches!(
+			r.event,
+			parachain::Event::mock_msg_queue(parachain::mock_msg_queue::Event::ExecutedDownward(Outcome::Complete(_)))
+		)));
+	});
+}
+
+#[test]
+fn transact_from_para_to_relay() {
+	reset();
+
+	ParaA::execute_with(|| {
+		assert_ok!(parachain::XcmRouter::send_xcm(
+			Junction::Parent.into(),
+			Xcm::Transact {
+				origin_type: OriginKind::SovereignAccount,
+				require_weight_at_most: 1_000_000_000,
+				call: relay::Call::System(fabric_system::Call::remark(vec![1, 2, 3])).encode(),
+			},
+		));
+	});
+
+	Relay::execute_with(|| {
+		assert!(relay::System::events().iter().any(|r| matches!(
+			r.event,
+			relay::Event::ump(ump::Event::ExecutedUpward(_, Outcome::Complete(_)))
+		)));
+	});
+}
+
+#[test]
+fn transact_between_paras() {
+	reset();
+
+	ParaA::execute_with(|| {
+		assert_ok!(parachain::XcmRouter::send_xcm(
+			(Junction::Parent, Junction::Parachain { id: PARA_B_ID }).into(),
+			Xcm::Transact {
+				origin_type: OriginKind::SovereignAccount,
+				require_weight_at_most: 1_000_000_000,
+				call: parachain::Call::System(fabric_system::Call::remark(vec![1, 2, 3])).encode(),
+			},
+		));
+	});
+
+	ParaB::execute_with(|| {
+		assert!(parachain::System::events().iter().any(|r| matches!(
+			r.event,
+			parachain::Event::mock_msg_queue(
+				parachain::mock_msg_queue::Event::ExecutedHorizontal(PARA_A_ID, Outcome::Complete(_))
+			)
+		)));
+	});
+}
+
+#[test]
+fn message_to_unknown_destination_is_rejected() {
+	reset();
+
+	ParaA::execute_with(|| {
+		assert_eq!(
+			parachain::XcmRouter::send_xcm(
+				(Junction::Parent, Junction::Parent).into(),
+				Xcm::WithdrawAsset { assets: vec![], effects: vec![] },
+			),
+			Err(xcm::v0::Error::CannotReachDestination),
+		);
+	});
+}